@@ -0,0 +1,169 @@
+//! Round-robin rotation across multiple API keys.
+//!
+//! Screen analysis picks the next key on every call so quota and rate limits
+//! are spread over all configured keys. A key that hits a rate limit (429) or
+//! an authentication failure (401/403) is benched for a cooldown period.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+/// How long a failing key is skipped before it is tried again.
+pub const KEY_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// Round-robin key selector with temporary exclusion of failing keys.
+#[derive(Debug, Default)]
+pub struct ApiKeyPool {
+    keys: Vec<String>,
+    next: usize,
+    benched_until: HashMap<String, Instant>,
+}
+
+impl ApiKeyPool {
+    pub fn new(keys: Vec<String>) -> Self {
+        let mut pool = Self::default();
+        pool.set_keys(keys);
+        pool
+    }
+
+    /// Replace the key list, keeping rotation state when the keys are unchanged.
+    /// Blank and duplicate keys are ignored.
+    pub fn set_keys(&mut self, keys: Vec<String>) {
+        let mut unique: Vec<String> = Vec::new();
+        for key in keys {
+            let key = key.trim().to_string();
+            if !key.is_empty() && !unique.contains(&key) {
+                unique.push(key);
+            }
+        }
+        if unique == self.keys {
+            return;
+        }
+        self.benched_until.retain(|k, _| unique.contains(k));
+        self.keys = unique;
+        self.next = 0;
+    }
+
+    /// Next available key in round-robin order.
+    /// When every key is benched, the one whose cooldown ends first is returned
+    /// so analysis is never blocked entirely.
+    pub fn next_key(&mut self, now: Instant) -> Option<String> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let len = self.keys.len();
+        for offset in 0..len {
+            let idx = (self.next + offset) % len;
+            if !self.is_benched(&self.keys[idx], now) {
+                self.next = (idx + 1) % len;
+                return Some(self.keys[idx].clone());
+            }
+        }
+        self.keys
+            .iter()
+            .min_by_key(|k| self.benched_until.get(*k).copied())
+            .cloned()
+    }
+
+    /// Temporarily exclude a key after a rate limit or authentication failure.
+    pub fn bench(&mut self, key: &str, now: Instant) {
+        if self.keys.iter().any(|k| k == key) {
+            self.benched_until
+                .insert(key.to_string(), now + KEY_COOLDOWN);
+        }
+    }
+
+    fn is_benched(&self, key: &str, now: Instant) -> bool {
+        self.benched_until
+            .get(key)
+            .is_some_and(|until| *until > now)
+    }
+}
+
+/// Whether an HTTP status should bench the key that produced it.
+pub fn should_bench_key(status: u16) -> bool {
+    matches!(status, 401 | 403 | 429)
+}
+
+static API_KEY_POOL: Lazy<Mutex<ApiKeyPool>> = Lazy::new(|| Mutex::new(ApiKeyPool::default()));
+
+/// Pick the next key from the shared pool, refreshing it with the configured keys.
+pub fn next_api_key(keys: &[String]) -> Option<String> {
+    let mut pool = API_KEY_POOL.lock().ok()?;
+    pool.set_keys(keys.to_vec());
+    pool.next_key(Instant::now())
+}
+
+/// Bench a key in the shared pool.
+pub fn bench_api_key(key: &str) {
+    if let Ok(mut pool) = API_KEY_POOL.lock() {
+        pool.bench(key, Instant::now());
+        tracing::warn!(
+            "API key {} temporarily excluded from rotation",
+            crate::mask_api_key(key)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn rotates_keys_round_robin() {
+        let mut pool = ApiKeyPool::new(keys(&["a", "b", "c"]));
+        let now = Instant::now();
+
+        let picked: Vec<String> = (0..4).filter_map(|_| pool.next_key(now)).collect();
+        assert_eq!(picked, keys(&["a", "b", "c", "a"]));
+    }
+
+    #[test]
+    fn benched_key_is_skipped_until_cooldown_ends() {
+        let mut pool = ApiKeyPool::new(keys(&["a", "b", "c"]));
+        let now = Instant::now();
+
+        pool.bench("b", now);
+        let picked: Vec<String> = (0..4).filter_map(|_| pool.next_key(now)).collect();
+        assert_eq!(picked, keys(&["a", "c", "a", "c"]));
+
+        let later = now + KEY_COOLDOWN + Duration::from_secs(1);
+        let picked: Vec<String> = (0..3).filter_map(|_| pool.next_key(later)).collect();
+        assert!(picked.contains(&"b".to_string()));
+    }
+
+    #[test]
+    fn all_benched_returns_key_recovering_first() {
+        let mut pool = ApiKeyPool::new(keys(&["a", "b"]));
+        let now = Instant::now();
+
+        pool.bench("b", now);
+        pool.bench("a", now + Duration::from_secs(10));
+        assert_eq!(pool.next_key(now), Some("b".to_string()));
+    }
+
+    #[test]
+    fn set_keys_ignores_blank_and_duplicate_keys() {
+        let mut pool = ApiKeyPool::new(keys(&["a", " ", "a", "b"]));
+        let now = Instant::now();
+
+        let picked: Vec<String> = (0..3).filter_map(|_| pool.next_key(now)).collect();
+        assert_eq!(picked, keys(&["a", "b", "a"]));
+        assert_eq!(ApiKeyPool::new(Vec::new()).next_key(now), None);
+    }
+
+    #[test]
+    fn bench_status_codes() {
+        assert!(should_bench_key(429));
+        assert!(should_bench_key(401));
+        assert!(should_bench_key(403));
+        assert!(!should_bench_key(500));
+        assert!(!should_bench_key(400));
+    }
+}
@@ -5,7 +5,7 @@
 
 use crate::backup::cleanup_old_auto_backups;
 use crate::errors::{AppError, AppResult};
-use crate::memory_storage::{get_settings_sync, save_settings_sync};
+use crate::memory_storage::{get_settings_sync, update_settings_sync};
 use chrono::{Local, NaiveDateTime};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -84,10 +84,9 @@ pub fn should_run_backup_now() -> bool {
 
 /// Update last_auto_backup_at timestamp
 pub fn update_last_backup_time() -> AppResult<()> {
-    let settings = get_settings_sync()?;
-    let mut settings = (*settings).clone();
-    settings.last_auto_backup_at = Some(Local::now().format("%Y-%m-%dT%H:%M:%S%.f").to_string());
-    save_settings_sync(&settings)
+    let now = Local::now().format("%Y-%m-%dT%H:%M:%S%.f").to_string();
+    update_settings_sync(|settings| settings.last_auto_backup_at = Some(now))?;
+    Ok(())
 }
 
 /// Run a single auto backup
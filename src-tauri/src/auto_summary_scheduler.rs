@@ -0,0 +1,272 @@
+//! Scheduled daily summary
+//!
+//! When `auto_summary_enabled` is set, the daily summary is generated once a
+//! day at `summary_time` (local time). A summary missed because the app was
+//! not running at that time is generated on the next start of the same day if
+//! `auto_summary_catch_up` is set. `last_auto_summary_date` records the last
+//! day handled, so each day gets at most one scheduled summary.
+//!
+//! The loop is spawned with `tauri::async_runtime::spawn` from the `.setup()`
+//! closure, so it runs on the app's Tokio runtime rather than on a thread
+//! without a reactor, and wakes every `SUMMARY_POLL_INTERVAL`.
+
+use crate::errors::AppResult;
+use crate::memory_storage::{get_settings_sync, update_settings_sync, Settings};
+use crate::shutdown::CAPTURE_SHUTDOWN;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How often the scheduler checks whether the summary is due.
+pub const SUMMARY_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `summary_time` used when the setting is missing.
+const DEFAULT_SUMMARY_TIME: &str = "18:00";
+
+/// Format of `last_auto_summary_date`.
+const SUMMARY_DATE_FORMAT: &str = "%Y-%m-%d";
+
+static SCHEDULER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Parse a `summary_time` value ("HH:MM").
+pub fn parse_summary_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Whether the scheduled summary should be generated at `now`.
+///
+/// * `summary_time` - Local time of day the summary is scheduled for
+/// * `last_run` - Day of the last scheduled summary, if any
+/// * `watching_since` - When the scheduler started; a scheduled time before
+///   it was missed while the app was not running
+/// * `catch_up` - Whether a missed summary is still generated later that day
+pub fn is_summary_due(
+    now: NaiveDateTime,
+    summary_time: NaiveTime,
+    last_run: Option<NaiveDate>,
+    watching_since: NaiveDateTime,
+    catch_up: bool,
+) -> bool {
+    let today = now.date();
+    if last_run.is_some_and(|day| day >= today) {
+        return false;
+    }
+    let scheduled = today.and_time(summary_time);
+    now >= scheduled && (watching_since <= scheduled || catch_up)
+}
+
+/// Start the scheduler on the Tauri async runtime. Does nothing if it runs already.
+pub fn start_scheduler() {
+    if SCHEDULER_RUNNING.swap(true, Ordering::SeqCst) {
+        tracing::info!("Auto summary scheduler is already running");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async {
+        tracing::info!("Auto summary scheduler started");
+        run_scheduler_loop().await;
+        SCHEDULER_RUNNING.store(false, Ordering::SeqCst);
+        tracing::info!("Auto summary scheduler stopped");
+    });
+}
+
+async fn run_scheduler_loop() {
+    let _loop_guard = CAPTURE_SHUTDOWN.enter_loop();
+    let watching_since = Local::now().naive_local();
+    loop {
+        if let Err(e) = check_and_run(watching_since).await {
+            tracing::warn!("Scheduled daily summary check failed: {}", e);
+        }
+        if CAPTURE_SHUTDOWN.sleep(SUMMARY_POLL_INTERVAL).await {
+            break;
+        }
+    }
+}
+
+/// Generate today's summary if it is due according to the current settings.
+async fn check_and_run(watching_since: NaiveDateTime) -> AppResult<()> {
+    let settings = get_settings_sync()?;
+    if !settings.auto_summary_enabled.unwrap_or(false) {
+        return Ok(());
+    }
+
+    let raw_time = settings
+        .summary_time
+        .as_deref()
+        .unwrap_or(DEFAULT_SUMMARY_TIME);
+    let Some(summary_time) = parse_summary_time(raw_time) else {
+        tracing::debug!("Invalid summary_time '{}', expected HH:MM", raw_time);
+        return Ok(());
+    };
+    let last_run = settings
+        .last_auto_summary_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, SUMMARY_DATE_FORMAT).ok());
+
+    let now = Local::now().naive_local();
+    let catch_up = settings.auto_summary_catch_up.unwrap_or(true);
+    if is_summary_due(now, summary_time, last_run, watching_since, catch_up) {
+        run_scheduled_summary(now.date()).await?;
+    }
+    Ok(())
+}
+
+/// Mark `day` as handled unless a scheduled summary already ran for it.
+/// Returns whether this call claimed the day.
+fn claim_summary_day(settings: &mut Settings, day: NaiveDate) -> bool {
+    let already_run = settings
+        .last_auto_summary_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, SUMMARY_DATE_FORMAT).ok())
+        .is_some_and(|last| last >= day);
+    if !already_run {
+        settings.last_auto_summary_date = Some(day.format(SUMMARY_DATE_FORMAT).to_string());
+    }
+    !already_run
+}
+
+/// Generate the summary for `today`. The day is claimed in the settings first,
+/// in one transaction, so a second run for the same day (another scheduler
+/// tick, a second app instance) backs off. It counts as handled whatever the
+/// outcome, so a failing API is not called again every minute.
+async fn run_scheduled_summary(today: NaiveDate) -> AppResult<()> {
+    let mut claimed = false;
+    let settings = update_settings_sync(|s| claimed = claim_summary_day(s, today))?;
+    if !claimed {
+        tracing::info!("Scheduled daily summary for {} already generated", today);
+        return Ok(());
+    }
+
+    let records = crate::services::report_service::get_daily_report_records(&settings)?;
+    if records.is_empty() {
+        tracing::info!(
+            "Skipping scheduled daily summary for {}: no records today",
+            today
+        );
+        return Ok(());
+    }
+
+    tracing::info!(
+        "Generating scheduled daily summary for {} from {} records",
+        today,
+        records.len()
+    );
+    match crate::services::report_service::generate_daily_summary_service(None, false).await {
+        Ok(path) => tracing::info!("Scheduled daily summary written: {}", path),
+        Err(e) => tracing::error!("Scheduled daily summary failed: {}", e),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn six_pm() -> NaiveTime {
+        NaiveTime::from_hms_opt(18, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_summary_time() {
+        assert_eq!(parse_summary_time("18:00"), Some(six_pm()));
+        assert_eq!(
+            parse_summary_time(" 09:30 "),
+            NaiveTime::from_hms_opt(9, 30, 0)
+        );
+        assert_eq!(parse_summary_time("25:00"), None);
+        assert_eq!(parse_summary_time("evening"), None);
+    }
+
+    #[test]
+    fn due_from_the_scheduled_minute_on() {
+        let started = at(16, 9, 0);
+        assert!(!is_summary_due(
+            at(16, 17, 59),
+            six_pm(),
+            None,
+            started,
+            false
+        ));
+        assert!(is_summary_due(
+            at(16, 18, 0),
+            six_pm(),
+            None,
+            started,
+            false
+        ));
+        // A late wake-up of the running scheduler still counts
+        assert!(is_summary_due(
+            at(16, 18, 3),
+            six_pm(),
+            None,
+            started,
+            false
+        ));
+    }
+
+    #[test]
+    fn runs_once_per_day() {
+        let started = at(15, 9, 0);
+        let today = at(16, 18, 0).date();
+        assert!(!is_summary_due(
+            at(16, 18, 1),
+            six_pm(),
+            Some(today),
+            started,
+            false
+        ));
+        // Yesterday's run does not block today's
+        assert!(is_summary_due(
+            at(16, 18, 1),
+            six_pm(),
+            Some(at(15, 18, 0).date()),
+            started,
+            false
+        ));
+    }
+
+    #[test]
+    fn a_day_is_claimed_only_once() {
+        let mut settings = Settings::default();
+        let day = at(16, 18, 0).date();
+
+        assert!(claim_summary_day(&mut settings, day));
+        assert_eq!(
+            settings.last_auto_summary_date.as_deref(),
+            Some("2026-03-16")
+        );
+        assert!(!claim_summary_day(&mut settings, day));
+        // An earlier day cannot move the marker back
+        assert!(!claim_summary_day(&mut settings, at(15, 18, 0).date()));
+        assert!(claim_summary_day(&mut settings, at(17, 18, 0).date()));
+    }
+
+    #[test]
+    fn missed_summary_is_caught_up_only_when_enabled() {
+        // App started at 20:00, after the scheduled time
+        let started = at(16, 20, 0);
+        assert!(is_summary_due(at(16, 20, 0), six_pm(), None, started, true));
+        assert!(!is_summary_due(
+            at(16, 20, 0),
+            six_pm(),
+            None,
+            started,
+            false
+        ));
+        // The next day is scheduled normally again
+        assert!(is_summary_due(
+            at(17, 18, 0),
+            six_pm(),
+            None,
+            started,
+            false
+        ));
+    }
+}
@@ -18,12 +18,22 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         daily_logger_lib::manual_entry::log_frontend_error,
         daily_logger_lib::manual_entry::open_obsidian_folder,
         daily_logger_lib::manual_entry::list_report_files,
+        daily_logger_lib::manual_entry::copy_record_summary,
         // Memory storage commands
         daily_logger_lib::memory_storage::get_today_records,
         daily_logger_lib::memory_storage::get_today_stats,
         daily_logger_lib::memory_storage::get_records_by_date_range,
+        daily_logger_lib::memory_storage::get_records_paginated,
+        daily_logger_lib::memory_storage::count_records,
+        daily_logger_lib::memory_storage::get_project_summary,
+        daily_logger_lib::memory_storage::get_recent_activity,
         daily_logger_lib::commands::settings_commands::get_settings,
+        daily_logger_lib::commands::settings_commands::get_settings_masked,
         daily_logger_lib::commands::settings_commands::save_settings,
+        daily_logger_lib::commands::settings_commands::reload_settings,
+        daily_logger_lib::commands::settings_commands::get_prompt_notices,
+        daily_logger_lib::commands::settings_commands::get_setup_status,
+        daily_logger_lib::commands::settings_commands::get_settings_schema,
         daily_logger_lib::ollama::test_api_connection_with_ollama,
         daily_logger_lib::commands::model_commands::get_model_info,
         daily_logger_lib::memory_storage::delete_record,
@@ -32,6 +42,8 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         daily_logger_lib::memory_storage::search_records,
         daily_logger_lib::memory_storage::get_default_tag_categories,
         daily_logger_lib::memory_storage::get_all_tags,
+        daily_logger_lib::memory_storage::get_all_keywords,
+        daily_logger_lib::memory_storage::get_keyword_cooccurrence,
         daily_logger_lib::memory_storage::get_tag_cloud_tags,
         daily_logger_lib::memory_storage::get_records_by_tag,
         // DATA-003: Manual tag system
@@ -44,11 +56,21 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         daily_logger_lib::memory_storage::get_tags_for_record,
         daily_logger_lib::memory_storage::get_tags_for_records,
         daily_logger_lib::memory_storage::get_records_by_manual_tags,
+        daily_logger_lib::memory_storage::tag_records,
+        daily_logger_lib::memory_storage::untag_records,
         // TAG-001: Tag color management
         daily_logger_lib::memory_storage::get_tag_colors,
         daily_logger_lib::memory_storage::set_tag_color,
         // FEAT-005: User notes for screenshot records
         daily_logger_lib::memory_storage::update_record_user_notes,
+        daily_logger_lib::memory_storage::update_record,
+        daily_logger_lib::memory_storage::update_record_content,
+        daily_logger_lib::memory_storage::get_records_tokens_used,
+        daily_logger_lib::memory_storage::confirm_record,
+        daily_logger_lib::memory_storage::confirm_records,
+        daily_logger_lib::memory_storage::get_heatmap,
+        daily_logger_lib::memory_storage::get_hourly_distribution,
+        daily_logger_lib::memory_storage::get_content_lang_stats,
         // SESSION-001: Session management
         daily_logger_lib::commands::session_commands::get_today_sessions,
         // SESSION-002: Session batch analysis
@@ -58,18 +80,27 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         daily_logger_lib::commands::session_commands::update_session_user_summary,
         // DATA-008: Statistics panel
         daily_logger_lib::memory_storage::get_statistics,
+        daily_logger_lib::memory_storage::get_overview_stats,
         // Report commands
         daily_logger_lib::commands::report_commands::generate_daily_summary,
+        daily_logger_lib::commands::report_commands::save_daily_summary,
         daily_logger_lib::commands::report_commands::generate_multilingual_daily_summary,
         daily_logger_lib::commands::report_commands::get_supported_languages,
         daily_logger_lib::commands::report_commands::get_default_summary_prompt,
         daily_logger_lib::commands::report_commands::generate_weekly_report,
         daily_logger_lib::commands::report_commands::generate_monthly_report,
         daily_logger_lib::commands::report_commands::generate_custom_report,
+        daily_logger_lib::commands::report_commands::generate_range_summary,
         daily_logger_lib::commands::report_commands::compare_reports,
         // DATA-004: Data export
         daily_logger_lib::export::export_records,
+        daily_logger_lib::export::cancel_export,
+        daily_logger_lib::export::export_to_sqlite,
+        daily_logger_lib::export::export_report_pdf,
         daily_logger_lib::export::open_export_dir,
+        daily_logger_lib::import::import_records_csv,
+        daily_logger_lib::import::import_records_json,
+        daily_logger_lib::data_dir::migrate_data_dir,
         // DATA-005: Data backup and restore
         daily_logger_lib::backup::create_backup,
         daily_logger_lib::backup::get_backup_info,
@@ -108,14 +139,22 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::take_screenshot,
         #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::preview_screenshot,
+        #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::reanalyze_record,
         #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::reanalyze_today_records,
         #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::reanalyze_records_by_date,
         #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::reanalyze_all,
+        #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::reanalyze_screenshot,
+        #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::get_default_analysis_prompt,
         #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::estimate_request_tokens,
+        #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::get_auto_capture_status,
         #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::get_work_time_status,
@@ -127,10 +166,15 @@ pub fn register_commands(builder: tauri::Builder<Wry>) -> tauri::Builder<Wry> {
         daily_logger_lib::commands::capture_commands::get_quality_filter_stats,
         #[cfg(feature = "screenshot")]
         daily_logger_lib::commands::capture_commands::reset_quality_filter_counter,
+        #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::calibrate_change_threshold,
+        #[cfg(feature = "screenshot")]
+        daily_logger_lib::commands::capture_commands::get_screenshot_disk_usage,
         // Timeline commands
         daily_logger_lib::timeline::get_timeline_today,
         daily_logger_lib::timeline::get_timeline_for_date,
         daily_logger_lib::timeline::get_timeline_for_range,
+        daily_logger_lib::timeline::get_coverage_gaps,
         // STAB-002: Auto backup scheduler
         daily_logger_lib::auto_backup_scheduler::trigger_auto_backup,
     ])
@@ -10,15 +10,27 @@
 //! SMART-004: Multi-monitor capture support
 //! EXP-002: Screenshot quality filter
 
+use crate::failure_alert::{deliver_failure_alert, poll_capture_failures, record_capture_failure};
+use crate::idle::{
+    system_idle_time, IdleTracker, IdleTransition, IDLE_POLL_INTERVAL, IDLE_THRESHOLD,
+};
 use crate::services::capture_service::{
-    get_auto_capture_status_service, get_default_analysis_prompt_service,
-    get_quality_filter_stats_service, get_work_time_status_service, reanalyze_record_service,
-    reanalyze_records_by_date_service, reanalyze_today_records_service,
-    reset_quality_filter_counter_service, should_capture_by_work_time_from_arc,
-    start_auto_capture_service, stop_auto_capture_service, take_screenshot_service,
-    trigger_auto_capture_service, trigger_auto_capture_with_arc, trigger_capture_service,
-    CaptureSettings, QualityFilterStats, ReanalyzeResult, ScreenAnalysis,
+    begin_reanalyze_all, calibrate_change_threshold_service, capture_backoff_seconds,
+    display_unavailable, emit_capture_event,
+    estimate_request_tokens as estimate_request_tokens_service, get_auto_capture_status_service,
+    get_default_analysis_prompt_service, get_quality_filter_stats_service,
+    get_screenshot_disk_usage_service, get_work_time_status_service, has_available_display,
+    reanalyze_all_targets, reanalyze_record_service, reanalyze_records_by_date_service,
+    reanalyze_records_paced, reanalyze_today_records_service, reset_quality_filter_counter_service,
+    should_capture_by_work_time_from_arc, start_auto_capture_service, stop_auto_capture_service,
+    take_analysis_failed, take_screenshot_service, trigger_auto_capture_service,
+    trigger_auto_capture_with_arc, trigger_capture_service, wait_startup_delay, CaptureEvent,
+    CaptureEventKind, CaptureSettings, CaptureTriggerSource, QualityFilterStats, ReanalyzeResult,
+    ScreenAnalysis, ScreenshotDestination, ScreenshotDiskUsage, ThresholdCalibration,
+    MAX_CAPTURE_BACKOFF_SECONDS, REANALYZE_ALL_MIN_INTERVAL,
 };
+use crate::services::model_service::TokenEstimate;
+use crate::shutdown::CAPTURE_SHUTDOWN;
 use crate::work_time::WorkTimeStatus;
 use std::time::Duration;
 use tauri::Emitter;
@@ -47,6 +59,21 @@ pub fn get_default_analysis_prompt() -> String {
     get_default_analysis_prompt_service()
 }
 
+/// Estimate the tokens of an analysis request and whether it fits the model's
+/// context window. Uses the saved settings when `settings` is not given.
+///
+/// This is a thin command wrapper that delegates to the capture service.
+#[tauri::command]
+pub fn estimate_request_tokens(
+    settings: Option<crate::memory_storage::Settings>,
+) -> Result<TokenEstimate, String> {
+    let settings = match settings {
+        Some(settings) => settings,
+        None => (*crate::memory_storage::get_settings_sync().map_err(|e| e.to_string())?).clone(),
+    };
+    Ok(estimate_request_tokens_service(&settings))
+}
+
 /// Start auto capture with the configured interval.
 ///
 /// This command initializes auto capture by validating settings and starting
@@ -57,10 +84,27 @@ pub async fn start_auto_capture(app: tauri::AppHandle) -> Result<(), String> {
     start_auto_capture_service().map_err(|e| e.to_string())?;
 
     let settings = load_capture_settings_internal();
-    let interval_minutes = settings.screenshot_interval;
+    let interval_seconds = settings.screenshot_interval_seconds;
+    let startup_delay_seconds = settings.startup_delay_seconds;
 
     // Spawn the capture loop (Tauri-specific, remains in command layer)
+    let alert_app = app.clone();
     tokio::spawn(async move {
+        // Registered so app exit waits for the current cycle to finish
+        let _loop_guard = CAPTURE_SHUTDOWN.enter_loop();
+
+        // Cold-start delay so the first screenshot is not taken before the desktop loads
+        wait_startup_delay(startup_delay_seconds, |d| async move {
+            CAPTURE_SHUTDOWN.sleep(d).await;
+        })
+        .await;
+        if CAPTURE_SHUTDOWN.is_requested()
+            || !crate::services::capture_service::is_auto_capture_running()
+        {
+            tracing::info!("Auto capture stopped during startup delay");
+            return;
+        }
+
         // Execute immediately on start — single Arc<Settings> read for both work time + capture
         if let Ok(arc) = crate::memory_storage::get_settings_sync() {
             if should_capture_by_work_time_from_arc(&arc) {
@@ -73,8 +117,23 @@ pub async fn start_auto_capture(app: tauri::AppHandle) -> Result<(), String> {
             }
         }
 
+        // Consecutive failed cycles stretch the interval (see `capture_backoff_seconds`)
+        let mut consecutive_failures: u32 = 0;
+        // Set while no display is available and the user chose to pause meanwhile
+        let mut paused_without_display = false;
         loop {
-            tokio::time::sleep(Duration::from_secs(interval_minutes * 60)).await;
+            let wait_seconds = capture_backoff_seconds(
+                interval_seconds,
+                consecutive_failures,
+                MAX_CAPTURE_BACKOFF_SECONDS,
+            );
+            if CAPTURE_SHUTDOWN
+                .sleep(Duration::from_secs(wait_seconds))
+                .await
+            {
+                tracing::info!("Auto capture loop shut down");
+                break;
+            }
 
             if !crate::services::capture_service::is_auto_capture_running() {
                 tracing::info!("Auto capture stopped");
@@ -82,18 +141,102 @@ pub async fn start_auto_capture(app: tauri::AppHandle) -> Result<(), String> {
             }
 
             // Single Arc<Settings> read for work time check + capture
+            let mut pause_without_display = true;
             if let Ok(arc) = crate::memory_storage::get_settings_sync() {
                 if !should_capture_by_work_time_from_arc(&arc) {
                     tracing::debug!("Outside work time, skipping capture");
+                    emit_capture_event(
+                        &CaptureEvent::new(CaptureEventKind::Skipped, CaptureTriggerSource::Auto)
+                            .with_reason("outside_work_time"),
+                    );
                     continue;
                 }
+                pause_without_display = arc.pause_capture_without_display.unwrap_or(true);
                 drop(arc); // Release Arc before capture (capture reads its own Arc)
             }
 
-            if let Err(e) = trigger_auto_capture_service().await {
-                tracing::error!("Auto capture failed: {}", e);
+            if paused_without_display {
+                if pause_without_display && !has_available_display() {
+                    emit_capture_event(
+                        &CaptureEvent::new(CaptureEventKind::Skipped, CaptureTriggerSource::Auto)
+                            .with_reason("no_display"),
+                    );
+                    continue;
+                }
+                tracing::info!("Display available again, resuming auto capture");
+                paused_without_display = false;
+            }
+
+            let result = trigger_auto_capture_service().await;
+            if pause_without_display && display_unavailable() {
+                tracing::warn!("No display available, auto capture paused until a display is back");
+                paused_without_display = true;
+                continue;
+            }
+            let settings = crate::memory_storage::get_settings_sync().ok();
+            let window_minutes = settings
+                .as_ref()
+                .and_then(|s| s.failure_alert_window_minutes);
+            if result.is_err() || take_analysis_failed() {
+                consecutive_failures = consecutive_failures.saturating_add(1);
+                tracing::warn!(
+                    "Auto capture cycle failed {} times in a row, next attempt in {} seconds",
+                    consecutive_failures,
+                    capture_backoff_seconds(
+                        interval_seconds,
+                        consecutive_failures,
+                        MAX_CAPTURE_BACKOFF_SECONDS
+                    )
+                );
             } else {
-                record_work_time_capture_internal();
+                consecutive_failures = 0;
+            }
+            let alert = match result {
+                Err(e) => {
+                    tracing::error!("Auto capture failed: {}", e);
+                    record_capture_failure(&e.to_string(), window_minutes)
+                }
+                Ok(()) => {
+                    record_work_time_capture_internal();
+                    poll_capture_failures(window_minutes)
+                }
+            };
+            if let Some(alert) = alert {
+                let webhook_url = settings.and_then(|s| s.failure_alert_webhook_url.clone());
+                deliver_failure_alert(&alert_app, alert, webhook_url).await;
+            }
+        }
+    });
+
+    // Capture as soon as the user is back from idle instead of waiting for the next cycle
+    tokio::spawn(async move {
+        let _loop_guard = CAPTURE_SHUTDOWN.enter_loop();
+        let mut tracker = IdleTracker::new(IDLE_THRESHOLD);
+        loop {
+            if CAPTURE_SHUTDOWN.sleep(IDLE_POLL_INTERVAL).await {
+                break;
+            }
+            if !crate::services::capture_service::is_auto_capture_running() {
+                break;
+            }
+            let Some(idle_for) = system_idle_time() else {
+                continue;
+            };
+            if tracker.update(idle_for) != Some(IdleTransition::BecameActive) {
+                continue;
+            }
+
+            let Ok(arc) = crate::memory_storage::get_settings_sync() else {
+                continue;
+            };
+            if !arc.capture_on_idle_wake.unwrap_or(true)
+                || !should_capture_by_work_time_from_arc(&arc)
+            {
+                continue;
+            }
+            tracing::info!("User active again after being idle, capturing now");
+            if let Err(e) = trigger_auto_capture_with_arc(arc).await {
+                tracing::warn!("Idle wake capture failed: {}", e);
             }
         }
     });
@@ -101,8 +244,11 @@ pub async fn start_auto_capture(app: tauri::AppHandle) -> Result<(), String> {
     // Spawn hourly threshold evaluation task
     let app_handle = app.clone();
     tokio::spawn(async move {
+        let _loop_guard = CAPTURE_SHUTDOWN.enter_loop();
         loop {
-            tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+            if CAPTURE_SHUTDOWN.sleep(Duration::from_secs(60 * 60)).await {
+                break;
+            }
 
             if !crate::services::capture_service::is_auto_capture_running() {
                 break;
@@ -139,8 +285,8 @@ pub async fn start_auto_capture(app: tauri::AppHandle) -> Result<(), String> {
     });
 
     tracing::info!(
-        "Auto capture started with interval {} minutes",
-        interval_minutes
+        "Auto capture started with interval {} seconds",
+        interval_seconds
     );
     Ok(())
 }
@@ -162,12 +308,31 @@ pub async fn trigger_capture() -> Result<(), String> {
     trigger_capture_service().await.map_err(|e| e.to_string())
 }
 
-/// Take a screenshot and save to disk (no AI analysis).
+/// Take a screenshot without AI analysis.
 ///
+/// Returns the saved file path, or a PNG data URL without writing a file when
+/// `in_memory` is true.
 /// This is a thin command wrapper that delegates to the capture service.
 #[tauri::command]
-pub async fn take_screenshot() -> Result<String, String> {
-    take_screenshot_service().await.map_err(|e| e.to_string())
+pub async fn take_screenshot(in_memory: Option<bool>) -> Result<String, String> {
+    let destination = if in_memory.unwrap_or(false) {
+        ScreenshotDestination::Memory
+    } else {
+        ScreenshotDestination::Disk
+    };
+    take_screenshot_service(destination)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Take a screenshot for preview only: nothing is saved, a PNG data URL is returned.
+///
+/// This is a thin command wrapper that delegates to the capture service.
+#[tauri::command]
+pub async fn preview_screenshot() -> Result<String, String> {
+    take_screenshot_service(ScreenshotDestination::Memory)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Reanalyze a single record.
@@ -180,6 +345,17 @@ pub async fn reanalyze_record(record_id: i64) -> Result<ScreenAnalysis, String>
         .map_err(|e| e.to_string())
 }
 
+/// Re-run analysis on an existing record's screenshot. Same as
+/// [`reanalyze_record`], kept for frontend callers of this name.
+///
+/// This is a thin command wrapper that delegates to the capture service.
+#[tauri::command]
+pub async fn reanalyze_screenshot(record_id: i64) -> Result<ScreenAnalysis, String> {
+    reanalyze_record_service(record_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Reanalyze all records with screenshots from today.
 ///
 /// This is a thin command wrapper that delegates to the capture service.
@@ -200,6 +376,46 @@ pub async fn reanalyze_records_by_date(date: String) -> Result<ReanalyzeResult,
         .map_err(|e| e.to_string())
 }
 
+/// Event carrying a `ReanalyzeProgress` after each record of a full reanalysis.
+pub const REANALYZE_PROGRESS_EVENT: &str = "reanalyze-progress";
+/// Event carrying the final `ReanalyzeResult` of a full reanalysis.
+pub const REANALYZE_FINISHED_EVENT: &str = "reanalyze-finished";
+
+/// Reanalyze all auto records with screenshots between two dates in the background.
+///
+/// Returns the number of records queued. Requests are paced to
+/// `REANALYZE_ALL_MIN_INTERVAL`; progress and the final result are pushed as
+/// `reanalyze-progress` / `reanalyze-finished` events.
+#[tauri::command]
+pub async fn reanalyze_all(
+    app: tauri::AppHandle,
+    start_date: String,
+    end_date: String,
+) -> Result<usize, String> {
+    let guard = begin_reanalyze_all().map_err(|e| e.to_string())?;
+    let records = reanalyze_all_targets(start_date, end_date).map_err(|e| e.to_string())?;
+    let total = records.len();
+    tokio::spawn(async move {
+        let _guard = guard;
+        let result = reanalyze_records_paced(records, REANALYZE_ALL_MIN_INTERVAL, |progress| {
+            let _ = app.emit(REANALYZE_PROGRESS_EVENT, progress);
+        })
+        .await;
+        let _ = app.emit(REANALYZE_FINISHED_EVENT, &result);
+    });
+    Ok(total)
+}
+
+/// Calibrate the screen change threshold by sampling frames for `seconds`.
+///
+/// This is a thin command wrapper that delegates to the capture service.
+#[tauri::command]
+pub async fn calibrate_change_threshold(seconds: u64) -> Result<ThresholdCalibration, String> {
+    calibrate_change_threshold_service(seconds)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get quality filter statistics.
 ///
 /// This is a thin command wrapper that delegates to the capture service.
@@ -210,6 +426,16 @@ pub async fn get_quality_filter_stats() -> Result<QualityFilterStats, String> {
         .map_err(|e| e.to_string())
 }
 
+/// Get the disk space taken by saved screenshots.
+///
+/// This is a thin command wrapper that delegates to the capture service.
+#[tauri::command]
+pub async fn get_screenshot_disk_usage() -> Result<ScreenshotDiskUsage, String> {
+    get_screenshot_disk_usage_service()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Reset quality filter counter.
 ///
 /// This is a thin command wrapper that delegates to the capture service.
@@ -22,27 +22,37 @@ pub mod settings_commands;
 pub use crate::commands::model_commands::get_model_info;
 
 // Settings commands (thin wrappers delegating to services)
-pub use crate::commands::settings_commands::{get_settings, save_settings};
+pub use crate::commands::settings_commands::{
+    get_settings, get_settings_masked, get_settings_schema, get_setup_status, reload_settings,
+    save_settings,
+};
 
 // Manual entry commands
 pub use crate::manual_entry::{
-    add_quick_note, get_log_file_path, get_logs_for_export, get_recent_logs, get_screenshot,
-    list_report_files, log_frontend_error, open_obsidian_folder, read_file, tray_quick_note,
+    add_quick_note, copy_record_summary, get_log_file_path, get_logs_for_export, get_recent_logs,
+    get_screenshot, list_report_files, log_frontend_error, open_obsidian_folder, read_file,
+    tray_quick_note,
 };
 
 // Memory storage commands (records, tags) - settings moved to commands/settings_commands.rs
 pub use crate::memory_storage::{
     add_tag_to_record,
+    confirm_record,
+    confirm_records,
     create_manual_tag,
     delete_manual_tag,
     delete_record,
+    get_all_keywords,
     get_all_manual_tags,
     get_all_tags,
     // Tags
     get_default_tag_categories,
+    get_heatmap,
     get_history_records,
     get_history_records_cursor,
+    get_overview_stats,
     get_productivity_trend,
+    get_project_summary,
     get_records_by_date_range,
     get_records_by_manual_tags,
     get_records_by_tag,
@@ -55,7 +65,9 @@ pub use crate::memory_storage::{
     get_today_stats,
     remove_tag_from_record,
     search_records,
+    tag_records,
     update_manual_tag,
+    update_record,
     update_record_user_notes,
 };
 
@@ -67,24 +79,32 @@ pub use crate::commands::session_commands::{
 // Capture commands (thin wrappers delegating to services)
 #[cfg(feature = "screenshot")]
 pub use crate::commands::capture_commands::{
-    get_auto_capture_status, get_default_analysis_prompt, get_quality_filter_stats,
-    get_work_time_status, reanalyze_record, reanalyze_records_by_date, reanalyze_today_records,
-    reset_quality_filter_counter, start_auto_capture, stop_auto_capture, take_screenshot,
-    trigger_capture,
+    calibrate_change_threshold, estimate_request_tokens, get_auto_capture_status,
+    get_default_analysis_prompt, get_quality_filter_stats, get_screenshot_disk_usage,
+    get_work_time_status, reanalyze_all, reanalyze_record, reanalyze_records_by_date,
+    reanalyze_screenshot, reanalyze_today_records, reset_quality_filter_counter,
+    start_auto_capture, stop_auto_capture, take_screenshot, trigger_capture,
 };
 
 // Report commands (thin wrappers delegating to services)
 pub use crate::commands::report_commands::{
     compare_reports, generate_custom_report, generate_daily_summary, generate_monthly_report,
-    generate_multilingual_daily_summary, generate_weekly_report, get_default_summary_prompt,
-    get_supported_languages,
+    generate_multilingual_daily_summary, generate_range_summary, generate_weekly_report,
+    get_default_summary_prompt, get_supported_languages, save_daily_summary,
 };
 
 // Export commands
 pub use crate::export::{
-    export_records, get_default_export_template, get_default_record_entry_template, open_export_dir,
+    export_records, export_to_sqlite, get_default_export_template,
+    get_default_record_entry_template, open_export_dir,
 };
 
+// Import commands
+pub use crate::import::{import_records_csv, import_records_json};
+
+// Data directory commands
+pub use crate::data_dir::migrate_data_dir;
+
 // Backup commands
 pub use crate::backup::{
     create_backup, delete_backup, get_backup_info, list_backups, restore_backup,
@@ -117,7 +137,9 @@ pub use crate::offline_queue::{
 pub use crate::monitor::get_monitors;
 
 // Timeline commands
-pub use crate::timeline::{get_timeline_for_date, get_timeline_for_range, get_timeline_today};
+pub use crate::timeline::{
+    get_coverage_gaps, get_timeline_for_date, get_timeline_for_range, get_timeline_today,
+};
 
 // Auto backup scheduler commands
 pub use crate::auto_backup_scheduler::trigger_auto_backup;
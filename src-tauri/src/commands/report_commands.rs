@@ -13,9 +13,10 @@
 use crate::services::report_service::{
     compare_reports_service, generate_custom_report_service, generate_daily_summary_service,
     generate_monthly_report_service, generate_multilingual_daily_summary_service,
-    generate_weekly_report_service,
+    generate_range_summary_service, generate_weekly_report_service,
     get_default_summary_prompt as get_default_summary_prompt_service,
-    get_supported_languages as get_supported_languages_service,
+    get_supported_languages as get_supported_languages_service, open_summary_after_generate,
+    save_daily_summary_service,
 };
 
 /// Get the list of supported languages for multilingual reports
@@ -40,11 +41,27 @@ pub fn get_default_summary_prompt() -> String {
 ///
 /// # Arguments
 /// * `vault_name` - Optional vault name to use. If None, uses default vault or auto-detection.
+/// * `dry_run` - When true, returns the generated text without writing it (defaults to false).
 #[tauri::command]
-pub async fn generate_daily_summary(vault_name: Option<String>) -> Result<String, String> {
-    generate_daily_summary_service(vault_name)
+pub async fn generate_daily_summary(
+    app: tauri::AppHandle,
+    vault_name: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<String, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let result = generate_daily_summary_service(vault_name, dry_run)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    open_summary_after_generate(&app, &result, dry_run);
+    Ok(result)
+}
+
+/// Save a daily summary previewed with `dry_run` to Obsidian.
+///
+/// This is a thin command wrapper that delegates to the report service.
+#[tauri::command]
+pub fn save_daily_summary(content: String, vault_name: Option<String>) -> Result<String, String> {
+    save_daily_summary_service(content, vault_name).map_err(|e| e.to_string())
 }
 
 /// Generate multilingual daily summary - DATA-007
@@ -92,6 +109,20 @@ pub async fn generate_custom_report(
         .map_err(|e| e.to_string())
 }
 
+/// Generate one summary file for the records between two dates
+///
+/// This is a thin command wrapper that delegates to the report service.
+#[tauri::command]
+pub async fn generate_range_summary(
+    start: String,
+    end: String,
+    dest_path: String,
+) -> Result<String, String> {
+    generate_range_summary_service(start, end, dest_path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Generate comparison report between two time periods - REPORT-004
 ///
 /// This is a thin command wrapper that delegates to the report service.
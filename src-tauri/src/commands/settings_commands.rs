@@ -7,7 +7,13 @@
 use std::sync::Arc;
 
 use crate::memory_storage::Settings;
-use crate::services::settings_service::{get_settings_service, save_settings_service};
+use crate::services::prompt_migration::{check_custom_prompts, PromptNotice};
+use crate::services::settings_schema::{settings_schema, SettingFieldSchema};
+use crate::services::settings_service::{
+    get_settings_masked_service, get_settings_service, reload_settings_service,
+    save_settings_service,
+};
+use crate::services::setup_service::{get_setup_status_service, SetupStatus};
 
 /// Get application settings
 ///
@@ -21,6 +27,15 @@ pub async fn get_settings() -> Result<Settings, String> {
         .map_err(|e| e.to_string())
 }
 
+/// Get application settings with API keys and passwords masked, for display
+///
+/// This is a thin command wrapper that delegates to the settings service.
+/// Code that needs the real keys reads them on the backend.
+#[tauri::command]
+pub async fn get_settings_masked() -> Result<Settings, String> {
+    get_settings_masked_service().map_err(|e| e.to_string())
+}
+
 /// Save application settings
 ///
 /// This is a thin command wrapper that delegates to the settings service.
@@ -29,3 +44,40 @@ pub async fn get_settings() -> Result<Settings, String> {
 pub async fn save_settings(settings: Settings) -> Result<(), String> {
     save_settings_service(&settings).map_err(|e| e.to_string())
 }
+
+/// Get the type, default and range of every settings field, for form validation
+///
+/// This is a thin command wrapper that delegates to the settings schema.
+#[tauri::command]
+pub fn get_settings_schema() -> Vec<SettingFieldSchema> {
+    settings_schema()
+}
+
+/// Get which first-run setup steps are done
+///
+/// This is a thin command wrapper that delegates to the setup service.
+#[tauri::command]
+pub async fn get_setup_status() -> Result<SetupStatus, String> {
+    get_setup_status_service().map_err(|e| e.to_string())
+}
+
+/// Reload settings from the database, discarding the in-memory cache
+///
+/// This is a thin command wrapper that delegates to the settings service.
+/// Useful when the database was modified by another instance.
+#[tauri::command]
+pub async fn reload_settings() -> Result<Settings, String> {
+    reload_settings_service()
+        .map(|arc: Arc<Settings>| Arc::try_unwrap(arc).unwrap_or_else(|arc| (*arc).clone()))
+        .map_err(|e| e.to_string())
+}
+
+/// List custom prompts missing a placeholder they need, so the user can update them
+///
+/// This is a thin command wrapper that delegates to the prompt migration service.
+#[tauri::command]
+pub async fn get_prompt_notices() -> Result<Vec<PromptNotice>, String> {
+    get_settings_service()
+        .map(|settings| check_custom_prompts(&settings))
+        .map_err(|e| e.to_string())
+}
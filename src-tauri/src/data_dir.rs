@@ -0,0 +1,514 @@
+//! Moving the application data to another directory.
+//!
+//! `migrate_data_dir` copies the database, screenshots, logs and every other
+//! file of the current data root to a new directory, verifies the copy and
+//! then writes a redirect file into the default data root. The redirect is
+//! read once at startup, so the app keeps using the old location until it is
+//! restarted. The old data is left in place.
+//!
+//! Captures are paused while the data is copied and stay off until the
+//! restart, so no record is written to the old database after its snapshot.
+//! A failed migration removes what it wrote to the new directory.
+
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::errors::{AppError, AppResult};
+use crate::memory_storage::DB_CONNECTION;
+
+/// Name of the redirect file inside the default data root.
+pub const DATA_DIR_REDIRECT_FILE: &str = "data_dir_redirect";
+
+/// Result of a data directory migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDirMigration {
+    pub new_dir: String,
+    pub record_count: i64,
+    /// Files copied besides the database
+    pub files_copied: usize,
+    pub bytes_copied: u64,
+    /// The new directory is used after the next start
+    pub restart_required: bool,
+}
+
+/// Default data root: `<system_data_dir>/DailyLogger`.
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("DailyLogger")
+}
+
+/// Path of the redirect file written by `migrate_data_dir`.
+pub fn redirect_file_path() -> PathBuf {
+    default_data_dir().join(DATA_DIR_REDIRECT_FILE)
+}
+
+/// Data root named by a redirect file, if the file exists and is not empty.
+pub fn read_redirect(redirect_file: &Path) -> Option<PathBuf> {
+    let target = std::fs::read_to_string(redirect_file).ok()?;
+    let target = target.trim();
+    (!target.is_empty()).then(|| PathBuf::from(target))
+}
+
+/// Point the redirect file at `new_dir`.
+pub fn write_redirect(redirect_file: &Path, new_dir: &Path) -> AppResult<()> {
+    if let Some(parent) = redirect_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(redirect_file, new_dir.to_string_lossy().as_bytes())?;
+    Ok(())
+}
+
+static REDIRECTED_DATA_DIR: Lazy<Option<PathBuf>> =
+    Lazy::new(|| read_redirect(&redirect_file_path()));
+
+/// Data root chosen by a previous migration, read once per process.
+pub fn redirected_data_dir() -> Option<PathBuf> {
+    REDIRECTED_DATA_DIR.clone()
+}
+
+/// Copy a directory tree, skipping the top-level entries named in `skip`.
+/// Returns the number of files and bytes copied.
+fn copy_tree(src: &Path, dst: &Path, skip: &[&str]) -> AppResult<(usize, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    if !src.exists() {
+        return Ok((files, bytes));
+    }
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if skip.iter().any(|s| name == *s) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            let (f, b) = copy_tree(&path, &dst.join(&name), &[])?;
+            files += f;
+            bytes += b;
+        } else if path.is_file() {
+            bytes += std::fs::copy(&path, dst.join(&name))?;
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// Number of files and bytes in a directory tree, skipping top-level `skip` entries.
+/// A missing directory counts as empty.
+pub fn measure_tree(dir: &Path, skip: &[&str]) -> AppResult<(usize, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    if !dir.exists() {
+        return Ok((files, bytes));
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if skip.iter().any(|s| entry.file_name() == *s) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            let (f, b) = measure_tree(&path, &[])?;
+            files += f;
+            bytes += b;
+        } else if path.is_file() {
+            bytes += entry.metadata()?.len();
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+fn record_count(conn: &Connection) -> AppResult<i64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM records", [], |row| row.get(0))?)
+}
+
+/// Check that `dst_root` can receive the data of `src_root`: it must be
+/// missing or empty, so a failed migration can remove everything in it.
+fn validate_migration_target(src_root: &Path, dst_root: &Path) -> AppResult<PathBuf> {
+    if dst_root.starts_with(src_root) || src_root.starts_with(dst_root) {
+        return Err(AppError::validation(
+            "The new data directory must not contain or be inside the current one",
+        ));
+    }
+    let dst_db = dst_root.join("data").join("local.db");
+    if dst_db.exists() {
+        return Err(AppError::validation(format!(
+            "A database already exists at {}",
+            dst_db.display()
+        )));
+    }
+    if dst_root.exists() && std::fs::read_dir(dst_root)?.next().is_some() {
+        return Err(AppError::validation(format!(
+            "The new data directory {} is not empty",
+            dst_root.display()
+        )));
+    }
+    Ok(dst_db)
+}
+
+/// Remove what a failed migration wrote to `dst_root`, which was missing
+/// (`created`) or empty before. Failures are logged.
+fn remove_partial_target(dst_root: &Path, created: bool) {
+    if let Err(e) = std::fs::remove_dir_all(dst_root) {
+        tracing::error!(
+            "Failed to remove partial migration target {:?}: {}",
+            dst_root,
+            e
+        );
+        return;
+    }
+    if !created {
+        if let Err(e) = std::fs::create_dir(dst_root) {
+            tracing::warn!("Failed to recreate data directory {:?}: {}", dst_root, e);
+        }
+    }
+}
+
+/// Write a consistent snapshot of the live database to the new data root
+/// with `VACUUM INTO`. Returns the number of records in the snapshot.
+///
+/// This is the only step that needs the database connection.
+pub fn snapshot_database(conn: &Connection, src_root: &Path, dst_root: &Path) -> AppResult<i64> {
+    let dst_db = validate_migration_target(src_root, dst_root)?;
+    std::fs::create_dir_all(dst_root.join("data"))?;
+    conn.execute(
+        "VACUUM INTO ?1",
+        rusqlite::params![dst_db.to_string_lossy()],
+    )?;
+    record_count(conn)
+}
+
+/// Point screenshot paths under `src_root` at the same files under `dst_root`.
+/// Returns the number of records updated.
+fn rewrite_screenshot_paths(
+    conn: &Connection,
+    src_root: &Path,
+    dst_root: &Path,
+) -> AppResult<usize> {
+    // The separator keeps a sibling such as `DailyLogger2` from matching
+    let src_prefix = format!(
+        "{}{}",
+        src_root.to_string_lossy(),
+        std::path::MAIN_SEPARATOR
+    );
+    let dst_prefix = format!(
+        "{}{}",
+        dst_root.to_string_lossy(),
+        std::path::MAIN_SEPARATOR
+    );
+    Ok(conn.execute(
+        "UPDATE records SET screenshot_path = ?2 || substr(screenshot_path, length(?1) + 1)
+         WHERE substr(screenshot_path, 1, length(?1)) = ?1",
+        rusqlite::params![src_prefix, dst_prefix],
+    )?)
+}
+
+/// Copy the files of `src_root` next to the database snapshot in `dst_root`
+/// and verify the copy.
+///
+/// Screenshot paths in the copied database are rewritten to the new root. The
+/// copy is accepted when the new database passes `integrity_check` with
+/// `expected_records` records and the copied files match the source in number
+/// and size. Logs are copied but not compared, since the running app keeps
+/// appending to them.
+pub fn migrate_data_files(
+    src_root: &Path,
+    dst_root: &Path,
+    expected_records: i64,
+) -> AppResult<DataDirMigration> {
+    // The database directory holds the snapshot
+    const SKIP: &[&str] = &["data", DATA_DIR_REDIRECT_FILE];
+    const SKIP_VERIFY: &[&str] = &["data", DATA_DIR_REDIRECT_FILE, "logs"];
+    let (files_copied, bytes_copied) = copy_tree(src_root, dst_root, SKIP)?;
+
+    let copied = Connection::open(dst_root.join("data").join("local.db"))?;
+    let rewritten = rewrite_screenshot_paths(&copied, src_root, dst_root)?;
+    tracing::debug!("Rewrote screenshot paths of {} records", rewritten);
+
+    let integrity: String = copied.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(AppError::database(format!(
+            "Copied database failed integrity check: {}",
+            integrity
+        )));
+    }
+    let copied_records = record_count(&copied)?;
+    if copied_records != expected_records {
+        return Err(AppError::database(format!(
+            "Copied database has {} records, expected {}",
+            copied_records, expected_records
+        )));
+    }
+    if measure_tree(dst_root, SKIP_VERIFY)? != measure_tree(src_root, SKIP_VERIFY)? {
+        return Err(AppError::file_io(
+            "Copied files do not match the current data directory",
+        ));
+    }
+
+    Ok(DataDirMigration {
+        new_dir: dst_root.to_string_lossy().to_string(),
+        record_count: copied_records,
+        files_copied,
+        bytes_copied,
+        restart_required: true,
+    })
+}
+
+/// Snapshot the database with `snapshot`, then copy and verify the files of
+/// `src_root`. On any failure the partial copy in `dst_root` is removed, so
+/// the migration can be retried.
+pub fn migrate_into(
+    src_root: &Path,
+    dst_root: &Path,
+    snapshot: impl FnOnce(&Path, &Path) -> AppResult<i64>,
+) -> AppResult<DataDirMigration> {
+    validate_migration_target(src_root, dst_root)?;
+    let created = !dst_root.exists();
+    let result = snapshot(src_root, dst_root)
+        .and_then(|expected_records| migrate_data_files(src_root, dst_root, expected_records));
+    if result.is_err() {
+        remove_partial_target(dst_root, created);
+    }
+    result
+}
+
+/// Tauri command: copy all data to `new_dir` and use it from the next start
+#[command]
+pub async fn migrate_data_dir(new_dir: String) -> Result<DataDirMigration, String> {
+    if let Some(overridden) = crate::data_dir_override() {
+        return Err(format!(
+            "The data directory is set by {} ({}); change that variable instead of migrating",
+            crate::DATA_DIR_ENV,
+            overridden.display()
+        ));
+    }
+    let new_dir = PathBuf::from(new_dir.trim());
+    if !new_dir.is_absolute() {
+        return Err("The new data directory must be an absolute path".to_string());
+    }
+    let current = crate::get_app_data_dir();
+
+    // Records captured after the snapshot would only reach the old database
+    #[cfg(feature = "screenshot")]
+    let _capture_pause = crate::services::capture_service::pause_capture().await;
+
+    // Only the snapshot holds the database lock; the app keeps working while files are copied
+    let migration = {
+        let (current, new_dir) = (current.clone(), new_dir.clone());
+        tokio::task::spawn_blocking(move || {
+            migrate_into(&current, &new_dir, |src_root, dst_root| {
+                let db = DB_CONNECTION.lock()?;
+                let conn = db
+                    .as_ref()
+                    .ok_or_else(|| AppError::database("Database not initialized"))?;
+                snapshot_database(conn, src_root, dst_root)
+            })
+        })
+        .await
+        .map_err(|e| format!("Data directory migration task failed: {}", e))?
+        .map_err(|e| e.to_string())?
+    };
+
+    write_redirect(&redirect_file_path(), &new_dir).map_err(|e| e.to_string())?;
+    #[cfg(feature = "screenshot")]
+    crate::services::capture_service::suspend_capture_until_restart();
+    tracing::info!(
+        "Migrated data directory {:?} -> {:?} ({} records, {} files); active after restart",
+        current,
+        new_dir,
+        migration.record_count,
+        migration.files_copied
+    );
+    Ok(migration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_source_root(root: &Path) -> Connection {
+        std::fs::create_dir_all(root.join("data")).unwrap();
+        std::fs::create_dir_all(root.join("screenshots").join("failed")).unwrap();
+        std::fs::create_dir_all(root.join("logs")).unwrap();
+        std::fs::write(root.join("screenshots").join("a.png"), b"png").unwrap();
+        std::fs::write(
+            root.join("screenshots").join("failed").join("b.png"),
+            b"png2",
+        )
+        .unwrap();
+        std::fs::write(root.join("logs").join("app.log"), b"log line").unwrap();
+        std::fs::write(root.join(".key"), b"secret key").unwrap();
+
+        let conn = Connection::open(root.join("data").join("local.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE records (id INTEGER PRIMARY KEY, timestamp TEXT NOT NULL, source_type TEXT NOT NULL, content TEXT NOT NULL, screenshot_path TEXT);
+             INSERT INTO records (timestamp, source_type, content) VALUES ('2026-03-14T01:00:00+00:00', 'manual', '迁移前的记录');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO records (timestamp, source_type, content, screenshot_path) VALUES ('2026-03-14T02:00:00+00:00', 'auto', '{}', ?1)",
+            rusqlite::params![root.join("screenshots").join("a.png").to_string_lossy()],
+        )
+        .unwrap();
+        conn
+    }
+
+    /// Snapshot the database and copy the files, as `migrate_data_dir` does.
+    fn migrate(conn: &Connection, src_root: &Path, dst_root: &Path) -> AppResult<DataDirMigration> {
+        migrate_into(src_root, dst_root, |src, dst| {
+            snapshot_database(conn, src, dst)
+        })
+    }
+
+    #[test]
+    fn migrated_records_can_be_queried_from_new_dir() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let dst_root = dst.path().join("DailyLogger");
+        let conn = create_source_root(src.path());
+
+        let migration = migrate(&conn, src.path(), &dst_root).unwrap();
+        assert_eq!(migration.record_count, 2);
+        assert_eq!(migration.files_copied, 4);
+        assert!(migration.restart_required);
+
+        let copied = Connection::open(dst_root.join("data").join("local.db")).unwrap();
+        let content: String = copied
+            .query_row(
+                "SELECT content FROM records WHERE source_type = 'manual'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(content, "迁移前的记录");
+        assert_eq!(
+            std::fs::read(dst_root.join("screenshots").join("failed").join("b.png")).unwrap(),
+            b"png2"
+        );
+        assert_eq!(std::fs::read(dst_root.join(".key")).unwrap(), b"secret key");
+
+        // Migrating onto an existing database is refused
+        assert!(migrate(&conn, src.path(), &dst_root).is_err());
+    }
+
+    #[test]
+    fn failed_migration_removes_the_partial_copy() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let conn = create_source_root(src.path());
+
+        // A record count mismatch fails verification after everything was copied
+        let failed = migrate_into(src.path(), dst.path(), |src_root, dst_root| {
+            snapshot_database(&conn, src_root, dst_root).map(|count| count + 1)
+        });
+        assert!(failed.is_err());
+        assert!(dst.path().is_dir());
+        assert_eq!(std::fs::read_dir(dst.path()).unwrap().count(), 0);
+
+        // The retry is not blocked by leftovers
+        assert_eq!(
+            migrate(&conn, src.path(), dst.path()).unwrap().record_count,
+            2
+        );
+    }
+
+    #[test]
+    fn migration_into_non_empty_dir_is_rejected() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let conn = create_source_root(src.path());
+        std::fs::write(dst.path().join("notes.txt"), b"keep me").unwrap();
+
+        assert!(migrate(&conn, src.path(), dst.path()).is_err());
+        assert_eq!(
+            std::fs::read(dst.path().join("notes.txt")).unwrap(),
+            b"keep me"
+        );
+    }
+
+    #[test]
+    fn migration_into_current_dir_is_rejected() {
+        let src = tempfile::tempdir().unwrap();
+        let conn = create_source_root(src.path());
+        assert!(migrate(&conn, src.path(), &src.path().join("moved")).is_err());
+    }
+
+    #[test]
+    fn screenshot_paths_point_into_the_new_dir() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let dst_root = dst.path().join("DailyLogger");
+        let conn = create_source_root(src.path());
+        // A sibling directory sharing the name prefix is left alone
+        let sibling = format!("{}2/c.png", src.path().to_string_lossy());
+        conn.execute(
+            "INSERT INTO records (timestamp, source_type, content, screenshot_path) VALUES ('2026-03-14T03:00:00+00:00', 'auto', '{}', ?1)",
+            rusqlite::params![sibling],
+        )
+        .unwrap();
+
+        migrate(&conn, src.path(), &dst_root).unwrap();
+
+        let copied = Connection::open(dst_root.join("data").join("local.db")).unwrap();
+        let paths: Vec<String> = copied
+            .prepare(
+                "SELECT screenshot_path FROM records WHERE screenshot_path IS NOT NULL ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let moved = dst_root.join("screenshots").join("a.png");
+        assert_eq!(paths, vec![moved.to_string_lossy().to_string(), sibling]);
+        assert!(moved.is_file());
+        // The source database is unchanged
+        let original: String = conn
+            .query_row(
+                "SELECT screenshot_path FROM records WHERE id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(Path::new(&original).starts_with(src.path()));
+    }
+
+    #[test]
+    fn measure_tree_counts_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("2026").join("03")).unwrap();
+        std::fs::write(dir.path().join("a.png"), b"12345").unwrap();
+        std::fs::write(dir.path().join("2026").join("03").join("b.png"), b"123").unwrap();
+        std::fs::create_dir_all(dir.path().join("empty")).unwrap();
+
+        assert_eq!(measure_tree(dir.path(), &[]).unwrap(), (2, 8));
+        assert_eq!(measure_tree(dir.path(), &["2026"]).unwrap(), (1, 5));
+        assert_eq!(
+            measure_tree(&dir.path().join("missing"), &[]).unwrap(),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn redirect_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let redirect = dir.path().join(DATA_DIR_REDIRECT_FILE);
+        assert_eq!(read_redirect(&redirect), None);
+
+        write_redirect(&redirect, Path::new("/data/DailyLogger")).unwrap();
+        assert_eq!(
+            read_redirect(&redirect),
+            Some(PathBuf::from("/data/DailyLogger"))
+        );
+
+        std::fs::write(&redirect, "  \n").unwrap();
+        assert_eq!(read_redirect(&redirect), None);
+    }
+}
@@ -0,0 +1,125 @@
+//! Debug dumps of failed screen analysis requests.
+//!
+//! When `debug_dump_failed_requests` is enabled, every failed analysis writes
+//! a directory under `<app data>/debug/` containing the request body, the
+//! screenshot and the error, so the failure can be attached to a bug report.
+//! API keys only travel in request headers and are never written; the inline
+//! base64 image is replaced by a reference to `screenshot.png`.
+
+use std::path::{Path, PathBuf};
+
+use crate::errors::AppResult;
+
+/// File name of the screenshot inside a dump directory.
+pub const DUMP_SCREENSHOT_FILE: &str = "screenshot.png";
+
+/// Directory receiving failed request dumps.
+pub fn get_debug_dir() -> PathBuf {
+    crate::get_app_data_dir().join("debug")
+}
+
+/// Copy of a chat completion payload with inline `data:` image URLs replaced
+/// by a reference to the dumped screenshot file.
+pub fn redact_payload_images(payload: &serde_json::Value) -> serde_json::Value {
+    match payload {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(key, value)| match value.as_str() {
+                    Some(url) if key == "url" && url.starts_with("data:") => (
+                        key.clone(),
+                        serde_json::Value::String(DUMP_SCREENSHOT_FILE.to_string()),
+                    ),
+                    _ => (key.clone(), redact_payload_images(value)),
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(redact_payload_images).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+/// Write a dump of a failed analysis request into a new directory below
+/// `debug_dir` and return that directory.
+///
+/// Contains `request.json` (payload without image data, plus endpoint and
+/// error), `screenshot.png` and `error.txt`.
+pub fn dump_failed_request(
+    debug_dir: &Path,
+    endpoint: &str,
+    payload: &serde_json::Value,
+    image_base64: &str,
+    error: &str,
+) -> AppResult<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S%.3f");
+    let dump_dir = debug_dir.join(format!("failed_request_{}", stamp));
+    std::fs::create_dir_all(&dump_dir)?;
+
+    let request = serde_json::json!({
+        "endpoint": endpoint,
+        "error": error,
+        "payload": redact_payload_images(payload),
+    });
+    std::fs::write(
+        dump_dir.join("request.json"),
+        serde_json::to_string_pretty(&request)?,
+    )?;
+
+    let image = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, image_base64)?;
+    std::fs::write(dump_dir.join(DUMP_SCREENSHOT_FILE), image)?;
+    std::fs::write(dump_dir.join("error.txt"), error)?;
+
+    Ok(dump_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_writes_request_screenshot_and_error_without_image_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let image_base64 =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, b"png bytes");
+        let payload = serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [{
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "analyze"},
+                    {"type": "image_url", "image_url": {"url": format!("data:image/png;base64,{}", image_base64)}}
+                ]
+            }]
+        });
+
+        let dump_dir = dump_failed_request(
+            dir.path(),
+            "https://api.example.com/v1/chat/completions",
+            &payload,
+            &image_base64,
+            "API request failed with status 500",
+        )
+        .unwrap();
+
+        let request: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dump_dir.join("request.json")).unwrap())
+                .unwrap();
+        assert_eq!(request["payload"]["model"], "gpt-4o");
+        assert_eq!(
+            request["payload"]["messages"][0]["content"][1]["image_url"]["url"],
+            DUMP_SCREENSHOT_FILE
+        );
+        assert_eq!(request["error"], "API request failed with status 500");
+        assert!(!request.to_string().contains(&image_base64));
+
+        assert_eq!(
+            std::fs::read(dump_dir.join(DUMP_SCREENSHOT_FILE)).unwrap(),
+            b"png bytes"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dump_dir.join("error.txt")).unwrap(),
+            "API request failed with status 500"
+        );
+    }
+}
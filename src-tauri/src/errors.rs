@@ -6,6 +6,7 @@
 //! Error codes are serialized alongside the message, allowing frontend
 //! to handle errors programmatically rather than by string pattern matching.
 
+use crate::i18n::{self, Lang, MessageKey};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -69,6 +70,9 @@ pub struct AppError {
     /// Additional context about the error (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
+    /// i18n key of the message when it came from the message table (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_key: Option<String>,
 }
 
 impl AppError {
@@ -78,6 +82,7 @@ impl AppError {
             code,
             message: message.into(),
             context: None,
+            message_key: None,
         }
     }
 
@@ -91,6 +96,7 @@ impl AppError {
             code,
             message: message.into(),
             context: Some(context.into()),
+            message_key: None,
         }
     }
 
@@ -138,6 +144,26 @@ impl AppError {
     pub fn timeout(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::Timeout, message)
     }
+
+    /// Create an error whose message is looked up by i18n key in the given language
+    pub fn localized(code: ErrorCode, key: MessageKey, lang: Lang) -> Self {
+        Self::localized_with(code, key, lang, &[])
+    }
+
+    /// Create a localized error, filling `{name}` placeholders in the message
+    pub fn localized_with(
+        code: ErrorCode,
+        key: MessageKey,
+        lang: Lang,
+        args: &[(&str, &str)],
+    ) -> Self {
+        Self {
+            code,
+            message: i18n::message_with(key, lang, args),
+            context: None,
+            message_key: Some(key.as_str().to_string()),
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -265,6 +291,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_localized_error_depends_on_language() {
+        let en = AppError::localized(ErrorCode::Auth, MessageKey::ApiKeyNotConfigured, Lang::En);
+        let zh = AppError::localized(ErrorCode::Auth, MessageKey::ApiKeyNotConfigured, Lang::ZhCn);
+
+        assert_ne!(en.message, zh.message);
+        assert_eq!(zh.message, "API 密钥未配置，请在设置中配置");
+        assert_eq!(
+            en.message_key.as_deref(),
+            Some("error.api_key_not_configured")
+        );
+        assert_eq!(en.message_key, zh.message_key);
+
+        let json = serde_json::to_string(&en).unwrap();
+        assert!(json.contains("\"message_key\":\"error.api_key_not_configured\""));
+    }
+
     #[test]
     fn test_from_string() {
         let err: AppError = "some error".into();
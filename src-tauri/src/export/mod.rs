@@ -1,17 +1,44 @@
-use crate::errors::AppResult;
+pub mod pdf;
+pub mod progress;
+
+use crate::errors::{AppError, AppResult};
 use crate::memory_storage::{self, Record};
+use progress::{CancellationToken, ExportProgress, ExportProgressReporter};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::command;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{command, Emitter};
 
 /// Export request parameters
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportRequest {
     pub start_date: String, // YYYY-MM-DD (local timezone)
     pub end_date: String,   // YYYY-MM-DD (local timezone)
-    pub format: String,     // "json" | "markdown"
+    pub format: String,     // "json" | "markdown" | "csv"
     #[serde(default)]
     pub custom_template: Option<String>, // Optional custom template for markdown format
+    #[serde(default)]
+    pub tags: Vec<String>, // Only export records carrying any of these tags
+    #[serde(default)]
+    pub project: Option<String>, // Only export records of this project
+    #[serde(default)]
+    pub split_by: SplitBy, // Markdown only: one file per day or week
+    #[serde(default)]
+    pub export_id: Option<String>, // Caller-chosen id for progress events and cancel_export
+}
+
+/// How a Markdown export is split into files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitBy {
+    /// A single file
+    #[default]
+    None,
+    /// One file per local date
+    Day,
+    /// One file per ISO week (Monday to Sunday)
+    Week,
 }
 
 /// Export result
@@ -24,10 +51,7 @@ pub struct ExportResult {
 
 /// Get the export directory path
 pub fn get_export_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("DailyLogger")
-        .join("exports")
+    crate::get_app_data_dir().join("exports")
 }
 
 /// Export records as JSON string
@@ -60,6 +84,149 @@ pub fn export_to_json(records: &[Record], start_date: &str, end_date: &str) -> A
     Ok(serde_json::to_string_pretty(&output)?)
 }
 
+/// Analysis fields of an auto record, expanded into separate CSV columns.
+#[derive(Debug, Default, PartialEq)]
+pub struct AutoRecordFields {
+    pub current_focus: String,
+    pub active_software: String,
+    pub context_keywords: String,
+}
+
+/// Expand `current_focus` / `active_software` / `context_keywords` from an auto
+/// record's JSON content. Fields that are missing or unparseable are left empty.
+pub fn expand_auto_fields(content: &str) -> AutoRecordFields {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(content) else {
+        return AutoRecordFields::default();
+    };
+    let text = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+    let context_keywords = value["context_keywords"]
+        .as_array()
+        .map(|keywords| {
+            keywords
+                .iter()
+                .filter_map(|k| k.as_str())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+        .unwrap_or_default();
+
+    AutoRecordFields {
+        current_focus: text("current_focus"),
+        active_software: text("active_software"),
+        context_keywords,
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Export records as CSV. Auto records get their analysis fields expanded into
+/// separate columns so the export can be analyzed in a spreadsheet.
+/// Starts with a UTF-8 BOM so Excel detects the encoding.
+pub fn export_to_csv(records: &[Record]) -> String {
+    let mut csv = String::from(
+        "\u{FEFF}id,timestamp,source_type,current_focus,active_software,context_keywords,content,screenshot_path\r\n",
+    );
+    for record in records {
+        let fields = if record.source_type == "auto" {
+            expand_auto_fields(&record.content)
+        } else {
+            AutoRecordFields::default()
+        };
+        let row = [
+            record.id.to_string(),
+            record.timestamp.clone(),
+            record.source_type.clone(),
+            fields.current_focus,
+            fields.active_software,
+            fields.context_keywords,
+            record.content.clone(),
+            record.screenshot_path.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quote a string as a YAML double-quoted scalar.
+/// JSON string escaping is a subset of YAML double-quoted escaping.
+fn yaml_quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+/// Build Obsidian YAML frontmatter with date, tags, record_count and source_breakdown.
+/// A range spanning several days also gets an `end_date`.
+///
+/// All string values are double-quoted so arbitrary tag text stays valid YAML
+/// and can be queried with Dataview.
+pub fn build_obsidian_frontmatter(
+    date: &str,
+    end_date: &str,
+    tags: &[String],
+    record_count: usize,
+    source_breakdown: &BTreeMap<String, usize>,
+) -> String {
+    let mut fm = String::from("---\n");
+    fm.push_str(&format!("date: {}\n", yaml_quote(date)));
+    if end_date != date {
+        fm.push_str(&format!("end_date: {}\n", yaml_quote(end_date)));
+    }
+
+    if tags.is_empty() {
+        fm.push_str("tags: []\n");
+    } else {
+        fm.push_str("tags:\n");
+        for tag in tags {
+            fm.push_str(&format!("  - {}\n", yaml_quote(tag)));
+        }
+    }
+
+    fm.push_str(&format!("record_count: {}\n", record_count));
+
+    if source_breakdown.is_empty() {
+        fm.push_str("source_breakdown: {}\n");
+    } else {
+        fm.push_str("source_breakdown:\n");
+        for (source, count) in source_breakdown {
+            fm.push_str(&format!("  {}: {}\n", yaml_quote(source), count));
+        }
+    }
+
+    fm.push_str("---\n");
+    fm
+}
+
+/// Build Obsidian frontmatter for the records of `date`..=`end_date`.
+/// Tags are collected from stored tags and AI tags; records are counted per source type.
+pub fn frontmatter_for_records(date: &str, end_date: &str, records: &[Record]) -> String {
+    let mut tags: Vec<String> = Vec::new();
+    let mut source_breakdown: BTreeMap<String, usize> = BTreeMap::new();
+
+    for record in records {
+        for tag in memory_storage::extract_tags_from_record_sources(
+            record.tags.as_deref(),
+            &record.content,
+        ) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        *source_breakdown
+            .entry(record.source_type.clone())
+            .or_insert(0) += 1;
+    }
+
+    build_obsidian_frontmatter(date, end_date, &tags, records.len(), &source_breakdown)
+}
+
 /// Default markdown export template
 const DEFAULT_EXPORT_TEMPLATE: &str = r#"## {{date}}
 
@@ -144,7 +311,7 @@ pub fn export_to_markdown_with_template(
     custom_template: Option<&str>,
 ) -> AppResult<String> {
     let exported_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
-    let mut md = String::new();
+    let mut md = frontmatter_for_records(start_date, end_date, records);
 
     md.push_str("# DailyLogger 数据导出\n\n");
     md.push_str(&format!("导出时间: {}\n", exported_at));
@@ -211,6 +378,8 @@ pub fn export_to_markdown_with_template(
                     user_notes: None,
                     session_id: None,
                     analysis_status: None,
+                    metadata: None,
+                    status: None,
                 },
                 template,
             );
@@ -245,6 +414,180 @@ pub fn export_to_markdown_with_template(
     Ok(md)
 }
 
+/// Local date of a record, `None` when its timestamp cannot be parsed.
+fn record_local_date(record: &Record) -> Option<chrono::NaiveDate> {
+    chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Local).date_naive())
+}
+
+/// Group key of a date: `YYYY-MM-DD` per day, `YYYY-Www` (ISO week) per week.
+pub fn split_key(date: chrono::NaiveDate, split_by: SplitBy) -> String {
+    use chrono::Datelike;
+    match split_by {
+        SplitBy::None => String::new(),
+        SplitBy::Day => date.format("%Y-%m-%d").to_string(),
+        SplitBy::Week => {
+            let week = date.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+    }
+}
+
+/// Date range covered by the group of `date`, limited to the exported range.
+fn split_date_range(
+    date: chrono::NaiveDate,
+    split_by: SplitBy,
+    start_date: &str,
+    end_date: &str,
+) -> (String, String) {
+    use chrono::Datelike;
+    let (first, last) = match split_by {
+        SplitBy::Week => {
+            let monday =
+                date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+            (monday, monday + chrono::Duration::days(6))
+        }
+        _ => (date, date),
+    };
+    let first = first.format("%Y-%m-%d").to_string();
+    let last = last.format("%Y-%m-%d").to_string();
+    (
+        first.max(start_date.to_string()),
+        last.min(end_date.to_string()),
+    )
+}
+
+/// File name of one split Markdown file.
+pub fn split_file_name(key: &str) -> String {
+    format!("dailylogger-{}.md", key)
+}
+
+/// A group of records written to one split file.
+#[derive(Debug, Clone)]
+pub struct RecordSplit {
+    pub key: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub records: Vec<Record>,
+}
+
+/// Group records by day or week, in ascending key order. Records whose
+/// timestamp cannot be parsed go into an `unknown` group.
+pub fn split_records(
+    records: &[Record],
+    split_by: SplitBy,
+    start_date: &str,
+    end_date: &str,
+) -> Vec<RecordSplit> {
+    if split_by == SplitBy::None {
+        return vec![RecordSplit {
+            key: String::new(),
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            records: records.to_vec(),
+        }];
+    }
+
+    let mut groups: BTreeMap<String, RecordSplit> = BTreeMap::new();
+    for record in records {
+        let date = record_local_date(record);
+        let key = date.map_or_else(|| "unknown".to_string(), |d| split_key(d, split_by));
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let (start, end) = date.map_or_else(
+                    || (start_date.to_string(), end_date.to_string()),
+                    |d| split_date_range(d, split_by, start_date, end_date),
+                );
+                RecordSplit {
+                    key,
+                    start_date: start,
+                    end_date: end,
+                    records: Vec::new(),
+                }
+            })
+            .records
+            .push(record.clone());
+    }
+    groups.into_values().collect()
+}
+
+/// Write one Markdown file per day or week into `dir`, reporting progress in
+/// records. Returns the number of files and their total size in bytes.
+/// On cancellation or error `dir` is removed again.
+fn write_split_markdown<F: FnMut(&ExportProgress)>(
+    records: &[Record],
+    request: &ExportRequest,
+    dir: &Path,
+    token: &CancellationToken,
+    reporter: &mut ExportProgressReporter<F>,
+) -> AppResult<(usize, u64)> {
+    std::fs::create_dir_all(dir)?;
+    let result = (|| -> AppResult<(usize, u64)> {
+        let splits = split_records(
+            records,
+            request.split_by,
+            &request.start_date,
+            &request.end_date,
+        );
+        reporter.start(records.len() as u64);
+        let mut total_size = 0;
+        for split in &splits {
+            token.check()?;
+            let content = export_to_markdown_with_template(
+                &split.records,
+                &split.start_date,
+                &split.end_date,
+                request.custom_template.as_deref(),
+            )?;
+            std::fs::write(dir.join(split_file_name(&split.key)), &content)?;
+            total_size += content.len() as u64;
+            reporter.advance(split.records.len() as u64);
+        }
+        Ok((splits.len(), total_size))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    result
+}
+
+/// Size of the pieces `write_file_chunked` writes between cancellation checks.
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Write `content` to `path` in chunks, reporting progress in bytes. The data
+/// goes to `<path>.part` first and is renamed only when complete, so a
+/// cancelled or failed export leaves nothing behind.
+fn write_file_chunked<F: FnMut(&ExportProgress)>(
+    path: &Path,
+    content: &[u8],
+    token: &CancellationToken,
+    reporter: &mut ExportProgressReporter<F>,
+) -> AppResult<()> {
+    let mut part_name = path.file_name().unwrap_or_default().to_os_string();
+    part_name.push(".part");
+    let part_path = path.with_file_name(part_name);
+    let result = (|| -> AppResult<()> {
+        reporter.start(content.len() as u64);
+        let mut file = std::fs::File::create(&part_path)?;
+        for chunk in content.chunks(EXPORT_CHUNK_SIZE) {
+            token.check()?;
+            file.write_all(chunk)?;
+            reporter.advance(chunk.len() as u64);
+        }
+        file.sync_all()?;
+        drop(file);
+        token.check()?;
+        std::fs::rename(&part_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&part_path);
+    }
+    result
+}
+
 /// Tauri command: open the export directory in the system file manager
 #[command]
 pub async fn open_export_dir(path: String) -> Result<(), String> {
@@ -286,34 +629,72 @@ pub async fn open_export_dir(path: String) -> Result<(), String> {
     Ok(())
 }
 
-/// Tauri command: export records to JSON or Markdown file
-#[command]
-pub async fn export_records(request: ExportRequest) -> Result<ExportResult, String> {
-    let records = memory_storage::get_records_for_export(&request.start_date, &request.end_date)
-        .map_err(|e| e.to_string())?;
+/// Write `records` as requested into `export_dir`, checking `token` and
+/// reporting progress as it goes. Nothing is left behind when cancelled.
+pub fn run_export<F: FnMut(&ExportProgress)>(
+    records: &[Record],
+    request: &ExportRequest,
+    export_dir: &Path,
+    token: &CancellationToken,
+    reporter: &mut ExportProgressReporter<F>,
+) -> AppResult<ExportResult> {
+    let now = chrono::Local::now();
+
+    if request.split_by != SplitBy::None {
+        if request.format != "markdown" {
+            return Err(AppError::validation(format!(
+                "Splitting into files is only supported for markdown, not {}",
+                request.format
+            )));
+        }
+        // Split exports go into their own directory inside the export directory
+        let dir = export_dir.join(format!(
+            "dailylogger-export-{}",
+            now.format("%Y-%m-%d_%H%M%S")
+        ));
+        let (file_count, file_size) =
+            write_split_markdown(records, request, &dir, token, reporter)?;
+        reporter.finish();
+        let path_str = dir.to_string_lossy().to_string();
+        tracing::info!(
+            "Exported {} records into {} files in {} ({} bytes)",
+            records.len(),
+            file_count,
+            path_str,
+            file_size
+        );
+        return Ok(ExportResult {
+            path: path_str,
+            record_count: records.len(),
+            file_size,
+        });
+    }
 
     let content = match request.format.as_str() {
-        "json" => export_to_json(&records, &request.start_date, &request.end_date)
-            .map_err(|e| e.to_string())?,
+        "json" => export_to_json(records, &request.start_date, &request.end_date)?,
         "markdown" => export_to_markdown_with_template(
-            &records,
+            records,
             &request.start_date,
             &request.end_date,
             request.custom_template.as_deref(),
-        )
-        .map_err(|e| e.to_string())?,
-        _ => return Err(format!("Unsupported export format: {}", request.format)),
+        )?,
+        "csv" => export_to_csv(records),
+        _ => {
+            return Err(AppError::validation(format!(
+                "Unsupported export format: {}",
+                request.format
+            )))
+        }
     };
+    token.check()?;
 
-    let export_dir = get_export_dir();
-    std::fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(export_dir)?;
 
     // Generate filename with timestamp to avoid overwriting previous exports
-    let now = chrono::Local::now();
-    let extension = if request.format == "json" {
-        "json"
-    } else {
-        "md"
+    let extension = match request.format.as_str() {
+        "json" => "json",
+        "csv" => "csv",
+        _ => "md",
     };
     let filename = format!(
         "dailylogger-export-{}.{}",
@@ -322,7 +703,8 @@ pub async fn export_records(request: ExportRequest) -> Result<ExportResult, Stri
     );
     let output_path = export_dir.join(&filename);
 
-    std::fs::write(&output_path, &content).map_err(|e| e.to_string())?;
+    write_file_chunked(&output_path, content.as_bytes(), token, reporter)?;
+    reporter.finish();
 
     let file_size = std::fs::metadata(&output_path)
         .map(|m| m.len())
@@ -343,6 +725,115 @@ pub async fn export_records(request: ExportRequest) -> Result<ExportResult, Stri
     })
 }
 
+/// Event carrying an `ExportProgress` while `export_records` runs.
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+/// Tauri command: export records to JSON, Markdown or CSV file(s)
+///
+/// Progress is pushed as `export-progress` events. When the request carries an
+/// `export_id`, `cancel_export` with that id stops the export and removes its
+/// partial output.
+#[command]
+pub async fn export_records(
+    app: tauri::AppHandle,
+    request: ExportRequest,
+) -> Result<ExportResult, String> {
+    let running = match request.export_id.as_deref() {
+        Some(id) => Some(progress::register_export(id).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    let token = running
+        .as_ref()
+        .map(|r| r.token().clone())
+        .unwrap_or_default();
+
+    let filter = memory_storage::RecordFilter {
+        tags: request.tags.clone(),
+        project: request.project.clone(),
+    };
+    let records = memory_storage::get_records_for_export_filtered(
+        &request.start_date,
+        &request.end_date,
+        &filter,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let export_id = request.export_id.clone().unwrap_or_default();
+    let mut reporter = ExportProgressReporter::new(export_id, |progress| {
+        let _ = app.emit(EXPORT_PROGRESS_EVENT, progress);
+    });
+    let result = run_export(&records, &request, &get_export_dir(), &token, &mut reporter);
+    if let Err(e) = &result {
+        if progress::is_cancelled_error(e) {
+            tracing::info!("Export {:?} cancelled", request.export_id);
+        }
+    }
+    result.map_err(|e| e.to_string())
+}
+
+/// Tauri command: cancel the running export started with `export_id`.
+/// Returns false if no such export is running.
+#[command]
+pub async fn cancel_export(export_id: String) -> Result<bool, String> {
+    progress::cancel_running_export(&export_id).map_err(|e| e.to_string())
+}
+
+/// Tauri command: export the records in a date range as a PDF report (timeline
+/// plus key screenshot thumbnails) to `dest_path`
+#[command]
+pub async fn export_report_pdf(
+    start_date: String,
+    end_date: String,
+    dest_path: String,
+) -> Result<ExportResult, String> {
+    let record_count =
+        pdf::write_report_pdf(&start_date, &end_date, &dest_path).map_err(|e| e.to_string())?;
+
+    let file_size = std::fs::metadata(&dest_path).map(|m| m.len()).unwrap_or(0);
+    tracing::info!(
+        "Exported {} records to PDF report {} ({} bytes)",
+        record_count,
+        dest_path,
+        file_size
+    );
+
+    Ok(ExportResult {
+        path: dest_path,
+        record_count,
+        file_size,
+    })
+}
+
+/// Tauri command: export records in a date range to a new standalone SQLite file
+#[command]
+pub async fn export_to_sqlite(
+    dest_path: String,
+    start_date: String,
+    end_date: String,
+) -> Result<ExportResult, String> {
+    let path = std::path::Path::new(&dest_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let record_count = memory_storage::export_records_to_sqlite_sync(path, &start_date, &end_date)
+        .map_err(|e| e.to_string())?;
+
+    let file_size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    tracing::info!(
+        "Exported {} records to SQLite file {} ({} bytes)",
+        record_count,
+        dest_path,
+        file_size
+    );
+
+    Ok(ExportResult {
+        path: dest_path,
+        record_count,
+        file_size,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -359,6 +850,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: None,
+            metadata: None,
+            status: None,
         }
     }
 
@@ -427,6 +920,99 @@ mod tests {
         );
     }
 
+    // ===== Frontmatter Tests =====
+
+    /// Minimal structural check for the frontmatter subset we emit:
+    /// delimited block, `key: value` / `- item` lines, and every quoted
+    /// scalar must be a valid double-quoted string.
+    fn assert_valid_frontmatter_yaml(frontmatter: &str) {
+        assert!(frontmatter.starts_with("---\n"));
+        assert!(frontmatter.ends_with("---\n"));
+        let body = &frontmatter[4..frontmatter.len() - 4];
+        for line in body.lines() {
+            let trimmed = line.trim_start();
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                serde_json::from_str::<String>(item).expect("list item is a quoted scalar");
+                continue;
+            }
+            let (key, value) = if trimmed.starts_with('"') {
+                let end = trimmed.rfind("\": ").expect("quoted key") + 1;
+                serde_json::from_str::<String>(&trimmed[..end]).expect("quoted key");
+                (&trimmed[..end], trimmed[end + 1..].trim())
+            } else {
+                trimmed.split_once(':').expect("key: value line")
+            };
+            assert!(!key.is_empty());
+            let value = value.trim();
+            if value.starts_with('"') {
+                serde_json::from_str::<String>(value).expect("quoted scalar");
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_obsidian_frontmatter_fields() {
+        let mut breakdown = BTreeMap::new();
+        breakdown.insert("auto".to_string(), 3);
+        breakdown.insert("manual".to_string(), 1);
+        let tags = vec!["开发".to_string(), "会议".to_string()];
+
+        let fm = build_obsidian_frontmatter("2026-03-14", "2026-03-14", &tags, 4, &breakdown);
+
+        assert_eq!(
+            fm,
+            "---\ndate: \"2026-03-14\"\ntags:\n  - \"开发\"\n  - \"会议\"\nrecord_count: 4\nsource_breakdown:\n  \"auto\": 3\n  \"manual\": 1\n---\n"
+        );
+        assert_valid_frontmatter_yaml(&fm);
+    }
+
+    #[test]
+    fn test_build_obsidian_frontmatter_quotes_special_characters() {
+        let tags = vec![
+            "a: b".to_string(),
+            "#hash".to_string(),
+            "say \"hi\"".to_string(),
+            "line\nbreak".to_string(),
+        ];
+        let fm = build_obsidian_frontmatter("2026-03-14", "2026-03-14", &tags, 0, &BTreeMap::new());
+
+        assert!(fm.contains("  - \"a: b\"\n"));
+        assert!(fm.contains("  - \"say \\\"hi\\\"\"\n"));
+        assert!(fm.contains("  - \"line\\nbreak\"\n"));
+        assert!(fm.contains("source_breakdown: {}\n"));
+        assert_valid_frontmatter_yaml(&fm);
+    }
+
+    #[test]
+    fn test_frontmatter_for_records_collects_tags_and_sources() {
+        let mut tagged = make_test_record(
+            1,
+            "2026-03-14T02:30:00+00:00",
+            "auto",
+            r#"{"current_focus":"写代码","tags":["开发"]}"#,
+        );
+        tagged.tags = Some(r#"["项目A"]"#.to_string());
+        let records = vec![
+            tagged,
+            make_test_record(2, "2026-03-14T03:15:00+00:00", "manual", "记录想法"),
+        ];
+
+        let fm = frontmatter_for_records("2026-03-14", "2026-03-14", &records);
+
+        assert!(fm.contains("  - \"项目A\"\n  - \"开发\"\n"));
+        assert!(fm.contains("record_count: 2\n"));
+        assert!(fm.contains("  \"auto\": 1\n  \"manual\": 1\n"));
+        assert_valid_frontmatter_yaml(&fm);
+    }
+
+    #[test]
+    fn test_frontmatter_for_records_empty() {
+        let fm = frontmatter_for_records("2026-03-14", "2026-03-14", &[]);
+        assert!(fm.contains("tags: []\n"));
+        assert!(fm.contains("record_count: 0\n"));
+        assert_valid_frontmatter_yaml(&fm);
+    }
+
     // ===== Markdown Export Tests =====
 
     #[test]
@@ -438,6 +1024,9 @@ mod tests {
 
         let result = export_to_markdown(&records, "2026-03-14", "2026-03-14").unwrap();
 
+        assert!(result.starts_with("---\ndate: \"2026-03-14\"\n"));
+        assert!(!result.contains("end_date:"));
+        assert!(result.contains("record_count: 2\n"));
         assert!(result.contains("# DailyLogger 数据导出"));
         assert!(result.contains("日期范围: 2026-03-14 至 2026-03-14"));
         assert!(result.contains("总记录数: 2"));
@@ -466,12 +1055,116 @@ mod tests {
 
         let result = export_to_markdown(&records, "2026-03-13", "2026-03-14").unwrap();
 
+        assert!(result.starts_with("---\ndate: \"2026-03-13\"\nend_date: \"2026-03-14\"\n"));
         // Should contain both dates as headings
         assert!(result.contains("## 2026-03-1"));
         assert!(result.contains("第一天工作"));
         assert!(result.contains("第二天工作"));
     }
 
+    #[test]
+    fn test_split_key_and_file_name() {
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        assert_eq!(split_key(date, SplitBy::Day), "2026-01-01");
+        // 2026-01-01 is a Thursday in ISO week 1 of 2026
+        assert_eq!(split_key(date, SplitBy::Week), "2026-W01");
+        let late = chrono::NaiveDate::from_ymd_opt(2027, 1, 1).unwrap();
+        assert_eq!(split_key(late, SplitBy::Week), "2026-W53");
+        assert_eq!(split_file_name("2026-W01"), "dailylogger-2026-W01.md");
+    }
+
+    #[test]
+    fn test_split_records_by_day() {
+        let records = vec![
+            make_test_record(1, "2026-03-17T12:00:00+00:00", "auto", "b"),
+            make_test_record(2, "2026-03-16T12:00:00+00:00", "auto", "a"),
+            make_test_record(3, "2026-03-17T12:30:00+00:00", "manual", "c"),
+            make_test_record(4, "bad", "manual", "d"),
+        ];
+
+        let splits = split_records(&records, SplitBy::Day, "2026-03-16", "2026-03-17");
+
+        let keys: Vec<&str> = splits.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["2026-03-16", "2026-03-17", "unknown"]);
+        assert_eq!(splits[1].records.len(), 2);
+        assert_eq!(splits[1].start_date, "2026-03-17");
+        assert_eq!(splits[1].end_date, "2026-03-17");
+        assert_eq!(splits[2].start_date, "2026-03-16");
+    }
+
+    #[test]
+    fn test_split_records_by_week_clamps_to_export_range() {
+        // 2026-03-15 is a Sunday, 2026-03-16 a Monday
+        let records = vec![
+            make_test_record(1, "2026-03-12T12:00:00+00:00", "auto", "a"),
+            make_test_record(2, "2026-03-15T12:00:00+00:00", "auto", "b"),
+            make_test_record(3, "2026-03-16T12:00:00+00:00", "auto", "c"),
+        ];
+
+        let splits = split_records(&records, SplitBy::Week, "2026-03-10", "2026-03-18");
+
+        assert_eq!(splits.len(), 2);
+        assert_eq!(splits[0].key, "2026-W11");
+        assert_eq!(splits[0].records.len(), 2);
+        assert_eq!(
+            (splits[0].start_date.as_str(), splits[0].end_date.as_str()),
+            ("2026-03-10", "2026-03-15")
+        );
+        assert_eq!(splits[1].key, "2026-W12");
+        assert_eq!(
+            (splits[1].start_date.as_str(), splits[1].end_date.as_str()),
+            ("2026-03-16", "2026-03-18")
+        );
+    }
+
+    #[test]
+    fn test_split_records_none_keeps_one_group() {
+        let records = vec![make_test_record(
+            1,
+            "2026-03-12T12:00:00+00:00",
+            "auto",
+            "a",
+        )];
+        let splits = split_records(&records, SplitBy::None, "2026-03-01", "2026-03-31");
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].start_date, "2026-03-01");
+    }
+
+    #[test]
+    fn test_write_split_markdown_writes_one_file_per_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let request = ExportRequest {
+            start_date: "2026-03-16".to_string(),
+            end_date: "2026-03-17".to_string(),
+            format: "markdown".to_string(),
+            custom_template: None,
+            tags: Vec::new(),
+            project: None,
+            split_by: SplitBy::Day,
+            export_id: None,
+        };
+        let records = vec![
+            make_test_record(1, "2026-03-16T12:00:00+00:00", "auto", "第一天"),
+            make_test_record(2, "2026-03-17T12:00:00+00:00", "manual", "第二天"),
+        ];
+
+        let mut reporter = ExportProgressReporter::new("", |_| {});
+        let (files, size) = write_split_markdown(
+            &records,
+            &request,
+            dir.path(),
+            &CancellationToken::new(),
+            &mut reporter,
+        )
+        .unwrap();
+
+        assert_eq!(files, 2);
+        assert!(size > 0);
+        let day2 = std::fs::read_to_string(dir.path().join("dailylogger-2026-03-17.md")).unwrap();
+        assert!(day2.contains("第二天"));
+        assert!(!day2.contains("第一天"));
+    }
+
     #[test]
     fn test_export_to_markdown_multiline_content() {
         let records = vec![make_test_record(
@@ -588,4 +1281,131 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
+
+    // ===== CSV Export Tests =====
+
+    #[test]
+    fn test_expand_auto_fields_parses_analysis_json() {
+        let fields = expand_auto_fields(
+            r#"{"current_focus":"写代码","active_software":"VS Code","context_keywords":["rust","export"]}"#,
+        );
+        assert_eq!(fields.current_focus, "写代码");
+        assert_eq!(fields.active_software, "VS Code");
+        assert_eq!(fields.context_keywords, "rust; export");
+    }
+
+    #[test]
+    fn test_expand_auto_fields_leaves_columns_empty_on_parse_failure() {
+        assert_eq!(expand_auto_fields("not json"), AutoRecordFields::default());
+        assert_eq!(expand_auto_fields(""), AutoRecordFields::default());
+
+        // Partially missing fields stay empty
+        let fields = expand_auto_fields(r#"{"current_focus":"会议"}"#);
+        assert_eq!(fields.current_focus, "会议");
+        assert!(fields.active_software.is_empty());
+        assert!(fields.context_keywords.is_empty());
+    }
+
+    #[test]
+    fn test_export_to_csv_expands_auto_records_only() {
+        let records = vec![
+            make_test_record(
+                1,
+                "2026-03-16T09:00:00+00:00",
+                "auto",
+                r#"{"current_focus":"写代码","active_software":"VS Code","context_keywords":["rust"]}"#,
+            ),
+            make_test_record(2, "2026-03-16T10:00:00+00:00", "manual", "想法, \"引用\""),
+        ];
+
+        let csv = export_to_csv(&records);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert!(lines[0].starts_with("\u{FEFF}id,timestamp,source_type,current_focus"));
+        assert!(lines[1].starts_with("1,2026-03-16T09:00:00+00:00,auto,写代码,VS Code,rust,"));
+        assert_eq!(
+            lines[2],
+            "2,2026-03-16T10:00:00+00:00,manual,,,,\"想法, \"\"引用\"\"\","
+        );
+    }
+
+    fn big_request(format: &str, split_by: SplitBy) -> (ExportRequest, Vec<Record>) {
+        let request = ExportRequest {
+            start_date: "2026-03-16".to_string(),
+            end_date: "2026-03-18".to_string(),
+            format: format.to_string(),
+            custom_template: None,
+            tags: Vec::new(),
+            project: None,
+            split_by,
+            export_id: Some("test".to_string()),
+        };
+        let filler = "x".repeat(1000);
+        let records = (0..300)
+            .map(|i| {
+                let ts = format!("2026-03-{}T12:00:00+00:00", 16 + i % 3);
+                make_test_record(i, &ts, "manual", &filler)
+            })
+            .collect();
+        (request, records)
+    }
+
+    #[test]
+    fn test_run_export_reports_progress_up_to_done() {
+        let dir = tempfile::tempdir().unwrap();
+        let (request, records) = big_request("csv", SplitBy::None);
+        let mut percents = Vec::new();
+        let mut reporter = ExportProgressReporter::new("test", |p| percents.push(p.percent));
+
+        let result = run_export(
+            &records,
+            &request,
+            dir.path(),
+            &CancellationToken::new(),
+            &mut reporter,
+        )
+        .unwrap();
+
+        assert_eq!(result.record_count, 300);
+        assert!(std::path::Path::new(&result.path).exists());
+        assert_eq!(percents.first(), Some(&0));
+        assert_eq!(percents.last(), Some(&100));
+        assert!(percents.len() > 2);
+        assert!(percents.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_run_export_cancelled_midway_leaves_no_file() {
+        for split_by in [SplitBy::None, SplitBy::Day] {
+            let dir = tempfile::tempdir().unwrap();
+            let (request, records) = big_request("markdown", split_by);
+            let token = CancellationToken::new();
+            let cancel = token.clone();
+            let mut reporter = ExportProgressReporter::new("test", |p| {
+                if p.percent > 0 {
+                    cancel.cancel();
+                }
+            });
+
+            let err =
+                run_export(&records, &request, dir.path(), &token, &mut reporter).unwrap_err();
+
+            assert!(progress::is_cancelled_error(&err));
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_run_export_cancelled_before_start_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (request, records) = big_request("json", SplitBy::None);
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut reporter = ExportProgressReporter::new("test", |_| {});
+
+        let err = run_export(&records, &request, dir.path(), &token, &mut reporter).unwrap_err();
+
+        assert!(progress::is_cancelled_error(&err));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
 }
@@ -0,0 +1,945 @@
+//! PDF report of a record timeline with key screenshot thumbnails.
+//!
+//! Export happens in two steps:
+//! 1. [`build_report_days`] and [`layout_report`] turn records into pages of
+//!    positioned text lines and images (pure, testable layout).
+//! 2. [`render_pdf`] writes those pages as a PDF with `printpdf`.
+//!
+//! Text uses a CJK-capable system font ([`find_report_font`]), subset to the
+//! glyphs the report uses and embedded in the file, so the report reads the
+//! same in every viewer without carrying a whole CJK font; thumbnails are
+//! embedded as JPEG.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use printpdf::{
+    ColorBits, ColorSpace, FontData, FontMetrics, GlyphMetrics, Image, ImageFilter, ImageTransform,
+    ImageXObject, Mm, PdfDocument, PdfLayerReference, Px,
+};
+
+use crate::errors::{AppError, AppResult};
+use crate::memory_storage::{self, Record};
+
+/// A4 page size in points.
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 14.0;
+const BODY_SIZE: f32 = 10.0;
+const LINE_SPACING: f32 = 1.4;
+/// Indent of record text below its time label.
+const ENTRY_INDENT: f32 = 12.0;
+
+/// Thumbnail box in points; images keep their aspect ratio inside it.
+const THUMBNAIL_MAX_WIDTH: f32 = 240.0;
+const THUMBNAIL_MAX_HEIGHT: f32 = 150.0;
+/// Pixel size thumbnails are downscaled to before embedding.
+const THUMBNAIL_PIXELS: u32 = 480;
+/// Screenshots shown per day, most important records first.
+pub const MAX_KEY_SCREENSHOTS_PER_DAY: usize = 4;
+
+/// A thumbnail to draw, with the pixel size of its source image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailRef {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One timeline entry of the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportEntry {
+    /// Local time, `HH:MM`
+    pub time: String,
+    pub text: String,
+    pub thumbnail: Option<ThumbnailRef>,
+}
+
+/// Entries of one local date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportDay {
+    pub date: String,
+    pub entries: Vec<ReportEntry>,
+}
+
+/// Something placed on a page. `y` is the top edge, measured from the top of
+/// the page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfElement {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        text: String,
+    },
+    Image {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        path: String,
+    },
+}
+
+/// A laid out page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfPage {
+    pub elements: Vec<PdfElement>,
+}
+
+/// Text shown for a record: the focus of an auto record, the content of a
+/// manual one.
+fn entry_text(record: &Record) -> String {
+    if record.source_type == "auto" {
+        let fields = super::expand_auto_fields(&record.content);
+        match (fields.current_focus.trim(), fields.active_software.trim()) {
+            ("", "") => record.content.clone(),
+            (focus, "") => focus.to_string(),
+            ("", software) => software.to_string(),
+            (focus, software) => format!("{}（{}）", focus, software),
+        }
+    } else {
+        record.content.clone()
+    }
+}
+
+/// Ids of the records whose screenshots are shown: per local date, the
+/// `MAX_KEY_SCREENSHOTS_PER_DAY` most important auto records with a
+/// screenshot (earlier records win ties).
+pub fn key_screenshot_ids(records: &[Record]) -> HashSet<i64> {
+    let mut by_day: std::collections::BTreeMap<String, Vec<&Record>> = Default::default();
+    for record in records
+        .iter()
+        .filter(|r| r.source_type == "auto" && r.screenshot_path.is_some())
+    {
+        by_day
+            .entry(local_date(&record.timestamp))
+            .or_default()
+            .push(record);
+    }
+    by_day
+        .into_values()
+        .flat_map(|mut day| {
+            day.sort_by(|a, b| {
+                memory_storage::record_importance(&b.content)
+                    .cmp(&memory_storage::record_importance(&a.content))
+                    .then_with(|| a.timestamp.cmp(&b.timestamp))
+            });
+            day.into_iter()
+                .take(MAX_KEY_SCREENSHOTS_PER_DAY)
+                .map(|r| r.id)
+        })
+        .collect()
+}
+
+fn local_date(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn local_time(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_else(|_| "--:--".to_string())
+}
+
+/// Group records (chronological order) into report days. `image_size` returns
+/// the pixel size of a screenshot, or `None` when it cannot be read; such
+/// screenshots are left out.
+pub fn build_report_days(
+    records: &[Record],
+    image_size: impl Fn(&str) -> Option<(u32, u32)>,
+) -> Vec<ReportDay> {
+    let key_ids = key_screenshot_ids(records);
+    let mut days: Vec<ReportDay> = Vec::new();
+    for record in records {
+        let date = local_date(&record.timestamp);
+        if days.last().is_none_or(|d| d.date != date) {
+            days.push(ReportDay {
+                date,
+                entries: Vec::new(),
+            });
+        }
+        let thumbnail = record
+            .screenshot_path
+            .as_deref()
+            .filter(|_| key_ids.contains(&record.id))
+            .and_then(|path| {
+                image_size(path).map(|(width, height)| ThumbnailRef {
+                    path: path.to_string(),
+                    width,
+                    height,
+                })
+            });
+        if let Some(day) = days.last_mut() {
+            day.entries.push(ReportEntry {
+                time: local_time(&record.timestamp),
+                text: entry_text(record),
+                thumbnail,
+            });
+        }
+    }
+    days
+}
+
+/// Approximate width of `c` at font `size`: half width for ASCII, full width
+/// for everything else. Slightly generous for proportional fonts, so wrapped
+/// lines stay inside the margins.
+fn char_width(c: char, size: f32) -> f32 {
+    if c.is_ascii() {
+        size * 0.5
+    } else {
+        size
+    }
+}
+
+/// Break `text` into lines no wider than `max_width`. Existing line breaks
+/// are kept; long lines break at the last space that fits, or mid-word when
+/// there is none (CJK text has no spaces).
+pub fn wrap_text(text: &str, max_width: f32, size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut width = 0.0;
+        for c in paragraph.chars() {
+            let w = char_width(c, size);
+            if width + w > max_width && !line.is_empty() {
+                let break_at = line.rfind(' ').filter(|&i| i > 0);
+                match break_at {
+                    Some(i) => {
+                        let rest = line[i + 1..].to_string();
+                        line.truncate(i);
+                        lines.push(std::mem::replace(&mut line, rest));
+                    }
+                    None => lines.push(std::mem::take(&mut line)),
+                }
+                width = line.chars().map(|c| char_width(c, size)).sum();
+            }
+            line.push(c);
+            width += w;
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Size of a `width`×`height` pixel image fitted into the thumbnail box.
+fn thumbnail_box(width: u32, height: u32) -> (f32, f32) {
+    let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+    let scale = (THUMBNAIL_MAX_WIDTH / w).min(THUMBNAIL_MAX_HEIGHT / h);
+    (w * scale, h * scale)
+}
+
+/// Page layout state: the page being filled and the next free y position.
+struct Layout {
+    pages: Vec<PdfPage>,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self {
+            pages: vec![PdfPage::default()],
+            y: MARGIN,
+        }
+    }
+
+    /// Start a new page unless `height` still fits on the current one.
+    fn reserve(&mut self, height: f32) {
+        if self.y + height > PAGE_HEIGHT - MARGIN && self.y > MARGIN {
+            self.pages.push(PdfPage::default());
+            self.y = MARGIN;
+        }
+    }
+
+    fn push(&mut self, element: PdfElement) {
+        if let Some(page) = self.pages.last_mut() {
+            page.elements.push(element);
+        }
+    }
+
+    fn text(&mut self, x: f32, size: f32, text: &str) {
+        let line_height = size * LINE_SPACING;
+        for line in wrap_text(text, PAGE_WIDTH - MARGIN - x, size) {
+            self.reserve(line_height);
+            self.push(PdfElement::Text {
+                x,
+                y: self.y,
+                size,
+                text: line,
+            });
+            self.y += line_height;
+        }
+    }
+}
+
+/// Lay out the report: a title, a subtitle, then per day a heading and the
+/// timeline entries with their thumbnails. Content flows onto new pages as
+/// needed; a day heading never ends up alone at the bottom of a page.
+pub fn layout_report(title: &str, subtitle: &str, days: &[ReportDay]) -> Vec<PdfPage> {
+    let mut layout = Layout::new();
+    layout.text(MARGIN, TITLE_SIZE, title);
+    layout.text(MARGIN, BODY_SIZE, subtitle);
+
+    for day in days {
+        layout.y += HEADING_SIZE;
+        // Keep the heading together with the first entry line
+        layout.reserve(HEADING_SIZE * LINE_SPACING + BODY_SIZE * LINE_SPACING);
+        layout.text(MARGIN, HEADING_SIZE, &day.date);
+
+        for entry in &day.entries {
+            layout.y += BODY_SIZE * 0.4;
+            layout.text(MARGIN, BODY_SIZE, &entry.time);
+            layout.text(MARGIN + ENTRY_INDENT, BODY_SIZE, &entry.text);
+            if let Some(thumbnail) = &entry.thumbnail {
+                let (width, height) = thumbnail_box(thumbnail.width, thumbnail.height);
+                layout.reserve(height + BODY_SIZE * 0.4);
+                layout.y += BODY_SIZE * 0.4;
+                layout.push(PdfElement::Image {
+                    x: MARGIN + ENTRY_INDENT,
+                    y: layout.y,
+                    width,
+                    height,
+                    path: thumbnail.path.clone(),
+                });
+                layout.y += height;
+            }
+        }
+    }
+    layout.pages
+}
+
+/// JPEG data of an image to embed.
+#[derive(Debug, Clone)]
+pub struct JpegImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Load a screenshot (its thumbnail when one exists), downscale it and encode it as JPEG.
+pub fn load_thumbnail_jpeg(path: &str) -> Option<JpegImage> {
+    let source =
+        crate::manual_entry::existing_thumbnail_path(path).unwrap_or_else(|| path.to_string());
+    let image = image::open(&source)
+        .map_err(|e| tracing::warn!("Failed to load screenshot {} for PDF: {}", source, e))
+        .ok()?
+        .thumbnail(THUMBNAIL_PIXELS, THUMBNAIL_PIXELS)
+        .to_rgb8();
+    let mut data = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 80)
+        .encode_image(&image)
+        .ok()?;
+    Some(JpegImage {
+        data,
+        width: image.width(),
+        height: image.height(),
+    })
+}
+
+/// CJK-capable fonts looked for, in order, to embed in the report. Only
+/// fonts with TrueType outlines qualify (see [`subset_report_font`]), which
+/// rules out the CFF-based Noto Sans CJK / Source Han Sans.
+const REPORT_FONT_CANDIDATES: &[&str] = &[
+    // Windows
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    // macOS
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    // Linux
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc",
+    "/usr/share/fonts/wqy-zenhei/wqy-zenhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/google-droid-sans-fonts/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/arphic/uming.ttc",
+];
+
+/// The first of `candidates` that exists.
+fn first_existing_font(candidates: &[&str]) -> Option<PathBuf> {
+    candidates
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+}
+
+/// Font file embedded in the report: the first CJK font of
+/// `REPORT_FONT_CANDIDATES` installed on this system.
+pub fn find_report_font() -> AppResult<PathBuf> {
+    first_existing_font(REPORT_FONT_CANDIDATES).ok_or_else(|| {
+        AppError::file_io("No CJK font found to embed in the PDF report (looked for Microsoft YaHei, SimHei, PingFang, STHeiti, WenQuanYi and Droid Sans Fallback; on Linux install fonts-wqy-microhei)")
+    })
+}
+
+/// Metrics printpdf needs for the characters of the report. Covering only
+/// those keeps the font's width and ToUnicode tables as small as its subset.
+#[derive(Debug, Clone)]
+struct ReportFont {
+    metrics: FontMetrics,
+    glyph_count: u16,
+    glyphs: HashMap<char, u16>,
+    glyph_metrics: HashMap<u16, GlyphMetrics>,
+}
+
+impl FontData for ReportFont {
+    fn font_metrics(&self) -> FontMetrics {
+        self.metrics
+    }
+
+    fn glyph_id(&self, c: char) -> Option<u16> {
+        self.glyphs.get(&c).copied()
+    }
+
+    fn glyph_ids(&self) -> HashMap<u16, char> {
+        self.glyphs.iter().map(|(&c, &id)| (id, c)).collect()
+    }
+
+    fn glyph_count(&self) -> u16 {
+        self.glyph_count
+    }
+
+    fn glyph_metrics(&self, glyph_id: u16) -> Option<GlyphMetrics> {
+        self.glyph_metrics.get(&glyph_id).copied()
+    }
+}
+
+/// Cut the first face of `font` (a TrueType font or collection) down to the
+/// glyphs of `text`, so the report embeds a few kilobytes instead of a CJK
+/// font of 15-20 MB. Fonts without TrueType outlines are rejected: printpdf
+/// embeds fonts as `FontFile2`, which cannot hold CFF outlines.
+fn subset_report_font(
+    font: &[u8],
+    text: impl Iterator<Item = char>,
+) -> AppResult<(Vec<u8>, ReportFont)> {
+    let face = ttf_parser::Face::parse(font, 0)
+        .map_err(|e| AppError::file_io(format!("Failed to read PDF font: {}", e)))?;
+    if face.tables().glyf.is_none() {
+        return Err(AppError::file_io(
+            "PDF font has no TrueType outlines and cannot be embedded",
+        ));
+    }
+
+    let mut glyphs = HashMap::new();
+    let mut glyph_metrics = HashMap::new();
+    for c in text {
+        let Some(id) = face.glyph_index(c) else {
+            continue;
+        };
+        glyphs.insert(c, id.0);
+        if let Some(width) = face.glyph_hor_advance(id) {
+            // Same height printpdf derives for its own fonts
+            let height = face
+                .glyph_bounding_box(id)
+                .map(|bbox| bbox.y_max - bbox.y_min - face.descender())
+                .unwrap_or(1000);
+            glyph_metrics.insert(
+                id.0,
+                GlyphMetrics {
+                    width: u32::from(width),
+                    height: height.max(0) as u32,
+                },
+            );
+        }
+    }
+
+    // Glyph 0 (.notdef) always stays; subsetting keeps glyph ids unchanged
+    let mut ids: Vec<u16> = std::iter::once(0).chain(glyphs.values().copied()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let subset = subsetter::subset(font, 0, subsetter::Profile::pdf(&ids))
+        .map_err(|e| AppError::file_io(format!("Failed to subset PDF font: {}", e)))?;
+
+    let report_font = ReportFont {
+        metrics: FontMetrics {
+            ascent: face.ascender(),
+            descent: face.descender(),
+            units_per_em: face.units_per_em(),
+        },
+        glyph_count: face.number_of_glyphs(),
+        glyphs,
+        glyph_metrics,
+    };
+    Ok((subset, report_font))
+}
+
+/// Layout points to printpdf millimetres.
+fn mm(points: f32) -> Mm {
+    Mm(points * 25.4 / 72.0)
+}
+
+/// Draw a JPEG with its top-left corner at (`x`, `y`) points from the top of
+/// the page, scaled to `width`×`height` points.
+fn draw_jpeg(
+    layer: &PdfLayerReference,
+    jpeg: JpegImage,
+    (x, y, width, height): (f32, f32, f32, f32),
+) {
+    let (pixel_width, pixel_height) = (jpeg.width.max(1), jpeg.height.max(1));
+    let image = Image::from(ImageXObject {
+        width: Px(pixel_width as usize),
+        height: Px(pixel_height as usize),
+        color_space: ColorSpace::Rgb,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: true,
+        image_data: jpeg.data,
+        image_filter: Some(ImageFilter::DCT),
+        smask: None,
+        clipping_bbox: None,
+    });
+    // At 72 dpi one pixel is one point, so the scale maps pixels to the box
+    image.add_to_layer(
+        layer.clone(),
+        ImageTransform {
+            translate_x: Some(mm(x)),
+            translate_y: Some(mm(PAGE_HEIGHT - y - height)),
+            scale_x: Some(width / pixel_width as f32),
+            scale_y: Some(height / pixel_height as f32),
+            dpi: Some(72.0),
+            ..Default::default()
+        },
+    );
+}
+
+/// Render laid out pages as a PDF with `font` (a TrueType font or
+/// collection, see [`subset_report_font`]) embedded for all text.
+/// `load_image` supplies the JPEG of an image element's path; images it
+/// cannot load are skipped.
+pub fn render_pdf(
+    title: &str,
+    pages: &[PdfPage],
+    font: &[u8],
+    load_image: impl Fn(&str) -> Option<JpegImage>,
+) -> AppResult<Vec<u8>> {
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(title, mm(PAGE_WIDTH), mm(PAGE_HEIGHT), "Layer 1");
+    let text = pages
+        .iter()
+        .flat_map(|page| &page.elements)
+        .filter_map(|element| match element {
+            PdfElement::Text { text, .. } => Some(text.chars()),
+            PdfElement::Image { .. } => None,
+        })
+        .flatten();
+    let (subset, font_data) = subset_report_font(font, text)?;
+    let font = doc
+        .add_external_font_data(subset, font_data)
+        .map_err(|e| AppError::file_io(format!("Failed to embed PDF font: {}", e)))?;
+
+    for (index, page) in pages.iter().enumerate() {
+        let (page_index, layer_index) = if index == 0 {
+            (first_page, first_layer)
+        } else {
+            doc.add_page(mm(PAGE_WIDTH), mm(PAGE_HEIGHT), "Layer 1")
+        };
+        let layer = doc.get_page(page_index).get_layer(layer_index);
+        for element in &page.elements {
+            match element {
+                PdfElement::Text { x, y, size, text } => {
+                    // Baseline sits about 0.88 of the font size below the top edge
+                    let baseline = PAGE_HEIGHT - y - size * 0.88;
+                    layer.use_text(text.as_str(), *size, mm(*x), mm(baseline), &font);
+                }
+                PdfElement::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    path,
+                } => {
+                    if let Some(jpeg) = load_image(path) {
+                        draw_jpeg(&layer, jpeg, (*x, *y, *width, *height));
+                    }
+                }
+            }
+        }
+    }
+
+    doc.save_to_bytes()
+        .map_err(|e| AppError::file_io(format!("Failed to render PDF report: {}", e)))
+}
+
+/// Write a PDF report of the records between two local dates (YYYY-MM-DD,
+/// inclusive) to `dest_path`. Returns the number of records in the report.
+pub fn write_report_pdf(start_date: &str, end_date: &str, dest_path: &str) -> AppResult<usize> {
+    let records = memory_storage::get_records_for_export(start_date, end_date)?;
+    let days = build_report_days(&records, |path| image::image_dimensions(path).ok());
+    let pages = layout_report(
+        "DailyLogger 工作报告",
+        &format!(
+            "日期范围：{} 至 {}　记录数：{}",
+            start_date,
+            end_date,
+            records.len()
+        ),
+        &days,
+    );
+    let font_path = find_report_font()?;
+    let font = std::fs::read(&font_path)
+        .map_err(|e| AppError::file_io(format!("Failed to read font {:?}: {}", font_path, e)))?;
+    let pdf = render_pdf("DailyLogger 工作报告", &pages, &font, load_thumbnail_jpeg)?;
+
+    let path = Path::new(dest_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, pdf)
+        .map_err(|e| AppError::file_io(format!("Failed to write PDF report: {}", e)))?;
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: i64, timestamp: &str, source_type: &str, content: &str) -> Record {
+        Record {
+            id,
+            timestamp: timestamp.to_string(),
+            source_type: source_type.to_string(),
+            content: content.to_string(),
+            screenshot_path: None,
+            monitor_info: None,
+            tags: None,
+            user_notes: None,
+            session_id: None,
+            analysis_status: None,
+            metadata: None,
+            status: None,
+        }
+    }
+
+    fn shot(id: i64, timestamp: &str, importance: u8) -> Record {
+        Record {
+            screenshot_path: Some(format!("/shots/{}.png", id)),
+            ..record(
+                id,
+                timestamp,
+                "auto",
+                &format!(
+                    r#"{{"current_focus":"任务{}","active_software":"Code","importance":{}}}"#,
+                    id, importance
+                ),
+            )
+        }
+    }
+
+    #[test]
+    fn wrap_text_breaks_at_spaces_and_inside_cjk_text() {
+        // 10pt: ASCII is 5pt wide, CJK 10pt
+        assert_eq!(
+            wrap_text("hello world again", 60.0, 10.0),
+            vec!["hello world", "again"]
+        );
+        assert_eq!(wrap_text("一二三四五", 30.0, 10.0), vec!["一二三", "四五"]);
+        assert_eq!(wrap_text("a\nb", 100.0, 10.0), vec!["a", "b"]);
+        assert_eq!(wrap_text("", 100.0, 10.0), vec![""]);
+    }
+
+    #[test]
+    fn key_screenshots_are_the_most_important_per_day() {
+        let records: Vec<Record> = (1..=6)
+            .map(|i| {
+                shot(
+                    i,
+                    &format!("2026-03-16T0{}:00:00+00:00", i + 1),
+                    (i * 10) as u8,
+                )
+            })
+            .chain(std::iter::once(record(
+                7,
+                "2026-03-16T09:00:00+00:00",
+                "manual",
+                "想法",
+            )))
+            .collect();
+
+        let ids = key_screenshot_ids(&records);
+
+        assert_eq!(ids, HashSet::from([3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn report_days_group_records_and_attach_thumbnails() {
+        let records = vec![
+            shot(1, "2026-03-16T12:00:00+00:00", 90),
+            record(2, "2026-03-16T12:30:00+00:00", "manual", "开会"),
+            shot(3, "2026-03-17T12:00:00+00:00", 90),
+        ];
+
+        let days = build_report_days(&records, |path| {
+            (path != "/shots/3.png").then_some((1920, 1080))
+        });
+
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].entries.len(), 2);
+        assert_eq!(days[0].entries[0].text, "任务1（Code）");
+        assert_eq!(
+            days[0].entries[0].thumbnail,
+            Some(ThumbnailRef {
+                path: "/shots/1.png".to_string(),
+                width: 1920,
+                height: 1080
+            })
+        );
+        assert_eq!(days[0].entries[1].text, "开会");
+        // Unreadable screenshots are left out
+        assert_eq!(days[1].entries[0].thumbnail, None);
+    }
+
+    #[test]
+    fn layout_paginates_and_stays_inside_the_margins() {
+        let entries: Vec<ReportEntry> = (0..60)
+            .map(|i| ReportEntry {
+                time: format!("{:02}:00", i % 24),
+                text: "记录内容".repeat(20),
+                thumbnail: (i % 10 == 0).then(|| ThumbnailRef {
+                    path: format!("/shots/{}.png", i),
+                    width: 1920,
+                    height: 1080,
+                }),
+            })
+            .collect();
+        let days = vec![ReportDay {
+            date: "2026-03-16".to_string(),
+            entries,
+        }];
+
+        let pages = layout_report("标题", "副标题", &days);
+
+        assert!(pages.len() > 1);
+        let mut images = 0;
+        for page in &pages {
+            assert!(!page.elements.is_empty());
+            for element in &page.elements {
+                let (x, top, bottom, right) = match element {
+                    PdfElement::Text { x, y, size, text } => (
+                        *x,
+                        *y,
+                        y + size * LINE_SPACING,
+                        x + text.chars().map(|c| char_width(c, *size)).sum::<f32>(),
+                    ),
+                    PdfElement::Image {
+                        x,
+                        y,
+                        width,
+                        height,
+                        ..
+                    } => {
+                        images += 1;
+                        assert!(*width <= THUMBNAIL_MAX_WIDTH && *height <= THUMBNAIL_MAX_HEIGHT);
+                        (*x, *y, y + height, x + width)
+                    }
+                };
+                assert!(x >= MARGIN && right <= PAGE_WIDTH - MARGIN + 0.01);
+                assert!(top >= MARGIN && bottom <= PAGE_HEIGHT - MARGIN + 0.01);
+            }
+        }
+        assert_eq!(images, 6);
+    }
+
+    #[test]
+    fn report_font_is_the_first_installed_candidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let font = dir.path().join("font.ttf");
+        std::fs::write(&font, b"font").unwrap();
+        let missing = dir.path().join("missing.ttc");
+
+        assert_eq!(
+            first_existing_font(&[missing.to_str().unwrap(), font.to_str().unwrap()]),
+            Some(font)
+        );
+        assert_eq!(first_existing_font(&[missing.to_str().unwrap()]), None);
+    }
+
+    #[test]
+    fn render_pdf_rejects_invalid_font_data() {
+        let pages = vec![PdfPage {
+            elements: vec![PdfElement::Text {
+                x: MARGIN,
+                y: MARGIN,
+                size: BODY_SIZE,
+                text: "日报 🖥️".to_string(),
+            }],
+        }];
+
+        let err = render_pdf("标题", &pages, b"not a font", |_| None).unwrap_err();
+        assert!(err.message.contains("font"));
+    }
+
+    /// A minimal TrueType font mapping each of `chars` to a square glyph.
+    fn test_font(chars: &[char]) -> Vec<u8> {
+        fn be16(v: i16) -> [u8; 2] {
+            v.to_be_bytes()
+        }
+        fn be32(v: u32) -> [u8; 4] {
+            v.to_be_bytes()
+        }
+        let glyph_count = chars.len() as i16 + 1;
+
+        // One contour through (0,0) (800,0) (800,800) (0,800), as deltas
+        let square = [
+            &be16(1)[..],
+            &be16(0),
+            &be16(0),
+            &be16(800),
+            &be16(800),
+            &be16(3),
+            &be16(0),
+            &[1, 1, 1, 1],
+            &be16(0),
+            &be16(800),
+            &be16(0),
+            &be16(-800),
+            &be16(0),
+            &be16(0),
+            &be16(800),
+            &be16(0),
+        ]
+        .concat();
+        // Glyph 0 (.notdef) is empty
+        let mut loca = [be32(0), be32(0)].concat();
+        let mut glyf = Vec::new();
+        for _ in chars {
+            glyf.extend_from_slice(&square);
+            loca.extend_from_slice(&be32(glyf.len() as u32));
+        }
+        let hmtx = [be16(1000), be16(0)].concat().repeat(glyph_count as usize);
+        // Format 12 cmap with one group per character
+        let mut cmap = [
+            &be16(0)[..],
+            &be16(1),
+            &be16(3),
+            &be16(10),
+            &be32(12),
+            &be16(12),
+            &be16(0),
+            &be32(16 + 12 * chars.len() as u32),
+            &be32(0),
+            &be32(chars.len() as u32),
+        ]
+        .concat();
+        for (glyph, &c) in (1..).zip(chars) {
+            cmap.extend([be32(c as u32), be32(c as u32), be32(glyph)].concat());
+        }
+        let head = [
+            &be32(0x0001_0000)[..],
+            &be32(0x0001_0000),
+            &be32(0),
+            &be32(0x5F0F_3CF5),
+            &be16(0),
+            &be16(1000),
+            &[0; 16],
+            &be16(0),
+            &be16(0),
+            &be16(800),
+            &be16(800),
+            &be16(0),
+            &be16(8),
+            &be16(2),
+            // Long loca offsets
+            &be16(1),
+            &be16(0),
+        ]
+        .concat();
+        let hhea = [
+            &be32(0x0001_0000)[..],
+            &be16(880),
+            &be16(-120),
+            &be16(0),
+            &be16(1000),
+            &[0; 22],
+            &be16(glyph_count),
+        ]
+        .concat();
+        let maxp = [&be32(0x0001_0000)[..], &be16(glyph_count), &[0; 26]].concat();
+        let post = [&be32(0x0003_0000)[..], &[0; 28]].concat();
+        let tables: [(&[u8; 4], Vec<u8>); 8] = [
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+            (b"post", post),
+        ];
+
+        let mut font = [&be32(0x0001_0000)[..], &be16(tables.len() as i16), &[0; 6]].concat();
+        let mut data = Vec::new();
+        for (tag, table) in &tables {
+            let offset = 12 + 16 * tables.len() + data.len();
+            font.extend(
+                [
+                    &tag[..],
+                    &be32(0),
+                    &be32(offset as u32),
+                    &be32(table.len() as u32),
+                ]
+                .concat(),
+            );
+            data.extend_from_slice(table);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+        font.extend(data);
+        font
+    }
+
+    #[test]
+    fn render_pdf_embeds_only_the_glyphs_of_the_report() {
+        // 1024 CJK characters, among them 日 (U+65E5) and 报 (U+62A5)
+        let chars: Vec<char> = ('\u{6200}'..'\u{6600}').collect();
+        let font = test_font(&chars);
+        let pages = vec![PdfPage {
+            elements: vec![PdfElement::Text {
+                x: MARGIN,
+                y: MARGIN,
+                size: BODY_SIZE,
+                text: "日报".to_string(),
+            }],
+        }];
+
+        let pdf = render_pdf("日报", &pages, &font, |_| None).unwrap();
+
+        let mut doc = printpdf::lopdf::Document::load_mem(&pdf).unwrap();
+        doc.decompress();
+        let page_ids = doc.get_pages();
+        assert_eq!(page_ids.len(), 1);
+        let streams = || doc.objects.values().filter_map(|o| o.as_stream().ok());
+
+        let font_file = streams().find(|s| s.dict.has(b"Length1")).unwrap();
+        assert!(font_file.content.len() < font.len() / 2);
+        let embedded = ttf_parser::Face::parse(&font_file.content, 0).unwrap();
+        let ri = embedded.glyph_index('日').unwrap();
+        let bao = embedded.glyph_index('报').unwrap();
+        assert!(embedded.glyph_bounding_box(ri).is_some());
+        // Outlines of characters the report does not use are dropped
+        let unused = embedded.glyph_index('我').unwrap();
+        assert!(embedded.glyph_bounding_box(unused).is_none());
+
+        let content = doc.get_page_content(page_ids[&1]).unwrap();
+        assert!(
+            String::from_utf8_lossy(&content).contains(&format!("<{:04X}{:04X}> Tj", ri.0, bao.0))
+        );
+        let to_unicode = streams()
+            .map(|s| String::from_utf8_lossy(&s.content).into_owned())
+            .find(|content| content.contains("beginbfchar"))
+            .unwrap();
+        assert!(to_unicode.contains("<65e5>") && to_unicode.contains("<62a5>"));
+        assert!(!to_unicode.contains("<6211>"));
+    }
+}
@@ -0,0 +1,237 @@
+//! Progress reporting and cancellation of long exports.
+//!
+//! A large export (tens of thousands of records) can take a while. The writer
+//! reports its progress through an [`ExportProgressReporter`] and checks a
+//! [`CancellationToken`] between units of work; on cancellation it removes
+//! whatever it had written so far. Neither type knows about Tauri, so the
+//! writers can be tested with a plain closure and a token cancelled by hand.
+
+use crate::errors::{AppError, AppResult, ErrorCode};
+use crate::i18n::{self, MessageKey};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Shared cancellation flag. Clones observe the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every holder of the token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// `Err` with [`MessageKey::ExportCancelled`] once cancelled.
+    pub fn check(&self) -> AppResult<()> {
+        if self.is_cancelled() {
+            Err(AppError::localized(
+                ErrorCode::Validation,
+                MessageKey::ExportCancelled,
+                i18n::current_lang(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Whether `error` is the one returned by [`CancellationToken::check`].
+pub fn is_cancelled_error(error: &AppError) -> bool {
+    error.message_key.as_deref() == Some(MessageKey::ExportCancelled.as_str())
+}
+
+/// Progress of one export, pushed as the `export-progress` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportProgress {
+    /// Id the caller passed with the export request (empty if none)
+    pub export_id: String,
+    /// Units of work done (records or bytes, depending on the export)
+    pub processed: u64,
+    /// Units of work in total
+    pub total: u64,
+    /// `processed / total` in whole percent, 0-100
+    pub percent: u8,
+}
+
+/// Turns "n of total done" into `ExportProgress` callbacks, calling back only
+/// when the whole percentage changes so a large export does not flood the UI.
+pub struct ExportProgressReporter<F: FnMut(&ExportProgress)> {
+    export_id: String,
+    total: u64,
+    processed: u64,
+    last_percent: Option<u8>,
+    on_progress: F,
+}
+
+impl<F: FnMut(&ExportProgress)> ExportProgressReporter<F> {
+    pub fn new(export_id: impl Into<String>, on_progress: F) -> Self {
+        Self {
+            export_id: export_id.into(),
+            total: 0,
+            processed: 0,
+            last_percent: None,
+            on_progress,
+        }
+    }
+
+    /// Start counting towards `total` units and report 0 %.
+    pub fn start(&mut self, total: u64) {
+        self.total = total;
+        self.processed = 0;
+        self.last_percent = None;
+        self.report();
+    }
+
+    /// Record `units` more units of work done.
+    pub fn advance(&mut self, units: u64) {
+        self.processed = (self.processed + units).min(self.total);
+        self.report();
+    }
+
+    /// Report 100 % (an empty export is done as soon as it starts).
+    pub fn finish(&mut self) {
+        self.processed = self.total;
+        if self.last_percent != Some(100) {
+            self.emit(100);
+        }
+    }
+
+    fn report(&mut self) {
+        let percent = if self.total == 0 {
+            0
+        } else {
+            (self.processed * 100 / self.total) as u8
+        };
+        if self.last_percent != Some(percent) {
+            self.emit(percent);
+        }
+    }
+
+    fn emit(&mut self, percent: u8) {
+        self.last_percent = Some(percent);
+        (self.on_progress)(&ExportProgress {
+            export_id: self.export_id.clone(),
+            processed: self.processed,
+            total: self.total,
+            percent,
+        });
+    }
+}
+
+/// Tokens of the exports currently running, by export id.
+static RUNNING_EXPORTS: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Keeps an export cancellable by id; unregisters it when dropped.
+pub struct RunningExport {
+    export_id: String,
+    token: CancellationToken,
+}
+
+impl RunningExport {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for RunningExport {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING_EXPORTS.lock() {
+            running.remove(&self.export_id);
+        }
+    }
+}
+
+/// Register an export under `export_id` so `cancel_running_export` can stop it.
+pub fn register_export(export_id: &str) -> AppResult<RunningExport> {
+    let mut running = RUNNING_EXPORTS.lock()?;
+    if running.contains_key(export_id) {
+        return Err(AppError::validation(format!(
+            "Export {} is already running",
+            export_id
+        )));
+    }
+    let token = CancellationToken::new();
+    running.insert(export_id.to_string(), token.clone());
+    Ok(RunningExport {
+        export_id: export_id.to_string(),
+        token,
+    })
+}
+
+/// Cancel the running export `export_id`. Returns false if no such export runs.
+pub fn cancel_running_export(export_id: &str) -> AppResult<bool> {
+    let running = RUNNING_EXPORTS.lock()?;
+    Ok(running
+        .get(export_id)
+        .map(CancellationToken::cancel)
+        .is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(total: u64, steps: &[u64]) -> Vec<u8> {
+        let mut percents = Vec::new();
+        let mut reporter = ExportProgressReporter::new("t", |p| percents.push(p.percent));
+        reporter.start(total);
+        for &step in steps {
+            reporter.advance(step);
+        }
+        reporter.finish();
+        percents
+    }
+
+    #[test]
+    fn reports_each_percentage_once() {
+        assert_eq!(collect(4, &[1, 1, 1, 1]), vec![0, 25, 50, 75, 100]);
+        // 1000 units in steps of 1 still give at most 101 callbacks
+        assert_eq!(collect(1000, &[1; 1000]).len(), 101);
+        // Overshooting is clamped
+        assert_eq!(collect(2, &[5]), vec![0, 100]);
+    }
+
+    #[test]
+    fn empty_export_goes_straight_to_done() {
+        assert_eq!(collect(0, &[]), vec![0, 100]);
+    }
+
+    #[test]
+    fn token_clones_share_the_flag() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+
+        clone.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(is_cancelled_error(&token.check().unwrap_err()));
+        // Only the message key marks a cancellation, not the text
+        let same_text = AppError::validation("Export cancelled");
+        assert!(!is_cancelled_error(&same_text));
+    }
+
+    #[test]
+    fn registered_exports_can_be_cancelled_by_id_until_dropped() {
+        let running = register_export("progress-test").unwrap();
+        assert!(register_export("progress-test").is_err());
+
+        assert!(cancel_running_export("progress-test").unwrap());
+        assert!(running.token().is_cancelled());
+
+        drop(running);
+        assert!(!cancel_running_export("progress-test").unwrap());
+    }
+}
@@ -0,0 +1,222 @@
+//! Aggregated alerts for repeated capture/analysis failures.
+//!
+//! Instead of notifying on every failure, failures are counted inside a time
+//! window. Once the window has elapsed a single summary alert is produced
+//! ("过去 10 分钟 5 次感知失败") and delivered as a frontend event and,
+//! optionally, a webhook POST.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::errors::{AppError, AppResult};
+
+/// Default aggregation window in minutes.
+pub const DEFAULT_ALERT_WINDOW_MINUTES: u64 = 10;
+
+/// Frontend event carrying a [`FailureAlert`].
+pub const FAILURE_ALERT_EVENT: &str = "capture-failure-alert";
+
+/// Summary of the failures collected in one window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureAlert {
+    pub failures: u32,
+    pub window_minutes: u64,
+    pub last_error: String,
+    pub message: String,
+}
+
+impl FailureAlert {
+    fn new(failures: u32, window_minutes: u64, last_error: String) -> Self {
+        let message = format!("过去 {} 分钟 {} 次感知失败", window_minutes, failures);
+        Self {
+            failures,
+            window_minutes,
+            last_error,
+            message,
+        }
+    }
+}
+
+/// Counts failures per window and emits at most one alert per window.
+#[derive(Debug)]
+pub struct FailureAggregator {
+    window: Duration,
+    window_start: Option<Instant>,
+    failures: u32,
+    last_error: String,
+}
+
+impl FailureAggregator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            window_start: None,
+            failures: 0,
+            last_error: String::new(),
+        }
+    }
+
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// Count a failure. The first failure opens a new window.
+    /// Returns the summary of the previous window if it has already elapsed.
+    pub fn record_failure(&mut self, now: Instant, error: &str) -> Option<FailureAlert> {
+        let alert = self.poll(now);
+        if self.window_start.is_none() {
+            self.window_start = Some(now);
+        }
+        self.failures += 1;
+        self.last_error = error.to_string();
+        alert
+    }
+
+    /// Close the current window if it has elapsed, returning its summary.
+    pub fn poll(&mut self, now: Instant) -> Option<FailureAlert> {
+        let start = self.window_start?;
+        if now.saturating_duration_since(start) < self.window {
+            return None;
+        }
+        let alert = FailureAlert::new(
+            self.failures,
+            self.window.as_secs() / 60,
+            std::mem::take(&mut self.last_error),
+        );
+        self.window_start = None;
+        self.failures = 0;
+        Some(alert)
+    }
+}
+
+static FAILURE_AGGREGATOR: Lazy<Mutex<FailureAggregator>> =
+    Lazy::new(|| Mutex::new(FailureAggregator::new(window_from_minutes(None))));
+
+fn window_from_minutes(minutes: Option<i32>) -> Duration {
+    let minutes = minutes
+        .filter(|m| *m > 0)
+        .map(|m| m as u64)
+        .unwrap_or(DEFAULT_ALERT_WINDOW_MINUTES);
+    Duration::from_secs(minutes * 60)
+}
+
+/// Count a capture failure in the shared aggregator.
+pub fn record_capture_failure(error: &str, window_minutes: Option<i32>) -> Option<FailureAlert> {
+    let mut aggregator = FAILURE_AGGREGATOR.lock().ok()?;
+    aggregator.set_window(window_from_minutes(window_minutes));
+    aggregator.record_failure(Instant::now(), error)
+}
+
+/// Close the shared aggregator's window if it has elapsed.
+pub fn poll_capture_failures(window_minutes: Option<i32>) -> Option<FailureAlert> {
+    let mut aggregator = FAILURE_AGGREGATOR.lock().ok()?;
+    aggregator.set_window(window_from_minutes(window_minutes));
+    aggregator.poll(Instant::now())
+}
+
+/// POST the alert as JSON to the configured webhook.
+pub async fn send_failure_webhook(url: &str, alert: &FailureAlert) -> AppResult<()> {
+    let client = crate::create_http_client(url, 10)?;
+    let response = client
+        .post(url)
+        .json(&serde_json::json!({
+            "text": alert.message,
+            "failures": alert.failures,
+            "window_minutes": alert.window_minutes,
+            "last_error": alert.last_error,
+        }))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(AppError::network(format!(
+            "Failure alert webhook returned {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+/// Deliver an alert to the frontend and, when configured, the webhook.
+/// Delivery failures are only logged.
+pub async fn deliver_failure_alert(
+    app: &tauri::AppHandle,
+    alert: FailureAlert,
+    webhook_url: Option<String>,
+) {
+    use tauri::Emitter;
+
+    tracing::warn!("{} (last error: {})", alert.message, alert.last_error);
+    if let Err(e) = app.emit(FAILURE_ALERT_EVENT, &alert) {
+        tracing::warn!("Failed to emit failure alert: {}", e);
+    }
+    if let Some(url) = webhook_url.filter(|u| !u.trim().is_empty()) {
+        if let Err(e) = send_failure_webhook(url.trim(), &alert).await {
+            tracing::warn!("Failed to send failure alert webhook: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(10 * 60);
+
+    #[test]
+    fn failures_within_window_produce_single_summary() {
+        let mut aggregator = FailureAggregator::new(WINDOW);
+        let start = Instant::now();
+
+        for i in 0..5 {
+            let now = start + Duration::from_secs(i * 60);
+            assert_eq!(
+                aggregator.record_failure(now, &format!("error {}", i)),
+                None
+            );
+        }
+        assert_eq!(aggregator.poll(start + Duration::from_secs(9 * 60)), None);
+
+        let alert = aggregator.poll(start + WINDOW).unwrap();
+        assert_eq!(alert.failures, 5);
+        assert_eq!(alert.last_error, "error 4");
+        assert_eq!(alert.message, "过去 10 分钟 5 次感知失败");
+
+        // Window closed: nothing more until the next failure
+        assert_eq!(aggregator.poll(start + WINDOW * 2), None);
+    }
+
+    #[test]
+    fn failure_after_window_flushes_previous_and_opens_new_window() {
+        let mut aggregator = FailureAggregator::new(WINDOW);
+        let start = Instant::now();
+
+        aggregator.record_failure(start, "first");
+        let alert = aggregator
+            .record_failure(start + WINDOW + Duration::from_secs(1), "second")
+            .unwrap();
+        assert_eq!(alert.failures, 1);
+        assert_eq!(alert.last_error, "first");
+
+        let next = aggregator
+            .poll(start + WINDOW * 2 + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(next.failures, 1);
+        assert_eq!(next.last_error, "second");
+    }
+
+    #[test]
+    fn poll_without_failures_returns_nothing() {
+        let mut aggregator = FailureAggregator::new(WINDOW);
+        assert_eq!(aggregator.poll(Instant::now() + WINDOW), None);
+    }
+
+    #[test]
+    fn window_from_minutes_falls_back_to_default() {
+        assert_eq!(window_from_minutes(Some(5)), Duration::from_secs(300));
+        assert_eq!(window_from_minutes(Some(0)), Duration::from_secs(600));
+        assert_eq!(window_from_minutes(None), Duration::from_secs(600));
+    }
+}
@@ -0,0 +1,270 @@
+//! Backend i18n for user-visible error messages.
+//!
+//! Errors are identified by a [`MessageKey`] and their text is picked from a
+//! per-language table according to the `language` setting (PERF-005).
+//! Messages may contain `{name}` placeholders filled in by [`message_with`].
+
+use crate::memory_storage::Settings;
+
+/// Supported UI languages for backend messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    ZhCn,
+}
+
+impl Lang {
+    /// Parse a language code such as "en", "zh-CN" or "zh".
+    /// Unknown codes fall back to English.
+    pub fn from_code(code: &str) -> Self {
+        if code.trim().to_lowercase().starts_with("zh") {
+            Lang::ZhCn
+        } else {
+            Lang::En
+        }
+    }
+
+    /// Language selected in settings, defaulting to English when unset.
+    pub fn from_settings(settings: &Settings) -> Self {
+        settings
+            .language
+            .as_deref()
+            .map(Self::from_code)
+            .unwrap_or_default()
+    }
+}
+
+/// Current UI language from the settings cache.
+/// Falls back to English when settings cannot be loaded.
+pub fn current_lang() -> Lang {
+    crate::memory_storage::get_settings_sync()
+        .map(|s| Lang::from_settings(&s))
+        .unwrap_or_default()
+}
+
+/// Identifiers of user-visible backend messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKey {
+    // Capture / screen analysis
+    ApiKeyNotConfigured,
+    ApiBaseUrlNotConfigured,
+    ScreenshotPermissionDenied,
+    NoMonitorsDetected,
+    NoDisplayAvailable,
+    MonitorNotFound,
+    /// Placeholder: `{error}`
+    ScreenshotTemporaryFailure,
+    ScreenshotSaveFailed,
+    // Synthesis / reports
+    ApiKeyRequiredNonOllama,
+    OfflineSummaryPreviewUnavailable,
+    OfflineDailySummaryQueued,
+    NoRecordsToday,
+    DailySummaryEmpty,
+    NoRecordsThisWeek,
+    NoRecordsThisMonth,
+    NoRecordsInRange,
+    EndDateBeforeStartDate,
+    NoRecordsInBothPeriods,
+    // Export
+    ExportCancelled,
+}
+
+impl MessageKey {
+    /// Every message key, used to check that all languages are covered.
+    pub const ALL: &'static [MessageKey] = &[
+        MessageKey::ApiKeyNotConfigured,
+        MessageKey::ApiBaseUrlNotConfigured,
+        MessageKey::ScreenshotPermissionDenied,
+        MessageKey::NoMonitorsDetected,
+        MessageKey::NoDisplayAvailable,
+        MessageKey::MonitorNotFound,
+        MessageKey::ScreenshotTemporaryFailure,
+        MessageKey::ScreenshotSaveFailed,
+        MessageKey::ApiKeyRequiredNonOllama,
+        MessageKey::OfflineSummaryPreviewUnavailable,
+        MessageKey::OfflineDailySummaryQueued,
+        MessageKey::NoRecordsToday,
+        MessageKey::DailySummaryEmpty,
+        MessageKey::NoRecordsThisWeek,
+        MessageKey::NoRecordsThisMonth,
+        MessageKey::NoRecordsInRange,
+        MessageKey::EndDateBeforeStartDate,
+        MessageKey::NoRecordsInBothPeriods,
+        MessageKey::ExportCancelled,
+    ];
+
+    /// Stable key exposed to the frontend alongside the translated message.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MessageKey::ApiKeyNotConfigured => "error.api_key_not_configured",
+            MessageKey::ApiBaseUrlNotConfigured => "error.api_base_url_not_configured",
+            MessageKey::ScreenshotPermissionDenied => "error.screenshot_permission_denied",
+            MessageKey::NoMonitorsDetected => "error.no_monitors_detected",
+            MessageKey::NoDisplayAvailable => "error.no_display_available",
+            MessageKey::MonitorNotFound => "error.monitor_not_found",
+            MessageKey::ScreenshotTemporaryFailure => "error.screenshot_temporary_failure",
+            MessageKey::ScreenshotSaveFailed => "error.screenshot_save_failed",
+            MessageKey::ApiKeyRequiredNonOllama => "error.api_key_required_non_ollama",
+            MessageKey::OfflineSummaryPreviewUnavailable => {
+                "error.offline_summary_preview_unavailable"
+            }
+            MessageKey::OfflineDailySummaryQueued => "error.offline_daily_summary_queued",
+            MessageKey::NoRecordsToday => "error.no_records_today",
+            MessageKey::DailySummaryEmpty => "error.daily_summary_empty",
+            MessageKey::NoRecordsThisWeek => "error.no_records_this_week",
+            MessageKey::NoRecordsThisMonth => "error.no_records_this_month",
+            MessageKey::NoRecordsInRange => "error.no_records_in_range",
+            MessageKey::EndDateBeforeStartDate => "error.end_date_before_start_date",
+            MessageKey::NoRecordsInBothPeriods => "error.no_records_in_both_periods",
+            MessageKey::ExportCancelled => "error.export_cancelled",
+        }
+    }
+}
+
+/// Look up the message text for a key in the given language.
+pub fn message(key: MessageKey, lang: Lang) -> &'static str {
+    match lang {
+        Lang::En => message_en(key),
+        Lang::ZhCn => message_zh_cn(key),
+    }
+}
+
+/// Look up a message and fill in its `{name}` placeholders.
+pub fn message_with(key: MessageKey, lang: Lang, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(message(key, lang).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+fn message_en(key: MessageKey) -> &'static str {
+    match key {
+        MessageKey::ApiKeyNotConfigured => "API key is not configured. Please set it in Settings",
+        MessageKey::ApiBaseUrlNotConfigured => "API Base URL not configured",
+        MessageKey::ScreenshotPermissionDenied => {
+            "Screen recording permission denied. Please allow it in system settings"
+        }
+        MessageKey::NoMonitorsDetected => "No monitor detected. Please check the display connection",
+        MessageKey::NoDisplayAvailable => {
+            "No display is currently available (remote desktop disconnected or no monitor attached)"
+        }
+        MessageKey::MonitorNotFound => {
+            "The selected monitor does not exist. Please check the multi-monitor settings"
+        }
+        MessageKey::ScreenshotTemporaryFailure => {
+            "Screenshot failed temporarily: {error}. It will be retried automatically"
+        }
+        MessageKey::ScreenshotSaveFailed => "Failed to save screenshot",
+        MessageKey::ApiKeyRequiredNonOllama => "API Key is required for non-Ollama endpoints",
+        MessageKey::OfflineSummaryPreviewUnavailable => {
+            "You are offline. Previewing the daily report requires a network connection"
+        }
+        MessageKey::OfflineDailySummaryQueued => {
+            "You are offline. The daily report has been queued and will be generated when the network is back"
+        }
+        MessageKey::NoRecordsToday => "No records today",
+        MessageKey::DailySummaryEmpty => "The daily report is empty",
+        MessageKey::NoRecordsThisWeek => "No records this week",
+        MessageKey::NoRecordsThisMonth => "No records this month",
+        MessageKey::NoRecordsInRange => "No records in the selected date range",
+        MessageKey::EndDateBeforeStartDate => "The end date cannot be earlier than the start date",
+        MessageKey::NoRecordsInBothPeriods => "No records in either period",
+        MessageKey::ExportCancelled => "Export cancelled",
+    }
+}
+
+fn message_zh_cn(key: MessageKey) -> &'static str {
+    match key {
+        MessageKey::ApiKeyNotConfigured => "API 密钥未配置，请在设置中配置",
+        MessageKey::ApiBaseUrlNotConfigured => "API 地址未配置",
+        MessageKey::ScreenshotPermissionDenied => {
+            "截图权限被拒绝，请在系统设置中允许应用进行屏幕录制"
+        }
+        MessageKey::NoMonitorsDetected => "未检测到显示器，请检查屏幕连接",
+        MessageKey::NoDisplayAvailable => "当前无可用显示器（远程桌面已断开或未连接显示器）",
+        MessageKey::MonitorNotFound => "指定的显示器不存在，请检查多显示器配置",
+        MessageKey::ScreenshotTemporaryFailure => "截图暂时失败: {error}，将自动重试",
+        MessageKey::ScreenshotSaveFailed => "截图保存失败",
+        MessageKey::ApiKeyRequiredNonOllama => "非 Ollama 端点需要配置 API 密钥",
+        MessageKey::OfflineSummaryPreviewUnavailable => "当前处于离线状态，日报预览需要网络连接",
+        MessageKey::OfflineDailySummaryQueued => {
+            "当前处于离线状态，日报生成已加入队列，网络恢复后将自动处理"
+        }
+        MessageKey::NoRecordsToday => "今日无记录",
+        MessageKey::DailySummaryEmpty => "日报内容为空",
+        MessageKey::NoRecordsThisWeek => "本周无记录",
+        MessageKey::NoRecordsThisMonth => "本月无记录",
+        MessageKey::NoRecordsInRange => "所选时间范围内无记录",
+        MessageKey::EndDateBeforeStartDate => "结束日期不能早于起始日期",
+        MessageKey::NoRecordsInBothPeriods => "两个时间段内均无记录",
+        MessageKey::ExportCancelled => "导出已取消",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lang_from_code_handles_common_codes() {
+        assert_eq!(Lang::from_code("zh-CN"), Lang::ZhCn);
+        assert_eq!(Lang::from_code("zh"), Lang::ZhCn);
+        assert_eq!(Lang::from_code("ZH-cn"), Lang::ZhCn);
+        assert_eq!(Lang::from_code("en"), Lang::En);
+        assert_eq!(Lang::from_code("ja"), Lang::En);
+        assert_eq!(Lang::from_code(""), Lang::En);
+    }
+
+    #[test]
+    fn lang_from_settings_defaults_to_english() {
+        let settings = Settings::default();
+        assert_eq!(Lang::from_settings(&settings), Lang::En);
+
+        let settings = Settings {
+            language: Some("zh-CN".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Lang::from_settings(&settings), Lang::ZhCn);
+    }
+
+    #[test]
+    fn same_key_has_different_text_per_language() {
+        for key in MessageKey::ALL {
+            let en = message(*key, Lang::En);
+            let zh = message(*key, Lang::ZhCn);
+            assert!(!en.is_empty(), "{} missing English text", key.as_str());
+            assert!(!zh.is_empty(), "{} missing Chinese text", key.as_str());
+            assert_ne!(en, zh, "{} is not translated", key.as_str());
+        }
+    }
+
+    #[test]
+    fn message_keys_are_unique() {
+        let mut keys: Vec<&str> = MessageKey::ALL.iter().map(|k| k.as_str()).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), MessageKey::ALL.len());
+    }
+
+    #[test]
+    fn message_with_fills_placeholders() {
+        let en = message_with(
+            MessageKey::ScreenshotTemporaryFailure,
+            Lang::En,
+            &[("error", "device busy")],
+        );
+        assert_eq!(
+            en,
+            "Screenshot failed temporarily: device busy. It will be retried automatically"
+        );
+
+        let zh = message_with(
+            MessageKey::ScreenshotTemporaryFailure,
+            Lang::ZhCn,
+            &[("error", "device busy")],
+        );
+        assert_eq!(zh, "截图暂时失败: device busy，将自动重试");
+    }
+}
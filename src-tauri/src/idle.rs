@@ -0,0 +1,172 @@
+//! User idle detection.
+//!
+//! Reads how long the user has not touched keyboard or mouse, and tracks the
+//! transitions between idle and active so auto capture can take a screenshot
+//! as soon as the user comes back instead of waiting for the next interval.
+//!
+//! - **Windows**: `GetLastInputInfo`
+//! - **macOS**: `HIDIdleTime` reported by `ioreg`
+//! - **Linux**: `xprintidle` command-line tool
+
+use std::time::Duration;
+
+/// Time without input after which the user counts as idle.
+pub const IDLE_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// How often the idle time is polled.
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Change of the user's idle state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTransition {
+    /// No input for at least the threshold
+    BecameIdle,
+    /// Input again after being idle
+    BecameActive,
+}
+
+/// Turns successive idle-time readings into idle/active transitions.
+#[derive(Debug, Clone)]
+pub struct IdleTracker {
+    threshold: Duration,
+    idle: bool,
+}
+
+impl IdleTracker {
+    /// A tracker that starts in the active state.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            idle: false,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Feed the current time since the last input; returns the transition it
+    /// caused, if any.
+    pub fn update(&mut self, idle_for: Duration) -> Option<IdleTransition> {
+        let idle = idle_for >= self.threshold;
+        if idle == self.idle {
+            return None;
+        }
+        self.idle = idle;
+        Some(if idle {
+            IdleTransition::BecameIdle
+        } else {
+            IdleTransition::BecameActive
+        })
+    }
+}
+
+/// Time since the last keyboard or mouse input, `None` when it cannot be read.
+#[cfg(target_os = "windows")]
+pub fn system_idle_time() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `info` is a valid LASTINPUTINFO with cbSize set.
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // SAFETY: GetTickCount has no preconditions.
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+}
+
+/// Time since the last keyboard or mouse input, `None` when it cannot be read.
+#[cfg(target_os = "macos")]
+pub fn system_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4"])
+        .output()
+        .ok()?;
+    parse_hid_idle_time(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Time since the last keyboard or mouse input, `None` when it cannot be read.
+#[cfg(target_os = "linux")]
+pub fn system_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_xprintidle(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Time since the last keyboard or mouse input, `None` when it cannot be read.
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn system_idle_time() -> Option<Duration> {
+    None
+}
+
+/// Parse the `"HIDIdleTime" = <nanoseconds>` line of `ioreg -c IOHIDSystem`.
+#[cfg_attr(not(any(target_os = "macos", test)), allow(dead_code))]
+fn parse_hid_idle_time(output: &str) -> Option<Duration> {
+    let line = output.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+    let nanos: u64 = line.split('=').nth(1)?.trim().parse().ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+/// Parse the milliseconds printed by `xprintidle`.
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_xprintidle(output: &str) -> Option<Duration> {
+    output.trim().parse().ok().map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_reports_each_transition_once() {
+        let mut tracker = IdleTracker::new(Duration::from_secs(300));
+
+        assert_eq!(tracker.update(Duration::from_secs(10)), None);
+        assert_eq!(
+            tracker.update(Duration::from_secs(300)),
+            Some(IdleTransition::BecameIdle)
+        );
+        assert!(tracker.is_idle());
+        assert_eq!(tracker.update(Duration::from_secs(900)), None);
+        assert_eq!(
+            tracker.update(Duration::from_secs(2)),
+            Some(IdleTransition::BecameActive)
+        );
+        assert!(!tracker.is_idle());
+        assert_eq!(tracker.update(Duration::from_secs(4)), None);
+    }
+
+    #[test]
+    fn tracker_does_not_wake_without_being_idle_first() {
+        let mut tracker = IdleTracker::new(Duration::from_secs(60));
+        for seconds in [0, 5, 30, 59, 0] {
+            assert_eq!(tracker.update(Duration::from_secs(seconds)), None);
+        }
+    }
+
+    #[test]
+    fn parses_ioreg_hid_idle_time() {
+        let output = "    | |   \"HIDIdleTime\" = 2500000000\n    | |   \"HIDKeyboardModifierMappingPairs\" = ()";
+        assert_eq!(
+            parse_hid_idle_time(output),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(parse_hid_idle_time("no idle time here"), None);
+    }
+
+    #[test]
+    fn parses_xprintidle_milliseconds() {
+        assert_eq!(
+            parse_xprintidle("1234\n"),
+            Some(Duration::from_millis(1234))
+        );
+        assert_eq!(parse_xprintidle("error"), None);
+    }
+}
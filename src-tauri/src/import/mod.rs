@@ -0,0 +1,480 @@
+use crate::errors::{AppError, AppResult};
+use crate::memory_storage;
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+/// CSV column names used for each record field.
+/// Unmapped or missing columns fall back to defaults: the import time for
+/// `timestamp`, `manual` for `source_type` and no tags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub timestamp: Option<String>,
+    pub content: Option<String>,
+    pub source_type: Option<String>,
+    pub tags: Option<String>,
+}
+
+impl Default for ColumnMapping {
+    /// Matches the column names written by `export_to_csv`.
+    fn default() -> Self {
+        Self {
+            timestamp: Some("timestamp".to_string()),
+            content: Some("content".to_string()),
+            source_type: Some("source_type".to_string()),
+            tags: Some("tags".to_string()),
+        }
+    }
+}
+
+/// A CSV row mapped to record fields, ready to insert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRecord {
+    /// UTC RFC3339
+    pub timestamp: String,
+    pub source_type: String,
+    pub content: String,
+    /// JSON array of tags
+    pub tags: Option<String>,
+}
+
+/// Parse CSV text (RFC 4180) into rows of fields.
+///
+/// Quoted fields may contain commas, doubled quotes and line breaks. Both LF
+/// and CRLF line endings are accepted, a leading UTF-8 BOM is ignored and
+/// blank lines are skipped.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                if !(row.len() == 1 && row[0].is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Parse an imported timestamp into UTC RFC3339.
+/// Accepts RFC3339 and local `YYYY-MM-DD HH:MM[:SS]` / `YYYY/MM/DD HH:MM[:SS]`
+/// (as written by spreadsheets).
+pub fn parse_import_timestamp(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&chrono::Utc).to_rfc3339());
+    }
+    const FORMATS: [&str; 5] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ];
+    FORMATS
+        .iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(value, f).ok())
+        .and_then(|naive| chrono::Local.from_local_datetime(&naive).earliest())
+        .map(|dt| dt.with_timezone(&chrono::Utc).to_rfc3339())
+}
+
+/// Normalize a tags cell: a JSON array is kept, otherwise the cell is split on
+/// `,` / `;` / `，`.
+fn tags_to_json(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let tags: Vec<String> = serde_json::from_str::<Vec<String>>(value).unwrap_or_else(|_| {
+        value
+            .split([',', ';', '，'])
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    });
+    if tags.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&tags).ok()
+}
+
+/// Map parsed CSV rows (first row is the header) to records.
+/// Each data row yields the record or a description of why it is invalid.
+/// `now` is the timestamp used when no timestamp column is mapped.
+pub fn map_csv_rows(
+    rows: &[Vec<String>],
+    mapping: &ColumnMapping,
+    now: &str,
+) -> Vec<Result<ImportedRecord, String>> {
+    let Some((header, data)) = rows.split_first() else {
+        return Vec::new();
+    };
+    let column = |name: &Option<String>| {
+        name.as_deref()
+            .and_then(|n| header.iter().position(|h| h.trim() == n.trim()))
+    };
+    let timestamp_col = column(&mapping.timestamp);
+    let content_col = column(&mapping.content);
+    let source_col = column(&mapping.source_type);
+    let tags_col = column(&mapping.tags);
+
+    data.iter()
+        .enumerate()
+        .map(|(i, row)| -> Result<ImportedRecord, String> {
+            // Line numbers as seen in a spreadsheet: header is line 1
+            let line = i + 2;
+            let cell = |col: Option<usize>| {
+                col.and_then(|c| row.get(c))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+
+            let content = cell(content_col)
+                .ok_or_else(|| format!("Row {}: empty content", line))?
+                .to_string();
+            let timestamp = match cell(timestamp_col) {
+                Some(value) => parse_import_timestamp(value)
+                    .ok_or_else(|| format!("Row {}: invalid timestamp '{}'", line, value))?,
+                None => now.to_string(),
+            };
+            let source_type = cell(source_col)
+                .map(|s| s.to_lowercase())
+                .unwrap_or_else(|| "manual".to_string());
+
+            Ok(ImportedRecord {
+                timestamp,
+                source_type,
+                content,
+                tags: cell(tags_col).and_then(tags_to_json),
+            })
+        })
+        .collect()
+}
+
+/// Outcome of an import: how many records were inserted and why the others
+/// were skipped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub inserted: usize,
+    /// Identical to an existing record (or an earlier row of the same input)
+    pub skipped_duplicate: usize,
+    /// Rows that could not be mapped to a record
+    pub skipped_invalid: usize,
+    /// One message per invalid row
+    pub errors: Vec<String>,
+}
+
+/// Map the items of a JSON import to records. Accepts the `export_to_json`
+/// document (`{"records": [...]}`) or a plain array of record objects.
+pub fn map_json_records(
+    value: &serde_json::Value,
+    now: &str,
+) -> Vec<Result<ImportedRecord, String>> {
+    let items = value
+        .get("records")
+        .unwrap_or(value)
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| -> Result<ImportedRecord, String> {
+            let index = i + 1;
+            if !item.is_object() {
+                return Err(format!("Record {}: not an object", index));
+            }
+            let text = |key: &str| {
+                item.get(key)
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+            };
+
+            let content = text("content")
+                .ok_or_else(|| format!("Record {}: empty content", index))?
+                .to_string();
+            let timestamp = match text("timestamp") {
+                Some(value) => parse_import_timestamp(value)
+                    .ok_or_else(|| format!("Record {}: invalid timestamp '{}'", index, value))?,
+                None => now.to_string(),
+            };
+            let source_type = text("source_type")
+                .map(|s| s.to_lowercase())
+                .unwrap_or_else(|| "manual".to_string());
+            let tags = match item.get("tags") {
+                Some(serde_json::Value::Array(_)) => tags_to_json(&item["tags"].to_string()),
+                Some(serde_json::Value::String(tags)) => tags_to_json(tags),
+                _ => None,
+            };
+
+            Ok(ImportedRecord {
+                timestamp,
+                source_type,
+                content,
+                tags,
+            })
+        })
+        .collect()
+}
+
+/// Insert mapped records, counting inserted, duplicate and invalid rows.
+fn store_imported_records(rows: Vec<Result<ImportedRecord, String>>) -> AppResult<ImportReport> {
+    let mut report = ImportReport::default();
+    for row in rows {
+        match row {
+            Ok(record) => {
+                let inserted = memory_storage::import_record_sync(
+                    &record.timestamp,
+                    &record.source_type,
+                    &record.content,
+                    record.tags.as_deref(),
+                )?;
+                if inserted.is_some() {
+                    report.inserted += 1;
+                } else {
+                    report.skipped_duplicate += 1;
+                }
+            }
+            Err(reason) => {
+                report.skipped_invalid += 1;
+                report.errors.push(reason);
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn read_import_file(src_path: &std::path::Path) -> AppResult<String> {
+    std::fs::read_to_string(src_path).map_err(|e| {
+        AppError::file_io(format!(
+            "Failed to read import file {}: {}",
+            src_path.display(),
+            e
+        ))
+    })
+}
+
+fn log_import_report(report: &ImportReport, src_path: &std::path::Path) {
+    tracing::info!(
+        "Imported {} records from {} ({} duplicates, {} invalid)",
+        report.inserted,
+        src_path.display(),
+        report.skipped_duplicate,
+        report.skipped_invalid
+    );
+}
+
+/// Import records from a CSV file using `column_mapping` (defaults to the
+/// `export_to_csv` column names). Invalid rows and duplicates of existing
+/// records are skipped and counted in the report.
+pub fn import_records_csv_sync(
+    src_path: &std::path::Path,
+    column_mapping: &ColumnMapping,
+) -> AppResult<ImportReport> {
+    let rows = parse_csv(&read_import_file(src_path)?);
+    let now = chrono::Utc::now().to_rfc3339();
+    let report = store_imported_records(map_csv_rows(&rows, column_mapping, &now))?;
+    log_import_report(&report, src_path);
+    Ok(report)
+}
+
+/// Import records from a JSON file written by `export_to_json` (or a plain
+/// array of records). Invalid items and duplicates are skipped and counted.
+pub fn import_records_json_sync(src_path: &std::path::Path) -> AppResult<ImportReport> {
+    let value: serde_json::Value = serde_json::from_str(&read_import_file(src_path)?)
+        .map_err(|e| AppError::validation(format!("Invalid JSON import file: {}", e)))?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let report = store_imported_records(map_json_records(&value, &now))?;
+    log_import_report(&report, src_path);
+    Ok(report)
+}
+
+/// Tauri command: import records from a CSV file
+#[command]
+pub async fn import_records_csv(
+    src_path: String,
+    column_mapping: Option<ColumnMapping>,
+) -> Result<ImportReport, String> {
+    import_records_csv_sync(
+        std::path::Path::new(&src_path),
+        &column_mapping.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Tauri command: import records from a JSON file
+#[command]
+pub async fn import_records_json(src_path: String) -> Result<ImportReport, String> {
+    import_records_json_sync(std::path::Path::new(&src_path)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_csv_handles_quotes_and_line_breaks() {
+        let text = "\u{FEFF}timestamp,content\r\n2026-03-14 09:00,\"第一行\n第二行\"\r\n\r\n2026-03-14 10:00,\"含 \"\"引号\"\", 和逗号\"\n";
+        assert_eq!(
+            parse_csv(text),
+            vec![
+                strings(&["timestamp", "content"]),
+                strings(&["2026-03-14 09:00", "第一行\n第二行"]),
+                strings(&["2026-03-14 10:00", "含 \"引号\", 和逗号"]),
+            ]
+        );
+        // Last row without trailing newline and an empty trailing field
+        assert_eq!(
+            parse_csv("a,b\n1,"),
+            vec![strings(&["a", "b"]), strings(&["1", ""])]
+        );
+        assert!(parse_csv("").is_empty());
+    }
+
+    #[test]
+    fn map_csv_rows_uses_mapping_and_defaults() {
+        let rows = parse_csv(
+            "时间,内容,标签\n2026-03-14T01:00:00Z,写代码,\"rust, tauri\"\nbad time,x,\n2026-03-14T02:00:00Z,,\n",
+        );
+        let mapping = ColumnMapping {
+            timestamp: Some("时间".to_string()),
+            content: Some("内容".to_string()),
+            source_type: None,
+            tags: Some("标签".to_string()),
+        };
+
+        let mapped = map_csv_rows(&rows, &mapping, "now");
+        assert_eq!(
+            mapped[0],
+            Ok(ImportedRecord {
+                timestamp: "2026-03-14T01:00:00+00:00".to_string(),
+                source_type: "manual".to_string(),
+                content: "写代码".to_string(),
+                tags: Some(r#"["rust","tauri"]"#.to_string()),
+            })
+        );
+        assert_eq!(
+            mapped[1],
+            Err("Row 3: invalid timestamp 'bad time'".to_string())
+        );
+        assert_eq!(mapped[2], Err("Row 4: empty content".to_string()));
+
+        // Without a timestamp column the import time is used
+        let rows = parse_csv("content\nnote\n");
+        let mapped = map_csv_rows(&rows, &ColumnMapping::default(), "now");
+        assert_eq!(mapped[0].as_ref().unwrap().timestamp, "now");
+    }
+
+    #[test]
+    #[serial]
+    fn import_records_csv_inserts_rows_and_skips_duplicates() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("records.csv");
+        std::fs::write(
+            &path,
+            "timestamp,source_type,content\n2026-03-14T01:00:00Z,manual,\"多行\n笔记\"\n2026-03-14T02:00:00Z,,第二条\n",
+        )
+        .unwrap();
+
+        let report = import_records_csv_sync(&path, &ColumnMapping::default()).unwrap();
+        assert_eq!(report.inserted, 2);
+        // Importing the same file again inserts nothing
+        let report = import_records_csv_sync(&path, &ColumnMapping::default()).unwrap();
+        assert_eq!(report.inserted, 0);
+        assert_eq!(report.skipped_duplicate, 2);
+
+        let records = crate::memory_storage::get_records_by_date_range_sync(
+            "2026-03-13".into(),
+            "2026-03-15".into(),
+        )
+        .unwrap();
+        assert!(records.iter().any(|r| r.content == "多行\n笔记"));
+    }
+
+    #[test]
+    #[serial]
+    fn import_report_counts_duplicates_and_invalid_rows() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().unwrap();
+
+        let csv_path = dir.path().join("records.csv");
+        std::fs::write(
+            &csv_path,
+            "timestamp,content\n2026-03-14T01:00:00Z,A\n2026-03-14T01:00:00Z,A\nyesterday,B\n2026-03-14T02:00:00Z,\n",
+        )
+        .unwrap();
+        let report = import_records_csv_sync(&csv_path, &ColumnMapping::default()).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                inserted: 1,
+                skipped_duplicate: 1,
+                skipped_invalid: 2,
+                errors: vec![
+                    "Row 4: invalid timestamp 'yesterday'".to_string(),
+                    "Row 5: empty content".to_string(),
+                ],
+            }
+        );
+
+        let json_path = dir.path().join("records.json");
+        std::fs::write(
+            &json_path,
+            r#"{"records": [
+                {"timestamp": "2026-03-14T01:00:00Z", "source_type": "manual", "content": "A"},
+                {"timestamp": "2026-03-14T03:00:00Z", "content": "C", "tags": ["x"]},
+                "not a record",
+                {"timestamp": "2026-03-14T04:00:00Z"}
+            ]}"#,
+        )
+        .unwrap();
+        let report = import_records_json_sync(&json_path).unwrap();
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.skipped_duplicate, 1);
+        assert_eq!(report.skipped_invalid, 2);
+        assert_eq!(
+            report.errors,
+            vec![
+                "Record 3: not an object".to_string(),
+                "Record 4: empty content".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Circuit breaker for calls to an external endpoint.
+//!
+//! After `failure_threshold` consecutive failures the circuit opens and calls
+//! are skipped for `open_duration`. Then it is half-open: one probe call is let
+//! through; its success closes the circuit, its failure opens it again.
+//!
+//! The breaker only holds state; the caller passes the current `Instant` and
+//! reports each outcome, so the transitions can be tested without waiting.
+
+use std::time::{Duration, Instant};
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through; failures are counted
+    Closed,
+    /// Calls are skipped until `until`
+    Open { until: Instant },
+    /// One probe call decides whether to close again; if it has not reported
+    /// back by `probe_deadline`, another probe is allowed
+    HalfOpen { probe_deadline: Instant },
+}
+
+/// Whether a call may be made now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallPermission {
+    /// Make the call and report its outcome
+    Allowed,
+    /// Skip the call; the circuit may let one through after `retry_in`
+    Rejected { retry_in: Duration },
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    consecutive_failures: u32,
+    state: CircuitState,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            consecutive_failures: 0,
+            state: CircuitState::Closed,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Ask to make a call at `now`. An open circuit whose wait is over turns
+    /// half-open and allows this call as the probe. A probe that never
+    /// reported back is given up after `open_duration` and another is allowed.
+    pub fn allow(&mut self, now: Instant) -> CallPermission {
+        match self.state {
+            CircuitState::Closed => CallPermission::Allowed,
+            CircuitState::Open { until }
+            | CircuitState::HalfOpen {
+                probe_deadline: until,
+            } if now < until => CallPermission::Rejected {
+                retry_in: until - now,
+            },
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => {
+                self.state = CircuitState::HalfOpen {
+                    probe_deadline: now + self.open_duration,
+                };
+                CallPermission::Allowed
+            }
+        }
+    }
+
+    /// Report a successful call: the circuit closes.
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.state = CircuitState::Closed;
+    }
+
+    /// Report a failed call at `now`. Opens the circuit when the probe failed
+    /// or the failures reached the threshold. Returns true if it just opened.
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let open = match self.state {
+            CircuitState::HalfOpen { .. } => true,
+            CircuitState::Closed => self.consecutive_failures >= self.failure_threshold,
+            CircuitState::Open { .. } => false,
+        };
+        if open {
+            self.state = CircuitState::Open {
+                until: now + self.open_duration,
+            };
+        }
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_FOR: Duration = Duration::from_secs(60);
+
+    fn opened(t0: Instant) -> CircuitBreaker {
+        let mut breaker = CircuitBreaker::new(3, OPEN_FOR);
+        assert!(!breaker.record_failure(t0));
+        assert!(!breaker.record_failure(t0));
+        assert!(breaker.record_failure(t0));
+        breaker
+    }
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let t0 = Instant::now();
+        let mut breaker = CircuitBreaker::new(3, OPEN_FOR);
+        breaker.record_failure(t0);
+        breaker.record_failure(t0);
+        // A success in between resets the count
+        breaker.record_success();
+        breaker.record_failure(t0);
+        breaker.record_failure(t0);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.allow(t0), CallPermission::Allowed);
+
+        let breaker = opened(t0);
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: t0 + OPEN_FOR
+            }
+        );
+    }
+
+    #[test]
+    fn open_circuit_rejects_until_the_wait_is_over() {
+        let t0 = Instant::now();
+        let mut breaker = opened(t0);
+
+        assert_eq!(
+            breaker.allow(t0 + Duration::from_secs(20)),
+            CallPermission::Rejected {
+                retry_in: Duration::from_secs(40)
+            }
+        );
+        assert_eq!(breaker.allow(t0 + OPEN_FOR), CallPermission::Allowed);
+        assert!(matches!(breaker.state(), CircuitState::HalfOpen { .. }));
+    }
+
+    #[test]
+    fn half_open_allows_a_single_probe() {
+        let t0 = Instant::now();
+        let mut breaker = opened(t0);
+        let t1 = t0 + OPEN_FOR;
+
+        assert_eq!(breaker.allow(t1), CallPermission::Allowed);
+        assert!(matches!(
+            breaker.allow(t1 + Duration::from_secs(1)),
+            CallPermission::Rejected { .. }
+        ));
+        // A probe that never reports back is given up after the open duration
+        assert_eq!(breaker.allow(t1 + OPEN_FOR), CallPermission::Allowed);
+    }
+
+    #[test]
+    fn successful_probe_closes_the_circuit() {
+        let t0 = Instant::now();
+        let mut breaker = opened(t0);
+        breaker.allow(t0 + OPEN_FOR);
+
+        breaker.record_success();
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        // Needs the full threshold again to reopen
+        assert!(!breaker.record_failure(t0 + OPEN_FOR));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn failed_probe_reopens_the_circuit() {
+        let t0 = Instant::now();
+        let mut breaker = opened(t0);
+        let t1 = t0 + OPEN_FOR;
+        breaker.allow(t1);
+
+        assert!(breaker.record_failure(t1));
+
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: t1 + OPEN_FOR
+            }
+        );
+        assert!(matches!(breaker.allow(t1), CallPermission::Rejected { .. }));
+    }
+}
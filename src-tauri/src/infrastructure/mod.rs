@@ -5,8 +5,10 @@
 //!
 //! ## Modules
 //!
+//! - `circuit_breaker` - Circuit breaker state machine for calls to external endpoints
 //! - `retry` - Shared retry utilities (backoff, jitter, error classification)
 //! - `state` - Application state management conventions and AppState definition
 
+pub mod circuit_breaker;
 pub mod retry;
 pub mod state;
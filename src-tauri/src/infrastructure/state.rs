@@ -16,6 +16,8 @@
 //! - `capture_service::SCREEN_STATE` - Capture state (module-private)
 //! - `offline_queue::QUEUE_PROCESSING` - Queue processing flag (module-private)
 //! - `silent_tracker::SILENT_PATTERN_TRACKER` - Silent pattern tracker (module-private)
+//! - `export::progress::RUNNING_EXPORTS` - Cancellation tokens of running exports (module-private)
+//! - `auto_summary_scheduler::SCHEDULER_RUNNING` - Scheduled summary loop flag (module-private)
 //!
 //! **Rule**: If state is only used within a single module, keep it module-local.
 //!
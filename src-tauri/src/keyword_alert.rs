@@ -0,0 +1,242 @@
+//! Keyword reminders for new auto records (`keyword_alert_rules` setting).
+//!
+//! Each rule lists keywords and a reminder message, e.g.
+//! `[{"keywords": ["摸鱼", "bilibili"], "message": "该专注了"}]`. When the
+//! analysis of a new auto record mentions one of a rule's keywords
+//! (case-insensitive) in `current_focus` or `context_keywords`, a
+//! [`KeywordAlert`] is delivered to the frontend as the `keyword-alert` event,
+//! which shows the notification. Records are checked once their analysis is
+//! stored; the pending placeholder written at capture time has neither field.
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+/// Frontend event carrying a [`KeywordAlert`].
+pub const KEYWORD_ALERT_EVENT: &str = "keyword-alert";
+
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
+/// Remember the app handle alerts are emitted with. Called once at startup.
+pub fn init(app: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// One keyword → reminder rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeywordAlertRule {
+    /// The rule fires when any of these appears in the record
+    pub keywords: Vec<String>,
+    /// Reminder shown to the user
+    pub message: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A rule that fired for a record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordAlert {
+    pub record_id: i64,
+    /// The keyword found in the record
+    pub keyword: String,
+    pub message: String,
+}
+
+/// Parse `keyword_alert_rules`. Missing or invalid JSON yields no rules.
+pub fn parse_keyword_alert_rules(json: Option<&str>) -> Vec<KeywordAlertRule> {
+    let Some(json) = json.filter(|j| !j.trim().is_empty()) else {
+        return Vec::new();
+    };
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        tracing::warn!("Invalid keyword_alert_rules, ignoring: {}", e);
+        Vec::new()
+    })
+}
+
+/// The first keyword of `rule` found in `text` (case-insensitive), if any.
+fn matching_keyword<'a>(rule: &'a KeywordAlertRule, text: &str) -> Option<&'a str> {
+    let text = text.to_lowercase();
+    rule.keywords
+        .iter()
+        .map(|k| k.trim())
+        .find(|k| !k.is_empty() && text.contains(&k.to_lowercase()))
+}
+
+/// The analyzed text of a record: `current_focus` and `context_keywords`,
+/// one per line. Empty for content that is not an analysis result.
+fn analyzed_text(content: &str) -> String {
+    let focus = serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|v| v.get("current_focus")?.as_str().map(str::to_string));
+    focus
+        .into_iter()
+        .chain(crate::memory_storage::context_keywords_from_content(
+            content,
+        ))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Alerts of the enabled rules matching a record's analyzed `current_focus`
+/// and `context_keywords`. Only auto records are checked.
+pub fn match_keyword_alerts(
+    rules: &[KeywordAlertRule],
+    record_id: i64,
+    source_type: &str,
+    content: &str,
+) -> Vec<KeywordAlert> {
+    if source_type != "auto" {
+        return Vec::new();
+    }
+    let text = analyzed_text(content);
+    if text.is_empty() {
+        return Vec::new();
+    }
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| {
+            matching_keyword(rule, &text).map(|keyword| KeywordAlert {
+                record_id,
+                keyword: keyword.to_string(),
+                message: rule.message.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Match a record against `rules` and hand each alert to `notify`.
+/// Returns the number of alerts triggered.
+pub fn trigger_keyword_alerts(
+    rules: &[KeywordAlertRule],
+    record_id: i64,
+    source_type: &str,
+    content: &str,
+    mut notify: impl FnMut(&KeywordAlert),
+) -> usize {
+    let alerts = match_keyword_alerts(rules, record_id, source_type, content);
+    for alert in &alerts {
+        tracing::info!(
+            "Keyword alert for record {}: '{}' → {}",
+            alert.record_id,
+            alert.keyword,
+            alert.message
+        );
+        notify(alert);
+    }
+    alerts.len()
+}
+
+/// Check the freshly stored analysis of an auto record against the configured
+/// rules and emit `keyword-alert` events. Does nothing when the app is not running.
+/// Reads settings, so it must not be called while holding the DB lock.
+pub fn check_analyzed_record(record_id: i64, content: &str) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let rules = match crate::memory_storage::get_settings_sync() {
+        Ok(settings) => parse_keyword_alert_rules(settings.keyword_alert_rules.as_deref()),
+        Err(e) => {
+            tracing::warn!("Failed to load settings for keyword alerts: {}", e);
+            return;
+        }
+    };
+
+    use tauri::Emitter;
+    trigger_keyword_alerts(&rules, record_id, "auto", content, |alert| {
+        if let Err(e) = app.emit(KEYWORD_ALERT_EVENT, alert) {
+            tracing::warn!("Failed to emit keyword alert: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<KeywordAlertRule> {
+        parse_keyword_alert_rules(Some(
+            r#"[
+                {"keywords": ["摸鱼", "Bilibili"], "message": "该专注了"},
+                {"keywords": ["微博"], "message": "少刷微博", "enabled": false}
+            ]"#,
+        ))
+    }
+
+    const BILIBILI_RECORD: &str = r#"{"current_focus":"在 bilibili 看游戏实况","active_software":"Chrome","context_keywords":["游戏","直播"],"active_window":{"title":"【游戏实况】- bilibili","process_name":"chrome"}}"#;
+
+    #[test]
+    fn parses_rules_and_ignores_invalid_json() {
+        let rules = rules();
+        assert_eq!(rules.len(), 2);
+        assert!(rules[0].enabled);
+        assert!(!rules[1].enabled);
+
+        assert!(parse_keyword_alert_rules(None).is_empty());
+        assert!(parse_keyword_alert_rules(Some("")).is_empty());
+        assert!(parse_keyword_alert_rules(Some("{not json")).is_empty());
+    }
+
+    #[test]
+    fn matching_keyword_fires_its_rule() {
+        let alerts = match_keyword_alerts(&rules(), 7, "auto", BILIBILI_RECORD);
+        assert_eq!(
+            alerts,
+            vec![KeywordAlert {
+                record_id: 7,
+                keyword: "Bilibili".to_string(),
+                message: "该专注了".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn context_keywords_fire_rules() {
+        let analyzed =
+            r#"{"current_focus":"刷视频","active_software":"Chrome","context_keywords":["摸鱼"]}"#;
+        let alerts = match_keyword_alerts(&rules(), 8, "auto", analyzed);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].keyword, "摸鱼");
+    }
+
+    #[test]
+    fn only_analyzed_fields_are_matched() {
+        // Pending placeholder: the title mentions bilibili but nothing is analyzed yet
+        let pending = r#"{"current_focus":"待分析","active_window":{"title":"【游戏实况】- bilibili","process_name":"chrome"}}"#;
+        assert!(match_keyword_alerts(&rules(), 1, "auto", pending).is_empty());
+        // Software and window title are not part of the analyzed text
+        let coding = r#"{"current_focus":"写代码","active_software":"bilibili-client","context_keywords":["rust"]}"#;
+        assert!(match_keyword_alerts(&rules(), 1, "auto", coding).is_empty());
+        assert!(match_keyword_alerts(&rules(), 1, "auto", "摸鱼").is_empty());
+    }
+
+    #[test]
+    fn no_alert_without_match_disabled_rule_or_manual_record() {
+        let rules = rules();
+        let coding = r#"{"current_focus":"编写日报调度器","active_software":"VS Code"}"#;
+        assert!(match_keyword_alerts(&rules, 1, "auto", coding).is_empty());
+        // The 微博 rule is disabled
+        let weibo = r#"{"current_focus":"刷微博","context_keywords":[]}"#;
+        assert!(match_keyword_alerts(&rules, 1, "auto", weibo).is_empty());
+        // Manual notes are the user's own words
+        let note = r#"{"current_focus":"今天摸鱼了"}"#;
+        assert!(match_keyword_alerts(&rules, 1, "manual", note).is_empty());
+    }
+
+    #[test]
+    fn trigger_notifies_once_per_matching_rule() {
+        let mut notified = Vec::new();
+        let count = trigger_keyword_alerts(&rules(), 3, "auto", BILIBILI_RECORD, |a| {
+            notified.push(a.message.clone())
+        });
+        assert_eq!(count, 1);
+        assert_eq!(notified, vec!["该专注了".to_string()]);
+
+        let count =
+            trigger_keyword_alerts(&rules(), 4, "auto", "{}", |_| panic!("no rule should fire"));
+        assert_eq!(count, 0);
+    }
+}
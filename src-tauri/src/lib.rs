@@ -1,12 +1,21 @@
+pub mod api_key_pool;
 pub mod auto_backup_scheduler;
+pub mod auto_summary_scheduler;
 pub mod backup;
 pub mod commands;
 pub mod crypto;
+pub mod data_dir;
+pub mod debug_dump;
 pub mod errors;
 pub mod export;
+pub mod failure_alert;
 #[cfg(feature = "screenshot")]
 pub mod hardware;
+pub mod i18n;
+pub mod idle;
+pub mod import;
 pub mod infrastructure;
+pub mod keyword_alert;
 pub mod manual_entry;
 pub mod memory_storage;
 #[cfg(feature = "screenshot")]
@@ -16,7 +25,10 @@ pub mod network_status;
 pub mod offline_queue;
 pub mod ollama;
 pub mod performance;
+pub mod record_hook;
+pub mod redaction;
 pub mod services;
+pub mod shutdown;
 pub mod silent_tracker;
 pub mod synthesis;
 pub mod timeline;
@@ -305,12 +317,25 @@ pub fn create_http_client_with_proxy(
         .map_err(|e| AppError::internal(format!("Failed to create HTTP client: {}", e)))
 }
 
-/// Returns the application data directory: `<system_data_dir>/DailyLogger`.
+/// Environment variable that overrides the application data root
+/// (useful for tests, portable installs and running multiple instances).
+pub const DATA_DIR_ENV: &str = "DAILYLOGGER_DATA_DIR";
+
+/// Data root set through `DAILYLOGGER_DATA_DIR`, if any.
+pub fn data_dir_override() -> Option<PathBuf> {
+    std::env::var_os(DATA_DIR_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns the application data directory: `DAILYLOGGER_DATA_DIR` when set,
+/// then the directory chosen by `migrate_data_dir`, otherwise
+/// `<system_data_dir>/DailyLogger`.
 /// Used by all modules that need access to the app's persistent data.
 pub fn get_app_data_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("DailyLogger")
+    data_dir_override()
+        .or_else(data_dir::redirected_data_dir)
+        .unwrap_or_else(data_dir::default_data_dir)
 }
 
 /// Extract the date portion (YYYY-MM-DD) from an RFC3339 timestamp.
@@ -435,6 +460,16 @@ pub fn init_app() -> tauri::Result<()> {
     ));
     tracing::info!("init_app: Database initialized successfully");
 
+    // Captures the user never confirmed are not kept forever
+    if let Err(e) = memory_storage::purge_stale_drafts_sync() {
+        tracing::warn!("init_app: Failed to purge stale draft records: {}", e);
+    }
+
+    // Custom prompts written for older placeholder semantics
+    if let Err(e) = services::prompt_migration::run_prompt_migration() {
+        tracing::warn!("init_app: Failed to migrate custom prompts: {}", e);
+    }
+
     // PERF-007: Defer silent pattern stats loading - load lazily on first access
     // This reduces startup time by not blocking on database query during init
     // The stats will be loaded when first accessed via get_recent_stats() or similar
@@ -486,11 +521,39 @@ mod tests {
     }
 
     #[test]
+    #[serial_test::serial]
     fn get_app_data_dir_returns_dailylogger_subdir() {
         let dir = get_app_data_dir();
         assert!(dir.ends_with("DailyLogger"));
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn get_app_data_dir_honors_env_override() {
+        // Keep the DailyLogger suffix so concurrently running path tests are unaffected
+        let root = std::env::temp_dir()
+            .join("dailylogger-env-override")
+            .join("DailyLogger");
+        std::env::set_var(DATA_DIR_ENV, &root);
+        let dir = get_app_data_dir();
+        let db_path = crate::backup::get_db_path();
+        std::env::remove_var(DATA_DIR_ENV);
+
+        assert_eq!(dir, root);
+        assert_eq!(db_path, root.join("data").join("local.db"));
+        assert!(get_app_data_dir().ends_with("DailyLogger"));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn get_app_data_dir_ignores_empty_env_override() {
+        std::env::set_var(DATA_DIR_ENV, "");
+        let dir = get_app_data_dir();
+        std::env::remove_var(DATA_DIR_ENV);
+
+        assert!(dir.ends_with("DailyLogger"));
+    }
+
     // Tests for is_local_url function
     #[test]
     fn is_local_url_localhost() {
@@ -547,6 +610,7 @@ mod error_handling_tests {
 
     /// Test that the app data directory is correctly determined
     #[test]
+    #[serial_test::serial]
     fn app_data_dir_ends_with_dailylogger() {
         let dir = get_app_data_dir();
         assert!(
@@ -96,6 +96,12 @@ fn main() {
             daily_logger_lib::network_status::start_network_monitor(app.handle().clone());
             write_diagnostic_file("Network monitor started");
 
+            // Hook commands configured in `on_record_command` are spawned through the shell plugin
+            daily_logger_lib::record_hook::init(app.handle().clone());
+            daily_logger_lib::keyword_alert::init(app.handle().clone());
+            #[cfg(feature = "screenshot")]
+            daily_logger_lib::services::capture_service::init_capture_events(app.handle().clone());
+
             // PERF-007: Defer tray icon setup to after window is shown
             // Setup runs synchronously before window display, so we spawn async
             #[cfg(not(any(target_os = "android", target_os = "ios")))]
@@ -129,19 +135,31 @@ fn main() {
             });
             write_diagnostic_file("Auto backup scheduler spawned (deferred)");
 
+            // Scheduled daily summary at `summary_time`; spawned on the Tauri runtime
+            daily_logger_lib::auto_summary_scheduler::start_scheduler();
+            write_diagnostic_file("Auto summary scheduler started");
+
             write_diagnostic_file("Tauri setup completed - window should be visible");
             Ok(())
         })
-        .run(tauri::generate_context!());
+        .build(tauri::generate_context!());
 
-    write_diagnostic_file("Tauri run completed");
-
-    if let Err(e) = result {
-        write_diagnostic_file(&format!("Tauri application error: {}", e));
-        tracing::error!("Tauri application error: {}", e);
-        write_crash_message(&format!("Tauri application error: {}", e));
-        std::process::exit(1);
+    match result {
+        Ok(app) => app.run(|_app, event| {
+            // Let background capture loops finish their current cycle before exiting
+            if let tauri::RunEvent::Exit = event {
+                daily_logger_lib::shutdown::shutdown_background_loops();
+            }
+        }),
+        Err(e) => {
+            write_diagnostic_file(&format!("Tauri application error: {}", e));
+            tracing::error!("Tauri application error: {}", e);
+            write_crash_message(&format!("Tauri application error: {}", e));
+            std::process::exit(1);
+        }
     }
 
+    write_diagnostic_file("Tauri run completed");
+
     write_diagnostic_file("Application exiting normally");
 }
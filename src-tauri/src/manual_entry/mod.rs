@@ -76,20 +76,156 @@ pub async fn open_obsidian_folder() -> Result<(), String> {
     open_obsidian_folder_sync().map_err(|e| e.to_string())
 }
 
+/// Maximum number of content characters in a shared record summary.
+pub const RECORD_SUMMARY_MAX_CHARS: usize = 200;
+
+/// Format a record as a short plain-text summary for sharing:
+/// local time and source on the first line, truncated content below.
+pub fn format_record_summary(record: &memory_storage::Record, max_chars: usize) -> String {
+    let time = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| record.timestamp.clone());
+
+    let content = record.content.trim();
+    let content = if content.chars().count() > max_chars {
+        format!("{}…", content.chars().take(max_chars).collect::<String>())
+    } else {
+        content.to_string()
+    };
+
+    format!(
+        "{} {}\n{}",
+        time,
+        crate::source_type_label(&record.source_type),
+        content
+    )
+}
+
+/// Write text to the system clipboard using the platform clipboard tool.
+fn copy_to_clipboard(text: &str) -> AppResult<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    #[cfg(target_os = "windows")]
+    let (candidates, bytes): (&[(&str, &[&str])], Vec<u8>) = {
+        // clip.exe only keeps non-ASCII text intact when given UTF-16LE with a BOM
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+        (&[("clip", &[])], bytes)
+    };
+
+    #[cfg(target_os = "macos")]
+    let (candidates, bytes): (&[(&str, &[&str])], Vec<u8>) =
+        (&[("pbcopy", &[])], text.as_bytes().to_vec());
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (candidates, bytes): (&[(&str, &[&str])], Vec<u8>) = (
+        &[("wl-copy", &[]), ("xclip", &["-selection", "clipboard"])],
+        text.as_bytes().to_vec(),
+    );
+
+    for (program, args) in candidates {
+        let mut child = match Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::debug!("Clipboard tool {} unavailable: {}", program, e);
+                continue;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&bytes)?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+        tracing::warn!("Clipboard tool {} exited with an error", program);
+    }
+
+    Err(AppError::internal("无法写入系统剪贴板"))
+}
+
+/// Copy a plain-text summary of a record to the system clipboard.
+/// Returns the copied text.
+pub fn copy_record_summary_sync(id: i64) -> AppResult<String> {
+    let record = memory_storage::get_record_by_id_sync(id)?;
+    let summary = format_record_summary(&record, RECORD_SUMMARY_MAX_CHARS);
+    copy_to_clipboard(&summary)?;
+    tracing::info!("Copied summary of record {} to clipboard", id);
+    Ok(summary)
+}
+
 #[command]
-pub async fn add_quick_note(content: String) -> Result<(), String> {
+pub async fn copy_record_summary(id: i64) -> Result<String, String> {
+    copy_record_summary_sync(id).map_err(|e| e.to_string())
+}
+
+/// Save a quick note. `metadata` is an optional JSON object stored with the
+/// record (e.g. a related git commit or task id).
+#[command]
+pub async fn add_quick_note(content: String, metadata: Option<String>) -> Result<(), String> {
     if content.trim().is_empty() {
         return Err(AppError::validation("Content cannot be empty").to_string());
     }
 
-    memory_storage::add_record("manual", &content, None, None, None).map_err(|e| e.to_string())?;
+    memory_storage::add_record_with_metadata(
+        "manual",
+        &content,
+        None,
+        None,
+        None,
+        None,
+        metadata.as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
 
     tracing::info!("Quick note added: {}...", &content[..content.len().min(50)]);
     Ok(())
 }
 
+/// Suffix of a screenshot file that is still being written in the background.
+pub const PENDING_SCREENSHOT_SUFFIX: &str = ".part";
+
+/// Temporary path a screenshot is written to before being renamed to `path`.
+pub fn pending_screenshot_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(PENDING_SCREENSHOT_SUFFIX);
+    std::path::PathBuf::from(name)
+}
+
+/// Directory next to the screenshots holding their thumbnails.
+pub const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Path of the thumbnail of the screenshot at `path`: `<dir>/thumbnails/<stem>.jpg`.
+pub fn thumbnail_path(path: &std::path::Path) -> std::path::PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.parent()
+        .unwrap_or_else(|| std::path::Path::new(""))
+        .join(THUMBNAILS_DIR)
+        .join(format!("{}.jpg", stem))
+}
+
+/// Thumbnail of the screenshot at `path`, if one was generated.
+pub fn existing_thumbnail_path(path: &str) -> Option<String> {
+    let thumbnail = thumbnail_path(std::path::Path::new(path));
+    thumbnail
+        .exists()
+        .then(|| thumbnail.to_string_lossy().to_string())
+}
+
 #[command]
 pub async fn get_screenshot(path: String) -> Result<String, String> {
+    let screenshot = std::path::Path::new(&path);
+    if !screenshot.exists() && pending_screenshot_path(screenshot).exists() {
+        return Err(AppError::file_io("截图仍在保存中，请稍后再试").to_string());
+    }
     let image_data = std::fs::read(&path).map_err(|e| e.to_string())?;
 
     let base64_data =
@@ -398,15 +534,43 @@ mod tests {
         setup_test_db();
 
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result = rt.block_on(add_quick_note("快速记录测试".to_string()));
+        let result = rt.block_on(add_quick_note("快速记录测试".to_string(), None));
         assert!(result.is_ok(), "add_quick_note should succeed");
     }
 
+    #[test]
+    #[serial]
+    fn test_add_quick_note_stores_metadata() {
+        setup_test_db();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let metadata = r#"{"git_commit":"a1b2c3d","task_id":"PROJ-42"}"#;
+        rt.block_on(add_quick_note(
+            "修复登录超时".to_string(),
+            Some(metadata.to_string()),
+        ))
+        .unwrap();
+
+        let records = memory_storage::get_today_records_sync().unwrap();
+        let note = records
+            .iter()
+            .find(|r| r.content == "修复登录超时")
+            .unwrap();
+        assert_eq!(note.metadata.as_deref(), Some(metadata));
+
+        // Metadata must be a JSON object
+        let result = rt.block_on(add_quick_note(
+            "另一条记录".to_string(),
+            Some("[1, 2]".to_string()),
+        ));
+        assert!(result.is_err());
+    }
+
     #[test]
     #[serial]
     fn test_add_quick_note_rejects_empty_content() {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result = rt.block_on(add_quick_note("".to_string()));
+        let result = rt.block_on(add_quick_note("".to_string(), None));
         assert!(result.is_err(), "Empty content should be rejected");
     }
 
@@ -764,12 +928,59 @@ mod tests {
         // Test with a long content
         let long_content = "a".repeat(10000);
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result = rt.block_on(add_quick_note(long_content.clone()));
+        let result = rt.block_on(add_quick_note(long_content.clone(), None));
         assert!(result.is_ok());
 
         // Test with unicode content
         let unicode_content = "你好世界🌍🎉";
-        let result = rt.block_on(add_quick_note(unicode_content.to_string()));
+        let result = rt.block_on(add_quick_note(unicode_content.to_string(), None));
         assert!(result.is_ok());
     }
+
+    fn summary_test_record(source_type: &str, content: &str) -> memory_storage::Record {
+        memory_storage::Record {
+            id: 1,
+            timestamp: "2026-03-16T06:05:00+00:00".to_string(),
+            source_type: source_type.to_string(),
+            content: content.to_string(),
+            screenshot_path: None,
+            monitor_info: None,
+            tags: None,
+            user_notes: None,
+            session_id: None,
+            analysis_status: None,
+            metadata: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn test_format_record_summary_includes_local_time_and_source() {
+        let record = summary_test_record("manual", "  讨论发布计划  ");
+        let summary = format_record_summary(&record, RECORD_SUMMARY_MAX_CHARS);
+
+        let expected_time = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+            .unwrap()
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        assert_eq!(summary, format!("{} ⚡ 闪念\n讨论发布计划", expected_time));
+    }
+
+    #[test]
+    fn test_format_record_summary_truncates_long_content() {
+        let record = summary_test_record("auto", &"你".repeat(300));
+        let summary = format_record_summary(&record, 10);
+
+        assert!(summary.contains("🖥️ 自动感知"));
+        assert!(summary.ends_with(&format!("{}…", "你".repeat(10))));
+    }
+
+    #[test]
+    fn test_format_record_summary_keeps_unparseable_timestamp() {
+        let mut record = summary_test_record("manual", "note");
+        record.timestamp = "not-a-time".to_string();
+
+        assert!(format_record_summary(&record, 50).starts_with("not-a-time "));
+    }
 }
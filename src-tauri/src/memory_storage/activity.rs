@@ -0,0 +1,339 @@
+//! "What was I doing?" summaries of the most recent auto records.
+//!
+//! The recent auto records are aggregated into the dominant software, the
+//! most frequent context keywords and the sequence of focus changes, so the
+//! last half hour can be reviewed without scrolling through records.
+
+use std::collections::HashMap;
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::errors::{AppError, AppResult};
+
+use super::{Record, SoftwareUsage, DB_CONNECTION};
+
+/// Longest window accepted by `get_recent_activity`, in minutes.
+pub const MAX_RECENT_ACTIVITY_MINUTES: u64 = 24 * 60;
+
+const TOP_KEYWORDS: usize = 10;
+
+/// A point where `current_focus` changed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusChange {
+    pub timestamp: String,
+    pub focus: String,
+}
+
+/// Aggregated summary of the auto records of the last `minutes`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentActivity {
+    pub minutes: u64,
+    pub record_count: usize,
+    /// Software of most records; ties go to the one seen last
+    pub dominant_software: Option<String>,
+    /// Records per software, most records first
+    pub software: Vec<SoftwareUsage>,
+    /// Most frequent context keywords, most common first
+    pub top_keywords: Vec<String>,
+    /// Focus in chronological order, consecutive repeats collapsed
+    pub focus_changes: Vec<FocusChange>,
+}
+
+/// Summarize auto records (any order) into a [`RecentActivity`].
+/// `software_by_record` maps record ids to their stored `software_normalized`.
+pub fn summarize_recent_activity(
+    records: &[Record],
+    minutes: u64,
+    software_by_record: &HashMap<i64, String>,
+) -> RecentActivity {
+    let mut records: Vec<&Record> = records.iter().filter(|r| r.source_type == "auto").collect();
+    records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    // software -> (count, index of last record)
+    let mut software: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut keywords: HashMap<String, usize> = HashMap::new();
+    let mut focus_changes: Vec<FocusChange> = Vec::new();
+
+    for (index, record) in records.iter().enumerate() {
+        if let Some(name) = software_by_record.get(&record.id) {
+            let entry = software.entry(name.clone()).or_insert((0, index));
+            entry.0 += 1;
+            entry.1 = index;
+        }
+
+        for keyword in super::projects::context_keywords_from_content(&record.content) {
+            let keyword = keyword.trim().to_string();
+            if !keyword.is_empty() {
+                *keywords.entry(keyword).or_insert(0) += 1;
+            }
+        }
+
+        let focus = serde_json::from_str::<serde_json::Value>(&record.content)
+            .ok()
+            .and_then(|v| {
+                v.get("current_focus")?
+                    .as_str()
+                    .map(|s| s.trim().to_string())
+            })
+            .filter(|f| !f.is_empty());
+        if let Some(focus) = focus {
+            if focus_changes.last().is_none_or(|last| last.focus != focus) {
+                focus_changes.push(FocusChange {
+                    timestamp: record.timestamp.clone(),
+                    focus,
+                });
+            }
+        }
+    }
+
+    let mut software: Vec<(String, (usize, usize))> = software.into_iter().collect();
+    software.sort_by(|(_, (ca, la)), (_, (cb, lb))| cb.cmp(ca).then(lb.cmp(la)));
+    let mut keywords: Vec<(String, usize)> = keywords.into_iter().collect();
+    keywords.sort_by(|(ka, ca), (kb, cb)| cb.cmp(ca).then(ka.cmp(kb)));
+
+    RecentActivity {
+        minutes,
+        record_count: records.len(),
+        dominant_software: software.first().map(|(name, _)| name.clone()),
+        software: software
+            .into_iter()
+            .map(|(name, (count, _))| SoftwareUsage {
+                name,
+                count: count as i64,
+            })
+            .collect(),
+        top_keywords: keywords
+            .into_iter()
+            .take(TOP_KEYWORDS)
+            .map(|(keyword, _)| keyword)
+            .collect(),
+        focus_changes,
+    }
+}
+
+/// Summarize the auto records of the last `minutes` (1 to 1440).
+pub fn get_recent_activity_sync(minutes: u64) -> AppResult<RecentActivity> {
+    if !(1..=MAX_RECENT_ACTIVITY_MINUTES).contains(&minutes) {
+        return Err(AppError::validation(format!(
+            "minutes must be between 1 and {}",
+            MAX_RECENT_ACTIVITY_MINUTES
+        )));
+    }
+    let since = (chrono::Utc::now() - chrono::Duration::minutes(minutes as i64)).to_rfc3339();
+
+    let mut software_by_record = HashMap::new();
+    let records = {
+        let db = DB_CONNECTION.lock()?;
+        let conn = db
+            .as_ref()
+            .ok_or_else(|| AppError::database("Database not initialized"))?;
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status, software_normalized FROM records
+             WHERE source_type = 'auto' AND timestamp >= ?1 ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            let record = Record {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                source_type: row.get(2)?,
+                content: row.get(3)?,
+                screenshot_path: row.get(4)?,
+                monitor_info: row.get(5)?,
+                tags: row.get(6)?,
+                user_notes: row.get(7)?,
+                session_id: row.get(8)?,
+                analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
+            };
+            Ok((record, row.get::<_, Option<String>>(12)?))
+        })?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (record, software) = row?;
+            if let Some(software) = software {
+                software_by_record.insert(record.id, software);
+            }
+            records.push(record);
+        }
+        records
+    };
+
+    Ok(summarize_recent_activity(
+        &records,
+        minutes,
+        &software_by_record,
+    ))
+}
+
+/// Tauri command: what happened in the last `minutes`.
+#[command]
+pub async fn get_recent_activity(minutes: u64) -> AppResult<RecentActivity> {
+    get_recent_activity_sync(minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn auto_record(
+        id: i64,
+        timestamp: &str,
+        software: &str,
+        focus: &str,
+        keywords: &[&str],
+    ) -> Record {
+        Record {
+            id,
+            timestamp: timestamp.to_string(),
+            source_type: "auto".to_string(),
+            content: serde_json::json!({
+                "current_focus": focus,
+                "active_software": software,
+                "context_keywords": keywords,
+            })
+            .to_string(),
+            screenshot_path: None,
+            monitor_info: None,
+            tags: None,
+            user_notes: None,
+            session_id: None,
+            analysis_status: None,
+            metadata: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn summarize_counts_software_keywords_and_focus_changes() {
+        let records = vec![
+            auto_record(
+                3,
+                "2026-03-16T09:10:00+00:00",
+                "Chrome",
+                "查文档",
+                &["rust"],
+            ),
+            auto_record(
+                1,
+                "2026-03-16T09:00:00+00:00",
+                "code.exe",
+                "写代码",
+                &["rust", "tauri"],
+            ),
+            auto_record(
+                2,
+                "2026-03-16T09:05:00+00:00",
+                "VSCode",
+                "写代码",
+                &["tauri"],
+            ),
+            auto_record(4, "2026-03-16T09:15:00+00:00", "Code", "写代码", &["rust"]),
+        ];
+
+        let software = HashMap::from([
+            (1, "VS Code".to_string()),
+            (2, "VS Code".to_string()),
+            (3, "Chrome".to_string()),
+            (4, "VS Code".to_string()),
+        ]);
+
+        let summary = summarize_recent_activity(&records, 30, &software);
+
+        assert_eq!(summary.minutes, 30);
+        assert_eq!(summary.record_count, 4);
+        assert_eq!(summary.dominant_software.as_deref(), Some("VS Code"));
+        assert_eq!(
+            summary.software,
+            vec![
+                SoftwareUsage {
+                    name: "VS Code".to_string(),
+                    count: 3
+                },
+                SoftwareUsage {
+                    name: "Chrome".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(summary.top_keywords, vec!["rust", "tauri"]);
+        let focus: Vec<&str> = summary
+            .focus_changes
+            .iter()
+            .map(|c| c.focus.as_str())
+            .collect();
+        assert_eq!(focus, vec!["写代码", "查文档", "写代码"]);
+        assert_eq!(
+            summary.focus_changes[1].timestamp,
+            "2026-03-16T09:10:00+00:00"
+        );
+    }
+
+    #[test]
+    fn summarize_ignores_manual_and_unparsable_records() {
+        let mut manual = auto_record(1, "2026-03-16T09:00:00+00:00", "Slack", "聊天", &[]);
+        manual.source_type = "manual".to_string();
+        let mut broken = auto_record(2, "2026-03-16T09:01:00+00:00", "", "", &[]);
+        broken.content = "not json".to_string();
+
+        let software = HashMap::from([(1, "Slack".to_string())]);
+
+        let summary = summarize_recent_activity(&[manual, broken], 30, &software);
+
+        assert_eq!(summary.record_count, 1);
+        assert_eq!(summary.dominant_software, None);
+        assert!(summary.software.is_empty());
+        assert!(summary.top_keywords.is_empty());
+        assert!(summary.focus_changes.is_empty());
+    }
+
+    #[test]
+    fn software_ties_go_to_the_most_recent() {
+        let records = vec![
+            auto_record(1, "2026-03-16T09:00:00+00:00", "Slack", "a", &[]),
+            auto_record(2, "2026-03-16T09:05:00+00:00", "Chrome", "b", &[]),
+        ];
+        let software = HashMap::from([(1, "Slack".to_string()), (2, "Chrome".to_string())]);
+        let summary = summarize_recent_activity(&records, 10, &software);
+        assert_eq!(summary.dominant_software.as_deref(), Some("Chrome"));
+    }
+
+    #[test]
+    #[serial]
+    fn recent_activity_only_reads_the_requested_window() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let recent = (chrono::Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
+        let old = (chrono::Utc::now() - chrono::Duration::minutes(90)).to_rfc3339();
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            let conn = db.as_ref().unwrap();
+            for (ts, software, normalized) in
+                [(&recent, "Code", "VS Code"), (&old, "Slack", "Slack")]
+            {
+                conn.execute(
+                    "INSERT INTO records (timestamp, source_type, content, software_normalized)
+                     VALUES (?1, 'auto', ?2, ?3)",
+                    params![
+                        ts,
+                        format!(
+                            r#"{{"current_focus":"x","active_software":"{}"}}"#,
+                            software
+                        ),
+                        normalized
+                    ],
+                )
+                .unwrap();
+            }
+        }
+
+        let summary = get_recent_activity_sync(30).unwrap();
+
+        assert_eq!(summary.record_count, 1);
+        assert_eq!(summary.dominant_software.as_deref(), Some("VS Code"));
+        assert!(get_recent_activity_sync(0).is_err());
+        assert!(get_recent_activity_sync(MAX_RECENT_ACTIVITY_MINUTES + 1).is_err());
+    }
+}
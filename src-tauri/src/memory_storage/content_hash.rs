@@ -0,0 +1,172 @@
+//! Content hashes of records.
+//!
+//! `records.content_hash` holds the SHA-256 (lowercase hex) of a record's
+//! stored content, indexed by `idx_records_content_hash`, so equal content is
+//! found without comparing full texts (duplicate detection on insert and
+//! import). Inserts and content updates set the hash together with the
+//! content; records written before the column existed are hashed when the
+//! database is opened and before lookups.
+
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::errors::{AppError, AppResult};
+
+use super::DB_CONNECTION;
+
+/// Value stored in `records.content_hash` for a record's stored content.
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Hash every record without `content_hash`. Returns the number hashed.
+pub(super) fn backfill_content_hash(conn: &Connection) -> AppResult<usize> {
+    let unhashed: Vec<(i64, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT id, content FROM records WHERE content_hash IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    if unhashed.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE records SET content_hash = ?1 WHERE id = ?2")?;
+        for (id, content) in &unhashed {
+            stmt.execute(params![content_hash(content), id])?;
+        }
+    }
+    tx.commit()?;
+    tracing::debug!("Hashed content of {} records", unhashed.len());
+    Ok(unhashed.len())
+}
+
+/// Ids of the records whose content hashes to `hash`, oldest first.
+pub fn find_records_by_content_hash_sync(hash: &str) -> AppResult<Vec<i64>> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    backfill_content_hash(conn)?;
+
+    let mut stmt = conn.prepare("SELECT id FROM records WHERE content_hash = ?1 ORDER BY id")?;
+    let ids = stmt
+        .query_map(params![hash], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn content_hash_is_sha256_hex() {
+        assert_eq!(
+            content_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            content_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    fn stored_hash(id: i64) -> Option<String> {
+        let db = DB_CONNECTION.lock().unwrap();
+        db.as_ref()
+            .unwrap()
+            .query_row(
+                "SELECT content_hash FROM records WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn inserts_store_hash_and_lookup_finds_it() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let id = super::super::add_record("manual", "整理会议纪要", None, None, None).unwrap();
+        assert_eq!(stored_hash(id), Some(content_hash("整理会议纪要")));
+        assert_eq!(
+            find_records_by_content_hash_sync(&content_hash("整理会议纪要")).unwrap(),
+            vec![id]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn content_updates_keep_the_hash_current() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let id =
+            super::super::add_record("auto", r#"{"current_focus":"待分析"}"#, None, None, None)
+                .unwrap();
+
+        let analyzed = r#"{"current_focus":"编写测试"}"#;
+        super::super::update_record_content_sync(id, analyzed).unwrap();
+        assert_eq!(stored_hash(id), Some(content_hash(analyzed)));
+
+        let failed = r#"{"current_focus":"分析失败"}"#;
+        super::super::mark_record_analysis_failed_sync(id, failed, None).unwrap();
+        assert_eq!(stored_hash(id), Some(content_hash(failed)));
+
+        let edited = r#"{"current_focus":"编写集成测试"}"#;
+        super::super::update_record_sync(id, edited).unwrap();
+        assert_eq!(stored_hash(id), Some(content_hash(edited)));
+        assert!(find_records_by_content_hash_sync(&content_hash(analyzed))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn hash_lookup_uses_the_index() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let db = DB_CONNECTION.lock().unwrap();
+        let conn = db.as_ref().unwrap();
+        let plan: Vec<String> = conn
+            .prepare("EXPLAIN QUERY PLAN SELECT id FROM records WHERE content_hash = ?1")
+            .unwrap()
+            .query_map(params!["x"], |row| row.get(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(
+            plan.iter()
+                .any(|step| step.contains("idx_records_content_hash")),
+            "{:?}",
+            plan
+        );
+    }
+
+    #[test]
+    fn backfill_hashes_records_without_hash() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE records (id INTEGER PRIMARY KEY, content TEXT NOT NULL, content_hash TEXT);
+             INSERT INTO records (content) VALUES ('debugging postgres locks'), ('lunch');",
+        )
+        .unwrap();
+
+        assert_eq!(backfill_content_hash(&conn).unwrap(), 2);
+        let hash: String = conn
+            .query_row(
+                "SELECT content_hash FROM records WHERE content = 'lunch'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hash, content_hash("lunch"));
+        // Nothing left to hash on a second run
+        assert_eq!(backfill_content_hash(&conn).unwrap(), 0);
+    }
+}
@@ -0,0 +1,251 @@
+//! Content language detection.
+//!
+//! Records mix Chinese and English. Each record is tagged in
+//! `records.content_lang` with the language of its text, judged by the share
+//! of CJK characters against Latin letters. Inserts set the tag directly; a
+//! content update clears it (`records_content_lang_au` trigger) and
+//! statistics fill in missing tags before counting.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::errors::{AppError, AppResult};
+
+use super::DB_CONNECTION;
+
+/// Language of a record's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentLang {
+    Zh,
+    En,
+    Mixed,
+    /// No CJK characters or Latin letters (empty, digits, symbols)
+    Unknown,
+}
+
+impl ContentLang {
+    /// Value stored in `records.content_lang`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentLang::Zh => "zh",
+            ContentLang::En => "en",
+            ContentLang::Mixed => "mixed",
+            ContentLang::Unknown => "unknown",
+        }
+    }
+}
+
+/// Latin letters that carry about as much text as one CJK character.
+const LETTERS_PER_CJK_CHAR: f64 = 3.0;
+/// Minimum share of one language for the text to count as that language.
+const DOMINANT_SHARE: f64 = 0.8;
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF | 0x3040..=0x30FF)
+}
+
+/// Language of `text` by character set: CJK characters against Latin
+/// letters, letters weighted by `LETTERS_PER_CJK_CHAR`. Either side needs a
+/// share of at least `DOMINANT_SHARE`, otherwise the text is mixed.
+pub fn detect_content_lang(text: &str) -> ContentLang {
+    let (cjk, latin) = text.chars().fold((0usize, 0usize), |(cjk, latin), c| {
+        if is_cjk(c) {
+            (cjk + 1, latin)
+        } else if c.is_ascii_alphabetic() {
+            (cjk, latin + 1)
+        } else {
+            (cjk, latin)
+        }
+    });
+    if cjk == 0 && latin == 0 {
+        return ContentLang::Unknown;
+    }
+    let cjk = cjk as f64;
+    let latin = latin as f64 / LETTERS_PER_CJK_CHAR;
+    let cjk_share = cjk / (cjk + latin);
+    if cjk_share >= DOMINANT_SHARE {
+        ContentLang::Zh
+    } else if cjk_share <= 1.0 - DOMINANT_SHARE {
+        ContentLang::En
+    } else {
+        ContentLang::Mixed
+    }
+}
+
+fn collect_json_text(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::String(s) => {
+            out.push_str(s);
+            out.push('\n');
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_json_text(v, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_json_text(v, out)),
+        _ => {}
+    }
+}
+
+/// Language of a record's content. Auto records store JSON; only its string
+/// values are text (the keys are always English).
+pub fn content_lang_for_content(content: &str) -> ContentLang {
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(value @ serde_json::Value::Object(_)) => {
+            let mut text = String::new();
+            collect_json_text(&value, &mut text);
+            detect_content_lang(&text)
+        }
+        _ => detect_content_lang(content),
+    }
+}
+
+/// Tag every record without `content_lang`. Returns the number tagged.
+pub(super) fn backfill_content_lang(conn: &Connection) -> AppResult<usize> {
+    let untagged: Vec<(i64, String)> = {
+        let mut stmt =
+            conn.prepare("SELECT id, content FROM records WHERE content_lang IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    if untagged.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE records SET content_lang = ?1 WHERE id = ?2")?;
+        for (id, content) in &untagged {
+            stmt.execute(params![content_lang_for_content(content).as_str(), id])?;
+        }
+    }
+    tx.commit()?;
+    tracing::debug!("Tagged content language of {} records", untagged.len());
+    Ok(untagged.len())
+}
+
+/// Number of records in one language.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentLangCount {
+    /// "zh", "en", "mixed" or "unknown"
+    pub lang: String,
+    pub count: i64,
+}
+
+/// Records per content language in `[start_date, end_date]`, most frequent
+/// first. Bounds are accepted as by `get_records_by_date_range_sync`.
+pub fn get_content_lang_stats_sync(
+    start_date: &str,
+    end_date: &str,
+) -> AppResult<Vec<ContentLangCount>> {
+    let start_utc = super::records::parse_range_bound(start_date, "start_date", false)?;
+    let end_utc = super::records::parse_range_bound(end_date, "end_date", true)?;
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    backfill_content_lang(conn)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT content_lang, COUNT(*) FROM records
+         WHERE timestamp >= ?1 AND timestamp < ?2
+         GROUP BY content_lang ORDER BY COUNT(*) DESC, content_lang",
+    )?;
+    let counts = stmt
+        .query_map(params![start_utc, end_utc], |row| {
+            Ok(ContentLangCount {
+                lang: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(counts)
+}
+
+/// Records per content language in a date range
+#[command]
+pub async fn get_content_lang_stats(
+    start_date: String,
+    end_date: String,
+) -> AppResult<Vec<ContentLangCount>> {
+    get_content_lang_stats_sync(&start_date, &end_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn detects_pure_chinese() {
+        assert_eq!(detect_content_lang("编写日报生成功能"), ContentLang::Zh);
+        // A few Latin letters do not change that
+        assert_eq!(
+            detect_content_lang("在 VS Code 中调试日报生成的定时任务"),
+            ContentLang::Zh
+        );
+    }
+
+    #[test]
+    fn detects_pure_english() {
+        assert_eq!(
+            detect_content_lang("Reviewing the pull request for the exporter"),
+            ContentLang::En
+        );
+        assert_eq!(
+            detect_content_lang("Fixing the 中 encoding bug in the exporter module"),
+            ContentLang::En
+        );
+    }
+
+    #[test]
+    fn detects_mixed_text() {
+        assert_eq!(
+            detect_content_lang("阅读 Rust async runtime 文档"),
+            ContentLang::Mixed
+        );
+    }
+
+    #[test]
+    fn text_without_letters_is_unknown() {
+        assert_eq!(detect_content_lang(""), ContentLang::Unknown);
+        assert_eq!(
+            detect_content_lang("2026-03-16 12:00 ✓"),
+            ContentLang::Unknown
+        );
+    }
+
+    #[test]
+    fn auto_record_json_is_judged_by_its_values() {
+        let content = r#"{"current_focus":"编写周报","active_software":"飞书","context_keywords":["周报","总结"]}"#;
+        assert_eq!(content_lang_for_content(content), ContentLang::Zh);
+        assert_eq!(content_lang_for_content("{not json"), ContentLang::En);
+    }
+
+    #[test]
+    #[serial]
+    fn stats_count_records_per_language_and_retag_edits() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let zh = super::super::add_record("manual", "整理会议纪要", None, None, None).unwrap();
+        super::super::add_record("manual", "writing release notes", None, None, None).unwrap();
+        super::super::add_record("manual", "补充 release notes 的中文部分", None, None, None)
+            .unwrap();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        let stats = get_content_lang_stats_sync(&today, &today).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert!(stats.iter().all(|s| s.count == 1));
+
+        super::super::update_record_content_sync(zh, "meeting notes").unwrap();
+        let stats = get_content_lang_stats_sync(&today, &today).unwrap();
+        assert_eq!(
+            stats[0],
+            ContentLangCount {
+                lang: "en".to_string(),
+                count: 2
+            }
+        );
+    }
+}
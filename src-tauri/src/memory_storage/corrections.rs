@@ -0,0 +1,175 @@
+//! Software recognition corrections learned from user edits.
+//!
+//! When the user corrects the `active_software` of an auto record, the
+//! "recognized → corrected" pair is counted in `software_corrections`.
+//! The most frequent pairs are injected into the screen analysis prompt.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AppError, AppResult};
+
+use super::DB_CONNECTION;
+
+/// Number of corrections injected into the analysis prompt.
+pub const MAX_PROMPT_CORRECTIONS: usize = 10;
+
+/// A learned "original → corrected" software name pair.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoftwareCorrection {
+    pub original: String,
+    pub corrected: String,
+    pub count: i64,
+}
+
+/// Extract `active_software` from an analysis content JSON.
+pub(crate) fn active_software_from_content(content: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let software = value.get("active_software")?.as_str()?.trim();
+    if software.is_empty() {
+        None
+    } else {
+        Some(software.to_string())
+    }
+}
+
+/// Store a correction using an existing connection, bumping its count if already known.
+/// Identical or empty names are ignored.
+pub(crate) fn insert_software_correction(
+    conn: &Connection,
+    original: &str,
+    corrected: &str,
+) -> AppResult<()> {
+    let (original, corrected) = (original.trim(), corrected.trim());
+    if original.is_empty() || corrected.is_empty() || original == corrected {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO software_corrections (original, corrected, count, updated_at)
+         VALUES (?1, ?2, 1, ?3)
+         ON CONFLICT(original, corrected)
+         DO UPDATE SET count = count + 1, updated_at = excluded.updated_at",
+        params![original, corrected, chrono::Utc::now().to_rfc3339()],
+    )?;
+
+    tracing::info!(
+        "Recorded software correction: {} -> {}",
+        original,
+        corrected
+    );
+    Ok(())
+}
+
+/// Store a "recognized → corrected" software name pair.
+pub fn record_software_correction(original: &str, corrected: &str) -> AppResult<()> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+    insert_software_correction(conn, original, corrected)
+}
+
+/// Most frequent corrections, most common first.
+pub fn get_common_software_corrections(limit: usize) -> AppResult<Vec<SoftwareCorrection>> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT original, corrected, count FROM software_corrections
+         ORDER BY count DESC, updated_at DESC
+         LIMIT ?1",
+    )?;
+    let corrections = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(SoftwareCorrection {
+                original: row.get(0)?,
+                corrected: row.get(1)?,
+                count: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(corrections)
+}
+
+/// Append learned corrections to an analysis prompt.
+/// Returns the prompt unchanged when there are no corrections.
+pub fn inject_software_corrections(prompt: &str, corrections: &[SoftwareCorrection]) -> String {
+    if corrections.is_empty() {
+        return prompt.to_string();
+    }
+
+    let lines: Vec<String> = corrections
+        .iter()
+        .map(|c| format!("- \"{}\" → \"{}\"", c.original, c.corrected))
+        .collect();
+    format!(
+        "{}\n\n用户曾修正过以下软件识别结果，识别 active_software 时请参考：\n{}",
+        prompt,
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn setup_test_db() {
+        crate::memory_storage::setup_test_db_with_schema();
+    }
+
+    #[test]
+    fn active_software_from_content_reads_field() {
+        assert_eq!(
+            active_software_from_content(r#"{"active_software":" VS Code "}"#),
+            Some("VS Code".to_string())
+        );
+        assert_eq!(
+            active_software_from_content(r#"{"active_software":""}"#),
+            None
+        );
+        assert_eq!(active_software_from_content("plain text"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn corrections_are_counted_and_ordered_by_frequency() {
+        setup_test_db();
+
+        record_software_correction("Code", "VS Code").unwrap();
+        record_software_correction("Chrome", "Arc").unwrap();
+        record_software_correction("Chrome", "Arc").unwrap();
+        // Identical names are not corrections
+        record_software_correction("Slack", "Slack").unwrap();
+
+        let corrections = get_common_software_corrections(10).unwrap();
+        assert_eq!(corrections.len(), 2);
+        assert_eq!(corrections[0].original, "Chrome");
+        assert_eq!(corrections[0].corrected, "Arc");
+        assert_eq!(corrections[0].count, 2);
+        assert_eq!(corrections[1].count, 1);
+
+        assert_eq!(get_common_software_corrections(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn inject_software_corrections_appends_hint() {
+        let prompt = "Analyze the screen";
+        assert_eq!(inject_software_corrections(prompt, &[]), prompt);
+
+        let injected = inject_software_corrections(
+            prompt,
+            &[SoftwareCorrection {
+                original: "Code".to_string(),
+                corrected: "VS Code".to_string(),
+                count: 3,
+            }],
+        );
+        assert!(injected.starts_with(prompt));
+        assert!(injected.contains("\"Code\" → \"VS Code\""));
+    }
+}
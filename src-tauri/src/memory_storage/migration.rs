@@ -12,7 +12,7 @@ use std::collections::HashSet;
 use std::time::SystemTime;
 
 /// Current schema version - increment when adding new migrations
-pub const CURRENT_SCHEMA_VERSION: i32 = 1;
+pub const CURRENT_SCHEMA_VERSION: i32 = 37;
 
 /// Represents a single database migration
 #[derive(Debug, Clone)]
@@ -317,10 +317,11 @@ impl Migration {
 
 /// Get all registered migrations in order
 fn get_migrations() -> Vec<Migration> {
-    vec![Migration {
-        version: 1,
-        description: "Initial schema - create all base tables and indexes",
-        sql: r#"
+    vec![
+        Migration {
+            version: 1,
+            description: "Initial schema - create all base tables and indexes",
+            sql: r#"
             -- records table (base schema + extensions for idempotent migration)
             -- CREATE TABLE IF NOT EXISTS creates the table only if it doesn't exist.
             -- For existing tables (legacy databases), columns are added by the pre-batch
@@ -422,29 +423,8 @@ fn get_migrations() -> Vec<Migration> {
 
             CREATE INDEX IF NOT EXISTS idx_record_manual_tags_tag_id ON record_manual_tags(tag_id);
 
-            -- FTS5 virtual table
-            CREATE VIRTUAL TABLE IF NOT EXISTS records_fts USING fts5(
-                content,
-                content='records',
-                content_rowid='id',
-                tokenize='unicode61'
-            );
-
-            -- FTS5 triggers
-            CREATE TRIGGER IF NOT EXISTS records_ai AFTER INSERT ON records BEGIN
-                INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS records_ad AFTER DELETE ON records BEGIN
-                INSERT INTO records_fts(records_fts, rowid, content)
-                VALUES ('delete', old.id, old.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS records_au AFTER UPDATE ON records BEGIN
-                INSERT INTO records_fts(records_fts, rowid, content)
-                VALUES ('delete', old.id, old.content);
-                INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
-            END;
+            -- The FTS5 index (records_fts) is created by schema::ensure_fts_index,
+            -- outside any migration, so SQLite builds without FTS5 can still start.
 
             -- Learning data persistence tables
             CREATE TABLE IF NOT EXISTS silent_pattern_stats (
@@ -462,7 +442,289 @@ fn get_migrations() -> Vec<Migration> {
                 PRIMARY KEY (date, hour)
             );
         "#,
-    }]
+        },
+        Migration {
+            version: 2,
+            description: "Add sensitive content filter settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN sensitive_filter_enabled INTEGER DEFAULT 0;
+            ALTER TABLE settings ADD COLUMN sensitive_keywords TEXT DEFAULT '[]';
+        "#,
+        },
+        Migration {
+            version: 3,
+            description: "Add log_llm_payload setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN log_llm_payload INTEGER DEFAULT 1;
+        "#,
+        },
+        Migration {
+            version: 4,
+            description: "Add software_corrections table",
+            sql: r#"
+            CREATE TABLE IF NOT EXISTS software_corrections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                original TEXT NOT NULL,
+                corrected TEXT NOT NULL,
+                count INTEGER NOT NULL DEFAULT 1,
+                updated_at TEXT NOT NULL,
+                UNIQUE(original, corrected)
+            );
+        "#,
+        },
+        Migration {
+            version: 5,
+            description: "Add screenshot_format setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN screenshot_format TEXT DEFAULT 'png';
+        "#,
+        },
+        Migration {
+            version: 6,
+            description: "Add api_keys setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN api_keys TEXT DEFAULT '[]';
+        "#,
+        },
+        Migration {
+            version: 7,
+            description: "Add open_summary_after_generate setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN open_summary_after_generate INTEGER DEFAULT 0;
+        "#,
+        },
+        Migration {
+            version: 8,
+            description: "Add startup_delay_seconds setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN startup_delay_seconds INTEGER DEFAULT 0;
+        "#,
+        },
+        Migration {
+            version: 9,
+            description: "Add day_start_hour setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN day_start_hour INTEGER DEFAULT 0;
+        "#,
+        },
+        Migration {
+            version: 10,
+            description: "Add failure alert aggregation settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN failure_alert_window_minutes INTEGER DEFAULT 10;
+            ALTER TABLE settings ADD COLUMN failure_alert_webhook_url TEXT;
+        "#,
+        },
+        Migration {
+            version: 11,
+            description: "Add projects setting and records.project column",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN projects TEXT;
+            ALTER TABLE records ADD COLUMN project TEXT;
+            CREATE INDEX IF NOT EXISTS idx_records_project ON records(project);
+        "#,
+        },
+        Migration {
+            version: 12,
+            description: "Store capture interval in seconds",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN screenshot_interval_seconds INTEGER DEFAULT 300;
+            UPDATE settings SET screenshot_interval_seconds = screenshot_interval * 60
+                WHERE screenshot_interval IS NOT NULL AND screenshot_interval > 0;
+        "#,
+        },
+        Migration {
+            version: 13,
+            description: "Add debug_dump_failed_requests setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN debug_dump_failed_requests INTEGER DEFAULT 0;
+        "#,
+        },
+        Migration {
+            version: 14,
+            description: "Add taskbar crop settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN crop_taskbar INTEGER DEFAULT 0;
+            ALTER TABLE settings ADD COLUMN taskbar_height INTEGER DEFAULT 48;
+        "#,
+        },
+        Migration {
+            version: 15,
+            description: "Add category_prompts setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN category_prompts TEXT;
+        "#,
+        },
+        Migration {
+            version: 16,
+            description: "Add records.status for draft/confirmed records",
+            sql: r#"
+            -- Existing records predate drafts and count as confirmed
+            ALTER TABLE records ADD COLUMN status TEXT DEFAULT 'confirmed';
+            CREATE INDEX IF NOT EXISTS idx_records_status ON records(status);
+        "#,
+        },
+        Migration {
+            version: 17,
+            description: "Add endpoint_image_formats setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN endpoint_image_formats TEXT;
+        "#,
+        },
+        Migration {
+            version: 18,
+            description: "Add A/B analysis prompt settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN analysis_prompt_b TEXT;
+            ALTER TABLE settings ADD COLUMN prompt_b_percent INTEGER DEFAULT 50;
+        "#,
+        },
+        Migration {
+            version: 19,
+            description: "Add extra_headers setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN extra_headers TEXT;
+        "#,
+        },
+        Migration {
+            version: 20,
+            description: "Add pause_capture_without_display setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN pause_capture_without_display INTEGER DEFAULT 1;
+        "#,
+        },
+        Migration {
+            version: 21,
+            description: "Add on_record_command hook",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN on_record_command TEXT;
+        "#,
+        },
+        Migration {
+            version: 22,
+            description: "Add software_aliases setting and records.software_normalized column",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN software_aliases TEXT;
+            ALTER TABLE records ADD COLUMN software_normalized TEXT;
+            CREATE INDEX IF NOT EXISTS idx_records_software_normalized ON records(software_normalized);
+        "#,
+        },
+        Migration {
+            version: 23,
+            description: "Add max_image_dimension setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN max_image_dimension INTEGER DEFAULT 7680;
+        "#,
+        },
+        Migration {
+            version: 24,
+            description: "Add capture_timeout_secs and capture_retries settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN capture_timeout_secs INTEGER DEFAULT 5;
+            ALTER TABLE settings ADD COLUMN capture_retries INTEGER DEFAULT 2;
+        "#,
+        },
+        Migration {
+            version: 25,
+            description: "Add capture_on_idle_wake setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN capture_on_idle_wake INTEGER DEFAULT 1;
+        "#,
+        },
+        Migration {
+            version: 26,
+            description: "Add redact_sensitive_content to settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN redact_sensitive_content INTEGER DEFAULT 1;
+        "#,
+        },
+        Migration {
+            version: 27,
+            description: "Add prompt_schema_version to settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN prompt_schema_version INTEGER;
+        "#,
+        },
+        Migration {
+            version: 28,
+            description: "Add records.content_lang column",
+            sql: r#"
+            ALTER TABLE records ADD COLUMN content_lang TEXT;
+            CREATE INDEX IF NOT EXISTS idx_records_content_lang ON records(content_lang);
+            -- Edited content is re-detected by the next language statistics
+            CREATE TRIGGER IF NOT EXISTS records_content_lang_au AFTER UPDATE OF content ON records BEGIN
+                UPDATE records SET content_lang = NULL WHERE id = new.id;
+            END;
+        "#,
+        },
+        Migration {
+            version: 29,
+            description: "Add records.tokens_used column",
+            sql: r#"
+            ALTER TABLE records ADD COLUMN tokens_used INTEGER;
+        "#,
+        },
+        Migration {
+            version: 30,
+            description: "Add scheduled daily summary settings",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN auto_summary_enabled INTEGER DEFAULT 0;
+            ALTER TABLE settings ADD COLUMN auto_summary_catch_up INTEGER DEFAULT 1;
+            ALTER TABLE settings ADD COLUMN last_auto_summary_date TEXT;
+        "#,
+        },
+        Migration {
+            version: 31,
+            description: "Add keyword_alert_rules setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN keyword_alert_rules TEXT;
+        "#,
+        },
+        Migration {
+            version: 32,
+            description: "Add capture_all_monitors setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN capture_all_monitors INTEGER DEFAULT 0;
+        "#,
+        },
+        Migration {
+            version: 33,
+            description: "Add exclude_regions setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN exclude_regions TEXT;
+        "#,
+        },
+        Migration {
+            version: 34,
+            description: "Add records.content_hash column",
+            sql: r#"
+            ALTER TABLE records ADD COLUMN content_hash TEXT;
+            CREATE INDEX IF NOT EXISTS idx_records_content_hash ON records(content_hash);
+            -- Existing records are hashed in Rust (backfill_content_hash), SQLite has no SHA-256
+        "#,
+        },
+        Migration {
+            version: 35,
+            description: "Add excluded_apps setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN excluded_apps TEXT;
+        "#,
+        },
+        Migration {
+            version: 36,
+            description: "Add draft_retention_days setting",
+            sql: r#"
+            ALTER TABLE settings ADD COLUMN draft_retention_days INTEGER DEFAULT 7;
+        "#,
+        },
+        Migration {
+            version: 37,
+            description: "Add records.metadata column",
+            sql: r#"
+            ALTER TABLE records ADD COLUMN metadata TEXT;
+        "#,
+        },
+    ]
 }
 
 /// Initialize the schema version tracking tables
@@ -931,6 +1193,9 @@ mod tests {
         // Verify migrations were recorded
         let history = get_migration_history(&conn).unwrap();
         assert!(!history.is_empty());
+
+        // Columns of later versioned migrations exist
+        assert!(column_exists(&conn, "records", "metadata"));
     }
 
     #[test]
@@ -954,9 +1219,9 @@ mod tests {
         assert_eq!(v1, v2);
         assert_eq!(v2, CURRENT_SCHEMA_VERSION);
 
-        // Should only have one migration recorded
+        // Each migration should be recorded exactly once
         let history = get_migration_history(&conn).unwrap();
-        assert_eq!(history.len(), 1);
+        assert_eq!(history.len(), get_migrations().len());
     }
 
     /// Regression test for the migration skip-path bug.
@@ -1017,6 +1282,41 @@ mod tests {
         assert!(column_exists(&conn, "records", "session_id"));
     }
 
+    /// Minute based capture intervals from older databases are converted to seconds.
+    #[test]
+    fn test_screenshot_interval_migrates_minutes_to_seconds() {
+        use rusqlite::Connection;
+        use tempfile::NamedTempFile;
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let conn = Connection::open(temp_file.path()).unwrap();
+
+        // Bring the database to the last minute-based schema version
+        init_schema_version_table(&conn).unwrap();
+        for migration in get_migrations().into_iter().filter(|m| m.version < 12) {
+            migration.execute(&conn).unwrap();
+        }
+        conn.execute("INSERT OR IGNORE INTO settings (id) VALUES (1)", [])
+            .unwrap();
+        conn.execute(
+            "UPDATE settings SET screenshot_interval = 3 WHERE id = 1",
+            [],
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        let seconds: i32 = conn
+            .query_row(
+                "SELECT screenshot_interval_seconds FROM settings WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(seconds, 180);
+        assert_eq!(get_current_version(&conn).unwrap(), CURRENT_SCHEMA_VERSION);
+    }
+
     /// Test that ensure_legacy_columns_exist is idempotent on a fully-migrated database.
     #[test]
     fn test_ensure_legacy_columns_idempotent() {
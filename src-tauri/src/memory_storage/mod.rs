@@ -1,7 +1,13 @@
+mod activity;
+mod content_hash;
+mod content_lang;
+mod corrections;
 pub mod migration;
+mod projects;
 mod records;
 mod schema;
 mod settings;
+mod software;
 pub mod tags;
 
 use once_cell::sync::Lazy;
@@ -13,10 +19,21 @@ use tauri::command;
 use crate::errors::{AppError, AppResult};
 
 pub use schema::init_database;
+// Re-export recent activity summaries (including Tauri command generated types)
+pub use activity::*;
+// Re-export content language detection (including Tauri command generated types)
+pub use content_hash::*;
+pub use content_lang::*;
+// Re-export software correction learning helpers
+pub use corrections::*;
 // Re-export all public items from settings module (including Tauri command generated types)
 pub use settings::*;
+// Re-export keyword based project classification (including Tauri command generated types)
+pub use projects::*;
 // Re-export all public items from records module
 pub use records::*;
+// Re-export software name normalization
+pub use software::*;
 // Re-export all public items from tags module (including Tauri command generated types)
 pub use tags::*;
 
@@ -122,6 +139,73 @@ pub struct Settings {
     pub last_auto_backup_at: Option<String>,  // RFC3339 时间戳
     // FEAT-008: 自定义导出模板 (v3.8.0)
     pub custom_export_template: Option<String>, // 用户自定义导出模板
+    // 敏感内容检测：命中时跳过 AI 分析
+    pub sensitive_filter_enabled: Option<bool>,
+    pub sensitive_keywords: Option<String>, // JSON: Vec<String> 额外的敏感关键词
+    // LLM 请求/响应原文日志开关（默认开启）
+    pub log_llm_payload: Option<bool>, // 为 false 时日志只记录长度与摘要
+    // 截图保存格式
+    pub screenshot_format: Option<String>, // png | jpeg | webp，编码失败时回退到 png
+    // 多 API 密钥轮换
+    pub api_keys: Option<String>, // JSON: Vec<String> 额外的 API 密钥（加密存储），分析时轮换使用
+    // 日报生成后自动打开文件
+    pub open_summary_after_generate: Option<bool>, // 为 true 时日报生成后用系统默认程序打开
+    // 自动截图冷启动延迟
+    pub startup_delay_seconds: Option<i32>, // 首次自动截图前等待的秒数
+    // "今天"起点（凌晨分界）
+    pub day_start_hour: Option<i32>, // 一天的起点小时（0-23），此前的记录算前一天
+    // 感知失败告警聚合
+    pub failure_alert_window_minutes: Option<i32>, // 失败告警聚合窗口（分钟）
+    pub failure_alert_webhook_url: Option<String>, // 失败汇总告警 webhook 地址（可选）
+    // 项目归类（项目 → 关键词）
+    pub projects: Option<String>, // JSON: [{"name": 项目名, "keywords": [关键词]}]
+    // 感知间隔（秒）
+    pub screenshot_interval_seconds: Option<i32>, // 自动感知间隔（秒），screenshot_interval（分钟）仅供界面展示
+    // 调试：保存失败的分析请求
+    pub debug_dump_failed_requests: Option<bool>, // 失败时保存请求体与截图到 debug 目录
+    // 截图裁剪任务栏
+    pub crop_taskbar: Option<bool>,  // 截图后裁掉屏幕底部任务栏
+    pub taskbar_height: Option<i32>, // 裁剪的任务栏高度（像素）
+    // 按软件类别的分析 prompt
+    pub category_prompts: Option<String>, // JSON: {"code": prompt, "design": prompt, ...}
+    // 按端点选择分析图片格式
+    pub endpoint_image_formats: Option<String>, // JSON: {"API 地址片段": "png" | "jpeg"}
+    // A/B 分析 prompt 实验
+    pub analysis_prompt_b: Option<String>, // A/B 实验的 B 组分析 prompt，为空则不做实验
+    pub prompt_b_percent: Option<i32>,     // 使用 B 组 prompt 的比例（0-100）
+    // AI 请求附加请求头
+    pub extra_headers: Option<String>, // JSON: {"Header-Name": "value"}，附加到 AI 请求，可覆盖 Authorization/User-Agent
+    // 无显示器时暂停感知
+    pub pause_capture_without_display: Option<bool>, // 无可用显示器（远程桌面断开等）时暂停自动感知，直到显示器恢复
+    // 记录产生后执行的自定义命令
+    pub on_record_command: Option<String>, // 记录产生后执行的命令模板
+    // 软件名规范化
+    pub software_aliases: Option<String>, // 软件名别名映射（别名 -> 规范名）
+    // Stitched screenshot size cap
+    pub max_image_dimension: Option<i32>, // longest side of stitched screenshots, 0 = no limit
+    // Screenshot timeout and retries
+    pub capture_timeout_secs: Option<i32>,
+    pub capture_retries: Option<i32>,
+    // Capture when the user returns from idle
+    pub capture_on_idle_wake: Option<bool>,
+    // Sensitive content redaction
+    pub redact_sensitive_content: Option<bool>, // Redact e-mails, phone numbers and tokens from analysis results
+    // Placeholder semantics version of the custom prompts
+    pub prompt_schema_version: Option<i32>,
+    // Scheduled daily summary
+    pub auto_summary_enabled: Option<bool>,
+    pub auto_summary_catch_up: Option<bool>, // generate a missed summary on the next start
+    pub last_auto_summary_date: Option<String>, // local date (YYYY-MM-DD) of the last scheduled summary
+    // Keyword alerts
+    pub keyword_alert_rules: Option<String>, // keyword -> reminder rules (JSON array)
+    // Multi-monitor capture
+    pub capture_all_monitors: Option<bool>, // capture every monitor, overriding capture_mode
+    // Excluded screen regions
+    pub exclude_regions: Option<String>,
+    // Excluded apps
+    pub excluded_apps: Option<String>,
+    // Draft retention
+    pub draft_retention_days: Option<i32>, // 未确认草稿的保留天数
 }
 
 /// AI-006: Custom API Header for various API providers (OpenRouter, Azure, Claude, etc.)
@@ -132,6 +216,30 @@ pub struct CustomHeader {
     pub sensitive: bool, // Whether the value should be encrypted
 }
 
+/// Headers set by the request builders themselves; custom and extra headers
+/// cannot replace them. Authorization may be replaced on purpose (e.g. a
+/// gateway token), in which case the API key is not sent.
+pub const PROTECTED_HEADERS: &[&str] = &["content-type", "content-length", "host"];
+
+/// Whether a header carries credentials: `Authorization`, `Proxy-Authorization`,
+/// `api-key` or any name containing `api-key`, `token` or `secret`. Values of
+/// such headers are encrypted at rest and masked in logs.
+pub fn is_sensitive_header(key: &str) -> bool {
+    let key = key.trim().to_ascii_lowercase();
+    key == "authorization"
+        || key == "proxy-authorization"
+        || ["api-key", "token", "secret"]
+            .iter()
+            .any(|part| key.contains(part))
+}
+
+/// Whether the headers carry their own credentials, replacing the Bearer API key.
+pub fn has_custom_auth_header(headers: &[CustomHeader]) -> bool {
+    headers.iter().any(|h| {
+        h.key.eq_ignore_ascii_case("authorization") || h.key.eq_ignore_ascii_case("api-key")
+    })
+}
+
 /// AI-006: Preset header templates for common API providers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeaderPreset {
@@ -219,6 +327,48 @@ impl Settings {
             .ok_or_else(|| AppError::validation("Obsidian path not configured"))
     }
 
+    /// Headers added to AI requests: `custom_headers` followed by the
+    /// `extra_headers` map (e.g. `User-Agent` or gateway routing headers).
+    /// Invalid JSON is ignored and protected headers are dropped. Credential
+    /// headers (see [`is_sensitive_header`]) are marked sensitive.
+    pub fn request_headers(&self) -> Vec<CustomHeader> {
+        let custom = self
+            .custom_headers
+            .as_deref()
+            .filter(|json| !json.is_empty())
+            .and_then(|json| serde_json::from_str::<Vec<CustomHeader>>(json).ok())
+            .unwrap_or_default();
+        let extra = self
+            .extra_headers
+            .as_deref()
+            .filter(|json| !json.is_empty())
+            .and_then(|json| {
+                serde_json::from_str::<std::collections::BTreeMap<String, String>>(json).ok()
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| CustomHeader {
+                key,
+                value,
+                sensitive: false,
+            });
+        custom
+            .into_iter()
+            .chain(extra)
+            .filter(|h| {
+                let key = h.key.trim();
+                !key.is_empty()
+                    && !PROTECTED_HEADERS
+                        .iter()
+                        .any(|p| key.eq_ignore_ascii_case(p))
+            })
+            .map(|h| CustomHeader {
+                sensitive: h.sensitive || is_sensitive_header(&h.key),
+                ..h
+            })
+            .collect()
+    }
+
     /// VAULT-001: Get vault by name
     pub fn get_vault_by_name(&self, name: &str) -> Option<ObsidianVault> {
         if let Some(ref vaults_json) = self.obsidian_vaults {
@@ -371,6 +521,57 @@ mod tests_ai_006 {
         assert!(claude.is_some());
     }
 
+    #[test]
+    fn test_request_headers_merge_extra_headers_without_protected_ones() {
+        let settings = Settings {
+            custom_headers: Some(
+                r#"[{"key":"X-Title","value":"DailyLogger","sensitive":false}]"#.to_string(),
+            ),
+            extra_headers: Some(
+                r#"{"X-Gateway-Route":"vision","User-Agent":"DailyLogger","Content-Type":"text/plain","Authorization":"Bearer gw"}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let headers = settings.request_headers();
+        let keys: Vec<&str> = headers.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys[0], "X-Title");
+        assert!(keys.contains(&"X-Gateway-Route"));
+        assert!(keys.contains(&"User-Agent"));
+        assert!(!keys.iter().any(|k| k.eq_ignore_ascii_case("content-type")));
+        // Authorization is an allowed override and replaces the API key
+        assert!(has_custom_auth_header(&headers));
+        let sensitive: Vec<&str> = headers
+            .iter()
+            .filter(|h| h.sensitive)
+            .map(|h| h.key.as_str())
+            .collect();
+        assert_eq!(sensitive, vec!["Authorization"]);
+
+        let invalid = Settings {
+            extra_headers: Some("not json".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.request_headers().is_empty());
+        assert!(!has_custom_auth_header(&invalid.request_headers()));
+    }
+
+    #[test]
+    fn test_sensitive_header_names() {
+        for key in [
+            "Authorization",
+            "api-key",
+            "X-Api-Key",
+            "X-Gateway-Token",
+            "Client-Secret",
+        ] {
+            assert!(is_sensitive_header(key), "{}", key);
+        }
+        for key in ["User-Agent", "X-Title", "anthropic-version"] {
+            assert!(!is_sensitive_header(key), "{}", key);
+        }
+    }
+
     #[test]
     fn test_settings_default_custom_headers() {
         let settings = Settings::default();
@@ -383,7 +584,7 @@ mod tests_ai_006 {
 // DATA-008: Statistics Types and Functions
 // ============================================
 
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate, Timelike};
 
 /// Statistics time range type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -925,6 +1126,187 @@ fn get_peak_hours(
     Ok(distributions)
 }
 
+// Overview Statistics (dashboard)
+// ============================================
+
+/// Number of entries returned in `OverviewStats::top_software`
+const OVERVIEW_TOP_SOFTWARE_LIMIT: usize = 5;
+
+/// Usage count of a single software in the overview
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SoftwareUsage {
+    pub name: String,
+    pub count: i64,
+}
+
+/// All-time aggregated statistics for the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverviewStats {
+    pub total_records: i64,
+    pub days_covered: i64,
+    pub average_per_day: f64,
+    pub auto_count: i64,
+    pub manual_count: i64,
+    pub auto_ratio: f64,   // Percentage 0-100
+    pub manual_ratio: f64, // Percentage 0-100
+    pub top_software: Vec<SoftwareUsage>,
+    pub most_active_hour: Option<u32>, // 0-23, local time
+}
+
+/// Minimal record projection used to compute the overview
+#[derive(Debug, Clone)]
+pub struct OverviewRecord {
+    pub timestamp: String,
+    pub source_type: String,
+    pub content: String,
+}
+
+fn parse_local_timestamp(timestamp: &str) -> Option<chrono::DateTime<Local>> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Local))
+}
+
+/// Count distinct local dates that have at least one record
+pub fn count_days_covered(records: &[OverviewRecord]) -> i64 {
+    records
+        .iter()
+        .filter_map(|r| parse_local_timestamp(&r.timestamp))
+        .map(|dt| dt.date_naive())
+        .collect::<std::collections::HashSet<_>>()
+        .len() as i64
+}
+
+/// Average records per covered day (0 when no day is covered)
+pub fn calculate_average_per_day(total: i64, days: i64) -> f64 {
+    if days > 0 {
+        total as f64 / days as f64
+    } else {
+        0.0
+    }
+}
+
+/// Count records per source type, returns (auto_count, manual_count)
+pub fn count_by_source_type(records: &[OverviewRecord]) -> (i64, i64) {
+    records
+        .iter()
+        .fold((0, 0), |(auto, manual), r| match r.source_type.as_str() {
+            "auto" => (auto + 1, manual),
+            "manual" => (auto, manual + 1),
+            _ => (auto, manual),
+        })
+}
+
+/// Percentage of `part` in `total` (0 when total is 0)
+pub fn calculate_ratio(part: i64, total: i64) -> f64 {
+    if total > 0 {
+        (part as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Extract software names from an auto record's `active_software` field.
+/// The field may contain several names separated by commas.
+fn extract_software_names(content: &str) -> Vec<String> {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|v| v.get("active_software")?.as_str().map(str::to_string))
+        .map(|s| {
+            s.split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Most frequently used software across auto records, sorted by count desc then name
+pub fn calculate_top_software(records: &[OverviewRecord], limit: usize) -> Vec<SoftwareUsage> {
+    let mut counts: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    for record in records.iter().filter(|r| r.source_type == "auto") {
+        for name in extract_software_names(&record.content) {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+
+    let mut usage: Vec<SoftwareUsage> = counts
+        .into_iter()
+        .map(|(name, count)| SoftwareUsage { name, count })
+        .collect();
+    usage.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    usage.truncate(limit);
+    usage
+}
+
+/// Local hour (0-23) with the most records; ties resolve to the earliest hour
+pub fn calculate_most_active_hour(records: &[OverviewRecord]) -> Option<u32> {
+    let mut hours = [0i64; 24];
+    for dt in records
+        .iter()
+        .filter_map(|r| parse_local_timestamp(&r.timestamp))
+    {
+        hours[dt.hour() as usize] += 1;
+    }
+
+    hours
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .max_by(|(ha, ca), (hb, cb)| ca.cmp(cb).then_with(|| hb.cmp(ha)))
+        .map(|(hour, _)| hour as u32)
+}
+
+/// Build the overview from a set of records
+pub fn build_overview_stats(records: &[OverviewRecord]) -> OverviewStats {
+    let total_records = records.len() as i64;
+    let days_covered = count_days_covered(records);
+    let (auto_count, manual_count) = count_by_source_type(records);
+
+    OverviewStats {
+        total_records,
+        days_covered,
+        average_per_day: calculate_average_per_day(total_records, days_covered),
+        auto_count,
+        manual_count,
+        auto_ratio: calculate_ratio(auto_count, total_records),
+        manual_ratio: calculate_ratio(manual_count, total_records),
+        top_software: calculate_top_software(records, OVERVIEW_TOP_SOFTWARE_LIMIT),
+        most_active_hour: calculate_most_active_hour(records),
+    }
+}
+
+/// Load the overview projection of all records
+fn load_overview_records(conn: &rusqlite::Connection) -> AppResult<Vec<OverviewRecord>> {
+    let mut stmt = conn.prepare("SELECT timestamp, source_type, content FROM records")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(OverviewRecord {
+            timestamp: row.get(0)?,
+            source_type: row.get(1)?,
+            content: row.get(2)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Get all-time overview statistics (sync version)
+pub fn get_overview_stats_sync() -> AppResult<OverviewStats> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let records = load_overview_records(conn)?;
+    Ok(build_overview_stats(&records))
+}
+
+/// Get all-time overview statistics for the dashboard
+#[command]
+pub async fn get_overview_stats() -> AppResult<OverviewStats> {
+    get_overview_stats_sync()
+}
+
 #[cfg(test)]
 mod tests_statistics {
     use super::*;
@@ -1296,6 +1678,116 @@ mod tests_statistics {
         .unwrap();
         assert_eq!(record_count, 1);
     }
+
+    fn overview_record(timestamp: &str, source_type: &str, content: &str) -> OverviewRecord {
+        OverviewRecord {
+            timestamp: timestamp.to_string(),
+            source_type: source_type.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_count_days_covered() {
+        let records = vec![
+            overview_record("2026-03-01T12:00:00+00:00", "auto", "{}"),
+            overview_record("2026-03-01T12:30:00+00:00", "manual", "note"),
+            overview_record("2026-03-03T12:00:00+00:00", "auto", "{}"),
+            overview_record("invalid", "auto", "{}"),
+        ];
+        assert_eq!(count_days_covered(&records), 2);
+        assert_eq!(count_days_covered(&[]), 0);
+    }
+
+    #[test]
+    fn test_calculate_average_per_day() {
+        assert_eq!(calculate_average_per_day(10, 4), 2.5);
+        assert_eq!(calculate_average_per_day(10, 0), 0.0);
+    }
+
+    #[test]
+    fn test_count_by_source_type_and_ratio() {
+        let records = vec![
+            overview_record("2026-03-01T12:00:00+00:00", "auto", "{}"),
+            overview_record("2026-03-01T12:01:00+00:00", "auto", "{}"),
+            overview_record("2026-03-01T12:02:00+00:00", "auto", "{}"),
+            overview_record("2026-03-01T12:03:00+00:00", "manual", "note"),
+        ];
+        let (auto, manual) = count_by_source_type(&records);
+        assert_eq!((auto, manual), (3, 1));
+        assert_eq!(calculate_ratio(auto, 4), 75.0);
+        assert_eq!(calculate_ratio(manual, 4), 25.0);
+        assert_eq!(calculate_ratio(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_calculate_top_software() {
+        let records = vec![
+            overview_record("t", "auto", r#"{"active_software":"VS Code"}"#),
+            overview_record("t", "auto", r#"{"active_software":"VS Code, Chrome"}"#),
+            overview_record("t", "auto", r#"{"active_software":"Chrome"}"#),
+            overview_record("t", "auto", r#"{"active_software":"Slack"}"#),
+            overview_record("t", "auto", "not json"),
+            overview_record("t", "manual", r#"{"active_software":"Ignored"}"#),
+        ];
+        let top = calculate_top_software(&records, 2);
+        assert_eq!(
+            top,
+            vec![
+                SoftwareUsage {
+                    name: "Chrome".to_string(),
+                    count: 2
+                },
+                SoftwareUsage {
+                    name: "VS Code".to_string(),
+                    count: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_most_active_hour() {
+        let busy = "2026-03-01T10:15:00+00:00";
+        let records = vec![
+            overview_record(busy, "auto", "{}"),
+            overview_record("2026-03-01T10:45:00+00:00", "auto", "{}"),
+            overview_record("2026-03-01T14:00:00+00:00", "manual", "note"),
+        ];
+        let expected = chrono::DateTime::parse_from_rfc3339(busy)
+            .unwrap()
+            .with_timezone(&Local)
+            .hour();
+        assert_eq!(calculate_most_active_hour(&records), Some(expected));
+        assert_eq!(calculate_most_active_hour(&[]), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_overview_stats_sync() {
+        crate::memory_storage::setup_test_db_with_schema();
+
+        add_record(
+            "auto",
+            r#"{"current_focus":"coding","active_software":"VS Code"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        add_record("manual", "quick note", None, None, None).unwrap();
+
+        let stats = get_overview_stats_sync().unwrap();
+        assert_eq!(stats.total_records, 2);
+        assert_eq!(stats.days_covered, 1);
+        assert_eq!(stats.average_per_day, 2.0);
+        assert_eq!(stats.auto_count, 1);
+        assert_eq!(stats.manual_count, 1);
+        assert_eq!(stats.auto_ratio, 50.0);
+        assert_eq!(stats.top_software.len(), 1);
+        assert_eq!(stats.top_software[0].name, "VS Code");
+        assert!(stats.most_active_hour.is_some());
+    }
 }
 
 // VAULT-001: Tests for multi-vault auto-selection
@@ -0,0 +1,275 @@
+//! Keyword based project classification.
+//!
+//! The `projects` setting maps project names to keywords. Auto records are
+//! assigned the project whose keywords best match the record's
+//! `context_keywords`, stored in `records.project`.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use crate::errors::{AppError, AppResult};
+
+use super::{Record, DB_CONNECTION};
+
+/// A project and the keywords that identify it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectRule {
+    pub name: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// Records of one project within a date range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub project: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub record_count: usize,
+    /// Distinct local dates with at least one record
+    pub active_days: usize,
+    /// Most frequent context keywords, most common first
+    pub top_keywords: Vec<String>,
+    pub records: Vec<Record>,
+}
+
+const TOP_KEYWORDS: usize = 10;
+
+/// Parse the `projects` setting; invalid JSON yields no rules.
+pub fn parse_project_rules(json: Option<&str>) -> Vec<ProjectRule> {
+    json.filter(|s| !s.trim().is_empty())
+        .and_then(|s| serde_json::from_str::<Vec<ProjectRule>>(s).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| !p.name.trim().is_empty())
+        .collect()
+}
+
+/// Number of project keywords found in the record keywords.
+/// Matching is case-insensitive and a keyword also matches inside a longer one
+/// (project keyword "tauri" matches record keyword "Tauri IPC").
+pub fn score_project(record_keywords: &[String], project: &ProjectRule) -> usize {
+    let record_keywords: Vec<String> = record_keywords.iter().map(|k| k.to_lowercase()).collect();
+    project
+        .keywords
+        .iter()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .filter(|k| record_keywords.iter().any(|rk| rk.contains(k.as_str())))
+        .count()
+}
+
+/// Best matching project, or `None` when no keyword matches.
+/// Ties go to the project configured first.
+pub fn match_project(record_keywords: &[String], projects: &[ProjectRule]) -> Option<String> {
+    let mut best: Option<(&ProjectRule, usize)> = None;
+    for project in projects {
+        let score = score_project(record_keywords, project);
+        if score > 0 && best.is_none_or(|(_, s)| score > s) {
+            best = Some((project, score));
+        }
+    }
+    best.map(|(p, _)| p.name.clone())
+}
+
+/// `context_keywords` of an analysis content JSON.
+pub(crate) fn context_keywords_from_content(content: &str) -> Vec<String> {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|v| v.get("context_keywords").cloned())
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok())
+        .unwrap_or_default()
+}
+
+/// Project for an auto record's content, using the configured rules.
+/// Reads settings, so it must not be called while holding the DB lock.
+pub(crate) fn project_for_content(content: &str) -> Option<String> {
+    let settings = super::get_settings_sync().ok()?;
+    let projects = parse_project_rules(settings.projects.as_deref());
+    if projects.is_empty() {
+        return None;
+    }
+    match_project(&context_keywords_from_content(content), &projects)
+}
+
+/// Summarize a project's records between two local dates (YYYY-MM-DD, inclusive).
+pub fn get_project_summary_sync(
+    project: &str,
+    start_date: &str,
+    end_date: &str,
+) -> AppResult<ProjectSummary> {
+    let records: Vec<Record> =
+        super::get_records_by_date_range_sync(start_date.to_string(), end_date.to_string())?;
+    let ids: Vec<i64> = {
+        let db = DB_CONNECTION.lock()?;
+        let conn = db
+            .as_ref()
+            .ok_or_else(|| AppError::database("Database not initialized"))?;
+        let mut stmt = conn.prepare("SELECT id FROM records WHERE project = ?1")?;
+        let rows = stmt.query_map(params![project], |row| row.get(0))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    let records: Vec<Record> = records
+        .into_iter()
+        .filter(|r| ids.contains(&r.id))
+        .collect();
+
+    let mut days: Vec<String> = records
+        .iter()
+        .filter_map(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).ok())
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect();
+    days.sort();
+    days.dedup();
+
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for keyword in records
+        .iter()
+        .flat_map(|r| context_keywords_from_content(&r.content))
+    {
+        match counts.iter_mut().find(|(k, _)| *k == keyword) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((keyword, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+
+    Ok(ProjectSummary {
+        project: project.to_string(),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        record_count: records.len(),
+        active_days: days.len(),
+        top_keywords: counts
+            .into_iter()
+            .take(TOP_KEYWORDS)
+            .map(|(k, _)| k)
+            .collect(),
+        records,
+    })
+}
+
+/// Summary of one project's records in a date range
+#[command]
+pub async fn get_project_summary(
+    project: String,
+    start_date: String,
+    end_date: String,
+) -> AppResult<ProjectSummary> {
+    get_project_summary_sync(&project, &start_date, &end_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn rule(name: &str, keywords: &[&str]) -> ProjectRule {
+        ProjectRule {
+            name: name.to_string(),
+            keywords: keywords.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn keywords(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn score_counts_case_insensitive_keyword_hits() {
+        let project = rule("DailyLogger", &["tauri", "Rust", "sqlite"]);
+        assert_eq!(
+            score_project(&keywords(&["Tauri IPC", "rust", "React"]), &project),
+            2
+        );
+        assert_eq!(score_project(&keywords(&["Excel"]), &project), 0);
+        assert_eq!(score_project(&[], &project), 0);
+    }
+
+    #[test]
+    fn match_project_picks_highest_score_then_first_configured() {
+        let projects = vec![
+            rule("Website", &["react", "css"]),
+            rule("DailyLogger", &["tauri", "rust", "react"]),
+            rule("Docs", &["markdown"]),
+        ];
+
+        assert_eq!(
+            match_project(&keywords(&["Rust", "Tauri", "React"]), &projects),
+            Some("DailyLogger".to_string())
+        );
+        // One hit each: the first configured project wins
+        assert_eq!(
+            match_project(&keywords(&["React"]), &projects),
+            Some("Website".to_string())
+        );
+        assert_eq!(match_project(&keywords(&["Excel"]), &projects), None);
+    }
+
+    #[test]
+    fn parse_project_rules_ignores_invalid_input() {
+        assert!(parse_project_rules(None).is_empty());
+        assert!(parse_project_rules(Some("not json")).is_empty());
+        let rules = parse_project_rules(Some(
+            r#"[{"name":"A","keywords":["x"]},{"name":" ","keywords":["y"]},{"name":"B"}]"#,
+        ));
+        assert_eq!(rules, vec![rule("A", &["x"]), rule("B", &[])]);
+    }
+
+    #[test]
+    #[serial]
+    fn auto_records_are_assigned_a_project() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let mut settings = (*super::super::get_settings_sync().unwrap()).clone();
+        settings.projects = Some(r#"[{"name":"DailyLogger","keywords":["tauri"]}]"#.to_string());
+        super::super::save_settings_sync(&settings).unwrap();
+
+        let matched = super::super::add_record(
+            "auto",
+            r#"{"context_keywords":["Tauri","Rust"]}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        super::super::add_record(
+            "auto",
+            r#"{"context_keywords":["Excel"]}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        super::super::add_record("manual", "tauri notes", None, None, None).unwrap();
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let summary = get_project_summary_sync("DailyLogger", &today, &today).unwrap();
+        assert_eq!(summary.record_count, 1);
+        assert_eq!(summary.records[0].id, matched);
+        assert_eq!(summary.active_days, 1);
+        assert_eq!(summary.top_keywords, keywords(&["Tauri", "Rust"]));
+    }
+
+    #[test]
+    #[serial]
+    fn analysis_update_assigns_project() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let mut settings = (*super::super::get_settings_sync().unwrap()).clone();
+        settings.projects = Some(r#"[{"name":"Docs","keywords":["markdown"]}]"#.to_string());
+        super::super::save_settings_sync(&settings).unwrap();
+
+        let id = super::super::add_record("auto", "{}", None, None, None).unwrap();
+        super::super::update_record_content_sync(id, r#"{"context_keywords":["Markdown"]}"#)
+            .unwrap();
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let summary = get_project_summary_sync("Docs", &today, &today).unwrap();
+        assert_eq!(summary.record_count, 1);
+    }
+}
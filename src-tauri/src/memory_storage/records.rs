@@ -1,5 +1,5 @@
-use chrono::{Datelike, TimeZone};
-use rusqlite::params;
+use chrono::{Datelike, TimeZone, Timelike};
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tauri::command;
 
@@ -20,10 +20,50 @@ fn naive_to_utc_rfc3339(dt: chrono::NaiveDateTime) -> String {
 fn date_to_utc_rfc3339(date: chrono::NaiveDate, h: u32, m: u32, s: u32) -> String {
     let naive_dt = date
         .and_hms_opt(h, m, s)
-        .expect("valid time: (h,m,s) are always 0,0,0 or 23,59,59");
+        .expect("valid time: h < 24, m < 60, s < 60");
     naive_to_utc_rfc3339(naive_dt)
 }
 
+/// Configured hour at which a new day starts (0-23), defaulting to midnight.
+pub fn configured_day_start_hour() -> u32 {
+    super::get_settings_sync()
+        .ok()
+        .and_then(|s| s.day_start_hour)
+        .unwrap_or(0)
+        .clamp(0, 23) as u32
+}
+
+/// The logical date `now` belongs to when days start at `day_start_hour`.
+/// With `day_start_hour = 4`, 02:00 still belongs to the previous day.
+pub fn logical_date(now: chrono::NaiveDateTime, day_start_hour: u32) -> chrono::NaiveDate {
+    (now - chrono::Duration::hours(day_start_hour as i64)).date()
+}
+
+/// UTC RFC3339 bounds `[start, end)` of the logical day containing `now`.
+pub fn logical_day_bounds(now: chrono::NaiveDateTime, day_start_hour: u32) -> (String, String) {
+    let date = logical_date(now, day_start_hour);
+    (
+        date_to_utc_rfc3339(date, day_start_hour, 0, 0),
+        date_to_utc_rfc3339(date + chrono::Duration::days(1), day_start_hour, 0, 0),
+    )
+}
+
+/// Today's logical date in local time, honoring `day_start_hour`.
+pub fn logical_today() -> chrono::NaiveDate {
+    logical_date(
+        chrono::Local::now().naive_local(),
+        configured_day_start_hour(),
+    )
+}
+
+/// Bounds of today's logical day in local time, honoring `day_start_hour`.
+fn today_bounds() -> (String, String) {
+    logical_day_bounds(
+        chrono::Local::now().naive_local(),
+        configured_day_start_hour(),
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Record {
     pub id: i64,
@@ -39,19 +79,39 @@ pub struct Record {
     pub user_notes: Option<String>,
     // SESSION-001: 时段关联和分析状态
     pub session_id: Option<i64>,
-    pub analysis_status: Option<String>, // pending | analyzed | user_edited
+    pub analysis_status: Option<String>, // pending | analyzed | user_edited | auto_error
+    // 附加结构化元数据（如关联的 git commit、任务 ID）
+    pub metadata: Option<String>, // JSON object serialized
+    // 草稿/已确认状态，只有已确认的记录进入日报
+    pub status: Option<String>, // draft | confirmed
 }
 
 /// Full-text search result with highlighting
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub record: Record,
-    /// Highlighted snippet with <mark> tags around matched keywords
+    /// Context around matched keywords, which are wrapped in <mark> tags
     pub snippet: String,
     /// Relevance score (lower is better with bm25)
     pub rank: f64,
 }
 
+/// Record count of one local day in the yearly heatmap
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeatmapDay {
+    /// Local date (YYYY-MM-DD)
+    pub date: String,
+    pub count: i64,
+}
+
+/// Record count of one local hour of a day
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HourlyBucket {
+    /// Local hour (0-23)
+    pub hour: u32,
+    pub count: i64,
+}
+
 /// EXP-005: Today's statistics for the summary widget
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TodayStats {
@@ -77,6 +137,8 @@ pub struct SessionScreenshot {
     pub record_id: i64,
     pub timestamp: String,
     pub screenshot_path: String,
+    /// Small preview for galleries; the full screenshot is loaded on demand
+    pub thumbnail_path: Option<String>,
 }
 
 pub fn add_record(
@@ -105,23 +167,281 @@ pub fn add_record_with_session(
     tags: Option<&str>,
     session_id: Option<i64>,
 ) -> AppResult<i64> {
+    add_record_with_metadata(
+        source_type,
+        content,
+        screenshot_path,
+        monitor_info,
+        tags,
+        session_id,
+        None,
+    )
+}
+
+/// Add record with optional structured metadata (a JSON object of arbitrary key-values)
+pub fn add_record_with_metadata(
+    source_type: &str,
+    content: &str,
+    screenshot_path: Option<&str>,
+    monitor_info: Option<&str>,
+    tags: Option<&str>,
+    session_id: Option<i64>,
+    metadata: Option<&str>,
+) -> AppResult<i64> {
+    if let Some(metadata) = metadata {
+        validate_record_metadata(metadata)?;
+    }
+
     // STAB-001 Task 4.2: Ensure database connection is valid before operation
     crate::memory_storage::schema::ensure_connection()?;
 
+    // Settings are read before taking the DB lock (a cache miss locks it too)
+    let (project, software_normalized) = if source_type == "auto" {
+        (
+            super::projects::project_for_content(content),
+            super::software::software_for_content(content),
+        )
+    } else {
+        (None, None)
+    };
+
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
     let timestamp = chrono::Utc::now().to_rfc3339();
+    let id = insert_record_at(
+        conn,
+        &timestamp,
+        NewRecord {
+            source_type,
+            content,
+            screenshot_path,
+            monitor_info,
+            tags,
+            session_id,
+            metadata,
+            project: project.as_deref(),
+            status: default_record_status(source_type),
+            software_normalized: software_normalized.as_deref(),
+        },
+    )?;
+    // The hook reads settings, so the DB lock is released first
+    drop(db);
+
+    // Auto records hold a pending placeholder until analyzed; their hook runs
+    // once the analysis is stored (record_hook::run_on_stored_record_hook)
+    if source_type != "auto" {
+        crate::record_hook::run_on_record_hook(&crate::record_hook::HookRecord {
+            id,
+            timestamp: &timestamp,
+            source_type,
+            content,
+            screenshot_path,
+        });
+    }
+    Ok(id)
+}
+
+/// Insert an imported record with its original timestamp (UTC RFC3339).
+/// Returns `None` when an identical record already exists.
+pub fn import_record_sync(
+    timestamp: &str,
+    source_type: &str,
+    content: &str,
+    tags: Option<&str>,
+) -> AppResult<Option<i64>> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    if find_duplicate_record(
+        conn,
+        timestamp,
+        source_type,
+        &sanitize_content(content),
+        None,
+    )?
+    .is_some()
+    {
+        return Ok(None);
+    }
+    insert_record_at(
+        conn,
+        timestamp,
+        NewRecord {
+            source_type,
+            content,
+            screenshot_path: None,
+            monitor_info: None,
+            tags,
+            session_id: None,
+            metadata: None,
+            project: None,
+            status: RECORD_STATUS_CONFIRMED,
+            software_normalized: None,
+        },
+    )
+    .map(Some)
+}
+
+/// Fields of a record about to be inserted.
+struct NewRecord<'a> {
+    source_type: &'a str,
+    content: &'a str,
+    screenshot_path: Option<&'a str>,
+    monitor_info: Option<&'a str>,
+    tags: Option<&'a str>,
+    session_id: Option<i64>,
+    metadata: Option<&'a str>,
+    project: Option<&'a str>,
+    status: &'a str,
+    software_normalized: Option<&'a str>,
+}
+
+/// Record status of automatic captures until the user confirms them.
+pub const RECORD_STATUS_DRAFT: &str = "draft";
+/// Record status of manual records and confirmed captures; only these go into daily reports.
+pub const RECORD_STATUS_CONFIRMED: &str = "confirmed";
+/// Days drafts are kept when `draft_retention_days` is not set; older drafts are deleted at startup.
+/// A setting of 0 or less keeps drafts forever.
+pub const DEFAULT_DRAFT_RETENTION_DAYS: i64 = 7;
+
+/// Automatic captures start as drafts, everything the user writes is confirmed.
+fn default_record_status(source_type: &str) -> &'static str {
+    if source_type == "auto" {
+        RECORD_STATUS_DRAFT
+    } else {
+        RECORD_STATUS_CONFIRMED
+    }
+}
+
+/// Importance of records without a score (manual records, captures analyzed
+/// before scoring existed).
+pub const DEFAULT_RECORD_IMPORTANCE: u8 = 50;
+/// Records scored at least this high are marked as important in daily reports.
+pub const HIGH_IMPORTANCE_THRESHOLD: u8 = 80;
+
+/// Importance score (0-100) from a JSON value; numbers are rounded and clamped,
+/// numeric strings are accepted.
+pub fn importance_from_value(value: &serde_json::Value) -> Option<u8> {
+    let score = match value {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => s.trim().parse::<f64>().ok()?,
+        _ => return None,
+    };
+    score
+        .is_finite()
+        .then(|| score.round().clamp(0.0, 100.0) as u8)
+}
+
+/// Importance of a record, read from the `importance` field of its analysis
+/// content, or `DEFAULT_RECORD_IMPORTANCE` when it has none.
+pub fn record_importance(content: &str) -> u8 {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|value| value.get("importance").and_then(importance_from_value))
+        .unwrap_or(DEFAULT_RECORD_IMPORTANCE)
+}
+
+/// Keep records whose importance is at least `min_importance` (all records when `None`).
+pub fn filter_by_min_importance(records: Vec<Record>, min_importance: Option<u8>) -> Vec<Record> {
+    match min_importance {
+        Some(min) => records
+            .into_iter()
+            .filter(|r| record_importance(&r.content) >= min)
+            .collect(),
+        None => records,
+    }
+}
+
+/// Existing record with the same source type, content and screenshot written in the
+/// same second. Guards against retry bugs writing the same analysis twice; captures
+/// with their own screenshot are never duplicates. Content is matched by
+/// `content_hash`; records not hashed yet are compared by their full content.
+fn find_duplicate_record(
+    conn: &rusqlite::Connection,
+    timestamp: &str,
+    source_type: &str,
+    content: &str,
+    screenshot_path: Option<&str>,
+) -> AppResult<Option<i64>> {
+    // RFC3339 timestamps: the first 19 chars are "YYYY-MM-DDTHH:MM:SS"
+    let second = timestamp.get(..19).unwrap_or(timestamp);
+    Ok(conn
+        .query_row(
+            "SELECT id FROM records
+             WHERE source_type = ?1 AND substr(timestamp, 1, 19) = ?3
+               AND (content_hash = ?4 OR (content_hash IS NULL AND content = ?2))
+               AND screenshot_path IS ?5
+             LIMIT 1",
+            params![
+                source_type,
+                content,
+                second,
+                super::content_hash::content_hash(content),
+                screenshot_path
+            ],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Insert a record at `timestamp`, skipping it when an identical record already exists.
+/// Returns the id of the inserted (or existing duplicate) record.
+fn insert_record_at(
+    conn: &rusqlite::Connection,
+    timestamp: &str,
+    record: NewRecord,
+) -> AppResult<i64> {
+    let content = sanitize_content(record.content);
 
     // STAB-001 AC4: Use explicit transaction for data integrity
     // Begin transaction and ensure rollback on error
     conn.execute("BEGIN TRANSACTION", [])?;
 
+    match find_duplicate_record(
+        conn,
+        timestamp,
+        record.source_type,
+        &content,
+        record.screenshot_path,
+    ) {
+        Ok(Some(existing_id)) => {
+            conn.execute("COMMIT", [])?;
+            tracing::warn!(
+                "Skipped duplicate {} record (same content as record {})",
+                record.source_type,
+                existing_id
+            );
+            return Ok(existing_id);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            let _ = conn.execute("ROLLBACK", []);
+            return Err(e);
+        }
+    }
+
     let result = conn.execute(
-        "INSERT INTO records (timestamp, source_type, content, screenshot_path, monitor_info, tags, session_id, analysis_status) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending')",
-        params![timestamp, source_type, content, screenshot_path, monitor_info, tags, session_id],
+        "INSERT INTO records (timestamp, source_type, content, screenshot_path, monitor_info, tags, session_id, analysis_status, metadata, project, status, software_normalized, content_lang, content_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending', ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            timestamp,
+            record.source_type,
+            content,
+            record.screenshot_path,
+            record.monitor_info,
+            record.tags,
+            record.session_id,
+            record.metadata,
+            record.project,
+            record.status,
+            record.software_normalized,
+            super::content_lang::content_lang_for_content(&content).as_str(),
+            super::content_hash::content_hash(&content)
+        ],
     );
 
     match result {
@@ -141,21 +461,56 @@ pub fn add_record_with_session(
     }
 }
 
-pub fn get_today_records_sync() -> AppResult<Vec<Record>> {
+/// Strip control characters from record text before it is stored.
+///
+/// Removes NUL, ESC and the other C0/C1 control characters (and DEL) that the
+/// frontend would otherwise render or choke on; tabs and line breaks are kept.
+/// HTML is left as is: the frontend escapes it when rendering.
+pub fn sanitize_content(content: &str) -> std::borrow::Cow<'_, str> {
+    let is_dangerous = |c: char| c.is_control() && !matches!(c, '\n' | '\r' | '\t');
+    if content.chars().any(is_dangerous) {
+        std::borrow::Cow::Owned(content.chars().filter(|c| !is_dangerous(*c)).collect())
+    } else {
+        std::borrow::Cow::Borrowed(content)
+    }
+}
+
+/// Record metadata must be a JSON object
+fn validate_record_metadata(metadata: &str) -> AppResult<()> {
+    let value: serde_json::Value = serde_json::from_str(metadata)
+        .map_err(|e| AppError::validation(format!("Invalid record metadata JSON: {}", e)))?;
+    if !value.is_object() {
+        return Err(AppError::validation(
+            "Record metadata must be a JSON object",
+        ));
+    }
+    Ok(())
+}
+
+/// SQL condition leaving out unconfirmed drafts (records without a status predate drafts)
+pub(crate) const CONFIRMED_RECORDS_CONDITION: &str = "(status IS NULL OR status != 'draft')";
+
+/// Records with `start <= timestamp < end` (UTC RFC3339), newest first.
+/// With `confirmed_only`, draft records are left out.
+fn get_records_between(start: &str, end: &str, confirmed_only: bool) -> AppResult<Vec<Record>> {
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
-    let today_start = date_to_utc_rfc3339(chrono::Local::now().date_naive(), 0, 0, 0);
-
-    let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
-         WHERE timestamp >= ?1 ORDER BY timestamp DESC",
-    )?;
+    let sql = format!(
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
+         WHERE timestamp >= ?1 AND timestamp < ?2{} ORDER BY timestamp DESC",
+        if confirmed_only {
+            format!(" AND {}", CONFIRMED_RECORDS_CONDITION)
+        } else {
+            String::new()
+        }
+    );
+    let mut stmt = conn.prepare(&sql)?;
 
     let records = stmt
-        .query_map(params![today_start], |row| {
+        .query_map(params![start, end], |row| {
             Ok(Record {
                 id: row.get(0)?,
                 timestamp: row.get(1)?,
@@ -167,6 +522,8 @@ pub fn get_today_records_sync() -> AppResult<Vec<Record>> {
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()
@@ -175,6 +532,12 @@ pub fn get_today_records_sync() -> AppResult<Vec<Record>> {
     Ok(records)
 }
 
+pub fn get_today_records_sync() -> AppResult<Vec<Record>> {
+    // Settings are read before taking the DB lock (a cache miss locks it too)
+    let (today_start, today_end) = today_bounds();
+    get_records_between(&today_start, &today_end, false)
+}
+
 /// Get records for the current week (Monday to Sunday)
 /// week_start_day: 0=Monday, 6=Sunday (default is Monday)
 pub fn get_week_records_sync(week_start_day: i32) -> AppResult<Vec<Record>> {
@@ -195,7 +558,7 @@ pub fn get_week_records_sync(week_start_day: i32) -> AppResult<Vec<Record>> {
     let week_end = date_to_utc_rfc3339(week_end_date, 23, 59, 59);
 
     let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
          WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp DESC",
     )?;
 
@@ -212,6 +575,8 @@ pub fn get_week_records_sync(week_start_day: i32) -> AppResult<Vec<Record>> {
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()
@@ -244,7 +609,7 @@ pub fn get_month_records_sync() -> AppResult<Vec<Record>> {
     let month_end = date_to_utc_rfc3339(next_month, 0, 0, 0);
 
     let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
          WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp DESC",
     )?;
 
@@ -261,6 +626,8 @@ pub fn get_month_records_sync() -> AppResult<Vec<Record>> {
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()
@@ -269,22 +636,24 @@ pub fn get_month_records_sync() -> AppResult<Vec<Record>> {
     Ok(records)
 }
 
+/// Today's records for summaries: drafts are left out until confirmed.
 pub fn get_all_today_records_for_summary() -> AppResult<Vec<Record>> {
-    get_today_records_sync()
+    let (today_start, today_end) = today_bounds();
+    get_records_between(&today_start, &today_end, true)
 }
 
 /// Get the count of today's records (more efficient than fetching all records).
 pub fn get_today_record_count_sync() -> AppResult<usize> {
+    let (today_start, today_end) = today_bounds();
+
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
-    let today_start = date_to_utc_rfc3339(chrono::Local::now().date_naive(), 0, 0, 0);
-
     let count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM records WHERE timestamp >= ?1",
-        params![today_start],
+        "SELECT COUNT(*) FROM records WHERE timestamp >= ?1 AND timestamp < ?2",
+        params![today_start, today_end],
         |row| row.get(0),
     )?;
 
@@ -294,13 +663,13 @@ pub fn get_today_record_count_sync() -> AppResult<usize> {
 /// EXP-005: Get today's statistics for the summary widget.
 /// Returns aggregated stats including record counts, time span, and busiest hour.
 pub fn get_today_stats_sync() -> AppResult<TodayStats> {
+    let (today_start, today_end) = today_bounds();
+
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
-    let today_start = date_to_utc_rfc3339(chrono::Local::now().date_naive(), 0, 0, 0);
-
     // Query basic stats in a single query
     let basic_stats = conn.query_row(
         "SELECT
@@ -309,8 +678,8 @@ pub fn get_today_stats_sync() -> AppResult<TodayStats> {
                 COALESCE(SUM(CASE WHEN source_type='manual' THEN 1 ELSE 0 END), 0) as manual_count,
                 MIN(timestamp) as first_time,
                 MAX(timestamp) as latest_time
-            FROM records WHERE timestamp >= ?1",
-        params![today_start],
+            FROM records WHERE timestamp >= ?1 AND timestamp < ?2",
+        params![today_start, today_end],
         |row| {
             Ok((
                 row.get::<_, i64>(0)?,            // total
@@ -329,11 +698,11 @@ pub fn get_today_stats_sync() -> AppResult<TodayStats> {
         conn.query_row(
             "SELECT CAST(strftime('%H', datetime(timestamp, 'localtime')) AS INTEGER) as hour, COUNT(*) as cnt
             FROM records
-            WHERE timestamp >= ?1
+            WHERE timestamp >= ?1 AND timestamp < ?2
             GROUP BY hour
             ORDER BY cnt DESC
             LIMIT 1",
-            params![today_start],
+            params![today_start, today_end],
             |row| Ok((row.get::<_, i64>(0)? as u32, row.get::<_, i64>(1)? as u32)),
         )?
     } else {
@@ -351,42 +720,269 @@ pub fn get_today_stats_sync() -> AppResult<TodayStats> {
     })
 }
 
+/// Every day of `year` with its record count, including days without records.
+/// `counts` maps local dates (YYYY-MM-DD) to record counts.
+pub fn fill_heatmap_year(
+    year: i32,
+    counts: &std::collections::HashMap<String, i64>,
+) -> AppResult<Vec<HeatmapDay>> {
+    let first = chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+        .ok_or_else(|| AppError::validation(format!("Invalid year: {}", year)))?;
+    Ok(first
+        .iter_days()
+        .take_while(|day| day.year() == year)
+        .map(|day| {
+            let date = day.format("%Y-%m-%d").to_string();
+            let count = counts.get(&date).copied().unwrap_or(0);
+            HeatmapDay { date, count }
+        })
+        .collect())
+}
+
+/// Record counts per local day for a whole year (see `fill_heatmap_year`)
+pub fn get_heatmap_sync(year: i32) -> AppResult<Vec<HeatmapDay>> {
+    let (start, end) = match (
+        chrono::NaiveDate::from_ymd_opt(year, 1, 1),
+        year.checked_add(1)
+            .and_then(|next| chrono::NaiveDate::from_ymd_opt(next, 1, 1)),
+    ) {
+        (Some(first), Some(next)) => (
+            date_to_utc_rfc3339(first, 0, 0, 0),
+            date_to_utc_rfc3339(next, 0, 0, 0),
+        ),
+        _ => return Err(AppError::validation(format!("Invalid year: {}", year))),
+    };
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT date(timestamp, 'localtime') AS day, COUNT(*)
+         FROM records
+         WHERE timestamp >= ?1 AND timestamp < ?2
+         GROUP BY day",
+    )?;
+    let counts = stmt
+        .query_map(params![start, end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<std::collections::HashMap<_, _>, _>>()?;
+
+    fill_heatmap_year(year, &counts)
+}
+
+/// Count RFC3339 `timestamps` per hour of `date` in `tz`. Always returns 24
+/// buckets; timestamps that fall on another day in `tz` or do not parse are ignored.
+pub fn hourly_distribution<Tz: TimeZone>(
+    timestamps: &[String],
+    date: chrono::NaiveDate,
+    tz: &Tz,
+) -> Vec<HourlyBucket> {
+    let mut counts = [0i64; 24];
+    for ts in timestamps {
+        let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) else {
+            continue;
+        };
+        let local = dt.with_timezone(tz);
+        if local.date_naive() == date {
+            counts[local.hour() as usize] += 1;
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .map(|(hour, &count)| HourlyBucket {
+            hour: hour as u32,
+            count,
+        })
+        .collect()
+}
+
+/// Record counts per local hour (0-23) of `date` (YYYY-MM-DD)
+pub fn get_hourly_distribution_sync(date: &str) -> AppResult<Vec<HourlyBucket>> {
+    let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+        AppError::validation(format!("Invalid date format (expected YYYY-MM-DD): {}", e))
+    })?;
+    let start = date_to_utc_rfc3339(day, 0, 0, 0);
+    let end = date_to_utc_rfc3339(day + chrono::Duration::days(1), 0, 0, 0);
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt =
+        conn.prepare("SELECT timestamp FROM records WHERE timestamp >= ?1 AND timestamp < ?2")?;
+    let timestamps = stmt
+        .query_map(params![start, end], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(hourly_distribution(&timestamps, day, &chrono::Local))
+}
+
+/// Parse a range bound given as `YYYY-MM-DD` (local midnight) or RFC3339 into
+/// a UTC RFC3339 string. A date used as `end` stands for the whole day, so it
+/// maps to the following local midnight.
+pub(super) fn parse_range_bound(value: &str, name: &str, is_end: bool) -> AppResult<String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let date = if is_end {
+            date + chrono::Duration::days(1)
+        } else {
+            date
+        };
+        return Ok(date_to_utc_rfc3339(date, 0, 0, 0));
+    }
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Utc).to_rfc3339())
+        .map_err(|e| {
+            AppError::validation(format!(
+                "Invalid {} format (expected YYYY-MM-DD or RFC3339): {}",
+                name, e
+            ))
+        })
+}
+
+/// Records with `timestamp` in `[start, end)`, newest first.
+///
+/// Both bounds accept `YYYY-MM-DD` (local day, converted like
+/// `get_today_records_sync`) or RFC3339. A date `end` includes that whole day,
+/// so `("2026-03-16", "2026-03-16")` returns the records of March 16.
+/// An empty range returns no records; `end` before `start` is an error.
 pub fn get_records_by_date_range_sync(
     start_date: String,
     end_date: String,
+) -> AppResult<Vec<Record>> {
+    let (start_utc, end_utc) = date_range_bounds(&start_date, &end_date)?;
+    get_records_between(&start_utc, &end_utc, false)
+}
+
+/// Like [`get_records_by_date_range_sync`], leaving out draft records.
+pub fn get_confirmed_records_by_date_range_sync(
+    start_date: String,
+    end_date: String,
+) -> AppResult<Vec<Record>> {
+    let (start_utc, end_utc) = date_range_bounds(&start_date, &end_date)?;
+    get_records_between(&start_utc, &end_utc, true)
+}
+
+/// UTC bounds of a date range for [`parse_range_bound`], the end day included.
+fn date_range_bounds(start_date: &str, end_date: &str) -> AppResult<(String, String)> {
+    let start_utc = parse_range_bound(start_date, "start_date", false)?;
+    let end_utc = parse_range_bound(end_date, "end_date", true)?;
+    if end_utc < start_utc {
+        return Err(AppError::validation(format!(
+            "end_date {} is before start_date {}",
+            end_date, start_date
+        )));
+    }
+    Ok((start_utc, end_utc))
+}
+
+/// Optional conditions narrowing the records of an export.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordFilter {
+    /// Keep records carrying any of these tags (AI tags or manual tags)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Keep records assigned to this project
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// SQL conditions (starting with ` AND`) and their values for `filter`.
+/// Parameters are numbered from `first_param`, so the clause can follow the
+/// date range parameters `?1`/`?2`.
+pub fn record_filter_clause(filter: &RecordFilter, first_param: usize) -> (String, Vec<String>) {
+    let mut sql = String::new();
+    let mut values = Vec::new();
+
+    let tags: Vec<&str> = filter
+        .tags
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if !tags.is_empty() {
+        let placeholders = (0..tags.len())
+            .map(|i| format!("?{}", first_param + i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sql.push_str(&format!(
+            " AND (EXISTS (SELECT 1 FROM json_each(CASE WHEN json_valid(records.tags) THEN records.tags ELSE '[]' END) WHERE value IN ({p}))
+              OR EXISTS (SELECT 1 FROM json_each(CASE WHEN json_valid(records.content) THEN json_extract(records.content, '$.tags') ELSE '[]' END) WHERE value IN ({p}))
+              OR EXISTS (SELECT 1 FROM record_manual_tags rmt JOIN manual_tags mt ON mt.id = rmt.tag_id
+                         WHERE rmt.record_id = records.id AND mt.name IN ({p})))",
+            p = placeholders
+        ));
+        values.extend(tags.iter().map(|t| t.to_string()));
+    }
+
+    if let Some(project) = filter
+        .project
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    {
+        sql.push_str(&format!(
+            " AND records.project = ?{}",
+            first_param + values.len()
+        ));
+        values.push(project.to_string());
+    }
+
+    (sql, values)
+}
+
+/// Get records within a date range for export (chronological ASC order).
+/// - start_date/end_date: YYYY-MM-DD format (local timezone)
+pub fn get_records_for_export(start_date: &str, end_date: &str) -> AppResult<Vec<Record>> {
+    get_records_for_export_filtered(start_date, end_date, &RecordFilter::default())
+}
+
+/// Get the records within a date range that match `filter`, for export
+/// (chronological ASC order).
+pub fn get_records_for_export_filtered(
+    start_date: &str,
+    end_date: &str,
+    filter: &RecordFilter,
 ) -> AppResult<Vec<Record>> {
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
-    // Parse start_date (YYYY-MM-DD) to local midnight 00:00:00
-    let start_naive = chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").map_err(|e| {
+    let start_naive = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d").map_err(|e| {
         AppError::validation(format!(
             "Invalid start_date format (expected YYYY-MM-DD): {}",
             e
         ))
     })?;
 
-    // Parse end_date (YYYY-MM-DD) to local midnight of next day (exclusive upper bound)
-    let end_naive = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|e| {
+    let end_naive = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d").map_err(|e| {
         AppError::validation(format!(
             "Invalid end_date format (expected YYYY-MM-DD): {}",
             e
         ))
     })?;
 
-    // Convert to UTC RFC3339
     let start_utc = date_to_utc_rfc3339(start_naive, 0, 0, 0);
     let end_utc = date_to_utc_rfc3339(end_naive, 23, 59, 59);
 
-    let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
-         WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp DESC",
-    )?;
+    let (filter_sql, filter_values) = record_filter_clause(filter, 3);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
+         WHERE timestamp >= ?1 AND timestamp <= ?2{} ORDER BY timestamp ASC",
+        filter_sql
+    ))?;
+    let query_params: Vec<&dyn rusqlite::ToSql> = [&start_utc as &dyn rusqlite::ToSql, &end_utc]
+        .into_iter()
+        .chain(filter_values.iter().map(|v| v as &dyn rusqlite::ToSql))
+        .collect();
 
     let records = stmt
-        .query_map(params![start_utc, end_utc], |row| {
+        .query_map(query_params.as_slice(), |row| {
             Ok(Record {
                 id: row.get(0)?,
                 timestamp: row.get(1)?,
@@ -398,6 +994,8 @@ pub fn get_records_by_date_range_sync(
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()
@@ -406,65 +1004,151 @@ pub fn get_records_by_date_range_sync(
     Ok(records)
 }
 
-/// Get records within a date range for export (chronological ASC order).
-/// - start_date/end_date: YYYY-MM-DD format (local timezone)
-pub fn get_records_for_export(start_date: &str, end_date: &str) -> AppResult<Vec<Record>> {
-    let db = DB_CONNECTION.lock()?;
-    let conn = db
-        .as_ref()
-        .ok_or_else(|| AppError::database("Database not initialized"))?;
-
+/// Copy the records between two local dates (YYYY-MM-DD, inclusive) into a
+/// new standalone SQLite file at `dest_path`, together with the `records`
+/// table schema. Returns the number of exported records.
+///
+/// The destination is attached to the live connection so rows are copied with
+/// a single `INSERT ... SELECT`; the running database is only read.
+pub fn export_records_to_sqlite_sync(
+    dest_path: &std::path::Path,
+    start_date: &str,
+    end_date: &str,
+) -> AppResult<usize> {
+    if dest_path.exists() {
+        return Err(AppError::validation(format!(
+            "Export file already exists: {}",
+            dest_path.display()
+        )));
+    }
     let start_naive = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d").map_err(|e| {
         AppError::validation(format!(
             "Invalid start_date format (expected YYYY-MM-DD): {}",
             e
         ))
     })?;
-
     let end_naive = chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d").map_err(|e| {
         AppError::validation(format!(
             "Invalid end_date format (expected YYYY-MM-DD): {}",
             e
         ))
     })?;
-
     let start_utc = date_to_utc_rfc3339(start_naive, 0, 0, 0);
     let end_utc = date_to_utc_rfc3339(end_naive, 23, 59, 59);
 
-    let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
-         WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp ASC",
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let create_sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'records'",
+        [],
+        |row| row.get(0),
     )?;
 
-    let records = stmt
-        .query_map(params![start_utc, end_utc], |row| {
-            Ok(Record {
-                id: row.get(0)?,
-                timestamp: row.get(1)?,
-                source_type: row.get(2)?,
-                content: row.get(3)?,
-                screenshot_path: row.get(4)?,
-                monitor_info: row.get(5)?,
-                tags: row.get(6)?,
-                user_notes: row.get(7)?,
-                session_id: row.get(8)?,
-                analysis_status: row.get(9)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| AppError::database(format!("Failed to collect records: {}", e)))?;
+    let copy = || -> AppResult<usize> {
+        rusqlite::Connection::open(dest_path)?.execute_batch(&create_sql)?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS export_db",
+            params![dest_path.to_string_lossy()],
+        )?;
+        let copied = conn.execute(
+            "INSERT INTO export_db.records SELECT * FROM main.records
+             WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp ASC",
+            params![start_utc, end_utc],
+        );
+        conn.execute("DETACH DATABASE export_db", [])?;
+        Ok(copied?)
+    };
 
-    Ok(records)
+    copy().inspect_err(|_| {
+        // Do not leave a half-written export behind
+        let _ = std::fs::remove_file(dest_path);
+    })
 }
 
-/// Delete a record by ID
+/// Delete a record by ID, together with its screenshot and thumbnail.
+///
+/// The full-text index entry goes with the row (`records_ad` trigger). The
+/// screenshot is kept if another record still points at it; removing the
+/// files is best-effort and never fails the deletion.
 pub fn delete_record_sync(id: i64) -> AppResult<()> {
+    let screenshot_path = {
+        let db = DB_CONNECTION.lock()?;
+        let conn = db
+            .as_ref()
+            .ok_or_else(|| AppError::database("Database not initialized"))?;
+        delete_record_row(conn, id)?
+    };
+
+    if let Some(path) = screenshot_path {
+        remove_screenshot_files(&path);
+    }
+
+    tracing::info!("Deleted record with id {}", id);
+    Ok(())
+}
+
+/// Delete a record row. Returns its screenshot path when no other record
+/// uses the file any more, so the caller can remove it after releasing the DB lock.
+fn delete_record_row(conn: &rusqlite::Connection, id: i64) -> AppResult<Option<String>> {
+    let screenshot_path: Option<Option<String>> = conn
+        .query_row(
+            "SELECT screenshot_path FROM records WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(screenshot_path) = screenshot_path else {
+        return Err(AppError::validation(format!(
+            "Record with id {} not found",
+            id
+        )));
+    };
+
+    conn.execute("DELETE FROM records WHERE id = ?1", params![id])?;
+
+    match screenshot_path {
+        Some(path) => {
+            let still_used: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM records WHERE screenshot_path = ?1",
+                params![path],
+                |row| row.get(0),
+            )?;
+            Ok((still_used == 0).then_some(path))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Remove a screenshot no longer referenced by any record, with its thumbnail.
+fn remove_screenshot_files(path: &str) {
+    let path = std::path::Path::new(path);
+    remove_file_best_effort(path);
+    remove_file_best_effort(&crate::manual_entry::thumbnail_path(path));
+}
+
+/// Remove `path`, logging instead of failing; a missing file is fine.
+fn remove_file_best_effort(path: &std::path::Path) {
+    match std::fs::remove_file(path) {
+        Ok(()) => tracing::debug!("Removed {}", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to remove {}: {}", path.display(), e),
+    }
+}
+
+/// Mark a draft record as confirmed so it is used in daily reports
+pub fn confirm_record_sync(id: i64) -> AppResult<()> {
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
-    let rows_affected = conn.execute("DELETE FROM records WHERE id = ?1", params![id])?;
+    let rows_affected = conn.execute(
+        "UPDATE records SET status = ?1 WHERE id = ?2",
+        params![RECORD_STATUS_CONFIRMED, id],
+    )?;
 
     if rows_affected == 0 {
         return Err(AppError::validation(format!(
@@ -473,10 +1157,112 @@ pub fn delete_record_sync(id: i64) -> AppResult<()> {
         )));
     }
 
-    tracing::info!("Deleted record with id {}", id);
+    tracing::info!("Confirmed record with id {}", id);
     Ok(())
 }
 
+/// Confirm every draft record between `start_date` and `end_date` (inclusive,
+/// local dates). Returns the number of records confirmed.
+pub fn confirm_records_by_date_range_sync(start_date: &str, end_date: &str) -> AppResult<usize> {
+    let (start_utc, end_utc) = date_range_bounds(start_date, end_date)?;
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let confirmed = conn.execute(
+        "UPDATE records SET status = ?1 WHERE status = ?2 AND timestamp >= ?3 AND timestamp < ?4",
+        params![
+            RECORD_STATUS_CONFIRMED,
+            RECORD_STATUS_DRAFT,
+            start_utc,
+            end_utc
+        ],
+    )?;
+    tracing::info!(
+        "Confirmed {} draft records from {} to {}",
+        confirmed,
+        start_date,
+        end_date
+    );
+    Ok(confirmed)
+}
+
+/// Confirm every draft record of a session. Returns the number of records confirmed.
+pub fn confirm_session_records_sync(session_id: i64) -> AppResult<usize> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let confirmed = conn.execute(
+        "UPDATE records SET status = ?1 WHERE status = ?2 AND session_id = ?3",
+        params![RECORD_STATUS_CONFIRMED, RECORD_STATUS_DRAFT, session_id],
+    )?;
+    tracing::info!(
+        "Confirmed {} draft records of session {}",
+        confirmed,
+        session_id
+    );
+    Ok(confirmed)
+}
+
+/// Retention of unconfirmed drafts in days, or `None` to keep them forever.
+fn draft_retention_days(setting: Option<i32>) -> Option<i64> {
+    match setting {
+        None => Some(DEFAULT_DRAFT_RETENTION_DAYS),
+        Some(days) if days <= 0 => None,
+        Some(days) => Some(i64::from(days)),
+    }
+}
+
+/// Delete draft records older than `draft_retention_days` (default
+/// [`DEFAULT_DRAFT_RETENTION_DAYS`]) that were never confirmed, together with
+/// their screenshots and thumbnails. Returns the number of deleted records.
+/// Nothing is deleted when `draft_retention_days` is 0 or less.
+pub fn purge_stale_drafts_sync() -> AppResult<usize> {
+    // Settings are read before taking the DB lock (a cache miss locks it too)
+    let Some(retention_days) =
+        draft_retention_days(super::get_settings_sync()?.draft_retention_days)
+    else {
+        return Ok(0);
+    };
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(retention_days)).to_rfc3339();
+
+    let (deleted, unused_screenshots) = {
+        let db = DB_CONNECTION.lock()?;
+        let conn = db
+            .as_ref()
+            .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+        let stale_ids: Vec<i64> = conn
+            .prepare("SELECT id FROM records WHERE status = ?1 AND timestamp < ?2")?
+            .query_map(params![RECORD_STATUS_DRAFT, cutoff], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        let tx = conn.unchecked_transaction()?;
+        let mut unused_screenshots = Vec::new();
+        for id in &stale_ids {
+            unused_screenshots.extend(delete_record_row(&tx, *id)?);
+        }
+        tx.commit()?;
+
+        if !stale_ids.is_empty() {
+            tracing::info!(
+                "Deleted {} unconfirmed draft records older than {} days",
+                stale_ids.len(),
+                retention_days
+            );
+        }
+        (stale_ids.len(), unused_screenshots)
+    };
+
+    for path in &unused_screenshots {
+        remove_screenshot_files(path);
+    }
+    Ok(deleted)
+}
+
 /// Get a single record by ID
 /// Used by reanalyze_record to fetch record details
 pub fn get_record_by_id_sync(id: i64) -> AppResult<Record> {
@@ -487,7 +1273,7 @@ pub fn get_record_by_id_sync(id: i64) -> AppResult<Record> {
 
     let record = conn
         .query_row(
-            "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status
+            "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status
              FROM records WHERE id = ?1",
             params![id],
             |row| {
@@ -502,6 +1288,8 @@ pub fn get_record_by_id_sync(id: i64) -> AppResult<Record> {
                     user_notes: row.get(7)?,
                     session_id: row.get(8)?,
                     analysis_status: row.get(9)?,
+                    metadata: row.get(10)?,
+                    status: row.get(11)?,
                 })
             },
         )
@@ -513,14 +1301,28 @@ pub fn get_record_by_id_sync(id: i64) -> AppResult<Record> {
 /// Update the content of a record by ID
 /// Used by offline queue retry to update screenshot analysis results
 pub fn update_record_content_sync(id: i64, content: &str) -> AppResult<()> {
+    // Analysis results carry the context keywords used for project matching
+    // and the active software
+    let project = super::projects::project_for_content(content);
+    let software_normalized = super::software::software_for_content(content);
+
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
     let rows_affected = conn.execute(
-        "UPDATE records SET content = ?1 WHERE id = ?2",
-        params![content, id],
+        "UPDATE records SET content = ?1, content_hash = ?4,
+             project = CASE WHEN source_type = 'auto' THEN ?3 ELSE project END,
+             software_normalized = CASE WHEN source_type = 'auto' THEN ?5 ELSE software_normalized END
+         WHERE id = ?2",
+        params![
+            content,
+            id,
+            project,
+            super::content_hash::content_hash(content),
+            software_normalized
+        ],
     )?;
 
     if rows_affected == 0 {
@@ -534,6 +1336,64 @@ pub fn update_record_content_sync(id: i64, content: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Mark a record whose screenshot analysis failed.
+/// The record and its screenshot are kept so the analysis can be retried later.
+/// `screenshot_path` repoints the record when the screenshot was moved (e.g. to `failed/`).
+pub fn mark_record_analysis_failed_sync(
+    id: i64,
+    content: &str,
+    screenshot_path: Option<&str>,
+) -> AppResult<()> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let rows_affected = conn.execute(
+        "UPDATE records SET content = ?1, content_hash = ?4, analysis_status = 'auto_error',
+             screenshot_path = COALESCE(?3, screenshot_path)
+         WHERE id = ?2",
+        params![
+            content,
+            id,
+            screenshot_path,
+            super::content_hash::content_hash(content)
+        ],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(AppError::validation(format!(
+            "Record with id {} not found",
+            id
+        )));
+    }
+
+    tracing::warn!("Marked record {} as auto_error", id);
+    Ok(())
+}
+
+/// Repoint (or clear) a record's screenshot path once a background save has finished.
+pub fn update_record_screenshot_path_sync(id: i64, screenshot_path: Option<&str>) -> AppResult<()> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let rows_affected = conn.execute(
+        "UPDATE records SET screenshot_path = ?1 WHERE id = ?2",
+        params![screenshot_path, id],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(AppError::validation(format!(
+            "Record with id {} not found",
+            id
+        )));
+    }
+
+    Ok(())
+}
+
 /// Update user notes for a specific record
 /// FEAT-005: User can add manual notes to screenshot records (#66)
 pub fn update_record_user_notes_sync(id: i64, user_notes: Option<&str>) -> AppResult<()> {
@@ -542,6 +1402,7 @@ pub fn update_record_user_notes_sync(id: i64, user_notes: Option<&str>) -> AppRe
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
+    let user_notes = user_notes.map(sanitize_content);
     let rows_affected = conn.execute(
         "UPDATE records SET user_notes = ?1, analysis_status = 'user_edited' WHERE id = ?2",
         params![user_notes, id],
@@ -558,6 +1419,145 @@ pub fn update_record_user_notes_sync(id: i64, user_notes: Option<&str>) -> AppRe
     Ok(())
 }
 
+/// Update a record's content after the user edited it.
+/// When the `active_software` of an auto record changes, the correction is
+/// remembered so later screen analyses can learn from it.
+pub fn update_record_sync(id: i64, content: &str) -> AppResult<()> {
+    let content = sanitize_content(content);
+    let software_normalized = super::software::software_for_content(&content);
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let (source_type, old_content): (String, String) = conn
+        .query_row(
+            "SELECT source_type, content FROM records WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| AppError::validation(format!("Record with id {} not found", id)))?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE records SET content = ?1, content_hash = ?3, analysis_status = 'user_edited',
+             software_normalized = CASE WHEN source_type = 'auto' THEN ?4 ELSE software_normalized END
+         WHERE id = ?2",
+        params![
+            content,
+            id,
+            super::content_hash::content_hash(&content),
+            software_normalized
+        ],
+    )?;
+
+    if source_type == "auto" {
+        let original = super::corrections::active_software_from_content(&old_content);
+        let corrected = super::corrections::active_software_from_content(&content);
+        if let (Some(original), Some(corrected)) = (original, corrected) {
+            super::corrections::insert_software_correction(&tx, &original, &corrected)?;
+        }
+    }
+    tx.commit()?;
+
+    tracing::info!("Updated record {}", id);
+    Ok(())
+}
+
+/// Add `tokens` to the API tokens spent on a record (`records.tokens_used`).
+/// Reanalyses add up, so the column holds the record's total cost.
+pub fn add_record_tokens_used_sync(id: i64, tokens: i64) -> AppResult<()> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let rows_affected = conn.execute(
+        "UPDATE records SET tokens_used = COALESCE(tokens_used, 0) + ?1 WHERE id = ?2",
+        params![tokens, id],
+    )?;
+    if rows_affected == 0 {
+        return Err(AppError::validation(format!(
+            "Record with id {} not found",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// API tokens spent on each of `record_ids`; records without usage are left out.
+pub fn get_tokens_used_for_records(
+    record_ids: Vec<i64>,
+) -> AppResult<std::collections::HashMap<i64, i64>> {
+    if record_ids.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let placeholders = vec!["?"; record_ids.len()].join(",");
+    let sql = format!(
+        "SELECT id, tokens_used FROM records WHERE tokens_used IS NOT NULL AND id IN ({})",
+        placeholders
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let usage = stmt
+        .query_map(rusqlite::params_from_iter(record_ids.iter()), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(usage)
+}
+
+/// Replace the text of a manual record, keeping its timestamp.
+/// Auto records are refused so their analysis JSON is not overwritten by accident.
+pub fn update_manual_record_content_sync(id: i64, content: &str) -> AppResult<()> {
+    if content.trim().is_empty() {
+        return Err(AppError::validation("Content cannot be empty"));
+    }
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let source_type: Option<String> = conn
+        .query_row(
+            "SELECT source_type FROM records WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match source_type.as_deref() {
+        None => {
+            return Err(AppError::validation(format!(
+                "Record with id {} not found",
+                id
+            )))
+        }
+        Some("manual") => {}
+        Some(other) => {
+            return Err(AppError::validation(format!(
+                "Record {} is an {} record; only manual records can be edited",
+                id, other
+            )))
+        }
+    }
+
+    let content = sanitize_content(content);
+    conn.execute(
+        "UPDATE records SET content = ?1, content_hash = ?3 WHERE id = ?2",
+        params![content, id, super::content_hash::content_hash(&content)],
+    )?;
+
+    tracing::info!("Updated content of manual record {}", id);
+    Ok(())
+}
+
 /// Get history records with filtering and pagination
 /// - start_date/end_date: YYYY-MM-DD format (local timezone)
 /// - source_type: None for all, Some("auto") or Some("manual") for filtering
@@ -570,12 +1570,160 @@ pub fn get_history_records_sync(
     page: i64,
     page_size: i64,
 ) -> AppResult<Vec<Record>> {
-    get_history_records_with_cursor_sync(start_date, end_date, source_type, page, page_size, None)
+    get_history_records_sorted_sync(
+        start_date,
+        end_date,
+        source_type,
+        page,
+        page_size,
+        None,
+        None,
+    )
+}
+
+/// Largest page `get_records_paginated_sync` returns, whatever the caller asks for.
+pub const MAX_RECORDS_PAGE_SIZE: usize = 500;
+
+fn validate_source_type(source_type: Option<&str>) -> AppResult<()> {
+    match source_type {
+        None | Some("auto") | Some("manual") => Ok(()),
+        Some(other) => Err(AppError::validation(format!(
+            "Invalid source_type '{}'. Must be 'auto', 'manual', or null for all",
+            other
+        ))),
+    }
+}
+
+/// One page of all records, newest first. `limit` is capped at
+/// `MAX_RECORDS_PAGE_SIZE`; `source_type` is `"auto"`, `"manual"` or `None` for all.
+pub fn get_records_paginated_sync(
+    offset: usize,
+    limit: usize,
+    source_type: Option<&str>,
+) -> AppResult<Vec<Record>> {
+    validate_source_type(source_type)?;
+    let limit = limit.min(MAX_RECORDS_PAGE_SIZE);
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
+         WHERE ?1 IS NULL OR source_type = ?1
+         ORDER BY timestamp DESC, id DESC LIMIT ?2 OFFSET ?3",
+    )?;
+    let records = stmt
+        .query_map(params![source_type, limit as i64, offset as i64], |row| {
+            Ok(Record {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                source_type: row.get(2)?,
+                content: row.get(3)?,
+                screenshot_path: row.get(4)?,
+                monitor_info: row.get(5)?,
+                tags: row.get(6)?,
+                user_notes: row.get(7)?,
+                session_id: row.get(8)?,
+                analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(records)
+}
+
+/// Number of records, for the page controls of `get_records_paginated_sync`.
+pub fn count_records_sync(source_type: Option<&str>) -> AppResult<i64> {
+    validate_source_type(source_type)?;
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM records WHERE ?1 IS NULL OR source_type = ?1",
+        params![source_type],
+        |row| row.get(0),
+    )?)
+}
+
+/// Sort keys accepted by `record_order_by_clause`, with the SQL expression each one sorts on.
+/// Importance lives in the analysis JSON; records without a score sort as
+/// `DEFAULT_RECORD_IMPORTANCE` (50).
+const RECORD_SORT_FIELDS: &[(&str, &str)] = &[
+    ("timestamp", "timestamp"),
+    (
+        "importance",
+        "CASE WHEN json_valid(content) THEN COALESCE(CAST(json_extract(content, '$.importance') AS REAL), 50) ELSE 50 END",
+    ),
+    ("source", "source_type"),
+];
+
+/// `ORDER BY` clause for history queries from user-supplied sort parameters.
+/// Only whitelisted fields and directions are accepted, so the values never reach SQL as is.
+/// Defaults to the newest records first (`id DESC`); ties are broken by id.
+pub fn record_order_by_clause(sort_by: Option<&str>, order: Option<&str>) -> AppResult<String> {
+    let direction = match order.map(|o| o.trim().to_lowercase()).as_deref() {
+        None | Some("desc") => "DESC",
+        Some("asc") => "ASC",
+        Some(other) => {
+            return Err(AppError::validation(format!(
+                "Invalid order '{}'. Must be 'asc' or 'desc'",
+                other
+            )))
+        }
+    };
+    let Some(sort_by) = sort_by else {
+        return Ok(format!("ORDER BY id {}", direction));
+    };
+    let expression = RECORD_SORT_FIELDS
+        .iter()
+        .find(|(name, _)| *name == sort_by.trim())
+        .map(|(_, expression)| *expression)
+        .ok_or_else(|| {
+            AppError::validation(format!(
+                "Invalid sort_by '{}'. Must be 'timestamp', 'importance' or 'source'",
+                sort_by
+            ))
+        })?;
+    Ok(format!(
+        "ORDER BY {} {}, id {}",
+        expression, direction, direction
+    ))
+}
+
+/// Offset-paginated history records sorted by `sort_by` (timestamp/importance/source)
+/// in `order` (asc/desc); see `record_order_by_clause`.
+pub fn get_history_records_sorted_sync(
+    start_date: String,
+    end_date: String,
+    source_type: Option<String>,
+    page: i64,
+    page_size: i64,
+    sort_by: Option<&str>,
+    order: Option<&str>,
+) -> AppResult<Vec<Record>> {
+    let order_by = record_order_by_clause(sort_by, order)?;
+    get_history_records_with_cursor_sync(
+        start_date,
+        end_date,
+        source_type,
+        page,
+        page_size,
+        None,
+        &order_by,
+    )
 }
 
 /// PERF-004: Cursor-based pagination for efficient history record retrieval
 /// - last_id: if provided, fetches records with id < last_id (efficient cursor pagination)
 ///   if not provided, uses traditional OFFSET pagination (backward compatible)
+/// - order_by: ORDER BY clause of the OFFSET queries; cursor queries always use `id DESC`
 pub fn get_history_records_with_cursor_sync(
     start_date: String,
     end_date: String,
@@ -583,6 +1731,7 @@ pub fn get_history_records_with_cursor_sync(
     page: i64,
     page_size: i64,
     last_id: Option<i64>,
+    order_by: &str,
 ) -> AppResult<Vec<Record>> {
     let db = DB_CONNECTION.lock()?;
     let conn = db
@@ -623,7 +1772,7 @@ pub fn get_history_records_with_cursor_sync(
                 )));
             }
             (
-                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND source_type = ?3 AND id < ?4
                      ORDER BY id DESC LIMIT ?5"
                         .to_string(),
@@ -646,10 +1795,10 @@ pub fn get_history_records_with_cursor_sync(
             }
             let offset = page * page_size;
             (
-                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND source_type = ?3
-                     ORDER BY id DESC LIMIT ?4 OFFSET ?5"
-                        .to_string(),
+                     {} LIMIT ?4 OFFSET ?5"
+                        .replace("{}", order_by),
                     vec![
                         Box::new(start_utc.clone()),
                         Box::new(end_utc.clone()),
@@ -662,7 +1811,7 @@ pub fn get_history_records_with_cursor_sync(
         (None, Some(last_id_val)) => {
             // Cursor-based pagination without source_type filter (efficient)
             (
-                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND id < ?3
                      ORDER BY id DESC LIMIT ?4"
                         .to_string(),
@@ -678,10 +1827,10 @@ pub fn get_history_records_with_cursor_sync(
             // Offset-based pagination without source_type filter (backward compatible)
             let offset = page * page_size;
             (
-                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                    "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2
-                     ORDER BY id DESC LIMIT ?3 OFFSET ?4"
-                        .to_string(),
+                     {} LIMIT ?3 OFFSET ?4"
+                        .replace("{}", order_by),
                     vec![
                         Box::new(start_utc.clone()),
                         Box::new(end_utc.clone()),
@@ -709,6 +1858,8 @@ pub fn get_history_records_with_cursor_sync(
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()
@@ -769,6 +1920,8 @@ pub fn get_history_records_cursor_sync(
             user_notes: row.get(7)?,
             session_id: row.get(8)?,
             analysis_status: row.get(9)?,
+            metadata: row.get(10)?,
+            status: row.get(11)?,
         })
     }
 
@@ -776,7 +1929,7 @@ pub fn get_history_records_cursor_sync(
     match (source_type, last_id) {
         (Some(st), Some(lid)) if st == "auto" || st == "manual" => {
             let mut stmt = conn.prepare(
-                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND source_type = ?3 AND id < ?4
                      ORDER BY id DESC LIMIT ?5",
             )?;
@@ -788,7 +1941,7 @@ pub fn get_history_records_cursor_sync(
         }
         (Some(st), None) if st == "auto" || st == "manual" => {
             let mut stmt = conn.prepare(
-                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND source_type = ?3
                      ORDER BY id DESC LIMIT ?4",
             )?;
@@ -800,7 +1953,7 @@ pub fn get_history_records_cursor_sync(
         }
         (None, Some(lid)) => {
             let mut stmt = conn.prepare(
-                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2 AND id < ?3
                      ORDER BY id DESC LIMIT ?4",
             )?;
@@ -812,7 +1965,7 @@ pub fn get_history_records_cursor_sync(
         }
         (None, None) => {
             let mut stmt = conn.prepare(
-                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status FROM records
+                "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status FROM records
                      WHERE timestamp >= ?1 AND timestamp <= ?2
                      ORDER BY id DESC LIMIT ?3",
             )?;
@@ -822,11 +1975,98 @@ pub fn get_history_records_cursor_sync(
                 .map_err(|e| AppError::database(format!("Failed to collect records: {}", e)))?;
             Ok(records)
         }
-        (Some(st), _) => Err(AppError::validation(format!(
-            "Invalid source_type '{}'. Must be 'auto', 'manual', or null for all",
-            st
-        ))),
+        (Some(st), _) => Err(AppError::validation(format!(
+            "Invalid source_type '{}'. Must be 'auto', 'manual', or null for all",
+            st
+        ))),
+    }
+}
+
+/// Characters of context kept on each side of a match in search snippets.
+pub const SNIPPET_CONTEXT_CHARS: usize = 30;
+
+/// Build a search snippet: every case-insensitive match of `query` wrapped in
+/// `<mark>` tags, with `context_chars` characters kept on each side.
+/// Overlapping windows are merged and omitted text is replaced by `…`.
+/// Without a match, the beginning of the content is returned.
+pub fn extract_snippet(content: &str, query: &str, context_chars: usize) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let needle: Vec<char> = query.trim().chars().collect();
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    let mut matches: Vec<(usize, usize)> = Vec::new();
+    if !needle.is_empty() {
+        let mut i = 0;
+        while i + needle.len() <= chars.len() {
+            if chars[i..i + needle.len()]
+                .iter()
+                .zip(&needle)
+                .all(|(a, b)| same(*a, *b))
+            {
+                matches.push((i, i + needle.len()));
+                i += needle.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        let head: String = chars.iter().take(context_chars * 2).collect();
+        return if chars.len() > context_chars * 2 {
+            format!("{}…", head)
+        } else {
+            head
+        };
+    }
+
+    // Context windows around matches, merged when they touch
+    let mut windows: Vec<(usize, usize)> = Vec::new();
+    for &(start, end) in &matches {
+        let from = start.saturating_sub(context_chars);
+        let to = (end + context_chars).min(chars.len());
+        match windows.last_mut() {
+            Some(last) if from <= last.1 => last.1 = to,
+            _ => windows.push((from, to)),
+        }
+    }
+
+    let mut snippet = String::new();
+    let mut next_match = matches.iter().peekable();
+    for (n, &(from, to)) in windows.iter().enumerate() {
+        if from > 0 || n > 0 {
+            snippet.push('…');
+        }
+        let mut pos = from;
+        while let Some(&&(start, end)) = next_match.peek() {
+            if start >= to {
+                break;
+            }
+            snippet.extend(&chars[pos..start]);
+            snippet.push_str("<mark>");
+            snippet.extend(&chars[start..end]);
+            snippet.push_str("</mark>");
+            pos = end;
+            next_match.next();
+        }
+        snippet.extend(&chars[pos..to]);
+    }
+    if windows.last().is_some_and(|w| w.1 < chars.len()) {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Escape `%`, `_` and `\` so they match literally in a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
+    escaped
 }
 
 /// Full-text search on records content
@@ -836,6 +2076,8 @@ pub fn get_history_records_cursor_sync(
 ///
 /// Note: For queries containing CJK characters, uses LIKE search as fallback
 /// since FTS5's unicode61 tokenizer doesn't handle Chinese word segmentation well.
+/// The same LIKE search is used when the full-text index is unavailable
+/// (SQLite built without FTS5, see `ensure_fts_index`).
 pub fn search_records_sync(
     query: &str,
     order_by: &str,
@@ -861,25 +2103,26 @@ pub fn search_records_sync(
             || (0xF900..=0xFAFF).contains(&cp)
     });
 
-    if has_cjk {
-        // Use LIKE search for CJK queries
+    let use_like = has_cjk || !super::schema::fts_index_exists(conn);
+
+    if use_like {
+        // Use LIKE search for CJK queries and when there is no full-text index
         // Note: Both time and rank order use the same SQL since LIKE doesn't have relevance score
         let sql = "SELECT
-                id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status
+                id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status
             FROM records
-            WHERE content LIKE ?1
+            WHERE content LIKE ?1 ESCAPE '\\'
             ORDER BY timestamp DESC
             LIMIT ?2";
 
         let mut stmt = conn.prepare(sql)?;
 
-        let like_pattern = format!("%{}%", query);
+        let like_pattern = format!("%{}%", escape_like(query));
 
         let results = stmt
             .query_map(params![like_pattern, limit], |row| {
                 let content: String = row.get(3)?;
-                // Manually highlight the keyword
-                let snippet = content.replace(query, &format!("<mark>{}</mark>", query));
+                let snippet = extract_snippet(&content, query, SNIPPET_CONTEXT_CHARS);
                 Ok(SearchResult {
                     record: Record {
                         id: row.get(0)?,
@@ -892,6 +2135,8 @@ pub fn search_records_sync(
                         user_notes: row.get(7)?,
                         session_id: row.get(8)?,
                         analysis_status: row.get(9)?,
+                        metadata: row.get(10)?,
+                        status: row.get(11)?,
                     },
                     snippet,
                     rank: 0.0, // LIKE search doesn't have relevance score
@@ -907,8 +2152,7 @@ pub fn search_records_sync(
 
         let sql = if order_by == "time" {
             "SELECT
-                r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status,
-                highlight(records_fts, 0, '<mark>', '</mark>') as snippet,
+                r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status, r.metadata, r.status,
                 bm25(records_fts) as rank
             FROM records_fts
             JOIN records r ON r.id = records_fts.rowid
@@ -917,8 +2161,7 @@ pub fn search_records_sync(
             LIMIT ?2"
         } else {
             "SELECT
-                r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status,
-                highlight(records_fts, 0, '<mark>', '</mark>') as snippet,
+                r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status, r.metadata, r.status,
                 bm25(records_fts) as rank
             FROM records_fts
             JOIN records r ON r.id = records_fts.rowid
@@ -934,21 +2177,25 @@ pub fn search_records_sync(
 
         let results = stmt
             .query_map(params![fts_query, limit], |row| {
+                let content: String = row.get(3)?;
+                let snippet = extract_snippet(&content, query, SNIPPET_CONTEXT_CHARS);
                 Ok(SearchResult {
                     record: Record {
                         id: row.get(0)?,
                         timestamp: row.get(1)?,
                         source_type: row.get(2)?,
-                        content: row.get(3)?,
+                        content,
                         screenshot_path: row.get(4)?,
                         monitor_info: row.get(5)?,
                         tags: row.get(6)?,
                         user_notes: row.get(7)?,
                         session_id: row.get(8)?,
                         analysis_status: row.get(9)?,
+                        metadata: row.get(10)?,
+                        status: row.get(11)?,
                     },
-                    snippet: row.get(10)?,
-                    rank: row.get(11)?,
+                    snippet,
+                    rank: row.get(12)?,
                 })
             })
             .map_err(|e| AppError::database(format!("Failed to search records: {}", e)))?
@@ -961,11 +2208,32 @@ pub fn search_records_sync(
 
 // ── Async Tauri command wrappers ──
 
+/// Today's records, optionally only those with importance >= `min_importance`.
+#[command]
+pub async fn get_today_records(min_importance: Option<u8>) -> AppResult<Vec<Record>> {
+    Ok(filter_by_min_importance(
+        get_today_records_sync()?,
+        min_importance,
+    ))
+}
+
+/// One page of all records, newest first (at most `MAX_RECORDS_PAGE_SIZE`)
+#[command]
+pub async fn get_records_paginated(
+    offset: usize,
+    limit: usize,
+    source_type: Option<String>,
+) -> AppResult<Vec<Record>> {
+    get_records_paginated_sync(offset, limit, source_type.as_deref())
+}
+
+/// Total number of records, optionally of one source type
 #[command]
-pub async fn get_today_records() -> AppResult<Vec<Record>> {
-    get_today_records_sync()
+pub async fn count_records(source_type: Option<String>) -> AppResult<i64> {
+    count_records_sync(source_type.as_deref())
 }
 
+/// Records in `[start_date, end_date)`; see `get_records_by_date_range_sync`
 #[command]
 pub async fn get_records_by_date_range(
     start_date: String,
@@ -988,10 +2256,20 @@ pub async fn get_history_records(
     source_type: Option<String>,
     page: Option<i64>,
     page_size: Option<i64>,
+    sort_by: Option<String>,
+    order: Option<String>,
 ) -> AppResult<Vec<Record>> {
     let page = page.unwrap_or(0);
     let page_size = page_size.unwrap_or(50);
-    get_history_records_sync(start_date, end_date, source_type, page, page_size)
+    get_history_records_sorted_sync(
+        start_date,
+        end_date,
+        source_type,
+        page,
+        page_size,
+        sort_by.as_deref(),
+        order.as_deref(),
+    )
 }
 
 /// PERF-004: Get history records with cursor-based pagination
@@ -1019,6 +2297,31 @@ pub async fn search_records(
     search_records_sync(&query, &order_by, limit)
 }
 
+/// Confirm a draft record so it is used in daily reports
+#[command]
+pub async fn confirm_record(id: i64) -> AppResult<()> {
+    confirm_record_sync(id)
+}
+
+/// Confirm all draft records of a date range, or of a session when
+/// `session_id` is given. Returns the number of records confirmed.
+#[command]
+pub async fn confirm_records(
+    start_date: Option<String>,
+    end_date: Option<String>,
+    session_id: Option<i64>,
+) -> AppResult<usize> {
+    match (session_id, start_date, end_date) {
+        (Some(session_id), _, _) => confirm_session_records_sync(session_id),
+        (None, Some(start_date), Some(end_date)) => {
+            confirm_records_by_date_range_sync(&start_date, &end_date)
+        }
+        _ => Err(AppError::validation(
+            "Either session_id or both start_date and end_date are required",
+        )),
+    }
+}
+
 /// Update user notes for a record
 /// FEAT-005: User can add manual notes to screenshot records (#66)
 #[command]
@@ -1026,12 +2329,42 @@ pub async fn update_record_user_notes(id: i64, user_notes: Option<String>) -> Ap
     update_record_user_notes_sync(id, user_notes.as_deref())
 }
 
+/// Update a record's content (user correction of an analysis result)
+#[command]
+pub async fn update_record(id: i64, content: String) -> AppResult<()> {
+    update_record_sync(id, &content)
+}
+
+/// API tokens spent on each of the given records
+#[command]
+pub async fn get_records_tokens_used(
+    record_ids: Vec<i64>,
+) -> AppResult<std::collections::HashMap<i64, i64>> {
+    get_tokens_used_for_records(record_ids)
+}
+
+/// Fix the text of a manual record (quick note)
+#[command]
+pub async fn update_record_content(id: i64, content: String) -> AppResult<()> {
+    update_manual_record_content_sync(id, &content)
+}
+
 /// EXP-005: Get today's statistics for the summary widget
 #[command]
 pub async fn get_today_stats() -> AppResult<TodayStats> {
     get_today_stats_sync()
 }
 
+#[command]
+pub async fn get_heatmap(year: i32) -> AppResult<Vec<HeatmapDay>> {
+    get_heatmap_sync(year)
+}
+
+#[command]
+pub async fn get_hourly_distribution(date: String) -> AppResult<Vec<HourlyBucket>> {
+    get_hourly_distribution_sync(&date)
+}
+
 /// SESSION-002: Get all pending-analysis records for a session
 ///
 /// Returns records that have `analysis_status = 'pending'` and belong to the given session.
@@ -1054,25 +2387,43 @@ pub fn get_records_by_session_id(session_id: i64) -> AppResult<Vec<SessionScreen
                 record_id: row.get(0)?,
                 timestamp: row.get(1)?,
                 screenshot_path: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                thumbnail_path: None,
             })
         })?
         .filter_map(|r| r.ok())
         .filter(|s| !s.screenshot_path.is_empty())
+        .map(|s| SessionScreenshot {
+            thumbnail_path: crate::manual_entry::existing_thumbnail_path(&s.screenshot_path),
+            ..s
+        })
         .collect::<Vec<_>>();
 
     Ok(screenshots)
 }
 
-/// SESSION-002: Update record content and analysis status after AI analysis
+/// SESSION-002: Update record content and analysis status after AI analysis.
+/// Project and software are derived from the result as in `update_record_content_sync`.
 pub fn update_record_analysis(record_id: i64, content: &str) -> AppResult<()> {
+    let project = super::projects::project_for_content(content);
+    let software_normalized = super::software::software_for_content(content);
+
     let db = DB_CONNECTION.lock()?;
     let conn = db
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
     conn.execute(
-        "UPDATE records SET content = ?1, analysis_status = 'analyzed' WHERE id = ?2",
-        params![content, record_id],
+        "UPDATE records SET content = ?1, content_hash = ?3, analysis_status = 'analyzed',
+             project = CASE WHEN source_type = 'auto' THEN ?5 ELSE project END,
+             software_normalized = CASE WHEN source_type = 'auto' THEN ?4 ELSE software_normalized END
+         WHERE id = ?2",
+        params![
+            content,
+            record_id,
+            super::content_hash::content_hash(content),
+            software_normalized,
+            project
+        ],
     )?;
 
     Ok(())
@@ -1204,6 +2555,62 @@ mod tests {
         );
     }
 
+    // ── day_start_hour ──
+
+    #[test]
+    fn logical_day_bounds_with_day_start_hour_4() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let yesterday = date - chrono::Duration::days(1);
+
+        // 02:00 still belongs to the previous day
+        let early = date.and_hms_opt(2, 0, 0).unwrap();
+        assert_eq!(logical_date(early, 4), yesterday);
+        assert_eq!(
+            logical_day_bounds(early, 4),
+            (
+                local_to_utc_rfc3339(yesterday.and_hms_opt(4, 0, 0).unwrap()),
+                local_to_utc_rfc3339(date.and_hms_opt(4, 0, 0).unwrap()),
+            )
+        );
+
+        // 04:00 starts the new day
+        let boundary = date.and_hms_opt(4, 0, 0).unwrap();
+        assert_eq!(logical_date(boundary, 4), date);
+        assert_eq!(
+            logical_day_bounds(boundary, 4).0,
+            local_to_utc_rfc3339(date.and_hms_opt(4, 0, 0).unwrap())
+        );
+
+        // Default of 0 keeps the calendar day
+        assert_eq!(logical_date(early, 0), date);
+        assert_eq!(
+            logical_day_bounds(early, 0).0,
+            local_to_utc_rfc3339(date.and_hms_opt(0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn today_records_follow_configured_day_start_hour() {
+        setup_test_db();
+        let mut settings = (*crate::memory_storage::get_settings_sync().unwrap()).clone();
+        settings.day_start_hour = Some(4);
+        crate::memory_storage::save_settings_sync(&settings).unwrap();
+
+        let today = logical_today();
+        let (inside, outside) = (
+            today.and_hms_opt(5, 0, 0).unwrap(),
+            today.and_hms_opt(3, 0, 0).unwrap(),
+        );
+        insert_record_with_ts(&local_to_utc_rfc3339(inside), "after day start");
+        insert_record_with_ts(&local_to_utc_rfc3339(outside), "before day start");
+
+        let records = get_today_records_sync().unwrap();
+        assert!(records.iter().any(|r| r.content == "after day start"));
+        assert!(!records.iter().any(|r| r.content == "before day start"));
+        assert_eq!(get_today_record_count_sync().unwrap(), 1);
+    }
+
     // ── End-to-end: add_record → get_today_records_sync ──
 
     #[test]
@@ -1456,6 +2863,97 @@ mod tests {
             .contains("Invalid start_date format"));
     }
 
+    #[test]
+    #[serial]
+    fn get_records_by_date_range_accepts_rfc3339_bounds_with_exclusive_end() {
+        setup_test_db();
+        insert_record_with_ts("2026-03-16T09:00:00+00:00", "nine");
+        insert_record_with_ts("2026-03-16T10:00:00+00:00", "ten");
+        insert_record_with_ts("2026-03-16T11:00:00+00:00", "eleven");
+
+        let records = get_records_by_date_range_sync(
+            "2026-03-16T17:00:00+08:00".to_string(),
+            "2026-03-16T11:00:00Z".to_string(),
+        )
+        .unwrap();
+
+        let contents: Vec<&str> = records.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, vec!["ten", "nine"]);
+    }
+
+    #[test]
+    #[serial]
+    fn get_records_by_date_range_empty_and_reversed_ranges() {
+        setup_test_db();
+        insert_record_with_ts("2026-03-16T09:00:00+00:00", "nine");
+
+        let empty = get_records_by_date_range_sync(
+            "2026-03-16T09:00:00Z".to_string(),
+            "2026-03-16T09:00:00Z".to_string(),
+        )
+        .unwrap();
+        assert!(empty.is_empty());
+
+        let reversed =
+            get_records_by_date_range_sync("2026-03-17".to_string(), "2026-03-16".to_string());
+        assert!(reversed.unwrap_err().message.contains("before start_date"));
+    }
+
+    // ── get_records_paginated_sync tests ──
+
+    #[test]
+    #[serial]
+    fn get_records_paginated_pages_newest_first_with_filter() {
+        setup_test_db();
+        for hour in 1..=5 {
+            insert_record_with_ts(
+                &format!("2026-03-16T{:02}:00:00+00:00", hour),
+                &format!("m{}", hour),
+            );
+        }
+        add_record("auto", r#"{"current_focus":"x"}"#, None, None, None).unwrap();
+
+        let page: Vec<String> = get_records_paginated_sync(1, 2, Some("manual"))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.content)
+            .collect();
+        assert_eq!(page, vec!["m4", "m3"]);
+
+        assert_eq!(get_records_paginated_sync(0, 10, None).unwrap().len(), 6);
+        assert_eq!(
+            get_records_paginated_sync(0, 10, Some("auto"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(get_records_paginated_sync(10, 10, None).unwrap().is_empty());
+        assert_eq!(count_records_sync(None).unwrap(), 6);
+        assert_eq!(count_records_sync(Some("manual")).unwrap(), 5);
+        assert!(count_records_sync(Some("draft")).is_err());
+        assert!(get_records_paginated_sync(0, 10, Some("draft")).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn get_records_paginated_caps_the_page_size() {
+        setup_test_db();
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            let conn = db.as_ref().unwrap();
+            for _ in 0..MAX_RECORDS_PAGE_SIZE + 5 {
+                conn.execute(
+                    "INSERT INTO records (timestamp, source_type, content) VALUES ('2026-03-16T12:00:00+00:00', 'manual', 'x')",
+                    [],
+                )
+                .unwrap();
+            }
+        }
+
+        let page = get_records_paginated_sync(0, usize::MAX, None).unwrap();
+        assert_eq!(page.len(), MAX_RECORDS_PAGE_SIZE);
+    }
+
     // ── get_history_records_sync tests ──
 
     #[test]
@@ -1513,6 +3011,73 @@ mod tests {
         assert!(manual_records.iter().all(|r| r.source_type == "manual"));
     }
 
+    #[test]
+    fn record_order_by_clause_whitelists_fields_and_directions() {
+        assert_eq!(
+            record_order_by_clause(None, None).unwrap(),
+            "ORDER BY id DESC"
+        );
+        assert_eq!(
+            record_order_by_clause(Some("timestamp"), Some("ASC")).unwrap(),
+            "ORDER BY timestamp ASC, id ASC"
+        );
+        assert_eq!(
+            record_order_by_clause(Some("source"), None).unwrap(),
+            "ORDER BY source_type DESC, id DESC"
+        );
+        assert!(record_order_by_clause(Some("importance"), Some("desc"))
+            .unwrap()
+            .contains("json_extract(content, '$.importance')"));
+
+        for bad in ["content", "id; DROP TABLE records", "source_type", ""] {
+            let err = record_order_by_clause(Some(bad), None).unwrap_err();
+            assert!(err.message.contains("Invalid sort_by"), "{}", bad);
+        }
+        let err = record_order_by_clause(Some("timestamp"), Some("desc; --")).unwrap_err();
+        assert!(err.message.contains("Invalid order"));
+    }
+
+    #[test]
+    #[serial]
+    fn get_history_records_sorted_by_importance_and_timestamp() {
+        setup_test_db();
+
+        add_record("manual", "闪念", None, None, None).unwrap();
+        add_record("auto", r#"{"importance":90}"#, None, None, None).unwrap();
+        add_record("auto", r#"{"importance":10}"#, None, None, None).unwrap();
+
+        let today = chrono::Local::now().date_naive();
+        let today_str = today.format("%Y-%m-%d").to_string();
+
+        let by_importance = get_history_records_sorted_sync(
+            today_str.clone(),
+            today_str.clone(),
+            None,
+            0,
+            50,
+            Some("importance"),
+            Some("desc"),
+        )
+        .unwrap();
+        let contents: Vec<&str> = by_importance.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec![r#"{"importance":90}"#, "闪念", r#"{"importance":10}"#]
+        );
+
+        let oldest_first = get_history_records_sorted_sync(
+            today_str.clone(),
+            today_str,
+            None,
+            0,
+            50,
+            Some("timestamp"),
+            Some("asc"),
+        )
+        .unwrap();
+        assert_eq!(oldest_first[0].content, "闪念");
+    }
+
     #[test]
     #[serial]
     fn get_history_records_invalid_source_type_returns_error() {
@@ -1547,6 +3112,47 @@ mod tests {
         assert!(!get_today_records_sync().unwrap().iter().any(|r| r.id == id));
     }
 
+    #[test]
+    #[serial]
+    fn delete_record_removes_its_screenshot_files() {
+        setup_test_db();
+        let dir = tempfile::tempdir().unwrap();
+        let screenshot = dir.path().join("screenshot_1.png");
+        std::fs::write(&screenshot, b"png").unwrap();
+        let thumbnail = crate::manual_entry::thumbnail_path(&screenshot);
+        std::fs::create_dir_all(thumbnail.parent().unwrap()).unwrap();
+        std::fs::write(&thumbnail, b"jpg").unwrap();
+        let path = screenshot.to_string_lossy().to_string();
+        let id = add_record("auto", "{}", Some(&path), None, None).unwrap();
+
+        delete_record_sync(id).unwrap();
+
+        assert!(!screenshot.exists());
+        assert!(!thumbnail.exists());
+        assert!(delete_record_sync(id).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn delete_record_tolerates_missing_or_shared_screenshot() {
+        setup_test_db();
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("gone.png").to_string_lossy().to_string();
+        let id = add_record("auto", "{}", Some(&missing), None, None).unwrap();
+        delete_record_sync(id).unwrap();
+
+        let shared = dir.path().join("shared.png");
+        std::fs::write(&shared, b"png").unwrap();
+        let path = shared.to_string_lossy().to_string();
+        let first = add_record("auto", r#"{"n":1}"#, Some(&path), None, None).unwrap();
+        let second = add_record("auto", r#"{"n":2}"#, Some(&path), None, None).unwrap();
+        assert_ne!(first, second);
+
+        delete_record_sync(first).unwrap();
+
+        assert!(shared.exists());
+    }
+
     #[test]
     #[serial]
     fn delete_nonexistent_record_returns_error() {
@@ -1557,6 +3163,59 @@ mod tests {
         assert!(result.unwrap_err().message.contains("not found"));
     }
 
+    // ── tokens_used tests ──
+
+    #[test]
+    #[serial]
+    fn tokens_used_add_up_per_record() {
+        setup_test_db();
+        let analyzed = add_record("auto", "{}", None, None, None).unwrap();
+        let manual = add_record("manual", "note", None, None, None).unwrap();
+
+        add_record_tokens_used_sync(analyzed, 1000).unwrap();
+        add_record_tokens_used_sync(analyzed, 250).unwrap();
+
+        let usage = get_tokens_used_for_records(vec![analyzed, manual]).unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[&analyzed], 1250);
+        assert!(add_record_tokens_used_sync(99999, 1).is_err());
+        assert!(get_tokens_used_for_records(vec![]).unwrap().is_empty());
+    }
+
+    // ── update_manual_record_content_sync tests ──
+
+    #[test]
+    #[serial]
+    fn update_manual_record_content_keeps_timestamp() {
+        setup_test_db();
+        let id = insert_record_with_ts("2026-03-16T09:00:00+00:00", "teh quick note");
+
+        update_manual_record_content_sync(id, "the quick note").unwrap();
+
+        let record = get_record_by_id_sync(id).unwrap();
+        assert_eq!(record.content, "the quick note");
+        assert_eq!(record.timestamp, "2026-03-16T09:00:00+00:00");
+    }
+
+    #[test]
+    #[serial]
+    fn update_manual_record_content_rejects_blank_auto_and_missing() {
+        setup_test_db();
+        let manual = add_record("manual", "note", None, None, None).unwrap();
+        let auto = add_record("auto", r#"{"current_focus":"x"}"#, None, None, None).unwrap();
+
+        let blank = update_manual_record_content_sync(manual, "  \n ").unwrap_err();
+        assert!(blank.message.contains("empty"));
+        let auto_err = update_manual_record_content_sync(auto, "overwrite").unwrap_err();
+        assert!(auto_err.message.contains("only manual records"));
+        assert_eq!(
+            get_record_by_id_sync(auto).unwrap().content,
+            r#"{"current_focus":"x"}"#
+        );
+        let missing = update_manual_record_content_sync(99999, "text").unwrap_err();
+        assert!(missing.message.contains("not found"));
+    }
+
     // ── search_records_sync tests ──
 
     #[test]
@@ -1623,6 +3282,36 @@ mod tests {
         assert!(!results.iter().any(|r| r.record.content == "hello world"));
     }
 
+    #[test]
+    #[serial]
+    fn search_like_fallback_matches_wildcards_literally() {
+        setup_test_db();
+
+        add_record("manual", "进度 100% 完成", None, None, None).unwrap();
+        add_record("manual", "进度 100 项", None, None, None).unwrap();
+        add_record("manual", "变量 user_id 为空", None, None, None).unwrap();
+        add_record("manual", "变量 userxid 为空", None, None, None).unwrap();
+        add_record("manual", "路径 C:\\日志", None, None, None).unwrap();
+
+        let contents = |query: &str| -> Vec<String> {
+            search_records_sync(query, "time", 50)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.record.content)
+                .collect()
+        };
+        // CJK queries take the LIKE path
+        assert_eq!(contents("100% 完"), vec!["进度 100% 完成"]);
+        assert_eq!(contents("user_id 为"), vec!["变量 user_id 为空"]);
+        assert_eq!(contents("C:\\日"), vec!["路径 C:\\日志"]);
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_backslash() {
+        assert_eq!(escape_like("50%_a\\b"), "50\\%\\_a\\\\b");
+        assert_eq!(escape_like("普通文本"), "普通文本");
+    }
+
     #[test]
     #[serial]
     fn search_respects_limit() {
@@ -1706,17 +3395,140 @@ mod tests {
         let ts2 = local_to_utc_rfc3339(today.and_hms_opt(8, 0, 0).unwrap());
         let ts3 = local_to_utc_rfc3339(today.and_hms_opt(12, 0, 0).unwrap());
 
-        insert_record_with_ts(&ts1, "10:00");
-        insert_record_with_ts(&ts2, "08:00");
-        insert_record_with_ts(&ts3, "12:00");
+        insert_record_with_ts(&ts1, "10:00");
+        insert_record_with_ts(&ts2, "08:00");
+        insert_record_with_ts(&ts3, "12:00");
+
+        let today_str = today.format("%Y-%m-%d").to_string();
+        let records = get_records_for_export(&today_str, &today_str).unwrap();
+
+        // Should be in ASC order (oldest first)
+        assert_eq!(records[0].content, "08:00");
+        assert_eq!(records[1].content, "10:00");
+        assert_eq!(records[2].content, "12:00");
+    }
+
+    #[test]
+    fn record_filter_clause_numbers_params_after_date_range() {
+        assert_eq!(
+            record_filter_clause(&RecordFilter::default(), 3),
+            (String::new(), vec![])
+        );
+
+        let (sql, values) = record_filter_clause(
+            &RecordFilter {
+                tags: vec!["开发".to_string(), " ".to_string(), "会议".to_string()],
+                project: Some("DailyLogger".to_string()),
+            },
+            3,
+        );
+        assert!(sql.contains("IN (?3, ?4)"));
+        assert!(sql.contains("mt.name IN (?3, ?4)"));
+        assert!(sql.ends_with("records.project = ?5"));
+        assert_eq!(values, vec!["开发", "会议", "DailyLogger"]);
+    }
+
+    #[test]
+    #[serial]
+    fn get_records_for_export_filtered_keeps_only_matching_records() {
+        setup_test_db();
+
+        let today = chrono::Local::now().date_naive();
+        let ts = local_to_utc_rfc3339(today.and_hms_opt(10, 0, 0).unwrap());
+        let ai_tagged = insert_record_with_ts(&ts, "ai tagged");
+        let content_tagged = insert_record_with_ts(&ts, r#"{"current_focus":"x","tags":["会议"]}"#);
+        let manual_tagged = insert_record_with_ts(&ts, "manual tagged");
+        let in_project = insert_record_with_ts(&ts, "in project");
+        insert_record_with_ts(&ts, "unrelated");
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            let conn = db.as_ref().unwrap();
+            conn.execute(
+                "UPDATE records SET tags = '[\"开发\"]', project = 'DailyLogger' WHERE id = ?1",
+                params![ai_tagged],
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE records SET project = 'DailyLogger' WHERE id = ?1",
+                params![in_project],
+            )
+            .unwrap();
+        }
+        let tag = crate::memory_storage::create_manual_tag("复盘".to_string(), "blue".to_string())
+            .unwrap();
+        crate::memory_storage::add_tag_to_record(manual_tagged, tag.id).unwrap();
+
+        let today_str = today.format("%Y-%m-%d").to_string();
+        let ids = |filter: RecordFilter| -> Vec<i64> {
+            let mut ids: Vec<i64> =
+                get_records_for_export_filtered(&today_str, &today_str, &filter)
+                    .unwrap()
+                    .iter()
+                    .map(|r| r.id)
+                    .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(
+            ids(RecordFilter {
+                tags: vec!["开发".to_string(), "会议".to_string(), "复盘".to_string()],
+                project: None,
+            }),
+            vec![ai_tagged, content_tagged, manual_tagged]
+        );
+        assert_eq!(
+            ids(RecordFilter {
+                tags: vec![],
+                project: Some("DailyLogger".to_string()),
+            }),
+            vec![ai_tagged, in_project]
+        );
+        assert_eq!(
+            ids(RecordFilter {
+                tags: vec!["开发".to_string()],
+                project: Some("DailyLogger".to_string()),
+            }),
+            vec![ai_tagged]
+        );
+        assert_eq!(ids(RecordFilter::default()).len(), 5);
+    }
+
+    #[test]
+    #[serial]
+    fn export_to_sqlite_creates_standalone_file_with_range_only() {
+        setup_test_db();
+
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today - chrono::Duration::days(1);
+        insert_record_with_ts(
+            &local_to_utc_rfc3339(today.and_hms_opt(9, 0, 0).unwrap()),
+            "today",
+        );
+        insert_record_with_ts(
+            &local_to_utc_rfc3339(yesterday.and_hms_opt(9, 0, 0).unwrap()),
+            "yesterday",
+        );
 
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("export.db");
         let today_str = today.format("%Y-%m-%d").to_string();
-        let records = get_records_for_export(&today_str, &today_str).unwrap();
+        let count = export_records_to_sqlite_sync(&dest, &today_str, &today_str).unwrap();
+        assert_eq!(count, 1);
 
-        // Should be in ASC order (oldest first)
-        assert_eq!(records[0].content, "08:00");
-        assert_eq!(records[1].content, "10:00");
-        assert_eq!(records[2].content, "12:00");
+        // The export opens on its own, without the app database
+        let exported = rusqlite::Connection::open(&dest).unwrap();
+        let contents: Vec<String> = exported
+            .prepare("SELECT content FROM records")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(contents, vec!["today".to_string()]);
+
+        // Existing files are never overwritten
+        assert!(export_records_to_sqlite_sync(&dest, &today_str, &today_str).is_err());
     }
 
     // ── Edge cases ──
@@ -1773,6 +3585,291 @@ mod tests {
         assert_eq!(record.content, unicode_content);
     }
 
+    // ── extract_snippet tests ──
+
+    #[test]
+    fn snippet_keeps_context_around_single_match() {
+        let snippet = extract_snippet("0123456789 rust 0123456789", "Rust", 3);
+        assert_eq!(snippet, "…89 <mark>rust</mark> 01…");
+    }
+
+    #[test]
+    fn snippet_marks_multiple_matches_and_merges_close_windows() {
+        let content = "foo bar foo ........................ foo";
+        let snippet = extract_snippet(content, "foo", 4);
+        assert_eq!(
+            snippet,
+            "<mark>foo</mark> bar <mark>foo</mark> ...…... <mark>foo</mark>"
+        );
+    }
+
+    #[test]
+    fn snippet_handles_boundaries_and_cjk() {
+        // Match at the very start and end: no ellipsis
+        assert_eq!(extract_snippet("测试", "测试", 5), "<mark>测试</mark>");
+        assert_eq!(
+            extract_snippet("今天写了测试代码", "测试", 2),
+            "…写了<mark>测试</mark>代码"
+        );
+        // No match: head of the content
+        assert_eq!(extract_snippet("abcdefgh", "zzz", 2), "abcd…");
+        assert_eq!(extract_snippet("abc", "", 2), "abc");
+    }
+
+    #[test]
+    #[serial]
+    fn auto_records_start_as_drafts_and_stale_drafts_are_purged() {
+        setup_test_db();
+        let draft_id = add_record("auto", "{}", None, None, None).unwrap();
+        let manual_id = add_record("manual", "写周报", None, None, None).unwrap();
+
+        let status = |id: i64| -> String {
+            let db = DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .query_row(
+                    "SELECT status FROM records WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(status(draft_id), RECORD_STATUS_DRAFT);
+        assert_eq!(status(manual_id), RECORD_STATUS_CONFIRMED);
+
+        // A fresh draft is kept
+        assert_eq!(purge_stale_drafts_sync().unwrap(), 0);
+
+        let old = (chrono::Utc::now() - chrono::Duration::days(DEFAULT_DRAFT_RETENTION_DAYS + 1))
+            .to_rfc3339();
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute("UPDATE records SET timestamp = ?1", params![old])
+                .unwrap();
+        }
+        assert_eq!(purge_stale_drafts_sync().unwrap(), 1);
+        assert!(get_record_by_id_sync(draft_id).is_err());
+        assert!(get_record_by_id_sync(manual_id).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn purge_follows_retention_setting_and_removes_screenshots() {
+        setup_test_db();
+        let mut settings = (*crate::memory_storage::get_settings_sync().unwrap()).clone();
+        settings.draft_retention_days = Some(2);
+        crate::memory_storage::save_settings_sync(&settings).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let screenshot = dir.path().join("screenshot_1.png");
+        std::fs::write(&screenshot, b"png").unwrap();
+        let thumbnail = crate::manual_entry::thumbnail_path(&screenshot);
+        std::fs::create_dir_all(thumbnail.parent().unwrap()).unwrap();
+        std::fs::write(&thumbnail, b"jpg").unwrap();
+        let path = screenshot.to_string_lossy().to_string();
+
+        let three_days_ago = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+        let stale_id = insert_auto_record_at(&three_days_ago, "{}", None);
+        let kept_id = insert_auto_record_at(&chrono::Utc::now().to_rfc3339(), "{}", None);
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute(
+                    "UPDATE records SET screenshot_path = ?1 WHERE id = ?2",
+                    params![path, stale_id],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(purge_stale_drafts_sync().unwrap(), 1);
+        assert!(get_record_by_id_sync(stale_id).is_err());
+        assert!(get_record_by_id_sync(kept_id).is_ok());
+        assert!(!screenshot.exists());
+        assert!(!thumbnail.exists());
+    }
+
+    #[test]
+    #[serial]
+    fn purge_is_disabled_by_non_positive_retention() {
+        setup_test_db();
+        let mut settings = (*crate::memory_storage::get_settings_sync().unwrap()).clone();
+        settings.draft_retention_days = Some(0);
+        crate::memory_storage::save_settings_sync(&settings).unwrap();
+
+        let long_ago = (chrono::Utc::now() - chrono::Duration::days(365)).to_rfc3339();
+        let draft_id = insert_auto_record_at(&long_ago, "{}", None);
+
+        assert_eq!(purge_stale_drafts_sync().unwrap(), 0);
+        assert!(get_record_by_id_sync(draft_id).is_ok());
+        assert_eq!(draft_retention_days(Some(-1)), None);
+        assert_eq!(
+            draft_retention_days(None),
+            Some(DEFAULT_DRAFT_RETENTION_DAYS)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn drafts_are_confirmed_in_bulk_by_date_range_or_session() {
+        setup_test_db();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let first = add_record("auto", r#"{"n":1}"#, None, None, None).unwrap();
+        let second = add_record("auto", r#"{"n":2}"#, None, None, None).unwrap();
+        assert_ne!(first, second);
+        let status = |id: i64| get_record_by_id_sync(id).unwrap().status.unwrap();
+
+        // The session only covers the first record
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute(
+                    "UPDATE records SET session_id = 42 WHERE id = ?1",
+                    params![first],
+                )
+                .unwrap();
+        }
+        assert_eq!(confirm_session_records_sync(42).unwrap(), 1);
+        assert_eq!(status(first), RECORD_STATUS_CONFIRMED);
+        assert_eq!(status(second), RECORD_STATUS_DRAFT);
+
+        assert_eq!(
+            confirm_records_by_date_range_sync(&today, &today).unwrap(),
+            1
+        );
+        assert_eq!(status(second), RECORD_STATUS_CONFIRMED);
+        assert!(confirm_records_by_date_range_sync(&today, "2000-01-01").is_err());
+    }
+
+    #[test]
+    fn heatmap_has_an_entry_for_every_day_of_the_year() {
+        let mut counts = std::collections::HashMap::new();
+        counts.insert("2024-02-29".to_string(), 3);
+
+        let leap = fill_heatmap_year(2024, &counts).unwrap();
+        assert_eq!(leap.len(), 366);
+        assert_eq!(leap[0].date, "2024-01-01");
+        assert_eq!(leap[365].date, "2024-12-31");
+        assert_eq!(leap[59].date, "2024-02-29");
+        assert_eq!(leap[59].count, 3);
+        assert_eq!(leap.iter().map(|d| d.count).sum::<i64>(), 3);
+
+        assert_eq!(fill_heatmap_year(2025, &counts).unwrap().len(), 365);
+    }
+
+    #[test]
+    #[serial]
+    fn heatmap_counts_records_on_their_local_day() {
+        setup_test_db();
+        add_record("manual", "first", None, None, None).unwrap();
+        add_record("manual", "second", None, None, None).unwrap();
+
+        let today = chrono::Local::now().date_naive();
+        let heatmap = get_heatmap_sync(today.year()).unwrap();
+        let today_str = today.format("%Y-%m-%d").to_string();
+        let entry = heatmap.iter().find(|d| d.date == today_str).unwrap();
+        assert_eq!(entry.count, 2);
+        assert_eq!(heatmap.iter().map(|d| d.count).sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn hourly_distribution_buckets_by_local_hour_across_offsets() {
+        let shanghai = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2026, 3, 16).unwrap();
+        let timestamps: Vec<String> = [
+            // 09:30 in Shanghai, stored in UTC
+            "2026-03-16T01:30:00+00:00",
+            // 09:05 in Shanghai, stored with the local offset
+            "2026-03-16T09:05:00+08:00",
+            // 00:10 in Shanghai, still the previous day in UTC
+            "2026-03-15T16:10:00Z",
+            // 23:59 in Shanghai, stored from a UTC-5 machine
+            "2026-03-16T10:59:00-05:00",
+            // The next day in Shanghai
+            "2026-03-16T16:00:00Z",
+            "not a timestamp",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let buckets = hourly_distribution(&timestamps, day, &shanghai);
+
+        assert_eq!(buckets.len(), 24);
+        assert!(buckets.iter().enumerate().all(|(i, b)| b.hour == i as u32));
+        assert_eq!(buckets[9].count, 2);
+        assert_eq!(buckets[0].count, 1);
+        assert_eq!(buckets[23].count, 1);
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<i64>(), 4);
+    }
+
+    #[test]
+    #[serial]
+    fn hourly_distribution_counts_records_of_the_requested_day() {
+        setup_test_db();
+        let today = chrono::Local::now().date_naive();
+        insert_record_with_ts(
+            &local_to_utc_rfc3339(today.and_hms_opt(1, 15, 0).unwrap()),
+            "early",
+        );
+        insert_record_with_ts(
+            &local_to_utc_rfc3339(today.and_hms_opt(14, 0, 0).unwrap()),
+            "afternoon",
+        );
+        insert_record_with_ts(
+            &local_to_utc_rfc3339(
+                (today - chrono::Duration::days(1))
+                    .and_hms_opt(14, 0, 0)
+                    .unwrap(),
+            ),
+            "yesterday",
+        );
+
+        let buckets = get_hourly_distribution_sync(&today.format("%Y-%m-%d").to_string()).unwrap();
+
+        assert_eq!(buckets.len(), 24);
+        assert_eq!(buckets[1].count, 1);
+        assert_eq!(buckets[14].count, 1);
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<i64>(), 2);
+        assert!(get_hourly_distribution_sync("2026/03/16").is_err());
+    }
+
+    #[test]
+    fn sanitize_content_strips_control_characters_but_keeps_line_breaks() {
+        assert_eq!(
+            sanitize_content("a\0b\x1b[31mred\x1b[0m\x7f\u{9b}c"),
+            "ab[31mred[0mc"
+        );
+        assert_eq!(
+            sanitize_content("第一行\r\n\t第二行\n"),
+            "第一行\r\n\t第二行\n"
+        );
+        assert!(matches!(
+            sanitize_content("<b>plain</b>"),
+            std::borrow::Cow::Borrowed("<b>plain</b>")
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn control_characters_are_removed_before_storing() {
+        setup_test_db();
+        let id = add_record("manual", "闪念\0\x1b]0;title\x07\n下一行", None, None, None).unwrap();
+        assert_eq!(
+            get_record_by_id_sync(id).unwrap().content,
+            "闪念]0;title\n下一行"
+        );
+
+        update_record_user_notes_sync(id, Some("备注\0")).unwrap();
+        update_record_sync(id, "改过\x1b").unwrap();
+        let record = get_record_by_id_sync(id).unwrap();
+        assert_eq!(record.user_notes.as_deref(), Some("备注"));
+        assert_eq!(record.content, "改过");
+    }
+
     // ── SearchResult tests ──
 
     #[test]
@@ -1788,6 +3885,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: None,
+            metadata: None,
+            status: None,
         };
 
         let result = SearchResult {
@@ -1816,6 +3915,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: Some("pending".to_string()),
+            metadata: None,
+            status: None,
         };
 
         let json = serde_json::to_string(&record).unwrap();
@@ -1844,6 +3945,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: None,
+            metadata: None,
+            status: None,
         };
 
         let cloned = record.clone();
@@ -1864,6 +3967,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: None,
+            metadata: None,
+            status: None,
         };
 
         let debug_str = format!("{:?}", record);
@@ -1913,6 +4018,57 @@ mod tests {
         assert!(records.is_empty());
     }
 
+    #[test]
+    #[serial]
+    fn search_falls_back_to_like_without_fts_index() {
+        setup_test_db();
+        add_record("manual", "tuning postgres indexes", None, None, None).unwrap();
+        {
+            let db = DB_CONNECTION.lock().unwrap();
+            let conn = db.as_ref().unwrap();
+            conn.execute_batch(
+                "DROP TRIGGER records_ai; DROP TRIGGER records_ad; DROP TRIGGER records_au;
+                 DROP TABLE records_fts;",
+            )
+            .unwrap();
+        }
+        add_record("manual", "postgres vacuum", None, None, None).unwrap();
+
+        let results = search_records_sync("postgres", "rank", 50).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.snippet.contains("postgres")));
+    }
+
+    #[test]
+    fn ensure_fts_index_backfills_existing_records() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE records (id INTEGER PRIMARY KEY, content TEXT NOT NULL);
+             INSERT INTO records (content) VALUES ('debugging postgres locks'), ('lunch');",
+        )
+        .unwrap();
+
+        assert!(super::super::schema::ensure_fts_index(&conn));
+        conn.execute(
+            "INSERT INTO records (content) VALUES ('postgres replication')",
+            [],
+        )
+        .unwrap();
+
+        let matches: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM records_fts WHERE records_fts MATCH 'postgres'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matches, 2);
+        // A second run finds the index complete and leaves it alone
+        assert!(super::super::schema::ensure_fts_index(&conn));
+        assert!(super::super::schema::fts_index_exists(&conn));
+    }
+
     #[test]
     #[serial]
     fn search_with_quotes_in_query() {
@@ -2009,13 +4165,21 @@ mod tests {
 
     #[test]
     #[serial]
-    fn get_all_today_records_for_summary_delegates() {
+    fn get_all_today_records_for_summary_leaves_out_drafts() {
         setup_test_db();
 
         add_record("manual", "test note", None, None, None).unwrap();
+        let draft_id = add_record("auto", "{}", None, None, None).unwrap();
 
         let records = get_all_today_records_for_summary().unwrap();
         assert!(records.iter().any(|r| r.content == "test note"));
+        assert!(records.iter().all(|r| r.id != draft_id));
+
+        confirm_record_sync(draft_id).unwrap();
+        let records = get_all_today_records_for_summary().unwrap();
+        let confirmed = records.iter().find(|r| r.id == draft_id).unwrap();
+        assert_eq!(confirmed.status.as_deref(), Some(RECORD_STATUS_CONFIRMED));
+        assert_eq!(get_today_records_sync().unwrap().len(), 2);
     }
 
     // ── Async wrapper tests ──
@@ -2027,10 +4191,67 @@ mod tests {
 
         add_record("manual", "async test", None, None, None).unwrap();
 
-        let records = get_today_records().await.unwrap();
+        let records = get_today_records(None).await.unwrap();
         assert!(records.iter().any(|r| r.content == "async test"));
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn get_today_records_filters_by_min_importance() {
+        setup_test_db();
+
+        add_record(
+            "auto",
+            r#"{"current_focus":"修复线上故障","importance":90}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        add_record(
+            "auto",
+            r#"{"current_focus":"浏览新闻","importance":10}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        // Older capture without a score and a manual note count as the default
+        add_record("auto", r#"{"current_focus":"写文档"}"#, None, None, None).unwrap();
+        add_record("manual", "闪念", None, None, None).unwrap();
+
+        assert_eq!(get_today_records(None).await.unwrap().len(), 4);
+        assert_eq!(get_today_records(Some(0)).await.unwrap().len(), 4);
+        assert_eq!(
+            get_today_records(Some(DEFAULT_RECORD_IMPORTANCE))
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        let important = get_today_records(Some(80)).await.unwrap();
+        assert_eq!(important.len(), 1);
+        assert!(important[0].content.contains("修复线上故障"));
+    }
+
+    #[test]
+    fn record_importance_reads_and_clamps_score() {
+        assert_eq!(record_importance(r#"{"importance":75}"#), 75);
+        assert_eq!(record_importance(r#"{"importance":87.6}"#), 88);
+        assert_eq!(record_importance(r#"{"importance":"60"}"#), 60);
+        assert_eq!(record_importance(r#"{"importance":250}"#), 100);
+        assert_eq!(record_importance(r#"{"importance":-3}"#), 0);
+        assert_eq!(
+            record_importance(r#"{"importance":"high"}"#),
+            DEFAULT_RECORD_IMPORTANCE
+        );
+        assert_eq!(
+            record_importance(r#"{"current_focus":"x"}"#),
+            DEFAULT_RECORD_IMPORTANCE
+        );
+        assert_eq!(record_importance("纯文本"), DEFAULT_RECORD_IMPORTANCE);
+    }
+
     #[tokio::test]
     #[serial]
     async fn async_get_records_by_date_range_works() {
@@ -2056,7 +4277,7 @@ mod tests {
 
         delete_record(id).await.unwrap();
 
-        let records = get_today_records().await.unwrap();
+        let records = get_today_records(None).await.unwrap();
         assert!(!records.iter().any(|r| r.id == id));
     }
 
@@ -2120,6 +4341,216 @@ mod tests {
         assert!(result.unwrap_err().message.contains("not found"));
     }
 
+    #[test]
+    #[serial]
+    fn update_record_sync_learns_active_software_correction() {
+        setup_test_db();
+
+        let auto_id = add_record(
+            "auto",
+            r#"{"current_focus":"coding","active_software":"Code"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let manual_id =
+            add_record("manual", r#"{"active_software":"Notes"}"#, None, None, None).unwrap();
+
+        update_record_sync(
+            auto_id,
+            r#"{"current_focus":"coding","active_software":"VS Code"}"#,
+        )
+        .unwrap();
+        update_record_sync(manual_id, r#"{"active_software":"Obsidian"}"#).unwrap();
+
+        let record = get_record_by_id_sync(auto_id).unwrap();
+        assert!(record.content.contains("VS Code"));
+        assert_eq!(record.analysis_status.as_deref(), Some("user_edited"));
+
+        // Only the auto record's correction is learned
+        let corrections = crate::memory_storage::get_common_software_corrections(10).unwrap();
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0].original, "Code");
+        assert_eq!(corrections[0].corrected, "VS Code");
+    }
+
+    #[test]
+    #[serial]
+    fn add_record_stores_normalized_software_for_auto_records() {
+        setup_test_db();
+
+        let auto_id = add_record(
+            "auto",
+            r#"{"current_focus":"写代码","active_software":"code.exe"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let manual_id = add_record(
+            "manual",
+            r#"{"active_software":"code.exe"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let db = DB_CONNECTION.lock().unwrap();
+        let conn = db.as_ref().unwrap();
+        let normalized = |id: i64| -> Option<String> {
+            conn.query_row(
+                "SELECT software_normalized FROM records WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(normalized(auto_id).as_deref(), Some("VS Code"));
+        assert_eq!(normalized(manual_id), None);
+    }
+
+    #[test]
+    #[serial]
+    fn content_updates_recompute_normalized_software() {
+        setup_test_db();
+        let id = add_record("auto", r#"{"current_focus":"待分析"}"#, None, None, None).unwrap();
+        let normalized = || -> Option<String> {
+            let db = DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .query_row(
+                    "SELECT software_normalized FROM records WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(normalized(), None);
+
+        update_record_content_sync(
+            id,
+            r#"{"current_focus":"写代码","active_software":"code.exe"}"#,
+        )
+        .unwrap();
+        assert_eq!(normalized().as_deref(), Some("VS Code"));
+
+        update_record_sync(
+            id,
+            r#"{"current_focus":"查资料","active_software":"msedge"}"#,
+        )
+        .unwrap();
+        assert_eq!(normalized().as_deref(), Some("Edge"));
+
+        update_record_analysis(id, r#"{"current_focus":"聊天","active_software":"weixin"}"#)
+            .unwrap();
+        assert_eq!(normalized().as_deref(), Some("WeChat"));
+    }
+
+    #[test]
+    #[serial]
+    fn add_record_with_metadata_round_trips() {
+        setup_test_db();
+
+        let metadata = r#"{"git_commit":"a1b2c3d","task_id":"PROJ-42"}"#;
+        let id =
+            add_record_with_metadata("manual", "fix bug", None, None, None, None, Some(metadata))
+                .unwrap();
+
+        let record = get_record_by_id_sync(id).unwrap();
+        let stored: serde_json::Value =
+            serde_json::from_str(record.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(stored["git_commit"], "a1b2c3d");
+        assert_eq!(stored["task_id"], "PROJ-42");
+
+        let today = get_today_records_sync().unwrap();
+        assert_eq!(today[0].metadata.as_deref(), Some(metadata));
+    }
+
+    fn insert_auto_record_at(timestamp: &str, content: &str, screenshot_path: Option<&str>) -> i64 {
+        let db = DB_CONNECTION.lock().unwrap();
+        insert_record_at(
+            db.as_ref().unwrap(),
+            timestamp,
+            NewRecord {
+                source_type: "auto",
+                content,
+                screenshot_path,
+                monitor_info: None,
+                tags: None,
+                session_id: None,
+                metadata: None,
+                project: None,
+                status: RECORD_STATUS_DRAFT,
+                software_normalized: None,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn duplicate_write_is_stored_only_once() {
+        setup_test_db();
+
+        let content = r#"{"current_focus":"写代码","active_software":"VS Code"}"#;
+        let first = insert_auto_record_at("2026-03-16T09:00:00.100+00:00", content, None);
+        let retry = insert_auto_record_at("2026-03-16T09:00:00.900+00:00", content, None);
+        assert_eq!(first, retry);
+
+        // Different content, a later second or another screenshot is a new record
+        let other = insert_auto_record_at("2026-03-16T09:00:00.950+00:00", "{}", None);
+        let later = insert_auto_record_at("2026-03-16T09:00:05+00:00", content, None);
+        let captured = insert_auto_record_at(
+            "2026-03-16T09:00:00.980+00:00",
+            content,
+            Some("/shots/b.png"),
+        );
+        assert_ne!(other, first);
+        assert_ne!(later, first);
+        assert_ne!(captured, first);
+
+        let db = DB_CONNECTION.lock().unwrap();
+        let count: i64 = db
+            .as_ref()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM records", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    #[serial]
+    fn add_record_without_metadata_returns_none() {
+        setup_test_db();
+
+        let id = add_record("manual", "plain note", None, None, None).unwrap();
+        let record = get_record_by_id_sync(id).unwrap();
+        assert!(record.metadata.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn add_record_with_metadata_rejects_non_object_json() {
+        setup_test_db();
+
+        assert!(add_record_with_metadata(
+            "manual",
+            "note",
+            None,
+            None,
+            None,
+            None,
+            Some("not json")
+        )
+        .is_err());
+        assert!(
+            add_record_with_metadata("manual", "note", None, None, None, None, Some("[1, 2]"))
+                .is_err()
+        );
+    }
+
     // NOTE: Performance benchmark tests moved to dedicated `mod benchmarks` below (CORE-008)
 }
 
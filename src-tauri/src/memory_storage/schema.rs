@@ -127,6 +127,22 @@ pub fn init_database() -> AppResult<()> {
     })?;
     tracing::info!("init_database: records table ready");
 
+    // Full-text index is optional: SQLite builds without FTS5 fall back to LIKE search
+    ensure_fts_index(&conn);
+
+    // Hash records written before the content_hash column existed
+    if let Err(e) = super::content_hash::backfill_content_hash(&conn) {
+        tracing::warn!("init_database: Failed to backfill content hashes: {}", e);
+    }
+
+    // Normalize the software of auto records written before software_normalized existed
+    if let Err(e) = super::software::backfill_software_normalized(&conn) {
+        tracing::warn!(
+            "init_database: Failed to backfill normalized software: {}",
+            e
+        );
+    }
+
     // All schema creation and updates are now handled by the migration system via run_migrations()
     // which is called above for all database initialization paths (new, legacy, and version updates)
 
@@ -254,6 +270,83 @@ fn migrate_plain_api_key_with_conn(conn: &Connection) -> AppResult<()> {
     Ok(())
 }
 
+/// Create the `records_fts` full-text index and its sync triggers, and
+/// rebuild the index when it does not cover every record (databases created
+/// before the index, or upgraded from a build without FTS5).
+///
+/// Returns false, after logging a warning, when this SQLite build lacks FTS5;
+/// `search_records_sync` then falls back to a LIKE scan.
+pub(crate) fn ensure_fts_index(conn: &Connection) -> bool {
+    let created = conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS records_fts USING fts5(
+            content,
+            content='records',
+            content_rowid='id',
+            tokenize='unicode61'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS records_ai AFTER INSERT ON records BEGIN
+            INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS records_ad AFTER DELETE ON records BEGIN
+            INSERT INTO records_fts(records_fts, rowid, content)
+            VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS records_au AFTER UPDATE ON records BEGIN
+            INSERT INTO records_fts(records_fts, rowid, content)
+            VALUES ('delete', old.id, old.content);
+            INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
+        END;",
+    );
+    if let Err(e) = created {
+        tracing::warn!(
+            "Full-text index unavailable ({}), record search falls back to LIKE",
+            e
+        );
+        return false;
+    }
+
+    // records_fts_docsize holds one row per indexed record
+    let counts = conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM records), (SELECT COUNT(*) FROM records_fts_docsize)",
+        [],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    );
+    match counts {
+        Ok((records, indexed)) if records != indexed => {
+            tracing::info!(
+                "Rebuilding full-text index ({} of {} records indexed)",
+                indexed,
+                records
+            );
+            if let Err(e) = conn.execute(
+                "INSERT INTO records_fts(records_fts) VALUES ('rebuild')",
+                [],
+            ) {
+                tracing::warn!("Failed to rebuild full-text index: {}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to check full-text index: {}", e),
+    }
+    true
+}
+
+/// Whether the `records_fts` full-text index exists.
+pub(crate) fn fts_index_exists(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'records_fts'",
+        [],
+        |_| Ok(()),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .is_some()
+}
+
 #[cfg(test)]
 pub fn init_test_database(conn: &Connection) -> AppResult<()> {
     // Create records table
@@ -268,10 +361,28 @@ pub fn init_test_database(conn: &Connection) -> AppResult<()> {
             tags TEXT,
             user_notes TEXT,
             session_id INTEGER REFERENCES sessions(id),
-            analysis_status TEXT DEFAULT 'pending'
+            analysis_status TEXT DEFAULT 'pending',
+            metadata TEXT,
+            project TEXT,
+            status TEXT DEFAULT 'confirmed',
+            software_normalized TEXT,
+            content_lang TEXT,
+            tokens_used INTEGER,
+            content_hash TEXT
         )",
         [],
     )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_records_content_hash ON records(content_hash)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS records_content_lang_au AFTER UPDATE OF content ON records BEGIN
+            UPDATE records SET content_lang = NULL WHERE id = new.id;
+        END",
+        [],
+    )?;
 
     // Create sessions table (SESSION-001)
     conn.execute(
@@ -374,48 +485,51 @@ pub fn init_test_database(conn: &Connection) -> AppResult<()> {
             auto_backup_retention INTEGER DEFAULT 5,
             last_auto_backup_at TEXT,
             auto_detect_vault_by_window INTEGER DEFAULT 0,
-            custom_export_template TEXT
+            custom_export_template TEXT,
+            sensitive_filter_enabled INTEGER DEFAULT 0,
+            sensitive_keywords TEXT DEFAULT '[]',
+            log_llm_payload INTEGER DEFAULT 1,
+            screenshot_format TEXT DEFAULT 'png',
+            api_keys TEXT DEFAULT '[]',
+            open_summary_after_generate INTEGER DEFAULT 0,
+            startup_delay_seconds INTEGER DEFAULT 0,
+            day_start_hour INTEGER DEFAULT 0,
+            failure_alert_window_minutes INTEGER DEFAULT 10,
+            failure_alert_webhook_url TEXT,
+            projects TEXT,
+            screenshot_interval_seconds INTEGER DEFAULT 300,
+            debug_dump_failed_requests INTEGER DEFAULT 0,
+            crop_taskbar INTEGER DEFAULT 0,
+            taskbar_height INTEGER DEFAULT 48,
+            category_prompts TEXT,
+            endpoint_image_formats TEXT,
+            analysis_prompt_b TEXT,
+            prompt_b_percent INTEGER DEFAULT 50,
+            extra_headers TEXT,
+            pause_capture_without_display INTEGER DEFAULT 1,
+            on_record_command TEXT,
+            software_aliases TEXT,
+            max_image_dimension INTEGER DEFAULT 7680,
+            capture_timeout_secs INTEGER DEFAULT 5,
+            capture_retries INTEGER DEFAULT 2,
+            capture_on_idle_wake INTEGER DEFAULT 1,
+            redact_sensitive_content INTEGER DEFAULT 1,
+            prompt_schema_version INTEGER,
+            auto_summary_enabled INTEGER DEFAULT 0,
+            auto_summary_catch_up INTEGER DEFAULT 1,
+            last_auto_summary_date TEXT,
+            keyword_alert_rules TEXT,
+            capture_all_monitors INTEGER DEFAULT 0,
+            exclude_regions TEXT,
+            excluded_apps TEXT,
+            draft_retention_days INTEGER DEFAULT 7
         )",
         [],
     )?;
 
     conn.execute("INSERT OR IGNORE INTO settings (id) VALUES (1)", [])?;
 
-    // Create FTS5 table
-    conn.execute(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS records_fts USING fts5(
-            content,
-            content='records',
-            content_rowid='id',
-            tokenize='unicode61'
-        )",
-        [],
-    )?;
-
-    // FTS5 triggers
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS records_ai AFTER INSERT ON records BEGIN
-            INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
-        END",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS records_ad AFTER DELETE ON records BEGIN
-            INSERT INTO records_fts(records_fts, rowid, content)
-            VALUES ('delete', old.id, old.content);
-        END",
-        [],
-    )?;
-
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS records_au AFTER UPDATE ON records BEGIN
-            INSERT INTO records_fts(records_fts, rowid, content)
-            VALUES ('delete', old.id, old.content);
-            INSERT INTO records_fts(rowid, content) VALUES (new.id, new.content);
-        END",
-        [],
-    )?;
+    ensure_fts_index(conn);
 
     // Create manual tags tables
     conn.execute(
@@ -474,6 +588,18 @@ pub fn init_test_database(conn: &Connection) -> AppResult<()> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS software_corrections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            original TEXT NOT NULL,
+            corrected TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 1,
+            updated_at TEXT NOT NULL,
+            UNIQUE(original, corrected)
+        )",
+        [],
+    )?;
+
     // DEBT-001: Ensure test isolation by clearing data tables after schema creation.
     // This prevents leftover data from previous tests affecting current test results.
     // Tables are recreated above, so this only clears data, not schema.
@@ -484,6 +610,7 @@ pub fn init_test_database(conn: &Connection) -> AppResult<()> {
     let _ = conn.execute("DELETE FROM offline_queue", []);
     let _ = conn.execute("DELETE FROM silent_pattern_stats", []);
     let _ = conn.execute("DELETE FROM work_time_activity", []);
+    let _ = conn.execute("DELETE FROM software_corrections", []);
     let _ = conn.execute("DELETE FROM schema_migrations", []);
     let _ = conn.execute("DELETE FROM schema_version", []);
     // Reset settings to default (keep row with id=1)
@@ -1,6 +1,7 @@
 use crate::crypto;
 use crate::errors::{AppError, AppResult};
 use once_cell::sync::Lazy;
+use rusqlite::Connection;
 use std::sync::{Arc, RwLock};
 
 use super::{Settings, DB_CONNECTION};
@@ -12,7 +13,8 @@ use super::{Settings, DB_CONNECTION};
 /// the full 64-field `Settings` struct.
 static SETTINGS_CACHE: Lazy<RwLock<Option<Arc<Settings>>>> = Lazy::new(|| RwLock::new(None));
 
-/// Invalidate the settings cache. Called when the database is re-initialized.
+/// Invalidate the settings cache. Called when the database is re-initialized,
+/// or when it may have been modified outside this process (`reload_settings`).
 pub fn invalidate_settings_cache() {
     if let Ok(mut cache) = SETTINGS_CACHE.write() {
         *cache = None;
@@ -32,6 +34,18 @@ pub fn get_settings_sync() -> AppResult<Arc<Settings>> {
         .as_ref()
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
+    let settings = Arc::new(load_settings(conn)?);
+
+    // Update cache
+    if let Ok(mut cache) = SETTINGS_CACHE.write() {
+        *cache = Some(Arc::clone(&settings));
+    }
+
+    Ok(settings)
+}
+
+/// Read the settings row and decrypt its secrets.
+fn load_settings(conn: &Connection) -> AppResult<Settings> {
     let mut stmt = conn
         .prepare(
             "SELECT api_base_url, api_key, model_name, screenshot_interval,
@@ -53,7 +67,37 @@ pub fn get_settings_sync() -> AppResult<Arc<Settings>> {
                 test_model_name, onboarding_completed, language,
                 preferred_language, supported_languages,
                 auto_backup_enabled, auto_backup_interval, auto_backup_retention,
-                last_auto_backup_at, custom_export_template
+                last_auto_backup_at, custom_export_template,
+                sensitive_filter_enabled, sensitive_keywords,
+                log_llm_payload,
+                screenshot_format,
+                api_keys,
+                open_summary_after_generate,
+                startup_delay_seconds,
+                day_start_hour,
+                failure_alert_window_minutes, failure_alert_webhook_url,
+                projects,
+                screenshot_interval_seconds,
+                debug_dump_failed_requests,
+                crop_taskbar, taskbar_height,
+                category_prompts,
+                endpoint_image_formats,
+                analysis_prompt_b, prompt_b_percent,
+                extra_headers,
+                pause_capture_without_display,
+                on_record_command,
+                software_aliases,
+                max_image_dimension,
+                capture_timeout_secs, capture_retries,
+                capture_on_idle_wake,
+                redact_sensitive_content,
+                prompt_schema_version,
+                auto_summary_enabled, auto_summary_catch_up, last_auto_summary_date,
+                keyword_alert_rules,
+                capture_all_monitors,
+                exclude_regions,
+                excluded_apps,
+                draft_retention_days
          FROM settings WHERE id = 1",
         )
         .map_err(AppError::from)?;
@@ -152,6 +196,93 @@ pub fn get_settings_sync() -> AppResult<Arc<Settings>> {
                 last_auto_backup_at: row.get("last_auto_backup_at")?,
                 // FEAT-008: Custom export template
                 custom_export_template: row.get("custom_export_template")?,
+                // Sensitive content skip
+                sensitive_filter_enabled: row
+                    .get::<_, Option<i32>>("sensitive_filter_enabled")?
+                    .map(|v| v != 0),
+                sensitive_keywords: row.get("sensitive_keywords")?,
+                // LLM payload logging
+                log_llm_payload: row
+                    .get::<_, Option<i32>>("log_llm_payload")?
+                    .map(|v| v != 0),
+                // Screenshot encoding format
+                screenshot_format: row.get("screenshot_format")?,
+                // API key rotation
+                api_keys: row.get("api_keys")?,
+                // Open summary after generate
+                open_summary_after_generate: row
+                    .get::<_, Option<i32>>("open_summary_after_generate")?
+                    .map(|v| v != 0),
+                // Startup delay before first capture
+                startup_delay_seconds: row.get("startup_delay_seconds")?,
+                // Day start hour
+                day_start_hour: row.get("day_start_hour")?,
+                // Failure alert aggregation
+                failure_alert_window_minutes: row.get("failure_alert_window_minutes")?,
+                failure_alert_webhook_url: row.get("failure_alert_webhook_url")?,
+                // Keyword based project classification
+                projects: row.get("projects")?,
+                // Capture interval in seconds
+                screenshot_interval_seconds: row.get("screenshot_interval_seconds")?,
+                // 调试：保存失败的分析请求
+                debug_dump_failed_requests: row
+                    .get::<_, Option<i32>>("debug_dump_failed_requests")?
+                    .map(|v| v != 0),
+                // 截图裁剪任务栏
+                crop_taskbar: row.get::<_, Option<i32>>("crop_taskbar")?.map(|v| v != 0),
+                taskbar_height: row.get("taskbar_height")?,
+                // 按软件类别的分析 prompt
+                category_prompts: row.get("category_prompts")?,
+                // 按端点选择分析图片格式
+                endpoint_image_formats: row.get("endpoint_image_formats")?,
+                // A/B 分析 prompt 实验
+                analysis_prompt_b: row.get("analysis_prompt_b")?,
+                prompt_b_percent: row.get("prompt_b_percent")?,
+                // AI 请求附加请求头
+                extra_headers: row.get("extra_headers")?,
+                // 无显示器时暂停感知
+                pause_capture_without_display: row
+                    .get::<_, Option<i32>>("pause_capture_without_display")?
+                    .map(|v| v != 0),
+                // 记录产生后执行的自定义命令
+                on_record_command: row.get("on_record_command")?,
+                // 软件名规范化
+                software_aliases: row.get("software_aliases")?,
+                // Stitched screenshot size cap
+                max_image_dimension: row.get("max_image_dimension")?,
+                // Screenshot timeout and retries
+                capture_timeout_secs: row.get("capture_timeout_secs")?,
+                capture_retries: row.get("capture_retries")?,
+                // Capture when the user returns from idle
+                capture_on_idle_wake: row
+                    .get::<_, Option<i32>>("capture_on_idle_wake")?
+                    .map(|v| v != 0),
+                // Sensitive content redaction
+                redact_sensitive_content: row
+                    .get::<_, Option<i32>>("redact_sensitive_content")?
+                    .map(|v| v != 0),
+                // Placeholder semantics version of the custom prompts
+                prompt_schema_version: row.get("prompt_schema_version")?,
+                // Scheduled daily summary
+                auto_summary_enabled: row
+                    .get::<_, Option<i32>>("auto_summary_enabled")?
+                    .map(|v| v != 0),
+                auto_summary_catch_up: row
+                    .get::<_, Option<i32>>("auto_summary_catch_up")?
+                    .map(|v| v != 0),
+                last_auto_summary_date: row.get("last_auto_summary_date")?,
+                // Keyword alerts
+                keyword_alert_rules: row.get("keyword_alert_rules")?,
+                // Multi-monitor capture
+                capture_all_monitors: row
+                    .get::<_, Option<i32>>("capture_all_monitors")?
+                    .map(|v| v != 0),
+                // Excluded screen regions
+                exclude_regions: row.get("exclude_regions")?,
+                // Excluded apps
+                excluded_apps: row.get("excluded_apps")?,
+                // Draft retention
+                draft_retention_days: row.get("draft_retention_days")?,
             })
         })
         .map_err(AppError::from)?;
@@ -175,7 +306,7 @@ pub fn get_settings_sync() -> AppResult<Arc<Settings>> {
                     match serde_json::from_str::<Vec<super::CustomHeader>>(custom_headers) {
                         Ok(mut headers) => {
                             for header in &mut headers {
-                                if header.sensitive
+                                if (header.sensitive || super::is_sensitive_header(&header.key))
                                     && !header.value.is_empty()
                                     && crypto::is_encrypted(&header.value)
                                 {
@@ -209,17 +340,103 @@ pub fn get_settings_sync() -> AppResult<Arc<Settings>> {
         settings
     };
 
-    let settings = Arc::new(settings);
+    // Decrypt rotated API keys (stored independently of the primary key)
+    let mut settings = settings;
+    if let Some(ref api_keys) = settings.api_keys {
+        settings.api_keys = Some(map_api_keys(api_keys, crypto::decrypt_api_key)?);
+    }
+    // Decrypt credential values of extra_headers
+    if let Some(ref extra_headers) = settings.extra_headers {
+        settings.extra_headers = Some(map_sensitive_extra_headers(
+            extra_headers,
+            crypto::decrypt_api_key,
+        )?);
+    }
 
-    // Update cache
+    Ok(settings)
+}
+
+/// Apply `f` to every non-empty key of an `api_keys` JSON array.
+/// Blank entries are dropped; unparseable JSON is kept as-is.
+fn map_api_keys(api_keys: &str, f: impl Fn(&str) -> AppResult<String>) -> AppResult<String> {
+    let Ok(keys) = serde_json::from_str::<Vec<String>>(api_keys) else {
+        tracing::error!("Failed to parse api_keys JSON");
+        return Ok(api_keys.to_string());
+    };
+    let mapped = keys
+        .iter()
+        .filter(|k| !k.trim().is_empty())
+        .map(|k| f(k.trim()))
+        .collect::<AppResult<Vec<_>>>()?;
+    Ok(serde_json::to_string(&mapped)?)
+}
+
+/// Apply `f` to the values of the credential headers (see
+/// [`super::is_sensitive_header`]) of an `extra_headers` JSON map.
+/// Unparseable JSON is kept as-is.
+fn map_sensitive_extra_headers(
+    extra_headers: &str,
+    f: impl Fn(&str) -> AppResult<String>,
+) -> AppResult<String> {
+    let Ok(mut headers) =
+        serde_json::from_str::<std::collections::BTreeMap<String, String>>(extra_headers)
+    else {
+        return Ok(extra_headers.to_string());
+    };
+    for (key, value) in headers.iter_mut() {
+        if super::is_sensitive_header(key) && !value.is_empty() {
+            *value = f(value)?;
+        }
+    }
+    Ok(serde_json::to_string(&headers)?)
+}
+
+pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    store_settings(conn, settings)?;
+
+    // Update cache with the saved (decrypted) settings
+    if let Ok(mut cache) = SETTINGS_CACHE.write() {
+        *cache = Some(Arc::new(settings.clone()));
+    }
+
+    tracing::info!("Settings saved");
+    Ok(())
+}
+
+/// Read-modify-write the settings in one transaction.
+///
+/// `f` is applied to the settings as currently stored (not the cache), so
+/// concurrent updates of other fields are not overwritten, unlike saving a
+/// clone taken earlier with [`save_settings_sync`]. The DB lock is held while
+/// `f` runs: it must not access the database. Returns the saved settings.
+pub fn update_settings_sync(f: impl FnOnce(&mut Settings)) -> AppResult<Arc<Settings>> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let tx = conn.unchecked_transaction()?;
+    let mut settings = load_settings(&tx)?;
+    f(&mut settings);
+    store_settings(&tx, &settings)?;
+    tx.commit()?;
+
+    let settings = Arc::new(settings);
     if let Ok(mut cache) = SETTINGS_CACHE.write() {
         *cache = Some(Arc::clone(&settings));
     }
 
+    tracing::info!("Settings updated");
     Ok(settings)
 }
 
-pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
+/// Encrypt the secrets of `settings` and write them to the settings row.
+fn store_settings(conn: &Connection, settings: &Settings) -> AppResult<()> {
     // Encrypt API key before saving
     let encrypted_api_key = if let Some(ref api_key) = settings.api_key {
         if !api_key.is_empty() && !crypto::is_encrypted(api_key) {
@@ -237,7 +454,7 @@ pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
             match serde_json::from_str::<Vec<super::CustomHeader>>(custom_headers) {
                 Ok(mut headers) => {
                     for header in &mut headers {
-                        if header.sensitive
+                        if (header.sensitive || super::is_sensitive_header(&header.key))
                             && !header.value.is_empty()
                             && !crypto::is_encrypted(&header.value)
                         {
@@ -278,6 +495,30 @@ pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
         None
     };
 
+    // Encrypt rotated API keys before saving
+    let encrypted_api_keys = match settings.api_keys {
+        Some(ref api_keys) => Some(map_api_keys(api_keys, |key| {
+            if crypto::is_encrypted(key) {
+                Ok(key.to_string())
+            } else {
+                crypto::encrypt_api_key(key)
+            }
+        })?),
+        None => None,
+    };
+
+    // Encrypt credential values of extra_headers before saving
+    let encrypted_extra_headers = match settings.extra_headers {
+        Some(ref extra_headers) => Some(map_sensitive_extra_headers(extra_headers, |value| {
+            if crypto::is_encrypted(value) {
+                Ok(value.to_string())
+            } else {
+                crypto::encrypt_api_key(value)
+            }
+        })?),
+        None => None,
+    };
+
     // AI-005: Auto-detect Ollama endpoint based on api_base_url
     let is_ollama = settings
         .api_base_url
@@ -285,11 +526,6 @@ pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
         .map(|url| crate::ollama::is_ollama_endpoint(url))
         .unwrap_or(false);
 
-    let db = DB_CONNECTION.lock()?;
-    let conn = db
-        .as_ref()
-        .ok_or_else(|| AppError::database("Database not initialized"))?;
-
     conn.execute(
         "UPDATE settings SET
             api_base_url = :api_base_url,
@@ -349,7 +585,44 @@ pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
             auto_backup_enabled = :auto_backup_enabled,
             auto_backup_interval = :auto_backup_interval,
             auto_backup_retention = :auto_backup_retention,
-            last_auto_backup_at = :last_auto_backup_at
+            last_auto_backup_at = :last_auto_backup_at,
+            sensitive_filter_enabled = :sensitive_filter_enabled,
+            sensitive_keywords = :sensitive_keywords,
+            log_llm_payload = :log_llm_payload,
+            screenshot_format = :screenshot_format,
+            api_keys = :api_keys,
+            open_summary_after_generate = :open_summary_after_generate,
+            startup_delay_seconds = :startup_delay_seconds,
+            day_start_hour = :day_start_hour,
+            failure_alert_window_minutes = :failure_alert_window_minutes,
+            failure_alert_webhook_url = :failure_alert_webhook_url,
+            projects = :projects,
+            screenshot_interval_seconds = :screenshot_interval_seconds,
+            debug_dump_failed_requests = :debug_dump_failed_requests,
+            crop_taskbar = :crop_taskbar,
+            taskbar_height = :taskbar_height,
+            category_prompts = :category_prompts,
+            endpoint_image_formats = :endpoint_image_formats,
+            analysis_prompt_b = :analysis_prompt_b,
+            prompt_b_percent = :prompt_b_percent,
+            extra_headers = :extra_headers,
+            pause_capture_without_display = :pause_capture_without_display,
+            on_record_command = :on_record_command,
+            software_aliases = :software_aliases,
+            max_image_dimension = :max_image_dimension,
+            capture_timeout_secs = :capture_timeout_secs,
+            capture_retries = :capture_retries,
+            capture_on_idle_wake = :capture_on_idle_wake,
+            redact_sensitive_content = :redact_sensitive_content,
+            prompt_schema_version = :prompt_schema_version,
+            auto_summary_enabled = :auto_summary_enabled,
+            auto_summary_catch_up = :auto_summary_catch_up,
+            last_auto_summary_date = :last_auto_summary_date,
+            keyword_alert_rules = :keyword_alert_rules,
+            capture_all_monitors = :capture_all_monitors,
+            exclude_regions = :exclude_regions,
+            excluded_apps = :excluded_apps,
+            draft_retention_days = :draft_retention_days
          WHERE id = 1",
         rusqlite::named_params! {
             ":api_base_url": settings.api_base_url,
@@ -410,16 +683,47 @@ pub fn save_settings_sync(settings: &Settings) -> AppResult<()> {
             ":auto_backup_interval": settings.auto_backup_interval,
             ":auto_backup_retention": settings.auto_backup_retention,
             ":last_auto_backup_at": settings.last_auto_backup_at,
+            ":sensitive_filter_enabled": settings.sensitive_filter_enabled.map(|v| if v { 1 } else { 0 }),
+            ":sensitive_keywords": settings.sensitive_keywords,
+            ":log_llm_payload": settings.log_llm_payload.map(|v| if v { 1 } else { 0 }),
+            ":screenshot_format": settings.screenshot_format,
+            ":api_keys": encrypted_api_keys,
+            ":open_summary_after_generate": settings.open_summary_after_generate.map(|v| if v { 1 } else { 0 }),
+            ":startup_delay_seconds": settings.startup_delay_seconds,
+            ":day_start_hour": settings.day_start_hour,
+            ":failure_alert_window_minutes": settings.failure_alert_window_minutes,
+            ":failure_alert_webhook_url": settings.failure_alert_webhook_url,
+            ":projects": settings.projects,
+            ":screenshot_interval_seconds": settings.screenshot_interval_seconds,
+            ":debug_dump_failed_requests": settings.debug_dump_failed_requests.map(|v| if v { 1 } else { 0 }),
+            ":crop_taskbar": settings.crop_taskbar.map(|v| if v { 1 } else { 0 }),
+            ":taskbar_height": settings.taskbar_height,
+            ":category_prompts": settings.category_prompts,
+            ":endpoint_image_formats": settings.endpoint_image_formats,
+            ":analysis_prompt_b": settings.analysis_prompt_b,
+            ":prompt_b_percent": settings.prompt_b_percent,
+            ":extra_headers": encrypted_extra_headers,
+            ":pause_capture_without_display": settings.pause_capture_without_display.map(|v| if v { 1 } else { 0 }),
+            ":on_record_command": settings.on_record_command,
+            ":software_aliases": settings.software_aliases,
+            ":max_image_dimension": settings.max_image_dimension,
+            ":capture_timeout_secs": settings.capture_timeout_secs,
+            ":capture_retries": settings.capture_retries,
+            ":capture_on_idle_wake": settings.capture_on_idle_wake.map(|v| if v { 1 } else { 0 }),
+            ":redact_sensitive_content": settings.redact_sensitive_content.map(|v| if v { 1 } else { 0 }),
+            ":prompt_schema_version": settings.prompt_schema_version,
+            ":auto_summary_enabled": settings.auto_summary_enabled.map(|v| if v { 1 } else { 0 }),
+            ":auto_summary_catch_up": settings.auto_summary_catch_up.map(|v| if v { 1 } else { 0 }),
+            ":last_auto_summary_date": settings.last_auto_summary_date,
+            ":keyword_alert_rules": settings.keyword_alert_rules,
+            ":capture_all_monitors": settings.capture_all_monitors.map(|v| if v { 1 } else { 0 }),
+            ":exclude_regions": settings.exclude_regions,
+            ":excluded_apps": settings.excluded_apps,
+            ":draft_retention_days": settings.draft_retention_days,
         },
     )
     .map_err(AppError::from)?;
 
-    // Update cache with the saved (decrypted) settings
-    if let Ok(mut cache) = SETTINGS_CACHE.write() {
-        *cache = Some(Arc::new(settings.clone()));
-    }
-
-    tracing::info!("Settings saved");
     Ok(())
 }
 
@@ -434,3 +738,26 @@ pub async fn get_settings() -> AppResult<Arc<Settings>> {
 pub async fn save_settings(settings: Settings) -> AppResult<()> {
     save_settings_sync(&settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_credential_extra_headers_are_mapped() {
+        let json =
+            r#"{"Authorization":"Bearer gw","User-Agent":"DailyLogger","X-Gateway-Token":"t"}"#;
+        let mapped =
+            map_sensitive_extra_headers(json, |value| Ok(format!("ENC:{}", value))).unwrap();
+        let headers: std::collections::BTreeMap<String, String> =
+            serde_json::from_str(&mapped).unwrap();
+        assert_eq!(headers["Authorization"], "ENC:Bearer gw");
+        assert_eq!(headers["X-Gateway-Token"], "ENC:t");
+        assert_eq!(headers["User-Agent"], "DailyLogger");
+
+        assert_eq!(
+            map_sensitive_extra_headers("not json", |_| unreachable!()).unwrap(),
+            "not json"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Software name normalization.
+//!
+//! Window titles and vision analysis report the same program under many
+//! names ("Code", "code.exe", "Visual Studio Code"). Auto records store a
+//! canonical name in `records.software_normalized` so statistics group them
+//! together. Built-in aliases cover common programs; the `software_aliases`
+//! setting (a JSON object of alias → canonical name) extends or overrides them.
+//! The column is recomputed when an auto record's content changes and for all
+//! auto records when the aliases change.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::errors::{AppError, AppResult};
+
+use super::DB_CONNECTION;
+
+/// Built-in alias → canonical name pairs. Aliases are compared by [`alias_key`].
+pub const BUILTIN_SOFTWARE_ALIASES: &[(&str, &str)] = &[
+    ("code", "VS Code"),
+    ("vscode", "VS Code"),
+    ("visualstudiocode", "VS Code"),
+    ("chrome", "Chrome"),
+    ("googlechrome", "Chrome"),
+    ("msedge", "Edge"),
+    ("microsoftedge", "Edge"),
+    ("firefox", "Firefox"),
+    ("mozillafirefox", "Firefox"),
+    ("safari", "Safari"),
+    ("idea", "IntelliJ IDEA"),
+    ("idea64", "IntelliJ IDEA"),
+    ("intellijidea", "IntelliJ IDEA"),
+    ("pycharm", "PyCharm"),
+    ("pycharm64", "PyCharm"),
+    ("terminal", "Terminal"),
+    ("iterm", "iTerm2"),
+    ("iterm2", "iTerm2"),
+    ("windowsterminal", "Windows Terminal"),
+    ("wt", "Windows Terminal"),
+    ("wechat", "WeChat"),
+    ("weixin", "WeChat"),
+    ("微信", "WeChat"),
+    ("slack", "Slack"),
+    ("feishu", "Feishu"),
+    ("lark", "Feishu"),
+    ("飞书", "Feishu"),
+    ("dingtalk", "DingTalk"),
+    ("钉钉", "DingTalk"),
+    ("winword", "Word"),
+    ("microsoftword", "Word"),
+    ("excel", "Excel"),
+    ("microsoftexcel", "Excel"),
+    ("explorer", "File Explorer"),
+    ("finder", "Finder"),
+];
+
+/// Comparison key of a software name: lowercase, without spaces, `-`, `_`
+/// and a trailing `.exe`.
+fn alias_key(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    let lower = lower.strip_suffix(".exe").unwrap_or(&lower);
+    lower
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .collect()
+}
+
+/// Parse the `software_aliases` setting into alias key → canonical name.
+/// Invalid JSON, non-string values and blank entries are ignored.
+pub fn parse_software_aliases(json: Option<&str>) -> HashMap<String, String> {
+    let Some(json) = json.filter(|j| !j.trim().is_empty()) else {
+        return HashMap::new();
+    };
+    let map: HashMap<String, serde_json::Value> = match serde_json::from_str(json) {
+        Ok(map) => map,
+        Err(e) => {
+            tracing::warn!("Ignoring invalid software_aliases setting: {}", e);
+            return HashMap::new();
+        }
+    };
+    map.into_iter()
+        .filter_map(|(alias, canonical)| {
+            let canonical = canonical.as_str()?.trim().to_string();
+            let key = alias_key(&alias);
+            (!key.is_empty() && !canonical.is_empty()).then_some((key, canonical))
+        })
+        .collect()
+}
+
+fn normalize_one(name: &str, user_aliases: &HashMap<String, String>) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let key = alias_key(name);
+    if let Some(canonical) = user_aliases.get(&key) {
+        return Some(canonical.clone());
+    }
+    BUILTIN_SOFTWARE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, canonical)| canonical.to_string())
+        .or_else(|| Some(name.to_string()))
+}
+
+/// Canonical name of a software name. User aliases take precedence over the
+/// built-in ones; unknown names are returned trimmed but otherwise unchanged.
+/// A comma separated list is normalized per entry with duplicates removed.
+/// Returns `None` for a blank name.
+pub fn normalize_software_name(
+    name: &str,
+    user_aliases: &HashMap<String, String>,
+) -> Option<String> {
+    let mut names: Vec<String> = Vec::new();
+    for part in name.split([',', '，']) {
+        if let Some(canonical) = normalize_one(part, user_aliases) {
+            if !names.contains(&canonical) {
+                names.push(canonical);
+            }
+        }
+    }
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// Normalized `active_software` of an auto record's content.
+/// Reads settings, so it must not be called while holding the DB lock.
+pub(crate) fn software_for_content(content: &str) -> Option<String> {
+    let software = super::corrections::active_software_from_content(content)?;
+    let user_aliases = super::get_settings_sync()
+        .map(|settings| parse_software_aliases(settings.software_aliases.as_deref()))
+        .unwrap_or_default();
+    normalize_software_name(&software, &user_aliases)
+}
+
+/// Recompute `software_normalized` of auto records from their content, only
+/// for records without a value when `only_missing`. Returns the number changed.
+fn refresh_software_normalized(
+    conn: &Connection,
+    user_aliases: &HashMap<String, String>,
+    only_missing: bool,
+) -> AppResult<usize> {
+    let sql = if only_missing {
+        "SELECT id, content, software_normalized FROM records
+         WHERE source_type = 'auto' AND software_normalized IS NULL"
+    } else {
+        "SELECT id, content, software_normalized FROM records WHERE source_type = 'auto'"
+    };
+    let changed: Vec<(i64, Option<String>)> = {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        let mut changed = Vec::new();
+        for row in rows {
+            let (id, content, current) = row?;
+            let normalized = super::corrections::active_software_from_content(&content)
+                .and_then(|software| normalize_software_name(&software, user_aliases));
+            if normalized != current {
+                changed.push((id, normalized));
+            }
+        }
+        changed
+    };
+    if changed.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE records SET software_normalized = ?1 WHERE id = ?2")?;
+        for (id, normalized) in &changed {
+            stmt.execute(params![normalized, id])?;
+        }
+    }
+    tx.commit()?;
+    tracing::debug!("Normalized software of {} records", changed.len());
+    Ok(changed.len())
+}
+
+/// Normalize the software of auto records written before the column existed.
+/// Aliases are read from `conn` since this runs before the connection is shared.
+pub(super) fn backfill_software_normalized(conn: &Connection) -> AppResult<usize> {
+    let aliases: Option<String> = conn
+        .query_row(
+            "SELECT software_aliases FROM settings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    refresh_software_normalized(conn, &parse_software_aliases(aliases.as_deref()), true)
+}
+
+/// Recompute the normalized software of every auto record with the current
+/// `software_aliases` setting. Returns the number of records changed.
+pub fn renormalize_software_sync() -> AppResult<usize> {
+    let user_aliases =
+        parse_software_aliases(super::get_settings_sync()?.software_aliases.as_deref());
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+    refresh_software_normalized(conn, &user_aliases, false)
+}
+
+/// Stored normalized software of each of `record_ids`; records without one are left out.
+pub fn get_software_normalized_for_records(
+    record_ids: Vec<i64>,
+) -> AppResult<HashMap<i64, String>> {
+    if record_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let placeholders = vec!["?"; record_ids.len()].join(",");
+    let sql = format!(
+        "SELECT id, software_normalized FROM records
+         WHERE software_normalized IS NOT NULL AND id IN ({})",
+        placeholders
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let software = stmt
+        .query_map(rusqlite::params_from_iter(record_ids.iter()), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(software)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_aliases_ignore_case_spacing_and_exe_suffix() {
+        let none = HashMap::new();
+        assert_eq!(
+            normalize_software_name("Code", &none).as_deref(),
+            Some("VS Code")
+        );
+        assert_eq!(
+            normalize_software_name("code.exe", &none).as_deref(),
+            Some("VS Code")
+        );
+        assert_eq!(
+            normalize_software_name(" Visual Studio Code ", &none).as_deref(),
+            Some("VS Code")
+        );
+        assert_eq!(
+            normalize_software_name("Google-Chrome", &none).as_deref(),
+            Some("Chrome")
+        );
+    }
+
+    #[test]
+    fn unknown_and_blank_names() {
+        let none = HashMap::new();
+        assert_eq!(
+            normalize_software_name("  Obsidian ", &none).as_deref(),
+            Some("Obsidian")
+        );
+        assert_eq!(normalize_software_name("   ", &none), None);
+    }
+
+    #[test]
+    fn user_aliases_override_builtin_ones() {
+        let user = parse_software_aliases(Some(
+            r#"{"Code": "Visual Studio Code", "obsidian.exe": "Obsidian", "bad": 1, "": "x"}"#,
+        ));
+        assert_eq!(user.len(), 2);
+        assert_eq!(
+            normalize_software_name("code", &user).as_deref(),
+            Some("Visual Studio Code")
+        );
+        assert_eq!(
+            normalize_software_name("OBSIDIAN", &user).as_deref(),
+            Some("Obsidian")
+        );
+        assert_eq!(
+            normalize_software_name("chrome", &user).as_deref(),
+            Some("Chrome")
+        );
+    }
+
+    #[test]
+    fn invalid_alias_setting_is_ignored() {
+        assert!(parse_software_aliases(None).is_empty());
+        assert!(parse_software_aliases(Some("not json")).is_empty());
+        assert!(parse_software_aliases(Some("[]")).is_empty());
+    }
+
+    #[test]
+    fn lists_are_normalized_per_entry_without_duplicates() {
+        let none = HashMap::new();
+        assert_eq!(
+            normalize_software_name("code, VSCode，chrome.exe", &none).as_deref(),
+            Some("VS Code, Chrome")
+        );
+    }
+
+    fn software_table() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE settings (id INTEGER PRIMARY KEY, software_aliases TEXT);
+             INSERT INTO settings (id, software_aliases) VALUES (1, '{"obsidian.exe": "Obsidian"}');
+             CREATE TABLE records (id INTEGER PRIMARY KEY, source_type TEXT NOT NULL,
+                 content TEXT NOT NULL, software_normalized TEXT);
+             INSERT INTO records (source_type, content) VALUES
+                 ('auto', '{"active_software":"code.exe"}'),
+                 ('auto', '{"active_software":"obsidian.exe"}'),
+                 ('manual', '{"active_software":"code.exe"}');"#,
+        )
+        .unwrap();
+        conn
+    }
+
+    fn stored(conn: &Connection) -> Vec<Option<String>> {
+        conn.prepare("SELECT software_normalized FROM records ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn backfill_uses_the_stored_aliases_and_skips_manual_records() {
+        let conn = software_table();
+
+        assert_eq!(backfill_software_normalized(&conn).unwrap(), 2);
+        assert_eq!(
+            stored(&conn),
+            vec![
+                Some("VS Code".to_string()),
+                Some("Obsidian".to_string()),
+                None
+            ]
+        );
+        assert_eq!(backfill_software_normalized(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn refresh_applies_changed_aliases_to_existing_records() {
+        let conn = software_table();
+        backfill_software_normalized(&conn).unwrap();
+
+        let aliases = parse_software_aliases(Some(r#"{"code": "Visual Studio Code"}"#));
+        assert_eq!(
+            refresh_software_normalized(&conn, &aliases, false).unwrap(),
+            2
+        );
+        assert_eq!(
+            stored(&conn),
+            vec![
+                Some("Visual Studio Code".to_string()),
+                Some("obsidian.exe".to_string()),
+                None
+            ]
+        );
+    }
+}
@@ -65,7 +65,10 @@ fn normalize_tag_name(tag: &str) -> Option<String> {
     }
 }
 
-fn extract_tags_from_record_sources(stored_tags: Option<&str>, content: &str) -> Vec<String> {
+pub(crate) fn extract_tags_from_record_sources(
+    stored_tags: Option<&str>,
+    content: &str,
+) -> Vec<String> {
     let mut seen = HashSet::new();
     let mut result = Vec::new();
 
@@ -164,6 +167,142 @@ pub fn get_all_tags() -> AppResult<Vec<String>> {
     Ok(result)
 }
 
+/// Keywords of one record: `context_keywords` of auto records and `tags` of
+/// manual records.
+fn record_keywords(source_type: &str, tags: Option<&str>, content: &str) -> Vec<String> {
+    match source_type {
+        "auto" => super::context_keywords_from_content(content),
+        "manual" => tags
+            .and_then(|t| serde_json::from_str::<Vec<String>>(t).ok())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Autocomplete vocabulary from `(source_type, tags, content)` record rows:
+/// `context_keywords` of auto records and `tags` of manual records.
+///
+/// Keywords are trimmed and deduplicated case-insensitively (the first seen
+/// spelling is kept), then ordered by frequency, ties alphabetically.
+pub fn rank_keywords<'a>(
+    rows: impl IntoIterator<Item = (&'a str, Option<&'a str>, &'a str)>,
+) -> Vec<String> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for (source_type, tags, content) in rows {
+        for keyword in record_keywords(source_type, tags, content) {
+            let keyword = keyword.trim();
+            if keyword.is_empty() {
+                continue;
+            }
+            counts
+                .entry(keyword.to_lowercase())
+                .or_insert_with(|| (keyword.to_string(), 0))
+                .1 += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = counts.into_values().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.into_iter().map(|(keyword, _)| keyword).collect()
+}
+
+/// Two keywords that appeared in the same record, and in how many records.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeywordEdge {
+    pub source: String,
+    pub target: String,
+    pub weight: usize,
+}
+
+/// Keyword co-occurrence edges from `(source_type, tags, content)` record rows.
+///
+/// Keywords are matched case-insensitively (the first seen spelling is kept)
+/// and counted once per record, so an edge's weight is the number of records
+/// containing both keywords. Within an edge `source` sorts before `target`
+/// (case-insensitively); edges are ordered by weight, ties alphabetically.
+pub fn keyword_cooccurrence<'a>(
+    rows: impl IntoIterator<Item = (&'a str, Option<&'a str>, &'a str)>,
+) -> Vec<KeywordEdge> {
+    let mut spellings: HashMap<String, String> = HashMap::new();
+    let mut weights: HashMap<(String, String), usize> = HashMap::new();
+    for (source_type, tags, content) in rows {
+        let mut keys: Vec<String> = Vec::new();
+        for keyword in record_keywords(source_type, tags, content) {
+            let keyword = keyword.trim();
+            if keyword.is_empty() {
+                continue;
+            }
+            let key = keyword.to_lowercase();
+            spellings
+                .entry(key.clone())
+                .or_insert_with(|| keyword.to_string());
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                *weights.entry((a.clone(), b.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut edges: Vec<((String, String), usize)> = weights.into_iter().collect();
+    edges.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    edges
+        .into_iter()
+        .map(|((a, b), weight)| KeywordEdge {
+            source: spellings[&a].clone(),
+            target: spellings[&b].clone(),
+            weight,
+        })
+        .collect()
+}
+
+/// Keyword co-occurrence network of the records between two local dates
+/// (YYYY-MM-DD, inclusive), as an edge list
+#[command]
+pub fn get_keyword_cooccurrence(
+    start_date: String,
+    end_date: String,
+) -> AppResult<Vec<KeywordEdge>> {
+    let records = super::get_records_by_date_range_sync(start_date, end_date)?;
+    Ok(keyword_cooccurrence(records.iter().map(|r| {
+        (
+            r.source_type.as_str(),
+            r.tags.as_deref(),
+            r.content.as_str(),
+        )
+    })))
+}
+
+/// Keyword vocabulary for search autocomplete, most frequent first
+#[command]
+pub fn get_all_keywords() -> AppResult<Vec<String>> {
+    let db_guard = DB_CONNECTION.lock()?;
+    let conn = db_guard
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT source_type, tags, content FROM records WHERE source_type IN ('auto', 'manual')",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rank_keywords(rows.iter().map(|(source, tags, content)| {
+        (source.as_str(), tags.as_deref(), content.as_str())
+    })))
+}
+
 /// Get tag cloud data from record tags (AI tags + stored tags).
 #[command]
 pub fn get_tag_cloud_tags() -> AppResult<Vec<TagCloudTag>> {
@@ -215,7 +354,7 @@ pub fn get_records_by_tag(tag: String) -> AppResult<Vec<Record>> {
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status
+        "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status
          FROM records
          ORDER BY timestamp DESC",
     )?;
@@ -233,6 +372,8 @@ pub fn get_records_by_tag(tag: String) -> AppResult<Vec<Record>> {
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .filter_map(|r| r.ok())
@@ -662,6 +803,119 @@ pub fn get_tags_for_records(record_ids: Vec<i64>) -> AppResult<HashMap<i64, Vec<
     Ok(result)
 }
 
+/// 批量为多条记录追加标签（写入 records.tags，事务内执行，自动去重）
+#[command]
+pub fn tag_records(ids: Vec<i64>, tags: Vec<String>) -> AppResult<()> {
+    if ids.is_empty() {
+        return Err(AppError::validation("记录 ID 列表不能为空"));
+    }
+
+    let new_tags: Vec<String> = tags.iter().filter_map(|t| normalize_tag_name(t)).collect();
+    if new_tags.is_empty() {
+        return Err(AppError::validation("标签列表不能为空"));
+    }
+
+    let db_guard = DB_CONNECTION.lock()?;
+    let conn = db_guard
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let tx = conn.unchecked_transaction()?;
+    for id in &ids {
+        let stored: Option<Option<String>> = tx
+            .query_row(
+                "SELECT tags FROM records WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(stored) = stored else {
+            return Err(AppError::validation(format!("记录 ID {} 不存在", id)));
+        };
+
+        let merged = merge_record_tags(stored.as_deref(), &new_tags);
+        tx.execute(
+            "UPDATE records SET tags = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&merged)?, id],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// 将新标签合并进已存储的标签 JSON 数组，保持原有顺序并去重
+fn merge_record_tags(stored_tags: Option<&str>, new_tags: &[String]) -> Vec<String> {
+    let existing: Vec<String> = stored_tags
+        .and_then(|s| serde_json::from_str::<Vec<String>>(s).ok())
+        .unwrap_or_default();
+
+    let mut seen = HashSet::new();
+    existing
+        .iter()
+        .chain(new_tags.iter())
+        .filter_map(|t| normalize_tag_name(t))
+        .filter(|t| seen.insert(t.clone()))
+        .collect()
+}
+
+/// 批量从多条记录移除标签（只改 records.tags，其他标签保留，事务内执行）
+#[command]
+pub fn untag_records(ids: Vec<i64>, tags: Vec<String>) -> AppResult<()> {
+    if ids.is_empty() {
+        return Err(AppError::validation("记录 ID 列表不能为空"));
+    }
+
+    let removed: HashSet<String> = tags.iter().filter_map(|t| normalize_tag_name(t)).collect();
+    if removed.is_empty() {
+        return Err(AppError::validation("标签列表不能为空"));
+    }
+
+    let db_guard = DB_CONNECTION.lock()?;
+    let conn = db_guard
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let tx = conn.unchecked_transaction()?;
+    for id in &ids {
+        let stored: Option<Option<String>> = tx
+            .query_row(
+                "SELECT tags FROM records WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(stored) = stored else {
+            return Err(AppError::validation(format!("记录 ID {} 不存在", id)));
+        };
+
+        let remaining = remove_record_tags(stored.as_deref(), &removed);
+        let remaining = if remaining.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&remaining)?)
+        };
+        tx.execute(
+            "UPDATE records SET tags = ?1 WHERE id = ?2",
+            params![remaining, id],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// 从已存储的标签 JSON 数组中去掉指定标签，其余标签保持原有顺序
+fn remove_record_tags(stored_tags: Option<&str>, removed: &HashSet<String>) -> Vec<String> {
+    stored_tags
+        .and_then(|s| serde_json::from_str::<Vec<String>>(s).ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|t| normalize_tag_name(t))
+        .filter(|t| !removed.contains(t))
+        .collect()
+}
+
 /// 按多个标签筛选记录（交集 AND 逻辑）
 #[command]
 pub fn get_records_by_manual_tags(
@@ -684,7 +938,7 @@ pub fn get_records_by_manual_tags(
 
     // 交集筛选：找出同时包含所有指定标签的记录
     let sql = format!(
-        "SELECT r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status
+        "SELECT r.id, r.timestamp, r.source_type, r.content, r.screenshot_path, r.monitor_info, r.tags, r.user_notes, r.session_id, r.analysis_status, r.metadata, r.status
          FROM records r
          WHERE r.id IN (
              SELECT record_id FROM record_manual_tags
@@ -726,6 +980,8 @@ pub fn get_records_by_manual_tags(
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -746,6 +1002,61 @@ mod tests {
         crate::memory_storage::setup_test_db_with_schema();
     }
 
+    #[test]
+    fn rank_keywords_merges_auto_keywords_and_manual_tags_by_frequency() {
+        let rows = [
+            ("auto", None, r#"{"context_keywords":["Rust","Tauri"]}"#),
+            (
+                "auto",
+                None,
+                r#"{"context_keywords":["rust "," ","SQLite"]}"#,
+            ),
+            ("manual", Some(r#"["tauri","Rust"]"#), "笔记"),
+            ("manual", Some("not json"), "笔记"),
+            ("auto", None, "plain text"),
+        ];
+
+        assert_eq!(rank_keywords(rows), vec!["Rust", "Tauri", "SQLite"]);
+        assert!(rank_keywords([]).is_empty());
+    }
+
+    #[test]
+    fn keyword_cooccurrence_counts_pairs_per_record() {
+        let rows = [
+            (
+                "auto",
+                None,
+                r#"{"context_keywords":["Rust","Tauri","SQLite"]}"#,
+            ),
+            (
+                "auto",
+                None,
+                r#"{"context_keywords":["tauri","rust","rust"]}"#,
+            ),
+            ("manual", Some(r#"["Rust","会议"]"#), "讨论"),
+            ("auto", None, r#"{"context_keywords":["单独"]}"#),
+            ("auto", None, "not json"),
+        ];
+
+        let edges = keyword_cooccurrence(rows);
+
+        let edge = |source: &str, target: &str, weight| KeywordEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            weight,
+        };
+        assert_eq!(
+            edges,
+            vec![
+                edge("Rust", "Tauri", 2),
+                edge("Rust", "SQLite", 1),
+                edge("Rust", "会议", 1),
+                edge("SQLite", "Tauri", 1),
+            ]
+        );
+        assert!(keyword_cooccurrence([]).is_empty());
+    }
+
     #[test]
     #[serial]
     fn get_default_tag_categories_returns_expected_tags() {
@@ -1245,4 +1556,113 @@ mod tests {
         assert!(result.contains_key(&record1));
         assert!(!result.contains_key(&record2));
     }
+
+    #[test]
+    #[serial]
+    fn tag_records_appends_tag_to_every_record() {
+        setup_test_db();
+
+        let record1 = add_record("manual", "record 1", None, None, None).unwrap();
+        let record2 = add_record("manual", "record 2", None, None, Some(r#"["开发"]"#)).unwrap();
+        let record3 = add_record("manual", "record 3", None, None, Some(r#"["会议"]"#)).unwrap();
+
+        tag_records(
+            vec![record1, record2, record3],
+            vec!["开发".to_string(), " 评审 ".to_string()],
+        )
+        .unwrap();
+
+        for id in [record1, record2, record3] {
+            let tagged = get_records_by_tag("评审".to_string()).unwrap();
+            assert!(
+                tagged.iter().any(|r| r.id == id),
+                "record {} missing tag",
+                id
+            );
+        }
+
+        // Existing tags are kept and duplicates are not added twice
+        let records = get_records_by_tag("开发".to_string()).unwrap();
+        let record2_tags = records
+            .iter()
+            .find(|r| r.id == record2)
+            .and_then(|r| r.tags.clone())
+            .unwrap();
+        assert_eq!(record2_tags, r#"["开发","评审"]"#);
+        let record3_tags: Vec<String> = get_records_by_tag("会议".to_string())
+            .unwrap()
+            .into_iter()
+            .filter_map(|r| r.tags)
+            .flat_map(|t| serde_json::from_str::<Vec<String>>(&t).unwrap())
+            .collect();
+        assert_eq!(record3_tags, vec!["会议", "开发", "评审"]);
+    }
+
+    #[test]
+    #[serial]
+    fn tag_records_rejects_empty_ids() {
+        setup_test_db();
+
+        let result = tag_records(vec![], vec!["开发".to_string()]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn tag_records_rolls_back_when_a_record_is_missing() {
+        setup_test_db();
+
+        let record = add_record("manual", "record", None, None, None).unwrap();
+        let result = tag_records(vec![record, record + 1000], vec!["开发".to_string()]);
+        assert!(result.is_err());
+
+        let tagged = get_records_by_tag("开发".to_string()).unwrap();
+        assert!(tagged.is_empty(), "partial update should be rolled back");
+    }
+
+    #[test]
+    #[serial]
+    fn untag_records_removes_only_the_given_tags() {
+        setup_test_db();
+
+        let record1 = add_record(
+            "manual",
+            "record 1",
+            None,
+            None,
+            Some(r#"["开发","评审","会议"]"#),
+        )
+        .unwrap();
+        let record2 = add_record("manual", "record 2", None, None, Some(r#"["评审"]"#)).unwrap();
+        let record3 = add_record("manual", "record 3", None, None, Some(r#"["评审"]"#)).unwrap();
+
+        untag_records(
+            vec![record1, record2],
+            vec![" 评审 ".to_string(), "会议".to_string(), "学习".to_string()],
+        )
+        .unwrap();
+
+        let tags_of = |id: i64| {
+            crate::memory_storage::get_record_by_id_sync(id)
+                .unwrap()
+                .tags
+        };
+        assert_eq!(tags_of(record1).as_deref(), Some(r#"["开发"]"#));
+        assert_eq!(tags_of(record2), None);
+        // Records not in the list keep the tag
+        assert_eq!(tags_of(record3).as_deref(), Some(r#"["评审"]"#));
+    }
+
+    #[test]
+    #[serial]
+    fn untag_records_rolls_back_when_a_record_is_missing() {
+        setup_test_db();
+
+        let record = add_record("manual", "record", None, None, Some(r#"["开发"]"#)).unwrap();
+        assert!(untag_records(vec![record], vec![]).is_err());
+        assert!(untag_records(vec![record, record + 1000], vec!["开发".to_string()]).is_err());
+
+        let tagged = get_records_by_tag("开发".to_string()).unwrap();
+        assert_eq!(tagged.len(), 1, "partial update should be rolled back");
+    }
 }
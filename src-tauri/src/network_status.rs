@@ -66,6 +66,19 @@ pub fn start_network_monitor(app: tauri::AppHandle) {
             "Network monitor started (interval: {}s)",
             CHECK_INTERVAL_SECS
         );
+
+        // Resume tasks queued before the last shutdown
+        let pending = crate::offline_queue::get_pending_count().unwrap_or(0);
+        if pending > 0 && check_connectivity().await {
+            tracing::info!("Resuming {} offline task(s) from a previous run", pending);
+            crate::offline_queue::process_queue().await;
+
+            use tauri::Emitter;
+            if let Ok(status) = crate::offline_queue::get_offline_queue_status() {
+                let _ = app.emit("offline-queue-updated", status);
+            }
+        }
+
         loop {
             tokio::time::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
 
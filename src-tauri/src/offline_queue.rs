@@ -61,6 +61,13 @@ pub struct OfflineTask {
     pub max_retries: i32,
 }
 
+/// Payload of a queued `ScreenshotAnalysis` task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenshotAnalysisPayload {
+    pub screenshot_path: String,
+    pub record_id: i64,
+}
+
 /// Maximum retries for a queued task
 const DEFAULT_MAX_RETRIES: i32 = 5;
 
@@ -124,6 +131,41 @@ pub fn enqueue_task(
     Ok(id)
 }
 
+/// Queue a failed screenshot analysis so it is retried once the network is back,
+/// including after an application restart.
+/// Returns `None` when the record already has a pending analysis task.
+pub fn enqueue_screenshot_analysis(
+    screenshot_path: &str,
+    record_id: i64,
+) -> AppResult<Option<i64>> {
+    {
+        let db = DB_CONNECTION.lock().map_err(AppError::from)?;
+        let conn = db
+            .as_ref()
+            .ok_or_else(|| AppError::database("Database not initialized"))?;
+        let already_queued: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM offline_queue
+             WHERE task_type = ?1 AND record_id = ?2 AND status = 'pending'",
+            params![OfflineTaskType::ScreenshotAnalysis.to_string(), record_id],
+            |row| row.get(0),
+        )?;
+        if already_queued > 0 {
+            return Ok(None);
+        }
+    }
+
+    let payload = serde_json::to_string(&ScreenshotAnalysisPayload {
+        screenshot_path: screenshot_path.to_string(),
+        record_id,
+    })?;
+    enqueue_task(
+        &OfflineTaskType::ScreenshotAnalysis,
+        &payload,
+        Some(record_id),
+    )
+    .map(Some)
+}
+
 /// Get all pending tasks from the queue, ordered by creation time (oldest first).
 pub fn get_pending_tasks() -> AppResult<Vec<OfflineTask>> {
     let db = DB_CONNECTION.lock().map_err(AppError::from)?;
@@ -175,8 +217,9 @@ pub fn mark_task_completed(task_id: i64) -> AppResult<()> {
     Ok(())
 }
 
-/// Mark a task as failed, incrementing the retry count.
-pub fn mark_task_failed(task_id: i64, error: &str) -> AppResult<()> {
+/// Mark a task as failed, incrementing the retry count. Returns whether the
+/// task has now used up its retries and failed permanently.
+pub fn mark_task_failed(task_id: i64, error: &str) -> AppResult<bool> {
     let db = DB_CONNECTION.lock().map_err(AppError::from)?;
     let conn = db
         .as_ref()
@@ -210,9 +253,10 @@ pub fn mark_task_failed(task_id: i64, error: &str) -> AppResult<()> {
             task_id,
             retry_count
         );
+        return Ok(true);
     }
 
-    Ok(())
+    Ok(false)
 }
 
 /// Get the count of pending tasks in the queue.
@@ -336,7 +380,13 @@ pub async fn process_queue() {
             }
             Err(e) => {
                 tracing::warn!("Queued task {} failed: {}", task.id, e);
-                let _ = mark_task_failed(task.id, &e.to_string());
+                let gave_up = mark_task_failed(task.id, &e.to_string()).unwrap_or(false);
+                // The analysis will not be stored, so the record's hook runs now
+                if gave_up && task_type == OfflineTaskType::ScreenshotAnalysis {
+                    if let Some(record_id) = task.record_id {
+                        crate::record_hook::run_on_stored_record_hook(record_id);
+                    }
+                }
             }
         }
     }
@@ -364,18 +414,13 @@ async fn execute_queued_task(
             #[cfg(feature = "screenshot")]
             {
                 // Parse payload to get screenshot_path and record_id
-                #[derive(serde::Deserialize)]
-                struct ScreenshotPayload {
-                    screenshot_path: String,
-                    record_id: i64,
-                }
-
-                let parsed: ScreenshotPayload = serde_json::from_str(payload).map_err(|e| {
-                    AppError::validation(format!(
-                        "Failed to parse ScreenshotAnalysis payload: {}",
-                        e
-                    ))
-                })?;
+                let parsed: ScreenshotAnalysisPayload =
+                    serde_json::from_str(payload).map_err(|e| {
+                        AppError::validation(format!(
+                            "Failed to parse ScreenshotAnalysis payload: {}",
+                            e
+                        ))
+                    })?;
 
                 crate::services::retry_screenshot_analysis_service(
                     &parsed.screenshot_path,
@@ -393,7 +438,7 @@ async fn execute_queued_task(
             Ok(())
         }
         OfflineTaskType::DailySummary => {
-            crate::services::report_service::generate_daily_summary_service(None)
+            crate::services::report_service::generate_daily_summary_service(None, false)
                 .await
                 .map(|_| ())
         }
@@ -512,9 +557,10 @@ mod tests {
 
         let id = enqueue_task(&OfflineTaskType::DailySummary, "{}", None).unwrap();
 
-        // Exhaust all retries
-        for _ in 0..DEFAULT_MAX_RETRIES {
-            mark_task_failed(id, "still failing").unwrap();
+        // Exhaust all retries; only the last one gives up
+        for attempt in 1..=DEFAULT_MAX_RETRIES {
+            let gave_up = mark_task_failed(id, "still failing").unwrap();
+            assert_eq!(gave_up, attempt == DEFAULT_MAX_RETRIES);
         }
 
         // Should no longer appear in pending tasks
@@ -576,4 +622,70 @@ mod tests {
         let cleaned = cleanup_old_tasks().unwrap();
         assert!(cleaned >= 1);
     }
+
+    fn insert_record() -> i64 {
+        let db = DB_CONNECTION.lock().unwrap();
+        let conn = db.as_ref().unwrap();
+        conn.execute(
+            "INSERT INTO records (timestamp, source_type, content) VALUES (?1, 'auto', 'test')",
+            params![chrono::Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    #[serial]
+    fn test_enqueue_screenshot_analysis_skips_duplicates() {
+        setup_test_db();
+        let record_id = insert_record();
+
+        let id = enqueue_screenshot_analysis("/tmp/shot.png", record_id).unwrap();
+        assert!(id.is_some());
+        assert_eq!(
+            enqueue_screenshot_analysis("/tmp/shot.png", record_id).unwrap(),
+            None
+        );
+
+        let tasks = get_pending_tasks().unwrap();
+        assert_eq!(tasks.len(), 1);
+        let payload: ScreenshotAnalysisPayload = serde_json::from_str(&tasks[0].payload).unwrap();
+        assert_eq!(
+            payload,
+            ScreenshotAnalysisPayload {
+                screenshot_path: "/tmp/shot.png".to_string(),
+                record_id,
+            }
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_pending_tasks_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("local.db");
+
+        {
+            let conn = rusqlite::Connection::open(&db_path).unwrap();
+            memory_storage::init_test_database(&conn).unwrap();
+            *DB_CONNECTION.lock().unwrap() = Some(conn);
+        }
+        let record_id = insert_record();
+        let task_id = enqueue_screenshot_analysis("/tmp/offline.png", record_id)
+            .unwrap()
+            .unwrap();
+
+        // Simulate an application restart: close and reopen the database file
+        *DB_CONNECTION.lock().unwrap() = None;
+        *DB_CONNECTION.lock().unwrap() = Some(rusqlite::Connection::open(&db_path).unwrap());
+
+        let tasks = get_pending_tasks().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, task_id);
+        assert_eq!(tasks[0].record_id, Some(record_id));
+        assert_eq!(get_pending_count().unwrap(), 1);
+
+        // Restore an in-memory database for the remaining tests
+        setup_test_db();
+    }
 }
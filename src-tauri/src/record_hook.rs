@@ -0,0 +1,422 @@
+//! User command run after each new record (`on_record_command` setting).
+//!
+//! Manual records run the hook when inserted. Auto records are inserted with
+//! a pending placeholder and run it once their analysis result is stored, so
+//! the hook sees the final content and screenshot path. When no analysis
+//! follows (capture-only mode, an open circuit, an analysis failure that is
+//! not retried, a retry that gave up) the hook runs with the record as stored;
+//! a later session analysis of a capture-only record runs it again.
+//!
+//! The template is split into a program and its arguments once, and record
+//! fields are substituted into individual arguments afterwards. The command is
+//! started directly (no shell), so a record's content can never turn into
+//! extra arguments or shell syntax. Templates that hand record fields to a
+//! shell or to inline code (`python -c`, `node -e`, also behind `env`) are
+//! rejected. The template itself is trusted configuration: these checks keep
+//! record fields out of code, not a malicious command out of the settings.
+//! Failures are only logged.
+
+use once_cell::sync::OnceCell;
+
+use crate::errors::{AppError, AppResult};
+
+/// Placeholders available in `on_record_command`.
+pub const HOOK_PLACEHOLDERS: &[&str] = &[
+    "{id}",
+    "{timestamp}",
+    "{source_type}",
+    "{content}",
+    "{screenshot_path}",
+];
+
+/// Programs that would execute their arguments as code.
+const SHELL_PROGRAMS: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "dash",
+    "fish",
+    "ksh",
+    "cmd",
+    "powershell",
+    "pwsh",
+];
+
+/// Interpreters and the flags after which they run inline code instead of a
+/// script file.
+const INLINE_CODE_PROGRAMS: &[(&str, &[&str])] = &[
+    ("python", &["-c"]),
+    ("py", &["-c"]),
+    ("node", &["-e", "--eval", "-p", "--print"]),
+    ("bun", &["-e", "--eval", "-p", "--print"]),
+    ("deno", &["eval"]),
+    ("perl", &["-e", "-E"]),
+    ("ruby", &["-e"]),
+    ("php", &["-r"]),
+    ("lua", &["-e"]),
+    ("osascript", &["-e"]),
+];
+
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
+/// Remember the app handle used to spawn hook commands. Called once at startup.
+pub fn init(app: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Record fields passed to the hook.
+#[derive(Debug, Clone)]
+pub struct HookRecord<'a> {
+    pub id: i64,
+    pub timestamp: &'a str,
+    pub source_type: &'a str,
+    pub content: &'a str,
+    pub screenshot_path: Option<&'a str>,
+}
+
+/// Split a command template into words. Whitespace separates words; single or
+/// double quotes group a word containing spaces.
+fn split_template(template: &str) -> AppResult<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in template.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(AppError::validation("记录钩子命令的引号未闭合"));
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn unknown_placeholder(word: &str) -> Option<&str> {
+    let mut rest = word;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start..];
+        let Some(end) = after.find('}') else {
+            return None;
+        };
+        let candidate = &after[..=end];
+        let name = &candidate[1..candidate.len() - 1];
+        let looks_like_placeholder =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if looks_like_placeholder && !HOOK_PLACEHOLDERS.contains(&candidate) {
+            return Some(candidate);
+        }
+        rest = &after[end + 1..];
+    }
+    None
+}
+
+fn has_placeholder(word: &str) -> bool {
+    HOOK_PLACEHOLDERS.iter().any(|p| word.contains(p))
+}
+
+/// Lowercase file name of a program without `.exe`; versioned Python
+/// binaries (`python3`, `python3.12`) are all `python`.
+fn program_name(program: &str) -> String {
+    let name = std::path::Path::new(program)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    if name.starts_with("python") {
+        "python".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn is_shell_program(program: &str) -> bool {
+    SHELL_PROGRAMS.contains(&program_name(program).as_str())
+}
+
+/// Whether `arg` is `flag`, a long flag with its value (`--eval=...`), or a
+/// group of short flags containing it (`-Sc` holds `-c`).
+fn is_flag(arg: &str, flag: &str) -> bool {
+    if arg == flag || (flag.starts_with("--") && arg.starts_with(&format!("{}=", flag))) {
+        return true;
+    }
+    let short = flag.strip_prefix('-').filter(|f| f.len() == 1);
+    match (short, arg.strip_prefix('-')) {
+        (Some(short), Some(group)) => {
+            !group.starts_with('-') && !group.contains('=') && group.contains(short)
+        }
+        _ => false,
+    }
+}
+
+/// The program `env [OPTION]... [NAME=VALUE]... PROGRAM ARGS` runs, with its
+/// arguments. `None` when env builds the command from a string (`-S`).
+fn unwrap_env<'a>(program: &'a str, args: &'a [String]) -> Option<(&'a str, &'a [String])> {
+    if program_name(program) != "env" {
+        return Some((program, args));
+    }
+    let mut rest = args;
+    while let Some((word, tail)) = rest.split_first() {
+        if word.starts_with("-S") || word.starts_with("--split-string") {
+            return None;
+        }
+        if word == "-u" || word == "--unset" || word == "-C" || word == "--chdir" {
+            rest = tail.get(1..).unwrap_or_default();
+        } else if word.starts_with('-') || word.contains('=') {
+            rest = tail;
+        } else {
+            return unwrap_env(word, tail);
+        }
+    }
+    Some((program, args))
+}
+
+/// Whether `program` runs some of `args` as code: a shell, or an
+/// interpreter given one of its inline-code flags.
+fn runs_inline_code(program: &str, args: &[String]) -> bool {
+    if is_shell_program(program) {
+        return true;
+    }
+    let name = program_name(program);
+    INLINE_CODE_PROGRAMS
+        .iter()
+        .filter(|(interpreter, _)| *interpreter == name)
+        .any(|(_, flags)| {
+            args.iter()
+                .any(|arg| flags.iter().any(|flag| is_flag(arg, flag)))
+        })
+}
+
+/// Check a command template and split it into program and argument templates.
+///
+/// Rejected: empty templates, unclosed quotes, unknown placeholders, a program
+/// built from record fields, and record fields handed to a shell (`sh -c`,
+/// `cmd /C`, ...) or an interpreter running inline code (`python -c`,
+/// `node -e`, `osascript -e`, ...) where they would be executed as code.
+pub fn parse_hook_template(template: &str) -> AppResult<(String, Vec<String>)> {
+    let mut words = split_template(template)?;
+    if words.is_empty() {
+        return Err(AppError::validation("记录钩子命令不能为空"));
+    }
+    if let Some(unknown) = words.iter().find_map(|w| unknown_placeholder(w)) {
+        return Err(AppError::validation(format!(
+            "记录钩子命令包含未知占位符 {}，可用：{}",
+            unknown,
+            HOOK_PLACEHOLDERS.join(" ")
+        )));
+    }
+
+    let program = words.remove(0);
+    if has_placeholder(&program) {
+        return Err(AppError::validation(
+            "记录钩子的程序名不能使用记录字段占位符",
+        ));
+    }
+    if words.iter().any(|w| has_placeholder(w)) {
+        match unwrap_env(&program, &words) {
+            Some((target, _)) if has_placeholder(target) => {
+                return Err(AppError::validation(
+                    "记录钩子的程序名不能使用记录字段占位符",
+                ));
+            }
+            Some((target, args)) if !runs_inline_code(target, args) => {}
+            _ => {
+                return Err(AppError::validation(
+                    "记录字段不能传给 shell 或解释器当作代码执行，请直接调用脚本并以参数接收",
+                ))
+            }
+        }
+    }
+    Ok((program, words))
+}
+
+/// Substitute record fields into each argument template. Every field stays
+/// inside the argument it was placed in, whatever characters it contains.
+pub fn fill_hook_args(args: &[String], record: &HookRecord) -> Vec<String> {
+    let id = record.id.to_string();
+    let content = crate::memory_storage::sanitize_content(record.content);
+    args.iter()
+        .map(|arg| {
+            arg.replace("{id}", &id)
+                .replace("{timestamp}", record.timestamp)
+                .replace("{source_type}", record.source_type)
+                .replace("{screenshot_path}", record.screenshot_path.unwrap_or(""))
+                // Last, so placeholder text inside the content stays literal
+                .replace("{content}", &content)
+        })
+        .collect()
+}
+
+/// Run the configured `on_record_command` for a new record, in the background.
+/// Does nothing when no command is configured or the app is not running.
+pub fn run_on_record_hook(record: &HookRecord) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let template = match crate::memory_storage::get_settings_sync() {
+        Ok(settings) => match settings.on_record_command.as_deref() {
+            Some(t) if !t.trim().is_empty() => t.to_string(),
+            _ => return,
+        },
+        Err(e) => {
+            tracing::warn!("Failed to load settings for record hook: {}", e);
+            return;
+        }
+    };
+
+    let (program, args) = match parse_hook_template(&template) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!("Invalid on_record_command: {}", e);
+            return;
+        }
+    };
+    let args = fill_hook_args(&args, record);
+    let record_id = record.id;
+    let command = {
+        use tauri_plugin_shell::ShellExt;
+        app.shell().command(&program).args(args)
+    };
+
+    tauri::async_runtime::spawn(async move {
+        match command.output().await {
+            Ok(output) if output.status.success() => {
+                tracing::debug!("Record hook finished for record {}", record_id);
+            }
+            Ok(output) => tracing::warn!(
+                "Record hook for record {} exited with {:?}: {}",
+                record_id,
+                output.status.code(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => tracing::warn!(
+                "Failed to run record hook {} for record {}: {}",
+                program,
+                record_id,
+                e
+            ),
+        }
+    });
+}
+
+/// Run `on_record_command` for a stored record with its current fields, e.g.
+/// an auto record whose analysis result was just written.
+/// Reads settings and the record, so it must not be called while holding the DB lock.
+pub fn run_on_stored_record_hook(record_id: i64) {
+    if APP_HANDLE.get().is_none() {
+        return;
+    }
+    match crate::memory_storage::get_record_by_id_sync(record_id) {
+        Ok(record) => run_on_record_hook(&HookRecord {
+            id: record.id,
+            timestamp: &record.timestamp,
+            source_type: &record.source_type,
+            content: &record.content,
+            screenshot_path: record.screenshot_path.as_deref(),
+        }),
+        Err(e) => tracing::warn!("Failed to load record {} for record hook: {}", record_id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record<'a>(content: &'a str) -> HookRecord<'a> {
+        HookRecord {
+            id: 7,
+            timestamp: "2026-03-01T10:00:00+00:00",
+            source_type: "auto",
+            content,
+            screenshot_path: None,
+        }
+    }
+
+    #[test]
+    fn parse_splits_program_and_quoted_args() {
+        let (program, args) =
+            parse_hook_template(r#"python3 "/opt/my hooks/on_record.py" --id {id} '{content}'"#)
+                .unwrap();
+        assert_eq!(program, "python3");
+        assert_eq!(
+            args,
+            vec!["/opt/my hooks/on_record.py", "--id", "{id}", "{content}"]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unsafe_or_malformed_templates() {
+        assert!(parse_hook_template("   ").is_err());
+        assert!(parse_hook_template("notify \"unterminated").is_err());
+        assert!(parse_hook_template("notify {unknown}").is_err());
+        assert!(parse_hook_template("{content} --flag").is_err());
+        assert!(parse_hook_template("sh -c \"echo {content}\"").is_err());
+        assert!(parse_hook_template("/bin/bash -c 'log {id}'").is_err());
+        assert!(parse_hook_template("cmd.exe /C echo {content}").is_err());
+
+        // Shells without record fields, and braces that are not placeholders, are fine
+        assert!(parse_hook_template("sh /opt/sync.sh").is_ok());
+        assert!(parse_hook_template(r#"curl -d {"a":1} {id}"#).is_ok());
+    }
+
+    #[test]
+    fn parse_rejects_record_fields_in_inline_code() {
+        assert!(parse_hook_template("python3 -c \"print('{content}')\"").is_err());
+        assert!(parse_hook_template("python3.12 -Sc 'import sys' {content}").is_err());
+        assert!(parse_hook_template("node -e 'log(`{content}`)'").is_err());
+        assert!(parse_hook_template("node --eval=x {id}").is_err());
+        assert!(parse_hook_template("perl -ne 'print' {content}").is_err());
+        assert!(parse_hook_template("osascript -e 'display notification \"{content}\"'").is_err());
+        assert!(parse_hook_template("env sh -c 'echo {content}'").is_err());
+        assert!(parse_hook_template("/usr/bin/env -i LANG=C bash -c 'x {id}'").is_err());
+        assert!(parse_hook_template("env -S 'sh -c x' {content}").is_err());
+        assert!(parse_hook_template("env {content}").is_err());
+
+        // Scripts run by an interpreter take record fields as plain arguments
+        assert!(parse_hook_template("python3 /opt/hook.py {content}").is_ok());
+        assert!(parse_hook_template("env LANG=C node hook.js --id {id}").is_ok());
+        assert!(parse_hook_template("osascript notify.scpt {content}").is_ok());
+        assert!(parse_hook_template("python3 -c 'print(1)'").is_ok());
+    }
+
+    #[test]
+    fn fill_keeps_each_field_inside_one_argument() {
+        let (_, args) = parse_hook_template("notify --id={id} {source_type} {content}").unwrap();
+        let malicious = "x; rm -rf ~ && echo {id} $(whoami)";
+
+        let filled = fill_hook_args(&args, &record(malicious));
+
+        assert_eq!(filled.len(), 3);
+        assert_eq!(filled[0], "--id=7");
+        assert_eq!(filled[1], "auto");
+        assert_eq!(filled[2], malicious);
+    }
+
+    #[test]
+    fn fill_strips_control_characters_and_blanks_missing_screenshot() {
+        let args = vec!["{content}".to_string(), "{screenshot_path}".to_string()];
+
+        let filled = fill_hook_args(&args, &record("a\u{0}b"));
+
+        assert_eq!(filled, vec!["ab".to_string(), String::new()]);
+    }
+}
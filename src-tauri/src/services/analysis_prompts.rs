@@ -0,0 +1,282 @@
+//! Analysis prompt selection by software category.
+//!
+//! The active window is classified with lightweight local rules (process name
+//! first, then window title). Each category can have its own analysis prompt
+//! in the `category_prompts` setting; categories without one use the base
+//! prompt plus a built-in focus hint, so e.g. IDE screenshots are asked about
+//! the tech stack.
+//!
+//! An optional A/B experiment swaps the base prompt for a second prompt on a
+//! configurable share of analyses; the variant used is stored with the result.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::window_info::ActiveWindow;
+
+/// Coarse software category of the active window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoftwareCategory {
+    Code,
+    Design,
+    Document,
+    Communication,
+    Browser,
+    Other,
+}
+
+impl SoftwareCategory {
+    /// Key used in the `category_prompts` setting.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Code => "code",
+            Self::Design => "design",
+            Self::Document => "document",
+            Self::Communication => "communication",
+            Self::Browser => "browser",
+            Self::Other => "other",
+        }
+    }
+
+    /// Hint appended to the base prompt when no category prompt is configured.
+    fn focus_hint(self) -> Option<&'static str> {
+        match self {
+            Self::Code => Some("当前为代码类软件：context_keywords 请侧重编程语言、框架/技术栈、正在编辑的文件或模块名，current_focus 说明具体在实现、调试还是阅读什么代码。"),
+            Self::Design => Some("当前为设计类软件：context_keywords 请侧重设计对象（页面、组件、图标等）与所用工具，current_focus 说明正在设计或修改的具体内容。"),
+            Self::Document => Some("当前为文档类软件：context_keywords 请侧重文档主题与章节，current_focus 说明正在撰写或审阅的内容。"),
+            Self::Communication => Some("当前为沟通类软件：只概括沟通主题，不要摘录聊天原文或联系人信息。"),
+            Self::Browser | Self::Other => None,
+        }
+    }
+}
+
+/// Base prompt variant of an A/B prompt experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromptVariant {
+    A,
+    B,
+}
+
+/// Variant for one analysis: `roll` is uniform in 0..100 and `b_percent` is
+/// the share (0-100) of analyses that use prompt B.
+pub fn choose_prompt_variant(b_percent: u32, roll: u32) -> PromptVariant {
+    if roll < b_percent.min(100) {
+        PromptVariant::B
+    } else {
+        PromptVariant::A
+    }
+}
+
+/// Process/title keywords per category, checked in order.
+const CATEGORY_RULES: &[(SoftwareCategory, &[&str])] = &[
+    (
+        SoftwareCategory::Code,
+        &[
+            "code",
+            "devenv",
+            "idea",
+            "idea64",
+            "pycharm",
+            "webstorm",
+            "goland",
+            "clion",
+            "rustrover",
+            "android studio",
+            "xcode",
+            "sublime",
+            "vim",
+            "nvim",
+            "emacs",
+            "cursor",
+            "zed",
+            "terminal",
+            "iterm",
+            "powershell",
+            "cmd.exe",
+            "wezterm",
+            "alacritty",
+        ],
+    ),
+    (
+        SoftwareCategory::Design,
+        &[
+            "figma",
+            "sketch",
+            "photoshop",
+            "illustrator",
+            "xd",
+            "affinity",
+            "blender",
+            "gimp",
+            "inkscape",
+            "pixso",
+            "mastergo",
+        ],
+    ),
+    (
+        SoftwareCategory::Document,
+        &[
+            "word",
+            "winword",
+            "excel",
+            "powerpoint",
+            "wps",
+            "pages",
+            "numbers",
+            "keynote",
+            "obsidian",
+            "notion",
+            "typora",
+            "onenote",
+            "acrobat",
+        ],
+    ),
+    (
+        SoftwareCategory::Communication,
+        &[
+            "wechat", "微信", "qq", "dingtalk", "钉钉", "feishu", "飞书", "lark", "slack", "teams",
+            "zoom", "telegram", "discord", "outlook", "mail",
+        ],
+    ),
+    (
+        SoftwareCategory::Browser,
+        &[
+            "chrome", "msedge", "edge", "firefox", "safari", "brave", "opera", "arc",
+        ],
+    ),
+];
+
+fn matches_keyword(text: &str, keyword: &str) -> bool {
+    // Short ASCII keywords ("qq", "xd", "code") must match a whole word so that
+    // e.g. "qqmusic" is not taken for "qq"
+    if keyword.len() <= 4 && keyword.is_ascii() {
+        text.split(|c: char| !c.is_alphanumeric())
+            .any(|word| word == keyword)
+    } else {
+        text.contains(keyword)
+    }
+}
+
+fn classify_text(text: &str) -> Option<SoftwareCategory> {
+    let text = text.to_lowercase();
+    CATEGORY_RULES
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|k| matches_keyword(&text, k)))
+        .map(|(category, _)| *category)
+}
+
+/// Classify the active window by process name, falling back to its title.
+pub fn classify_software(window: &ActiveWindow) -> SoftwareCategory {
+    classify_text(&window.process_name)
+        .or_else(|| classify_text(&window.title))
+        .unwrap_or(SoftwareCategory::Other)
+}
+
+/// Parse the `category_prompts` setting; invalid JSON yields no overrides.
+pub fn parse_category_prompts(json: Option<&str>) -> HashMap<String, String> {
+    json.and_then(|s| serde_json::from_str::<HashMap<String, String>>(s).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, prompt)| !prompt.trim().is_empty())
+        .collect()
+}
+
+/// Analysis prompt for a category: the configured category prompt if any,
+/// otherwise `base_prompt` with the category's built-in focus hint.
+pub fn select_analysis_prompt(
+    category: SoftwareCategory,
+    category_prompts: &HashMap<String, String>,
+    base_prompt: &str,
+) -> String {
+    if let Some(prompt) = category_prompts.get(category.key()) {
+        return prompt.clone();
+    }
+    match category.focus_hint() {
+        Some(hint) => format!("{}\n\n{}", base_prompt, hint),
+        None => base_prompt.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(process_name: &str, title: &str) -> ActiveWindow {
+        ActiveWindow {
+            title: title.to_string(),
+            process_name: process_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn classifies_by_process_name_then_title() {
+        assert_eq!(
+            classify_software(&window("Code.exe", "main.rs - dailyLogger")),
+            SoftwareCategory::Code
+        );
+        assert_eq!(
+            classify_software(&window("idea64.exe", "")),
+            SoftwareCategory::Code
+        );
+        assert_eq!(
+            classify_software(&window("Figma", "")),
+            SoftwareCategory::Design
+        );
+        assert_eq!(
+            classify_software(&window("WeChat.exe", "")),
+            SoftwareCategory::Communication
+        );
+        // Unknown process: the title decides
+        assert_eq!(
+            classify_software(&window("electron", "Notion - 周报")),
+            SoftwareCategory::Document
+        );
+        assert_eq!(classify_software(&window("", "")), SoftwareCategory::Other);
+    }
+
+    #[test]
+    fn short_keywords_match_whole_words_only() {
+        assert_eq!(
+            classify_software(&window("qqmusic", "")),
+            SoftwareCategory::Other
+        );
+        assert_eq!(
+            classify_software(&window("QQ.exe", "")),
+            SoftwareCategory::Communication
+        );
+    }
+
+    #[test]
+    fn prompt_variant_follows_b_share() {
+        assert_eq!(choose_prompt_variant(0, 0), PromptVariant::A);
+        assert_eq!(choose_prompt_variant(100, 99), PromptVariant::B);
+        assert_eq!(choose_prompt_variant(150, 99), PromptVariant::B);
+
+        let b_count = (0..100)
+            .filter(|roll| choose_prompt_variant(30, *roll) == PromptVariant::B)
+            .count();
+        assert_eq!(b_count, 30);
+    }
+
+    #[test]
+    fn category_prompt_overrides_hint() {
+        let prompts = parse_category_prompts(Some(r#"{"design": "设计专用 prompt", "code": " "}"#));
+
+        assert_eq!(
+            select_analysis_prompt(SoftwareCategory::Design, &prompts, "BASE"),
+            "设计专用 prompt"
+        );
+        // Blank override is ignored: base prompt plus the built-in code hint
+        let code = select_analysis_prompt(SoftwareCategory::Code, &prompts, "BASE");
+        assert!(code.starts_with("BASE\n\n"));
+        assert!(code.contains("技术栈"));
+        // No hint for uncategorized software
+        assert_eq!(
+            select_analysis_prompt(SoftwareCategory::Other, &prompts, "BASE"),
+            "BASE"
+        );
+        assert!(parse_category_prompts(Some("not json")).is_empty());
+    }
+}
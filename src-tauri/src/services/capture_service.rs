@@ -9,21 +9,33 @@
 //! SMART-004: Multi-monitor capture support
 //! EXP-002: Screenshot quality filter
 
-use crate::errors::{AppError, AppResult};
+use crate::api_key_pool;
+use crate::errors::{AppError, AppResult, ErrorCode};
+use crate::i18n::{Lang, MessageKey};
+use crate::infrastructure::circuit_breaker::{CallPermission, CircuitBreaker, CircuitState};
 use crate::memory_storage;
 use crate::monitor::get_monitor_list;
-use crate::monitor_types::{CaptureMode, MonitorInfo};
+use crate::monitor_types::{CaptureMode, MonitorDetail, MonitorInfo};
+use crate::services::analysis_prompts::{
+    choose_prompt_variant, classify_software, parse_category_prompts, select_analysis_prompt,
+    PromptVariant,
+};
 use crate::services::session_service::detect_or_create_session;
 use crate::silent_tracker::{
     calculate_optimal_silent_minutes, current_threshold, has_sufficient_data, record_capture,
     set_threshold, CaptureReason,
 };
-use crate::window_info::{get_active_window, should_capture_by_window, ActiveWindow};
+use crate::window_info::{
+    active_window_title, detect_sensitive_window, excluded_app_match, get_active_window,
+    should_capture_by_window, should_skip_for_sensitive, ActiveWindow,
+};
 use crate::work_time::{is_in_work_time, WorkTimeSettings};
 
 use chrono::Utc;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
@@ -41,6 +53,29 @@ const DEFAULT_CHANGE_THRESHOLD: f64 = 3.0;
 /// Default: force capture after 30 minutes of no change
 const DEFAULT_MAX_SILENT_MINUTES: u64 = 30;
 
+/// Calibration: interval between sampled frames
+const CALIBRATION_SAMPLE_INTERVAL_MS: u64 = 1000;
+
+/// Calibration: allowed sampling duration range (seconds)
+const MIN_CALIBRATION_SECONDS: u64 = 5;
+const MAX_CALIBRATION_SECONDS: u64 = 300;
+
+/// Calibration: recommended threshold is clamped into this range (percent)
+const MIN_CHANGE_THRESHOLD: i32 = 1;
+const MAX_CHANGE_THRESHOLD: i32 = 30;
+
+/// Calibration: the given percentile of sampled change rates is treated as normal noise
+const CALIBRATION_NOISE_PERCENTILE: f64 = 0.9;
+
+/// Calibration: safety margin applied on top of the noise level
+const CALIBRATION_NOISE_MARGIN: f64 = 1.5;
+
+/// Screenshots narrower or shorter than this (pixels) are treated as failed captures
+const MIN_SCREENSHOT_SIDE: u32 = 64;
+
+/// A screenshot smaller than this fraction of its monitor in either dimension is abnormal
+const MIN_SCREENSHOT_FRACTION: f64 = 0.25;
+
 /// Default analysis prompt
 pub const DEFAULT_ANALYSIS_PROMPT: &str = r#"你是一个工作分析助手。请分析这张截图，识别用户当前的工作内容和活动。
 
@@ -50,13 +85,15 @@ pub const DEFAULT_ANALYSIS_PROMPT: &str = r#"你是一个工作分析助手。
   "current_focus": "正在做什么",
   "active_software": "使用的软件名称",
   "context_keywords": ["关键词1", "关键词2", "关键词3"],
-  "tags": ["开发", "会议", "写作", "学习", "研究", "沟通", "规划", "文档", "测试", "设计"]
+  "tags": ["开发", "会议", "写作", "学习", "研究", "沟通", "规划", "文档", "测试", "设计"],
+  "importance": 50
 }
 
 注意：
 1. context_keywords 应该是从截图中识别出的具体主题或任务
 2. tags 从以下列表选择 1-3 个最相关的: ["开发", "会议", "写作", "学习", "研究", "沟通", "规划", "文档", "测试", "设计"]
 3. 如果无法确定，可以描述为"未知"或"其他"
+4. importance 为 0-100 的整数，表示这段工作对日报的重要程度：推进核心任务、解决问题、做出决策为高分，闲逛、等待、重复操作为低分
 
 返回纯 JSON，不要添加任何其他文字。"#;
 
@@ -66,6 +103,12 @@ pub const DEFAULT_ANALYSIS_PROMPT: &str = r#"你是一个工作分析助手。
 
 static AUTO_CAPTURE_RUNNING: AtomicBool = AtomicBool::new(false);
 
+// Held while a capture runs; `pause_capture` takes it to wait for the running capture
+static CAPTURE_GATE: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+// Set once captures must not run again before the app restarts (data directory moved)
+static CAPTURE_SUSPENDED: AtomicBool = AtomicBool::new(false);
+
 /// Stores the last thumbnail fingerprint and the timestamp of the last actual capture.
 struct ScreenState {
     last_fingerprint: Option<Vec<u8>>,
@@ -82,6 +125,12 @@ static SCREEN_STATE: Lazy<Mutex<ScreenState>> = Lazy::new(|| {
 // EXP-002: Quality filter counter for today's filtered screenshots
 static FILTERED_TODAY: AtomicU32 = AtomicU32::new(0);
 
+// Whether the latest screen capture found no usable display (drives the capture pause)
+static DISPLAY_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+// Whether the screenshot analysis of the latest capture failed (drives loop backoff)
+static LAST_ANALYSIS_FAILED: AtomicBool = AtomicBool::new(false);
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Data Structures
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -94,6 +143,14 @@ pub struct QualityFilterStats {
     pub quality_filter_threshold: f64,
 }
 
+/// Disk space taken by the screenshot directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotDiskUsage {
+    pub path: String,
+    pub total_bytes: u64,
+    pub file_count: usize,
+}
+
 /// Screenshot analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScreenAnalysis {
@@ -104,6 +161,71 @@ pub struct ScreenAnalysis {
     pub active_window: Option<ActiveWindow>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+    /// Prompt variant used when an A/B prompt experiment is running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_variant: Option<PromptVariant>,
+    /// Importance for the daily report (0-100); missing or invalid scores
+    /// fall back to `DEFAULT_RECORD_IMPORTANCE`
+    #[serde(
+        default = "default_importance",
+        deserialize_with = "deserialize_importance"
+    )]
+    pub importance: u8,
+    /// Tokens the analysis request(s) used, as reported by the API; stored in
+    /// `records.tokens_used`, not in the content JSON
+    #[serde(skip)]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Token usage of one analysis request (`usage` of the API response).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u32 {
+        self.prompt_tokens.saturating_add(self.completion_tokens)
+    }
+
+    /// Usage of a chat completion response; `None` if the API reported none.
+    pub fn from_response(response_body: &serde_json::Value) -> Option<Self> {
+        let usage = response_body.get("usage")?;
+        let tokens = |key: &str| usage.get(key)?.as_u64().map(|n| n as u32);
+        let prompt_tokens = tokens("prompt_tokens");
+        let completion_tokens = tokens("completion_tokens");
+        if prompt_tokens.is_none() && completion_tokens.is_none() {
+            return None;
+        }
+        Some(Self {
+            prompt_tokens: prompt_tokens.unwrap_or(0),
+            completion_tokens: completion_tokens.unwrap_or(0),
+        })
+    }
+
+    /// Usage of two requests together.
+    pub fn combine(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(Self {
+                prompt_tokens: a.prompt_tokens.saturating_add(b.prompt_tokens),
+                completion_tokens: a.completion_tokens.saturating_add(b.completion_tokens),
+            }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+fn default_importance() -> u8 {
+    memory_storage::DEFAULT_RECORD_IMPORTANCE
+}
+
+fn deserialize_importance<'de, D>(deserializer: D) -> Result<u8, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(memory_storage::importance_from_value(&value).unwrap_or_else(default_importance))
 }
 
 /// Capture settings derived from app settings
@@ -111,17 +233,25 @@ pub struct ScreenAnalysis {
 pub struct CaptureSettings {
     pub api_base_url: String,
     pub api_key: String,
+    /// Extra keys rotated together with `api_key` (round-robin)
+    pub api_keys: Vec<String>,
     pub model_name: String,
-    pub screenshot_interval: u64,
+    /// Auto capture interval in seconds
+    pub screenshot_interval_seconds: u64,
     pub analysis_prompt: Option<String>,
     pub change_threshold: f64,
     pub max_silent_minutes: u64,
     pub window_whitelist: Vec<String>,
     pub window_blacklist: Vec<String>,
+    /// Apps never captured while in the foreground (`excluded_apps`)
+    pub excluded_apps: Vec<String>,
     pub use_whitelist_only: bool,
     pub capture_mode: String,
+    /// Capture every monitor stitched side by side, whatever `capture_mode` says
+    pub capture_all_monitors: bool,
     pub selected_monitor_index: usize,
     pub capture_only_mode: bool,
+    /// `custom_headers` plus the `extra_headers` map, sent with analysis requests
     pub custom_headers: Vec<crate::memory_storage::CustomHeader>,
     pub quality_filter_enabled: bool,
     pub quality_filter_threshold: f64,
@@ -130,6 +260,35 @@ pub struct CaptureSettings {
     pub proxy_port: Option<i32>,
     pub proxy_username: Option<String>,
     pub proxy_password: Option<String>,
+    pub sensitive_filter_enabled: bool,
+    pub sensitive_keywords: Vec<String>,
+    pub language: Lang,
+    pub screenshot_format: ScreenshotFormat,
+    /// Choose PNG or JPEG per screenshot from its content (`screenshot_format` = "auto")
+    pub screenshot_format_auto: bool,
+    /// Seconds to wait before the first auto capture (lets the desktop finish loading)
+    pub startup_delay_seconds: u64,
+    /// Save the request body and screenshot of failed analyses to the debug directory
+    pub debug_dump_failed_requests: bool,
+    /// Put model output into errors (and so logs and records); otherwise only
+    /// its length and a digest (`log_llm_payload`)
+    pub log_llm_payload: bool,
+    /// Pixels cut from the bottom of each screenshot (taskbar); 0 disables cropping
+    pub crop_bottom_pixels: u32,
+    /// Longest side of a stitched all-monitor screenshot; 0 disables scaling
+    pub max_image_dimension: u32,
+    /// Timeout and retries of a single screenshot
+    pub capture_retry: CaptureRetry,
+    /// Screen areas blacked out of every screenshot (`exclude_regions`)
+    pub exclude_regions: Vec<ExcludeRegion>,
+    /// Analysis prompt per software category key (see `analysis_prompts`)
+    pub category_prompts: HashMap<String, String>,
+    /// Image format sent to the analysis endpoint (see `endpoint_image_format`)
+    pub analysis_image_format: ScreenshotFormat,
+    /// Prompt B of the A/B prompt experiment; `None` disables the experiment
+    pub analysis_prompt_b: Option<String>,
+    /// Share (0-100) of analyses that use prompt B
+    pub prompt_b_percent: u32,
 }
 
 impl Default for CaptureSettings {
@@ -137,15 +296,18 @@ impl Default for CaptureSettings {
         Self {
             api_base_url: "https://api.openai.com/v1".to_string(),
             api_key: String::new(),
+            api_keys: Vec::new(),
             model_name: "gpt-4o".to_string(),
-            screenshot_interval: 5,
+            screenshot_interval_seconds: DEFAULT_CAPTURE_INTERVAL_SECONDS,
             analysis_prompt: None,
             change_threshold: DEFAULT_CHANGE_THRESHOLD,
             max_silent_minutes: DEFAULT_MAX_SILENT_MINUTES,
             window_whitelist: Vec::new(),
             window_blacklist: Vec::new(),
+            excluded_apps: Vec::new(),
             use_whitelist_only: false,
             capture_mode: "primary".to_string(),
+            capture_all_monitors: false,
             selected_monitor_index: 0,
             capture_only_mode: false,
             custom_headers: Vec::new(),
@@ -156,6 +318,22 @@ impl Default for CaptureSettings {
             proxy_port: None,
             proxy_username: None,
             proxy_password: None,
+            sensitive_filter_enabled: false,
+            sensitive_keywords: Vec::new(),
+            language: Lang::default(),
+            screenshot_format: ScreenshotFormat::default(),
+            screenshot_format_auto: false,
+            startup_delay_seconds: 0,
+            debug_dump_failed_requests: false,
+            log_llm_payload: true,
+            crop_bottom_pixels: 0,
+            max_image_dimension: DEFAULT_MAX_IMAGE_DIMENSION,
+            capture_retry: CaptureRetry::default(),
+            exclude_regions: Vec::new(),
+            category_prompts: HashMap::new(),
+            analysis_image_format: ScreenshotFormat::Png,
+            analysis_prompt_b: None,
+            prompt_b_percent: DEFAULT_PROMPT_B_PERCENT,
         }
     }
 }
@@ -169,6 +347,16 @@ pub struct ReanalyzeResult {
     pub errors: Vec<String>,
 }
 
+/// Result of a change threshold calibration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdCalibration {
+    pub sample_count: usize,
+    pub mean_change_rate: f64,
+    pub noise_change_rate: f64,
+    pub previous_threshold: i32,
+    pub recommended_threshold: i32,
+}
+
 /// Payload for silent-threshold-adjusted event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThresholdAdjustment {
@@ -177,16 +365,100 @@ pub struct ThresholdAdjustment {
     pub reason: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CaptureTriggerSource {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureTriggerSource {
     Auto,
     Manual,
 }
 
+/// Frontend event carrying a [`CaptureEvent`].
+pub const CAPTURE_EVENT: &str = "capture-event";
+
+/// Stage of a capture reported to the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureEventKind {
+    Started,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Structured capture progress for the frontend status light and log stream.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaptureEvent {
+    pub kind: CaptureEventKind,
+    /// RFC3339 time the event was produced
+    pub timestamp: String,
+    pub trigger: CaptureTriggerSource,
+    /// Why the capture failed or was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Time since the capture started; absent on `started`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Record written by a successful capture
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<i64>,
+}
+
+impl CaptureEvent {
+    pub fn new(kind: CaptureEventKind, trigger: CaptureTriggerSource) -> Self {
+        Self {
+            kind,
+            timestamp: Utc::now().to_rfc3339(),
+            trigger,
+            reason: None,
+            duration_ms: None,
+            record_id: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    fn with_duration(mut self, started: Instant) -> Self {
+        self.duration_ms = Some(started.elapsed().as_millis() as u64);
+        self
+    }
+}
+
+/// How a capture ended when it did not fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CaptureOutcome {
+    Stored(i64),
+    Skipped(&'static str),
+}
+
+static CAPTURE_EVENT_APP: once_cell::sync::OnceCell<tauri::AppHandle> =
+    once_cell::sync::OnceCell::new();
+
+/// Remember the app handle capture events are emitted with. Called once at startup.
+pub fn init_capture_events(app: tauri::AppHandle) {
+    let _ = CAPTURE_EVENT_APP.set(app);
+}
+
+/// Emit a capture event to the frontend; a no-op until `init_capture_events` ran.
+pub fn emit_capture_event(event: &CaptureEvent) {
+    use tauri::Emitter;
+    if let Some(app) = CAPTURE_EVENT_APP.get() {
+        if let Err(e) = app.emit(CAPTURE_EVENT, event) {
+            tracing::warn!("Failed to emit capture event: {}", e);
+        }
+    }
+}
+
 /// Validate that API key is configured, returning a standardized error if not.
 fn require_api_key(settings: &CaptureSettings) -> AppResult<()> {
-    if settings.api_key.is_empty() {
-        return Err(AppError::auth("API 密钥未配置，请在设置中配置"));
+    if settings.api_key.is_empty() && settings.api_keys.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::Auth,
+            MessageKey::ApiKeyNotConfigured,
+            settings.language,
+        ));
     }
     Ok(())
 }
@@ -196,6 +468,8 @@ fn require_api_key(settings: &CaptureSettings) -> AppResult<()> {
 pub enum ScreenshotErrorKind {
     PermissionDenied,
     NoMonitors,
+    /// No usable display at all (disconnected remote desktop session, headless machine)
+    NoDisplay,
     MonitorNotFound,
     TemporaryFailure,
     Unknown,
@@ -231,6 +505,9 @@ fn classify_screenshot_error(error: &str) -> ScreenshotErrorKind {
     {
         return ScreenshotErrorKind::PermissionDenied;
     }
+    if error_lower.contains(&NO_DISPLAY_ERROR.to_lowercase()) {
+        return ScreenshotErrorKind::NoDisplay;
+    }
     if error_lower.contains("no monitors") || error_lower.contains("monitor not found") {
         return ScreenshotErrorKind::NoMonitors;
     }
@@ -247,20 +524,20 @@ fn classify_screenshot_error(error: &str) -> ScreenshotErrorKind {
 }
 
 /// Get a user-friendly error message based on the error kind
-pub fn get_screenshot_error_message(kind: &ScreenshotErrorKind, original_error: &str) -> String {
-    match kind {
-        ScreenshotErrorKind::PermissionDenied => {
-            "截图权限被拒绝，请在系统设置中允许应用进行屏幕录制".to_string()
-        }
-        ScreenshotErrorKind::NoMonitors => "未检测到显示器，请检查屏幕连接".to_string(),
-        ScreenshotErrorKind::MonitorNotFound => {
-            "指定的显示器不存在，请检查多显示器配置".to_string()
-        }
-        ScreenshotErrorKind::TemporaryFailure => {
-            format!("截图暂时失败: {}，将自动重试", original_error)
-        }
-        ScreenshotErrorKind::Unknown => original_error.to_string(),
-    }
+pub fn get_screenshot_error_message(
+    kind: &ScreenshotErrorKind,
+    original_error: &str,
+    lang: Lang,
+) -> String {
+    let key = match kind {
+        ScreenshotErrorKind::PermissionDenied => MessageKey::ScreenshotPermissionDenied,
+        ScreenshotErrorKind::NoMonitors => MessageKey::NoMonitorsDetected,
+        ScreenshotErrorKind::NoDisplay => MessageKey::NoDisplayAvailable,
+        ScreenshotErrorKind::MonitorNotFound => MessageKey::MonitorNotFound,
+        ScreenshotErrorKind::TemporaryFailure => MessageKey::ScreenshotTemporaryFailure,
+        ScreenshotErrorKind::Unknown => return original_error.to_string(),
+    };
+    crate::i18n::message_with(key, lang, &[("error", original_error)])
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -277,6 +554,65 @@ fn compute_fingerprint(image_base64: &str) -> AppResult<Vec<u8>> {
     Ok(thumb.into_raw())
 }
 
+/// Widths of the monitors laid side by side in a stitched all-monitor
+/// screenshot, left to right; empty for a single-monitor capture.
+fn stitched_monitor_widths(mode: CaptureMode, monitor_info: &MonitorInfo) -> Vec<u32> {
+    if mode != CaptureMode::All {
+        return Vec::new();
+    }
+    monitor_info.monitors.iter().map(|m| m.width).collect()
+}
+
+/// Fingerprint columns showing each monitor of a stitched screenshot.
+/// A single monitor covers all columns.
+fn fingerprint_column_bands(monitor_widths: &[u32]) -> Vec<std::ops::Range<usize>> {
+    let columns = THUMB_SIZE as usize;
+    let total: u64 = monitor_widths.iter().map(|&w| w as u64).sum();
+    if monitor_widths.len() < 2 || total == 0 {
+        return vec![0..columns];
+    }
+    let mut bands = Vec::new();
+    let mut covered = 0u64;
+    let mut start = 0usize;
+    for &width in monitor_widths {
+        covered += width as u64;
+        let end = ((covered * columns as u64 + total / 2) / total) as usize;
+        let end = end.max(start + 1).min(columns);
+        if start < end {
+            bands.push(start..end);
+        }
+        start = end;
+    }
+    bands
+}
+
+/// Change rate between two fingerprints. For a stitched screenshot the rate is
+/// taken per monitor and the highest counts, so a change on one screen is not
+/// diluted by the unchanged others.
+fn stitched_change_rate(a: &[u8], b: &[u8], monitor_widths: &[u32]) -> f64 {
+    let bands = fingerprint_column_bands(monitor_widths);
+    let columns = THUMB_SIZE as usize;
+    if bands.len() < 2 || a.len() != b.len() || a.len() != columns * columns {
+        return calc_change_rate(a, b);
+    }
+    bands
+        .iter()
+        .map(|band| {
+            let (band_a, band_b): (Vec<u8>, Vec<u8>) = a
+                .chunks(columns)
+                .zip(b.chunks(columns))
+                .flat_map(|(row_a, row_b)| {
+                    row_a[band.clone()]
+                        .iter()
+                        .copied()
+                        .zip(row_b[band.clone()].iter().copied())
+                })
+                .unzip();
+            calc_change_rate(&band_a, &band_b)
+        })
+        .fold(0.0, f64::max)
+}
+
 fn calc_change_rate(a: &[u8], b: &[u8]) -> f64 {
     if a.len() != b.len() {
         return 100.0;
@@ -326,6 +662,7 @@ fn compute_quality_score(image_base64: &str) -> AppResult<f64> {
 
 fn should_capture(
     fingerprint: &[u8],
+    monitor_widths: &[u32],
     change_threshold: f64,
     max_silent_minutes: u64,
 ) -> Option<CaptureReason> {
@@ -333,12 +670,36 @@ fn should_capture(
         tracing::warn!("SCREEN_STATE mutex was poisoned, recreating state");
         poisoned.into_inner()
     });
-    let silent_exceeded =
-        state.last_capture_time.elapsed() >= Duration::from_secs(max_silent_minutes * 60);
-    let changed = match &state.last_fingerprint {
+    let reason = decide_capture_reason(
+        state.last_fingerprint.as_deref(),
+        fingerprint,
+        monitor_widths,
+        state.last_capture_time.elapsed(),
+        change_threshold,
+        max_silent_minutes,
+    )?;
+    record_capture(reason);
+    state.last_fingerprint = Some(fingerprint.to_vec());
+    state.last_capture_time = Instant::now();
+    Some(reason)
+}
+
+/// Whether a frame is worth keeping given the previous kept frame and the time
+/// since it was kept; `None` means the screen is unchanged and the silent
+/// limit not yet reached. `monitor_widths` lays out a stitched screenshot
+/// (see `stitched_change_rate`).
+fn decide_capture_reason(
+    last_fingerprint: Option<&[u8]>,
+    fingerprint: &[u8],
+    monitor_widths: &[u32],
+    since_last_capture: Duration,
+    change_threshold: f64,
+    max_silent_minutes: u64,
+) -> Option<CaptureReason> {
+    let changed = match last_fingerprint {
         None => true,
         Some(prev) => {
-            let rate = calc_change_rate(prev, fingerprint);
+            let rate = stitched_change_rate(prev, fingerprint, monitor_widths);
             tracing::debug!(
                 "Screen change rate: {:.2}% (threshold: {:.1}%)",
                 rate,
@@ -347,22 +708,41 @@ fn should_capture(
             rate >= change_threshold
         }
     };
-    let reason = if changed {
-        CaptureReason::ScreenChanged
-    } else if silent_exceeded {
+    if changed {
+        Some(CaptureReason::ScreenChanged)
+    } else if since_last_capture >= Duration::from_secs(max_silent_minutes * 60) {
         tracing::info!(
             "Screen unchanged but max silent time ({} min) exceeded, forcing capture",
             max_silent_minutes
         );
-        CaptureReason::SilentTimeout
+        Some(CaptureReason::SilentTimeout)
     } else {
         tracing::debug!("Screen unchanged, skipping capture");
+        None
+    }
+}
+
+/// Change rate at the noise percentile of the samples (None when there are no valid samples)
+fn noise_change_rate(samples: &[f64]) -> Option<f64> {
+    let mut sorted: Vec<f64> = samples.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
         return None;
-    };
-    record_capture(reason);
-    state.last_fingerprint = Some(fingerprint.to_vec());
-    state.last_capture_time = Instant::now();
-    Some(reason)
+    }
+    sorted.sort_by(f64::total_cmp);
+    let index = ((sorted.len() - 1) as f64 * CALIBRATION_NOISE_PERCENTILE).round() as usize;
+    Some(sorted[index])
+}
+
+/// Recommend a change threshold (percent) from change rates sampled between consecutive frames.
+///
+/// The rate at the noise percentile is treated as the normal fluctuation of this screen;
+/// the threshold is set a margin above it so that noise does not trigger captures.
+pub fn recommend_change_threshold(samples: &[f64]) -> i32 {
+    match noise_change_rate(samples) {
+        Some(noise) => ((noise * CALIBRATION_NOISE_MARGIN).ceil() as i32)
+            .clamp(MIN_CHANGE_THRESHOLD, MAX_CHANGE_THRESHOLD),
+        None => DEFAULT_CHANGE_THRESHOLD as i32,
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -374,34 +754,70 @@ fn parse_window_patterns(json: Option<&str>) -> Vec<String> {
         .unwrap_or_default()
 }
 
+/// Default auto capture interval (5 minutes).
+pub const DEFAULT_CAPTURE_INTERVAL_SECONDS: u64 = 300;
+/// Shortest auto capture interval accepted.
+pub const MIN_CAPTURE_INTERVAL_SECONDS: u64 = 10;
+
+/// Effective auto capture interval in seconds.
+/// Prefers `screenshot_interval_seconds` and falls back to the legacy minute value.
+pub fn capture_interval_seconds(seconds: Option<i32>, minutes: Option<i32>) -> u64 {
+    seconds
+        .filter(|s| *s > 0)
+        .map(|s| s as u64)
+        .or_else(|| minutes.filter(|m| *m > 0).map(|m| m as u64 * 60))
+        .unwrap_or(DEFAULT_CAPTURE_INTERVAL_SECONDS)
+        .max(MIN_CAPTURE_INTERVAL_SECONDS)
+}
+
+/// Longest auto capture interval reached by backing off after failures (1 hour).
+pub const MAX_CAPTURE_BACKOFF_SECONDS: u64 = 60 * 60;
+
+/// Auto capture interval after `consecutive_failures` failed cycles: doubles
+/// with every failure up to `max_seconds` (never below `interval_seconds`).
+pub fn capture_backoff_seconds(
+    interval_seconds: u64,
+    consecutive_failures: u32,
+    max_seconds: u64,
+) -> u64 {
+    let factor = 2u64.saturating_pow(consecutive_failures);
+    interval_seconds
+        .saturating_mul(factor)
+        .min(max_seconds.max(interval_seconds))
+}
+
+/// Whether the analysis of a capture stored since the last call failed.
+/// Clears the flag, so a cycle that stores nothing reads `false`.
+pub fn take_analysis_failed() -> bool {
+    LAST_ANALYSIS_FAILED.swap(false, Ordering::SeqCst)
+}
+
 /// Extract capture-related fields from a Settings reference into CaptureSettings.
 /// Shared by both `load_capture_settings()` and `load_capture_settings_from_arc()`.
 fn capture_settings_from_settings(s: &crate::memory_storage::Settings) -> CaptureSettings {
-    let custom_headers = if let Some(ref headers_json) = s.custom_headers {
-        if !headers_json.is_empty() {
-            serde_json::from_str::<Vec<crate::memory_storage::CustomHeader>>(headers_json)
-                .unwrap_or_default()
-        } else {
-            Vec::new()
-        }
-    } else {
-        Vec::new()
-    };
+    let custom_headers = s.request_headers();
     CaptureSettings {
         api_base_url: s.api_base_url.clone().unwrap_or_default(),
         api_key: s.api_key.clone().unwrap_or_default(),
+        api_keys: parse_window_patterns(s.api_keys.as_deref()),
         model_name: s.model_name.clone().unwrap_or_else(|| "gpt-4o".to_string()),
-        screenshot_interval: s.screenshot_interval.unwrap_or(5) as u64,
+        screenshot_interval_seconds: capture_interval_seconds(
+            s.screenshot_interval_seconds,
+            s.screenshot_interval,
+        ),
         analysis_prompt: s.analysis_prompt.clone(),
         change_threshold: s.change_threshold.unwrap_or(3) as f64,
         max_silent_minutes: s.max_silent_minutes.unwrap_or(30) as u64,
         window_whitelist: parse_window_patterns(s.window_whitelist.as_deref()),
         window_blacklist: parse_window_patterns(s.window_blacklist.as_deref()),
+        excluded_apps: parse_window_patterns(s.excluded_apps.as_deref()),
         use_whitelist_only: s.use_whitelist_only.unwrap_or(false),
         capture_mode: s
             .capture_mode
             .clone()
             .unwrap_or_else(|| "primary".to_string()),
+        capture_all_monitors: s.capture_all_monitors.unwrap_or(false),
+        exclude_regions: parse_exclude_regions(s.exclude_regions.as_deref()),
         selected_monitor_index: s.selected_monitor_index.unwrap_or(0) as usize,
         capture_only_mode: s.capture_only_mode.unwrap_or(false),
         custom_headers,
@@ -412,6 +828,49 @@ fn capture_settings_from_settings(s: &crate::memory_storage::Settings) -> Captur
         proxy_port: s.proxy_port,
         proxy_username: s.proxy_username.clone(),
         proxy_password: s.proxy_password.clone(),
+        sensitive_filter_enabled: s.sensitive_filter_enabled.unwrap_or(false),
+        sensitive_keywords: parse_window_patterns(s.sensitive_keywords.as_deref()),
+        language: Lang::from_settings(s),
+        screenshot_format: ScreenshotFormat::from_setting(s.screenshot_format.as_deref()),
+        screenshot_format_auto: ScreenshotFormat::is_auto_setting(s.screenshot_format.as_deref()),
+        startup_delay_seconds: s.startup_delay_seconds.unwrap_or(0).max(0) as u64,
+        debug_dump_failed_requests: s.debug_dump_failed_requests.unwrap_or(false),
+        log_llm_payload: s.log_llm_payload.unwrap_or(true),
+        crop_bottom_pixels: if s.crop_taskbar.unwrap_or(false) {
+            s.taskbar_height
+                .unwrap_or(DEFAULT_TASKBAR_HEIGHT as i32)
+                .max(0) as u32
+        } else {
+            0
+        },
+        max_image_dimension: s
+            .max_image_dimension
+            .unwrap_or(DEFAULT_MAX_IMAGE_DIMENSION as i32)
+            .max(0) as u32,
+        capture_retry: CaptureRetry {
+            timeout: Duration::from_secs(
+                s.capture_timeout_secs
+                    .unwrap_or(DEFAULT_CAPTURE_TIMEOUT_SECS as i32)
+                    .max(1) as u64,
+            ),
+            retries: s
+                .capture_retries
+                .unwrap_or(DEFAULT_CAPTURE_RETRIES as i32)
+                .clamp(0, MAX_CAPTURE_RETRIES as i32) as u32,
+        },
+        category_prompts: parse_category_prompts(s.category_prompts.as_deref()),
+        analysis_image_format: endpoint_image_format(
+            s.api_base_url.as_deref().unwrap_or_default(),
+            &parse_endpoint_image_formats(s.endpoint_image_formats.as_deref()),
+        ),
+        analysis_prompt_b: s
+            .analysis_prompt_b
+            .clone()
+            .filter(|prompt| !prompt.trim().is_empty()),
+        prompt_b_percent: s
+            .prompt_b_percent
+            .unwrap_or(DEFAULT_PROMPT_B_PERCENT as i32)
+            .clamp(0, 100) as u32,
     }
 }
 
@@ -512,10 +971,9 @@ pub fn evaluate_and_adjust_threshold() -> Option<(u64, u64)> {
     );
     if new_threshold != old_threshold {
         set_threshold(new_threshold);
-        // Reuse the same Arc for the mutation save — avoids a second get_settings_sync() call
-        let mut settings_mut = (*settings).clone();
-        settings_mut.max_silent_minutes = Some(new_threshold as i32);
-        if let Err(e) = memory_storage::save_settings_sync(&settings_mut) {
+        if let Err(e) = memory_storage::update_settings_sync(|s| {
+            s.max_silent_minutes = Some(new_threshold as i32);
+        }) {
             tracing::error!("Failed to save adjusted threshold: {}", e);
         }
         tracing::info!(
@@ -528,30 +986,395 @@ pub fn evaluate_and_adjust_threshold() -> Option<(u64, u64)> {
     None
 }
 
+/// Sample frames for `seconds`, compute the change rate distribution and
+/// write the recommended change threshold back to settings.
+pub async fn calibrate_change_threshold_service(seconds: u64) -> AppResult<ThresholdCalibration> {
+    if !(MIN_CALIBRATION_SECONDS..=MAX_CALIBRATION_SECONDS).contains(&seconds) {
+        return Err(AppError::validation(format!(
+            "Calibration duration must be between {} and {} seconds",
+            MIN_CALIBRATION_SECONDS, MAX_CALIBRATION_SECONDS
+        )));
+    }
+
+    let capture_settings = load_capture_settings();
+    let capture_mode = effective_capture_mode(&capture_settings);
+    let frame_count = (seconds * 1000 / CALIBRATION_SAMPLE_INTERVAL_MS).max(2);
+
+    let mut samples = Vec::new();
+    let mut previous: Option<Vec<u8>> = None;
+    for frame in 0..frame_count {
+        let (image_base64, monitor_info) = capture_screen_with_mode(
+            capture_mode,
+            capture_settings.selected_monitor_index,
+            capture_settings.crop_bottom_pixels,
+            capture_settings.max_image_dimension,
+            capture_settings.capture_retry,
+        )?;
+        let image_base64 = mask_excluded_regions(image_base64, &capture_settings.exclude_regions)?;
+        let fingerprint = compute_fingerprint(&image_base64)?;
+        if let Some(prev) = &previous {
+            let monitor_widths = stitched_monitor_widths(capture_mode, &monitor_info);
+            samples.push(stitched_change_rate(prev, &fingerprint, &monitor_widths));
+        }
+        previous = Some(fingerprint);
+        if frame + 1 < frame_count {
+            tokio::time::sleep(Duration::from_millis(CALIBRATION_SAMPLE_INTERVAL_MS)).await;
+        }
+    }
+
+    let recommended_threshold = recommend_change_threshold(&samples);
+    let settings = memory_storage::get_settings_sync()?;
+    let previous_threshold = settings
+        .change_threshold
+        .unwrap_or(DEFAULT_CHANGE_THRESHOLD as i32);
+    memory_storage::update_settings_sync(|s| s.change_threshold = Some(recommended_threshold))?;
+
+    let mean_change_rate = if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().sum::<f64>() / samples.len() as f64
+    };
+    tracing::info!(
+        "Change threshold calibrated from {}% to {}% ({} samples)",
+        previous_threshold,
+        recommended_threshold,
+        samples.len()
+    );
+
+    Ok(ThresholdCalibration {
+        sample_count: samples.len(),
+        mean_change_rate,
+        noise_change_rate: noise_change_rate(&samples).unwrap_or(0.0),
+        previous_threshold,
+        recommended_threshold,
+    })
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Screen Capture Functions
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Whether a captured image size is plausible: both sides at least
+/// `MIN_SCREENSHOT_SIDE`, and not far smaller than the monitor it was taken from.
+pub fn is_resolution_plausible(width: u32, height: u32, expected: Option<(u32, u32)>) -> bool {
+    if width < MIN_SCREENSHOT_SIDE || height < MIN_SCREENSHOT_SIDE {
+        return false;
+    }
+    match expected {
+        Some((expected_width, expected_height)) if expected_width > 0 && expected_height > 0 => {
+            width as f64 >= expected_width as f64 * MIN_SCREENSHOT_FRACTION
+                && height as f64 >= expected_height as f64 * MIN_SCREENSHOT_FRACTION
+        }
+        _ => true,
+    }
+}
+
+fn check_screenshot_resolution(image_base64: &str, expected: Option<(u32, u32)>) -> AppResult<()> {
+    let image_data =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, image_base64)?;
+    let (width, height) = image::ImageReader::new(std::io::Cursor::new(image_data))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    if is_resolution_plausible(width, height, expected) {
+        return Ok(());
+    }
+    Err(AppError::screenshot(match expected {
+        Some((w, h)) => format!(
+            "Abnormal screenshot resolution {}x{} (monitor is {}x{})",
+            width, height, w, h
+        ),
+        None => format!("Abnormal screenshot resolution {}x{}", width, height),
+    }))
+}
+
+/// Default height of the cropped taskbar in pixels (Windows taskbar at 100% scaling).
+pub const DEFAULT_TASKBAR_HEIGHT: u32 = 48;
+
+/// Cut `pixels` rows off the bottom of an image.
+/// The image is returned unchanged when the crop would remove all of it.
+pub fn crop_bottom(image: &image::DynamicImage, pixels: u32) -> image::DynamicImage {
+    if pixels == 0 || pixels >= image.height() {
+        return image.clone();
+    }
+    image.crop_imm(0, 0, image.width(), image.height() - pixels)
+}
+
+/// A screen area kept out of screenshots (`exclude_regions` setting).
+///
+/// Coordinates are pixels of the captured image with the origin at its
+/// top-left corner; for an all-monitor capture that is the stitched image.
+/// Parts of a region outside the image are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExcludeRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Parse `exclude_regions`. Missing or invalid JSON yields no regions.
+fn parse_exclude_regions(json: Option<&str>) -> Vec<ExcludeRegion> {
+    let Some(json) = json.filter(|j| !j.trim().is_empty()) else {
+        return Vec::new();
+    };
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        tracing::warn!("Invalid exclude_regions, ignoring: {}", e);
+        Vec::new()
+    })
+}
+
+/// Paint `regions` black, clamped to the image bounds.
+fn black_out_regions(image: &mut image::RgbaImage, regions: &[ExcludeRegion]) {
+    let (width, height) = image.dimensions();
+    for region in regions {
+        let x_end = region.x.saturating_add(region.width).min(width);
+        let y_end = region.y.saturating_add(region.height).min(height);
+        for y in region.y.min(y_end)..y_end {
+            for x in region.x.min(x_end)..x_end {
+                image.put_pixel(x, y, image::Rgba([0, 0, 0, 255]));
+            }
+        }
+    }
+}
+
+/// Black out the excluded regions of a captured screenshot. Runs right after
+/// the capture, so the private areas are neither fingerprinted, saved nor sent
+/// to the analysis API.
+fn mask_excluded_regions(image_base64: String, regions: &[ExcludeRegion]) -> AppResult<String> {
+    if regions.is_empty() {
+        return Ok(image_base64);
+    }
+    let image_data =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &image_base64)?;
+    let mut image = image::load_from_memory(&image_data)?.to_rgba8();
+    black_out_regions(&mut image, regions);
+    let mut buffer = Vec::new();
+    image::DynamicImage::ImageRgba8(image)
+        .write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .map_err(|e| AppError::screenshot(format!("Failed to encode screenshot: {}", e)))?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        &buffer,
+    ))
+}
+
+fn crop_screenshot_bottom(image_base64: &str, pixels: u32) -> AppResult<String> {
+    let image_data =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, image_base64)?;
+    let cropped = crop_bottom(&image::load_from_memory(&image_data)?, pixels);
+    let mut buffer = Vec::new();
+    cropped
+        .write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )
+        .map_err(|e| AppError::screenshot(format!("Failed to encode screenshot: {}", e)))?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        &buffer,
+    ))
+}
+
+/// Marker of the error returned when no display can be captured.
+pub const NO_DISPLAY_ERROR: &str = "No display available";
+
+fn no_display_error(detail: &str) -> AppError {
+    AppError::screenshot(format!("{}: {}", NO_DISPLAY_ERROR, detail))
+}
+
+/// Check a monitor listing for a usable display.
+///
+/// An empty list, a "no monitors" error or only zero-sized monitors (what a
+/// disconnected remote desktop session reports) yield the no-display error.
+fn require_display(monitors: AppResult<Vec<MonitorDetail>>) -> AppResult<Vec<MonitorDetail>> {
+    match monitors {
+        Ok(monitors) if monitors.iter().any(|m| m.width > 0 && m.height > 0) => Ok(monitors),
+        Ok(monitors) if monitors.is_empty() => Err(no_display_error("monitor list is empty")),
+        Ok(_) => Err(no_display_error("all monitors report a zero size")),
+        Err(e) if classify_screenshot_error(&e.message) == ScreenshotErrorKind::NoMonitors => {
+            Err(no_display_error(&e.message))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether a display can be captured right now (cheap, no screenshot taken).
+pub fn has_available_display() -> bool {
+    require_display(get_monitor_list()).is_ok()
+}
+
+/// Whether the latest capture failed because no display was available.
+pub fn display_unavailable() -> bool {
+    DISPLAY_UNAVAILABLE.load(Ordering::SeqCst)
+}
+
+/// Default of the `capture_timeout_secs` setting.
+pub const DEFAULT_CAPTURE_TIMEOUT_SECS: u64 = 5;
+/// Default of the `capture_retries` setting.
+pub const DEFAULT_CAPTURE_RETRIES: u32 = 2;
+/// Upper bound of the `capture_retries` setting.
+pub const MAX_CAPTURE_RETRIES: u32 = 10;
+
+/// How long a screenshot may take and how often a failed one is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureRetry {
+    pub timeout: Duration,
+    /// Attempts after the first one
+    pub retries: u32,
+}
+
+impl Default for CaptureRetry {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_CAPTURE_TIMEOUT_SECS),
+            retries: DEFAULT_CAPTURE_RETRIES,
+        }
+    }
+}
+
+/// Run `f` on a helper thread and give up after `timeout`. A capture that
+/// hangs in the platform API cannot be cancelled; its thread is left to finish
+/// on its own and its result is dropped.
+fn run_with_timeout<T, F>(timeout: Duration, f: F) -> AppResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("screenshot".to_string())
+        .spawn(move || {
+            let _ = tx.send(f());
+        })
+        .map_err(|e| AppError::screenshot(format!("Failed to start capture thread: {}", e)))?;
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(AppError::screenshot(format!(
+            "Screenshot timed out after {}s",
+            timeout.as_secs_f32()
+        ))),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Err(AppError::screenshot(
+            "Capture thread exited without a result",
+        )),
+    }
+}
+
+/// Call `attempt` (with the attempt number, starting at 0) until it succeeds,
+/// at most `retries + 1` times. Errors rejected by `is_retryable` end the loop
+/// at once. Returns the last error when every attempt fails.
+fn retry_capture<T>(
+    retries: u32,
+    mut attempt: impl FnMut(u32) -> AppResult<T>,
+    is_retryable: impl Fn(&AppError) -> bool,
+) -> AppResult<T> {
+    let mut n = 0;
+    loop {
+        match attempt(n) {
+            Ok(value) => return Ok(value),
+            Err(e) if n < retries && is_retryable(&e) => {
+                tracing::warn!("Screenshot attempt {}/{} failed: {}", n + 1, retries + 1, e);
+                n += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Capture once with the configured timeout, retrying failures. A missing
+/// display is not retried: it will not come back within a few seconds.
+fn capture_screen_with_retry(
+    mode: CaptureMode,
+    selected_index: usize,
+    max_image_dimension: u32,
+    retry: CaptureRetry,
+) -> AppResult<(String, MonitorInfo, Option<(u32, u32)>)> {
+    retry_capture(
+        retry.retries,
+        |_| {
+            run_with_timeout(retry.timeout, move || {
+                capture_screen_once(mode, selected_index, max_image_dimension)
+            })
+        },
+        |e| !e.message.starts_with(NO_DISPLAY_ERROR),
+    )
+}
+
+/// Capture the screen, retrying once when the image resolution is abnormal
+/// so a broken capture is never sent to the LLM. Failed or timed out captures
+/// are retried as configured in `retry`. `crop_bottom_pixels` rows
+/// (the taskbar) are removed after the resolution check; a stitched
+/// all-monitor image is scaled down to `max_image_dimension`.
 fn capture_screen_with_mode(
     mode: CaptureMode,
     selected_index: usize,
+    crop_bottom_pixels: u32,
+    max_image_dimension: u32,
+    retry: CaptureRetry,
 ) -> AppResult<(String, MonitorInfo)> {
-    let monitor_details = get_monitor_list()?;
+    let first = capture_screen_with_retry(mode, selected_index, max_image_dimension, retry);
+    DISPLAY_UNAVAILABLE.store(
+        first
+            .as_ref()
+            .is_err_and(|e| e.message.starts_with(NO_DISPLAY_ERROR)),
+        Ordering::SeqCst,
+    );
+    let (image, monitor_info, expected) = first?;
+    let (image, monitor_info) = match check_screenshot_resolution(&image, expected) {
+        Ok(()) => (image, monitor_info),
+        Err(e) => {
+            tracing::warn!("{}, retrying capture once", e);
+            let (image, monitor_info, expected) =
+                capture_screen_with_retry(mode, selected_index, max_image_dimension, retry)?;
+            check_screenshot_resolution(&image, expected)?;
+            (image, monitor_info)
+        }
+    };
+
+    if crop_bottom_pixels == 0 {
+        return Ok((image, monitor_info));
+    }
+    Ok((
+        crop_screenshot_bottom(&image, crop_bottom_pixels)?,
+        monitor_info,
+    ))
+}
+
+/// Capture once, returning the image, monitor info and the expected size of the
+/// captured monitor (`None` when several monitors are stitched together).
+fn capture_screen_once(
+    mode: CaptureMode,
+    selected_index: usize,
+    max_image_dimension: u32,
+) -> AppResult<(String, MonitorInfo, Option<(u32, u32)>)> {
+    let monitor_details = require_display(get_monitor_list())?;
     let monitors = xcap::Monitor::all().map_err(|e| AppError::screenshot(e.to_string()))?;
     if monitors.is_empty() {
-        return Err(AppError::screenshot("No monitors found"));
+        return Err(no_display_error("monitor list is empty"));
     }
     let monitor_info = MonitorInfo {
         count: monitor_details.len(),
         monitors: monitor_details.clone(),
     };
-    let image = match mode {
+    let expected_size = |index: usize| monitor_details.get(index).map(|m| (m.width, m.height));
+    let (image, expected) = match mode {
         CaptureMode::Primary => {
             let primary_index = monitor_details
                 .iter()
                 .position(|m| m.is_primary)
                 .unwrap_or(0);
-            capture_single_monitor_xcap(&monitors, primary_index)?
+            // A primary monitor that cannot be captured for no specific reason
+            // means there is no display session to capture (e.g. RDP disconnected)
+            let image = capture_single_monitor_xcap(&monitors, primary_index).map_err(|e| {
+                if classify_screenshot_error(&e.message) == ScreenshotErrorKind::Unknown {
+                    no_display_error(&e.message)
+                } else {
+                    e
+                }
+            })?;
+            (image, expected_size(primary_index))
         }
         CaptureMode::Secondary => {
             let index = if selected_index < monitors.len() {
@@ -562,11 +1385,17 @@ fn capture_screen_with_mode(
                     .position(|m| !m.is_primary)
                     .unwrap_or(0)
             };
-            capture_single_monitor_xcap(&monitors, index)?
+            (
+                capture_single_monitor_xcap(&monitors, index)?,
+                expected_size(index),
+            )
         }
-        CaptureMode::All => stitch_monitors_xcap(&monitors, &monitor_details)?,
+        CaptureMode::All => (
+            stitch_monitors_xcap(&monitors, &monitor_details, max_image_dimension)?,
+            None,
+        ),
     };
-    Ok((image, monitor_info))
+    Ok((image, monitor_info, expected))
 }
 
 fn capture_single_monitor_xcap(monitors: &[xcap::Monitor], index: usize) -> AppResult<String> {
@@ -588,12 +1417,12 @@ fn capture_single_monitor_xcap(monitors: &[xcap::Monitor], index: usize) -> AppR
 fn stitch_monitors_xcap(
     monitors: &[xcap::Monitor],
     monitor_details: &[crate::monitor_types::MonitorDetail],
+    max_image_dimension: u32,
 ) -> AppResult<String> {
     if monitors.is_empty() {
         return Err(AppError::screenshot("No monitors to stitch"));
     }
-    let mut captured_images: Vec<(crate::monitor_types::MonitorDetail, image::RgbaImage)> =
-        Vec::new();
+    let mut captured_images: Vec<(u32, image::RgbaImage)> = Vec::new();
     for (index, _monitor) in monitors.iter().enumerate() {
         let image_base64 = capture_single_monitor_xcap(monitors, index)?;
         let image_data =
@@ -601,44 +1430,13 @@ fn stitch_monitors_xcap(
                 .map_err(|e| {
                     AppError::screenshot(format!("Failed to decode captured image: {}", e))
                 })?;
-        let img = image::load_from_memory(&image_data)?;
-        let rgba_image = img.to_rgba8();
-        let detail = monitor_details.get(index).cloned().unwrap_or_else(|| {
-            crate::monitor_types::MonitorDetail {
-                index,
-                name: format!("Monitor {}", index + 1),
-                width: rgba_image.width(),
-                height: rgba_image.height(),
-                x: 0,
-                y: 0,
-                is_primary: false,
-            }
-        });
-        captured_images.push((detail, rgba_image));
-    }
-    if captured_images.len() == 1 {
-        let (_, img) = &captured_images[0];
-        let mut buffer = Vec::new();
-        let mut cursor = std::io::Cursor::new(&mut buffer);
-        img.write_to(&mut cursor, image::ImageFormat::Png)
-            .map_err(|e| AppError::screenshot(format!("Failed to encode stitched image: {}", e)))?;
-        return Ok(base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            &buffer,
-        ));
-    }
-    let total_width: u32 = captured_images.iter().map(|(d, _)| d.width).sum();
-    let max_height: u32 = captured_images
-        .iter()
-        .map(|(_, img)| img.height())
-        .max()
-        .unwrap_or(0);
-    let mut stitched = image::RgbaImage::new(total_width, max_height);
-    let mut x_offset: u32 = 0;
-    for (detail, img) in &captured_images {
-        image::imageops::overlay(&mut stitched, img, x_offset as i64, 0);
-        x_offset += detail.width;
+        let rgba_image = image::load_from_memory(&image_data)?.to_rgba8();
+        let slot_width = monitor_details
+            .get(index)
+            .map_or(rgba_image.width(), |detail| detail.width);
+        captured_images.push((slot_width, rgba_image));
     }
+    let stitched = stitch_and_fit(captured_images, max_image_dimension);
     let mut buffer = Vec::new();
     let mut cursor = std::io::Cursor::new(&mut buffer);
     stitched
@@ -650,34 +1448,569 @@ fn stitch_monitors_xcap(
     ))
 }
 
-fn save_screenshot(image_base64: &str) -> Option<String> {
-    let image_data =
-        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, image_base64).ok()?;
-    let img = image::load_from_memory(&image_data).ok()?;
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%f");
-    let filename = format!("screenshot_{}.png", timestamp);
-    let user_home = dirs::home_dir()?;
-    let daily_logger_dir = user_home.join("DailyLogger");
-    let screenshots_dir = daily_logger_dir.join("screenshots");
-    std::fs::create_dir_all(&screenshots_dir).ok()?;
-    let path = screenshots_dir.join(&filename);
-    img.save(&path).ok()?;
+/// Default of the `max_image_dimension` setting, in pixels.
+pub const DEFAULT_MAX_IMAGE_DIMENSION: u32 = 7680;
+
+/// Size of a `width`×`height` image scaled down (aspect ratio kept) so neither
+/// side exceeds `max_dimension`. Smaller images and `max_dimension == 0` are
+/// left unchanged; sides never drop below 1 pixel.
+fn fit_within(width: u32, height: u32, max_dimension: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if max_dimension == 0 || longest <= max_dimension {
+        return (width, height);
+    }
+    let scale = |side: u32| ((side as u64 * max_dimension as u64) / longest as u64).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+/// Place monitor images side by side, left to right, and scale the result down
+/// to fit `max_dimension`. Each image takes a slot of the given width (its
+/// monitor's logical width); the canvas is as tall as the tallest image.
+fn stitch_and_fit(
+    mut images: Vec<(u32, image::RgbaImage)>,
+    max_dimension: u32,
+) -> image::RgbaImage {
+    let stitched = if images.len() == 1 {
+        images.remove(0).1
+    } else {
+        let total_width: u32 = images.iter().map(|(slot, _)| slot).sum();
+        let max_height = images
+            .iter()
+            .map(|(_, img)| img.height())
+            .max()
+            .unwrap_or(0);
+        let mut canvas = image::RgbaImage::new(total_width, max_height);
+        let mut x_offset: u32 = 0;
+        for (slot_width, img) in &images {
+            image::imageops::overlay(&mut canvas, img, x_offset as i64, 0);
+            x_offset += slot_width;
+        }
+        canvas
+    };
+
+    let (width, height) = fit_within(stitched.width(), stitched.height(), max_dimension);
+    if (width, height) == stitched.dimensions() {
+        return stitched;
+    }
+    tracing::info!(
+        "Scaling stitched screenshot from {}x{} to {}x{}",
+        stitched.width(),
+        stitched.height(),
+        width,
+        height
+    );
+    image::imageops::resize(
+        &stitched,
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    )
+}
+
+/// Screenshot directory: `<DAILYLOGGER_DATA_DIR>/screenshots` when the data root
+/// is overridden, otherwise ~/DailyLogger/screenshots
+fn screenshots_dir() -> Option<PathBuf> {
+    if let Some(root) = crate::data_dir_override() {
+        return Some(root.join("screenshots"));
+    }
+    Some(dirs::home_dir()?.join("DailyLogger").join("screenshots"))
+}
+
+/// Subdirectory of the screenshot directory that holds screenshots whose analysis failed.
+const FAILED_SCREENSHOTS_DIR: &str = "failed";
+
+/// Move a screenshot into `<screenshots_dir>/failed/` so failed captures are easy to inspect.
+/// Falls back to copying when the file cannot be renamed (e.g. across filesystems).
+/// Returns the new path, or `None` if the screenshot could not be moved or copied.
+fn move_screenshot_to_failed(screenshot_path: &Path, screenshots_dir: &Path) -> Option<String> {
+    let failed_dir = screenshots_dir.join(FAILED_SCREENSHOTS_DIR);
+    std::fs::create_dir_all(&failed_dir).ok()?;
+    let target = failed_dir.join(screenshot_path.file_name()?);
+
+    if std::fs::rename(screenshot_path, &target).is_err() {
+        std::fs::copy(screenshot_path, &target).ok()?;
+    }
+    // Failed screenshots are not shown in galleries
+    let _ = std::fs::remove_file(crate::manual_entry::thumbnail_path(screenshot_path));
+    Some(target.to_string_lossy().to_string())
+}
+
+/// Compensation when the record of a failed screenshot could not be updated:
+/// move the screenshot back from `failed/` so the record's path stays valid.
+fn restore_failed_screenshot(failed_path: &Path, original_path: &Path) -> bool {
+    if std::fs::rename(failed_path, original_path).is_ok() {
+        return true;
+    }
+    if std::fs::copy(failed_path, original_path).is_ok() {
+        let _ = std::fs::remove_file(failed_path);
+        return true;
+    }
+    false
+}
+
+/// File format used when saving screenshots to disk (`screenshot_format` setting).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ScreenshotFormat {
+    /// Parse the `screenshot_format` setting; unknown values fall back to PNG.
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            Some("jpeg") | Some("jpg") => ScreenshotFormat::Jpeg,
+            Some("webp") => ScreenshotFormat::WebP,
+            _ => ScreenshotFormat::Png,
+        }
+    }
+
+    /// Whether the `screenshot_format` setting asks to choose the format by content.
+    pub fn is_auto_setting(value: Option<&str>) -> bool {
+        value.is_some_and(|v| v.trim().eq_ignore_ascii_case("auto"))
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::WebP => "webp",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "image/png",
+            ScreenshotFormat::Jpeg => "image/jpeg",
+            ScreenshotFormat::WebP => "image/webp",
+        }
+    }
+
+    fn image_format(self) -> image::ImageFormat {
+        match self {
+            ScreenshotFormat::Png => image::ImageFormat::Png,
+            ScreenshotFormat::Jpeg => image::ImageFormat::Jpeg,
+            ScreenshotFormat::WebP => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// Parse the `endpoint_image_formats` setting: a JSON object mapping a
+/// pattern of the API base URL to `"png"` or `"jpeg"`. Other formats and
+/// invalid JSON are ignored.
+pub fn parse_endpoint_image_formats(json: Option<&str>) -> Vec<(String, ScreenshotFormat)> {
+    json.and_then(|s| serde_json::from_str::<HashMap<String, String>>(s).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(pattern, _)| !pattern.trim().is_empty())
+        .filter_map(
+            |(pattern, format)| match ScreenshotFormat::from_setting(Some(&format)) {
+                ScreenshotFormat::WebP => None,
+                format => Some((pattern.trim().to_lowercase(), format)),
+            },
+        )
+        .collect()
+}
+
+/// Image format for the analysis endpoint at `api_base_url`: the mapping whose
+/// pattern is contained in the URL (case-insensitive, longest pattern wins),
+/// PNG when none matches.
+pub fn endpoint_image_format(
+    api_base_url: &str,
+    formats: &[(String, ScreenshotFormat)],
+) -> ScreenshotFormat {
+    let url = api_base_url.to_lowercase();
+    formats
+        .iter()
+        .filter(|(pattern, _)| url.contains(pattern.as_str()))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, format)| *format)
+        .unwrap_or(ScreenshotFormat::Png)
+}
+
+/// `data:` URL of a PNG screenshot in the given format, re-encoding when it is not PNG.
+pub fn analysis_image_data_url(png_base64: &str, format: ScreenshotFormat) -> AppResult<String> {
+    let image_base64 = match format {
+        ScreenshotFormat::Png => png_base64.to_string(),
+        _ => {
+            let bytes =
+                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, png_base64)?;
+            let img = image::load_from_memory(&bytes)?;
+            base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                encode_image(&img, format)?,
+            )
+        }
+    };
+    Ok(format!(
+        "data:{};base64,{}",
+        format.mime_type(),
+        image_base64
+    ))
+}
+
+/// Encode an image in the given format.
+fn encode_image(img: &image::DynamicImage, format: ScreenshotFormat) -> AppResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut buffer);
+    match format {
+        // JPEG has no alpha channel
+        ScreenshotFormat::Jpeg => image::DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(&mut cursor, format.image_format())?,
+        _ => img.write_to(&mut cursor, format.image_format())?,
+    }
+    Ok(buffer)
+}
+
+/// Encode a screenshot in the configured format, falling back to PNG when that fails
+/// (e.g. JPEG/WebP rejecting unusual dimensions). Returns the bytes and the format used.
+fn encode_screenshot(
+    img: &image::DynamicImage,
+    preferred: ScreenshotFormat,
+) -> AppResult<(Vec<u8>, ScreenshotFormat)> {
+    encode_with_fallback(img, preferred, encode_image)
+}
+
+fn encode_with_fallback<F>(
+    img: &image::DynamicImage,
+    preferred: ScreenshotFormat,
+    encode: F,
+) -> AppResult<(Vec<u8>, ScreenshotFormat)>
+where
+    F: Fn(&image::DynamicImage, ScreenshotFormat) -> AppResult<Vec<u8>>,
+{
+    let preferred_err = match encode(img, preferred) {
+        Ok(bytes) => return Ok((bytes, preferred)),
+        Err(e) if preferred != ScreenshotFormat::Png => e,
+        Err(e) => {
+            tracing::error!(
+                "Failed to encode {}x{} screenshot as PNG: {}",
+                img.width(),
+                img.height(),
+                e
+            );
+            return Err(AppError::screenshot(format!(
+                "Failed to encode screenshot: {}",
+                e
+            )));
+        }
+    };
+
+    tracing::warn!(
+        "Failed to encode {}x{} screenshot as {}: {}, falling back to PNG",
+        img.width(),
+        img.height(),
+        preferred.extension(),
+        preferred_err
+    );
+    match encode(img, ScreenshotFormat::Png) {
+        Ok(bytes) => {
+            tracing::info!(
+                "Screenshot encoded as PNG after {} failed",
+                preferred.extension()
+            );
+            Ok((bytes, ScreenshotFormat::Png))
+        }
+        Err(e) => {
+            tracing::error!("PNG fallback encoding also failed: {}", e);
+            Err(AppError::screenshot(format!(
+                "Failed to encode screenshot as {} ({}) and PNG ({})",
+                preferred.extension(),
+                preferred_err,
+                e
+            )))
+        }
+    }
+}
+
+fn save_screenshot_to(
+    screenshots_dir: &Path,
+    image_base64: &str,
+    format: ScreenshotFormat,
+    auto_format: bool,
+) -> Option<String> {
+    std::fs::create_dir_all(screenshots_dir).ok()?;
+    write_screenshot_file(
+        &screenshots_dir.join(screenshot_filename(format)),
+        image_base64,
+        format,
+        auto_format,
+    )
+}
+
+/// Longest side of the sample a screenshot's content is measured on.
+const COMPLEXITY_SAMPLE_SIZE: u32 = 256;
+/// Luma step between neighbouring pixels that counts as a sharp edge.
+const SHARP_EDGE_STEP: u8 = 40;
+/// Share of distinct colors from which a screenshot looks photographic.
+const PHOTO_COLOR_VARIETY: f64 = 0.25;
+/// Share of sharp edges from which a screenshot is treated as text or UI.
+const TEXT_EDGE_DENSITY: f64 = 0.08;
+
+/// Content measures of a screenshot, each between 0 and 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageComplexity {
+    /// Distinct colors per sampled pixel: low for flat UI, high for photos
+    pub color_variety: f64,
+    /// Share of pixels with a sharp luma step to their right neighbour: high for text
+    pub edge_density: f64,
+}
+
+/// Measure a screenshot on a sample of at most `COMPLEXITY_SAMPLE_SIZE` pixels
+/// per side. Nearest-neighbour sampling keeps the original colors and edges.
+pub fn image_complexity(img: &image::DynamicImage) -> ImageComplexity {
+    let sample = if img.width().max(img.height()) > COMPLEXITY_SAMPLE_SIZE {
+        img.resize(
+            COMPLEXITY_SAMPLE_SIZE,
+            COMPLEXITY_SAMPLE_SIZE,
+            image::imageops::FilterType::Nearest,
+        )
+    } else {
+        img.clone()
+    };
+    let rgb = sample.to_rgb8();
+    let pixels = (rgb.width() as usize * rgb.height() as usize).max(1);
+
+    let colors: std::collections::HashSet<[u8; 3]> = rgb.pixels().map(|p| p.0).collect();
+    let luma = sample.to_luma8();
+    let edges = luma
+        .rows()
+        .map(|row| {
+            let row: Vec<u8> = row.map(|p| p.0[0]).collect();
+            row.windows(2)
+                .filter(|pair| pair[0].abs_diff(pair[1]) > SHARP_EDGE_STEP)
+                .count()
+        })
+        .sum::<usize>();
+
+    ImageComplexity {
+        color_variety: colors.len() as f64 / pixels as f64,
+        edge_density: edges as f64 / pixels as f64,
+    }
+}
+
+/// Save format for a screenshot's content: JPEG for photo-like images (many
+/// colors, few sharp edges), PNG for text and UI, which JPEG would blur.
+pub fn format_for_content(complexity: ImageComplexity) -> ScreenshotFormat {
+    if complexity.color_variety >= PHOTO_COLOR_VARIETY
+        && complexity.edge_density < TEXT_EDGE_DENSITY
+    {
+        ScreenshotFormat::Jpeg
+    } else {
+        ScreenshotFormat::Png
+    }
+}
+
+fn screenshot_filename(format: ScreenshotFormat) -> String {
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S_%f");
+    format!("screenshot_{}.{}", timestamp, format.extension())
+}
+
+/// Decode, encode and write a screenshot to `path`.
+/// The file is written under a `.part` name and renamed when complete, so readers
+/// never see a half-written screenshot. The extension follows the format actually
+/// used, which differs from `path` when encoding fell back to PNG or `auto_format`
+/// chose the format from the content (see [`format_for_content`]).
+fn write_screenshot_file(
+    path: &Path,
+    image_base64: &str,
+    format: ScreenshotFormat,
+    auto_format: bool,
+) -> Option<String> {
+    let image_data =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, image_base64).ok()?;
+    let img = image::load_from_memory(&image_data).ok()?;
+    let format = if auto_format {
+        format_for_content(image_complexity(&img))
+    } else {
+        format
+    };
+    let (bytes, format) = encode_screenshot(&img, format).ok()?;
+    let path = path.with_extension(format.extension());
+    let part_path = crate::manual_entry::pending_screenshot_path(&path);
+    if let Err(e) = std::fs::write(&part_path, bytes) {
+        tracing::error!("Failed to write screenshot {:?}: {}", part_path, e);
+        let _ = std::fs::remove_file(&part_path);
+        return None;
+    }
+    std::fs::rename(&part_path, &path).ok()?;
+    if let Err(e) = write_thumbnail(&img, &path) {
+        tracing::warn!("Failed to write thumbnail for {:?}: {}", path, e);
+    }
     Some(path.to_string_lossy().to_string())
 }
 
+/// Longest side of screenshot thumbnails, in pixels.
+pub const THUMBNAIL_MAX_SIDE: u32 = 320;
+
+/// Write a JPEG thumbnail of `img` (aspect ratio kept, at most
+/// `THUMBNAIL_MAX_SIDE` wide and high) to the thumbnail path of `screenshot_path`.
+fn write_thumbnail(img: &image::DynamicImage, screenshot_path: &Path) -> AppResult<PathBuf> {
+    let thumbnail_path = crate::manual_entry::thumbnail_path(screenshot_path);
+    if let Some(dir) = thumbnail_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let thumbnail = img.thumbnail(THUMBNAIL_MAX_SIDE, THUMBNAIL_MAX_SIDE);
+    std::fs::write(
+        &thumbnail_path,
+        encode_image(&thumbnail, ScreenshotFormat::Jpeg)?,
+    )?;
+    Ok(thumbnail_path)
+}
+
+/// A screenshot being written to disk on a blocking background task.
+struct PendingScreenshot {
+    /// Path the screenshot will have once written
+    path: String,
+    task: tokio::task::JoinHandle<Option<String>>,
+}
+
+impl PendingScreenshot {
+    /// Wait for the write to finish and return the final path, or `None` if it failed.
+    async fn wait(self) -> Option<String> {
+        match self.task.await {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::error!("Screenshot save task failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Start saving a screenshot in the background and return its planned path right away,
+/// so the capture pipeline does not wait for encoding and disk I/O.
+fn spawn_screenshot_save(
+    screenshots_dir: &Path,
+    image_base64: &str,
+    format: ScreenshotFormat,
+    auto_format: bool,
+) -> Option<PendingScreenshot> {
+    std::fs::create_dir_all(screenshots_dir).ok()?;
+    let path = screenshots_dir.join(screenshot_filename(format));
+    let planned = path.clone();
+    let image_base64 = image_base64.to_string();
+    let task = tokio::task::spawn_blocking(move || {
+        write_screenshot_file(&planned, &image_base64, format, auto_format)
+    });
+    Some(PendingScreenshot {
+        path: path.to_string_lossy().to_string(),
+        task,
+    })
+}
+
+/// Compensation when no record could be stored for a capture: wait for the
+/// background save and delete the screenshot and its thumbnail, so no orphan
+/// file is left behind.
+async fn discard_pending_screenshot(pending: Option<PendingScreenshot>) {
+    let Some(pending) = pending else {
+        return;
+    };
+    if let Some(saved) = pending.wait().await {
+        let saved = Path::new(&saved);
+        if let Err(e) = std::fs::remove_file(saved) {
+            tracing::warn!("Failed to remove orphan screenshot {:?}: {}", saved, e);
+        }
+        let _ = std::fs::remove_file(crate::manual_entry::thumbnail_path(saved));
+    }
+}
+
+/// Wait for a background screenshot save and repoint the record when the final path
+/// differs from the planned one (PNG fallback) or the save failed.
+async fn finish_screenshot_save(
+    pending: Option<PendingScreenshot>,
+    record_id: i64,
+) -> Option<String> {
+    let pending = pending?;
+    let planned = pending.path.clone();
+    let saved = pending.wait().await;
+    if saved.as_deref() != Some(planned.as_str()) {
+        if saved.is_none() {
+            tracing::error!("Failed to save screenshot for record_id={}", record_id);
+        }
+        if let Err(e) =
+            memory_storage::update_record_screenshot_path_sync(record_id, saved.as_deref())
+        {
+            tracing::error!(
+                "Failed to update screenshot path for record_id={}: {}",
+                record_id,
+                e
+            );
+        }
+    }
+    saved
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // AI Analysis Functions
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Key for the next analysis request: rotates through `api_key` and `api_keys`
+/// when extra keys are configured, otherwise always the primary key.
+fn select_api_key(settings: &CaptureSettings) -> String {
+    if settings.api_keys.is_empty() {
+        return settings.api_key.clone();
+    }
+    let keys: Vec<String> = std::iter::once(settings.api_key.clone())
+        .chain(settings.api_keys.iter().cloned())
+        .collect();
+    api_key_pool::next_api_key(&keys).unwrap_or_else(|| settings.api_key.clone())
+}
+
+/// Parse the analysis out of a chat completion. Errors only quote the model
+/// output when `log_payload` is set: they end up in logs and in the record.
+fn parse_analysis_response(
+    response_body: &serde_json::Value,
+    log_payload: bool,
+) -> AppResult<ScreenAnalysis> {
+    let content = response_body["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| {
+            AppError::validation(format!(
+                "Invalid API response format: {}",
+                crate::synthesis::llm_payload_for_log(&response_body.to_string(), log_payload)
+            ))
+        })?;
+    let content = content.trim();
+    let json_start = content.find('{').unwrap_or(0);
+    let json_end = content.rfind('}').map(|i| i + 1).unwrap_or(content.len());
+    let json_str = &content[json_start..json_end];
+    let analysis: ScreenAnalysis = serde_json::from_str(json_str).map_err(|e| {
+        AppError::validation(format!(
+            "Failed to parse analysis: {}. Content: {}",
+            e,
+            crate::synthesis::llm_payload_for_log(content, log_payload)
+        ))
+    })?;
+    let usage = TokenUsage::from_response(response_body);
+    if let Some(usage) = usage {
+        tracing::debug!(
+            "Analysis used {} prompt + {} completion tokens",
+            usage.prompt_tokens,
+            usage.completion_tokens
+        );
+    }
+    Ok(ScreenAnalysis { usage, ..analysis })
+}
+
 async fn analyze_screen(
     settings: &CaptureSettings,
     image_base64: &str,
 ) -> AppResult<ScreenAnalysis> {
     require_api_key(settings)?;
-    let prompt = settings
+    let base_prompt = settings
         .analysis_prompt
         .as_deref()
         .unwrap_or(DEFAULT_ANALYSIS_PROMPT);
+    let corrections =
+        memory_storage::get_common_software_corrections(memory_storage::MAX_PROMPT_CORRECTIONS)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load software corrections: {}", e);
+                Vec::new()
+            });
+    let prompt = memory_storage::inject_software_corrections(base_prompt, &corrections);
     let client = crate::create_http_client_with_proxy(
         &settings.api_base_url,
         60,
@@ -693,6 +2026,7 @@ async fn analyze_screen(
             None
         },
     )?;
+    let image_url = analysis_image_data_url(image_base64, settings.analysis_image_format)?;
     let payload = serde_json::json!({
         "model": settings.model_name,
         "messages": [
@@ -703,49 +2037,56 @@ async fn analyze_screen(
                     {
                         "type": "image_url",
                         "image_url": {
-                            "url": format!("data:image/png;base64,{}", image_base64)
+                            "url": image_url
                         }
                     }
                 ]
             }
         ],
-        "max_tokens": 1000
+        "max_tokens": ANALYSIS_MAX_TOKENS
     });
-    let mut request = client
-        .post(format!("{}/chat/completions", settings.api_base_url))
-        .header("Content-Type", "application/json");
-    if !settings.api_key.is_empty() {
-        request = request.header("Authorization", format!("Bearer {}", settings.api_key));
-    }
-    for header in &settings.custom_headers {
-        request = request.header(&header.key, &header.value);
-    }
-    let response = request.json(&payload).send().await?;
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(AppError::network(format!(
-            "API request failed with status {}: {}",
-            status, body
-        )));
+    let endpoint = format!("{}/chat/completions", settings.api_base_url);
+    let result = async {
+        let api_key = select_api_key(settings);
+        let mut request = client
+            .post(&endpoint)
+            .header("Content-Type", "application/json");
+        if !api_key.is_empty() && !memory_storage::has_custom_auth_header(&settings.custom_headers)
+        {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        for header in &settings.custom_headers {
+            request = request.header(&header.key, &header.value);
+        }
+        let response = request.json(&payload).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            if !settings.api_keys.is_empty() && api_key_pool::should_bench_key(status.as_u16()) {
+                api_key_pool::bench_api_key(&api_key);
+            }
+            let body = response.text().await.unwrap_or_default();
+            return Err(analysis_status_error(status, &body));
+        }
+        let response_body: serde_json::Value = response.json().await?;
+        parse_analysis_response(&response_body, settings.log_llm_payload)
     }
-    let response_body: serde_json::Value = response.json().await?;
-    let content = response_body["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or_else(|| {
-            AppError::validation(format!("Invalid API response format: {:?}", response_body))
-        })?;
-    let content = content.trim();
-    let json_start = content.find('{').unwrap_or(0);
-    let json_end = content.rfind('}').map(|i| i + 1).unwrap_or(content.len());
-    let json_str = &content[json_start..json_end];
-    let analysis: ScreenAnalysis = serde_json::from_str(json_str).map_err(|e| {
-        AppError::validation(format!(
-            "Failed to parse analysis: {}. Content: {}",
-            e, content
-        ))
-    })?;
-    Ok(analysis)
+    .await;
+
+    if let Err(e) = &result {
+        if settings.debug_dump_failed_requests {
+            match crate::debug_dump::dump_failed_request(
+                &crate::debug_dump::get_debug_dir(),
+                &endpoint,
+                &payload,
+                image_base64,
+                &e.to_string(),
+            ) {
+                Ok(dir) => tracing::info!("Saved failed analysis request to {}", dir.display()),
+                Err(dump_err) => tracing::warn!("Failed to dump analysis request: {}", dump_err),
+            }
+        }
+    }
+    result
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -766,11 +2107,48 @@ pub fn start_auto_capture_service() -> AppResult<()> {
     if should_analyze_immediately(&settings, CaptureTriggerSource::Auto) {
         require_api_key(&settings)?;
     }
+    if let Ok(arc) = memory_storage::get_settings_sync() {
+        warn_if_request_exceeds_context(&arc);
+    }
     set_threshold(settings.max_silent_minutes);
     AUTO_CAPTURE_RUNNING.store(true, Ordering::SeqCst);
     Ok(())
 }
 
+/// Captures are held off while this is alive; see `pause_capture`.
+pub struct CapturePause {
+    _gate: tokio::sync::MutexGuard<'static, ()>,
+}
+
+/// Wait for a running capture to finish, then hold off new captures until the
+/// returned guard is dropped. Captures attempted meanwhile are skipped.
+pub async fn pause_capture() -> CapturePause {
+    CapturePause {
+        _gate: CAPTURE_GATE.lock().await,
+    }
+}
+
+/// Skip every capture until the app restarts, e.g. after the data directory
+/// was moved and new records would land in the old database.
+pub fn suspend_capture_until_restart() {
+    CAPTURE_SUSPENDED.store(true, Ordering::SeqCst);
+    tracing::info!("Capture suspended until restart");
+}
+
+/// Wait `delay_seconds` before the first auto capture so the desktop has finished
+/// loading after login/autostart. `sleep` is injected so tests can observe the delay.
+pub async fn wait_startup_delay<F, Fut>(delay_seconds: u64, sleep: F)
+where
+    F: FnOnce(Duration) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    if delay_seconds == 0 {
+        return;
+    }
+    tracing::info!("Waiting {}s before the first auto capture", delay_seconds);
+    sleep(Duration::from_secs(delay_seconds)).await;
+}
+
 /// Service function to stop auto capture
 pub fn stop_auto_capture_service() {
     AUTO_CAPTURE_RUNNING.store(false, Ordering::SeqCst);
@@ -781,26 +2159,28 @@ pub fn stop_auto_capture_service() {
 pub async fn trigger_capture_service() -> AppResult<()> {
     let settings = load_capture_settings();
     require_api_key(&settings)?;
-    capture_and_store_inner(settings, CaptureTriggerSource::Manual)
+    let lang = settings.language;
+    run_capture_with_events(settings, CaptureTriggerSource::Manual)
         .await
         .map_err(|e| {
             let err_str = e.to_string();
             tracing::error!("Trigger capture failed: {}", err_str);
             let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
+            AppError::screenshot(get_screenshot_error_message(&kind, &err_str, lang))
         })
 }
 
 /// Service function to trigger a single auto capture using current settings.
 pub async fn trigger_auto_capture_service() -> AppResult<()> {
     let settings = load_capture_settings();
-    capture_and_store_inner(settings, CaptureTriggerSource::Auto)
+    let lang = settings.language;
+    run_capture_with_events(settings, CaptureTriggerSource::Auto)
         .await
         .map_err(|e| {
             let err_str = e.to_string();
             tracing::error!("Auto trigger capture failed: {}", err_str);
             let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
+            AppError::screenshot(get_screenshot_error_message(&kind, &err_str, lang))
         })
 }
 
@@ -810,13 +2190,14 @@ pub async fn trigger_auto_capture_with_arc(
     arc: std::sync::Arc<crate::memory_storage::Settings>,
 ) -> AppResult<()> {
     let settings = load_capture_settings_from_arc(&arc);
-    capture_and_store_inner(settings, CaptureTriggerSource::Auto)
+    let lang = settings.language;
+    run_capture_with_events(settings, CaptureTriggerSource::Auto)
         .await
         .map_err(|e| {
             let err_str = e.to_string();
             tracing::error!("Auto trigger capture failed: {}", err_str);
             let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
+            AppError::screenshot(get_screenshot_error_message(&kind, &err_str, lang))
         })
 }
 
@@ -827,38 +2208,90 @@ pub async fn trigger_capture_with_arc(
 ) -> AppResult<()> {
     let settings = load_capture_settings_from_arc(&arc);
     require_api_key(&settings)?;
-    capture_and_store_inner(settings, CaptureTriggerSource::Manual)
+    let lang = settings.language;
+    run_capture_with_events(settings, CaptureTriggerSource::Manual)
         .await
         .map_err(|e| {
             let err_str = e.to_string();
             tracing::error!("Trigger capture failed: {}", err_str);
             let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
+            AppError::screenshot(get_screenshot_error_message(&kind, &err_str, lang))
         })
 }
 
-/// Service function to take a screenshot and save to disk (no AI analysis)
-pub async fn take_screenshot_service() -> AppResult<String> {
+/// Where `take_screenshot_service` puts the screenshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotDestination {
+    /// Save a file in the screenshot directory and return its path
+    Disk,
+    /// Write nothing and return a PNG data URL (for repeated previews)
+    Memory,
+}
+
+/// Turn a captured screenshot into the result of `take_screenshot_service`.
+fn deliver_screenshot(
+    image_base64: &str,
+    destination: ScreenshotDestination,
+    screenshots_dir: Option<&Path>,
+    settings: &CaptureSettings,
+) -> AppResult<String> {
+    match destination {
+        ScreenshotDestination::Memory => {
+            analysis_image_data_url(image_base64, ScreenshotFormat::Png)
+        }
+        ScreenshotDestination::Disk => {
+            let screenshot_path = screenshots_dir
+                .and_then(|dir| {
+                    save_screenshot_to(
+                        dir,
+                        image_base64,
+                        settings.screenshot_format,
+                        settings.screenshot_format_auto,
+                    )
+                })
+                .ok_or_else(|| {
+                    tracing::error!("Failed to save screenshot to disk");
+                    AppError::localized(
+                        ErrorCode::Screenshot,
+                        MessageKey::ScreenshotSaveFailed,
+                        settings.language,
+                    )
+                })?;
+            tracing::info!("Screenshot saved for preview: {}", screenshot_path);
+            Ok(screenshot_path)
+        }
+    }
+}
+
+/// Service function to take a screenshot without AI analysis. Returns the
+/// saved file path, or a data URL when `destination` is memory.
+pub async fn take_screenshot_service(destination: ScreenshotDestination) -> AppResult<String> {
     let settings = load_capture_settings();
-    let capture_mode = settings
-        .capture_mode
-        .parse::<CaptureMode>()
-        .unwrap_or(CaptureMode::Primary);
-    let result =
-        capture_screen_with_mode(capture_mode, settings.selected_monitor_index).map_err(|e| {
-            let err_str = e.to_string();
-            tracing::error!("Screenshot capture failed: {}", err_str);
-            let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
-        })?;
-    let image_base64 = result.0;
-    let screenshot_path = save_screenshot(&image_base64).ok_or_else(|| {
-        tracing::error!("Failed to save screenshot to disk");
-        AppError::screenshot("截图保存失败")
+    let capture_mode = effective_capture_mode(&settings);
+    let result = capture_screen_with_mode(
+        capture_mode,
+        settings.selected_monitor_index,
+        settings.crop_bottom_pixels,
+        settings.max_image_dimension,
+        settings.capture_retry,
+    )
+    .map_err(|e| {
+        let err_str = e.to_string();
+        tracing::error!("Screenshot capture failed: {}", err_str);
+        let kind = classify_screenshot_error(&err_str);
+        AppError::screenshot(get_screenshot_error_message(
+            &kind,
+            &err_str,
+            settings.language,
+        ))
     })?;
-    tracing::info!("Screenshot saved for preview: {}", screenshot_path);
-    // Return the path with monitor info
-    Ok(screenshot_path)
+    let image_base64 = mask_excluded_regions(result.0, &settings.exclude_regions)?;
+    deliver_screenshot(
+        &image_base64,
+        destination,
+        screenshots_dir().as_deref(),
+        &settings,
+    )
 }
 
 /// Get auto capture status
@@ -881,13 +2314,30 @@ pub async fn get_quality_filter_stats_service() -> AppResult<QualityFilterStats>
     })
 }
 
+/// Total size and number of files below the screenshot directory
+pub async fn get_screenshot_disk_usage_service() -> AppResult<ScreenshotDiskUsage> {
+    let dir = screenshots_dir()
+        .ok_or_else(|| AppError::file_io("Cannot determine screenshot directory"))?;
+    let scan_dir = dir.clone();
+    let (file_count, total_bytes) =
+        tokio::task::spawn_blocking(move || crate::data_dir::measure_tree(&scan_dir, &[]))
+            .await
+            .map_err(|e| AppError::internal(format!("Disk usage task failed: {}", e)))??;
+    Ok(ScreenshotDiskUsage {
+        path: dir.to_string_lossy().to_string(),
+        total_bytes,
+        file_count,
+    })
+}
+
 /// Reset quality filter counter
 pub async fn reset_quality_filter_counter_service() -> AppResult<()> {
     reset_filtered_count();
     Ok(())
 }
 
-/// Reanalyze a single record
+/// Re-run screenshot analysis for an existing record, e.g. an `auto_error`
+/// record or one that should be analyzed again with a different model.
 pub async fn reanalyze_record_service(record_id: i64) -> AppResult<ScreenAnalysis> {
     if record_id <= 0 {
         return Err(AppError::validation(format!(
@@ -897,20 +2347,11 @@ pub async fn reanalyze_record_service(record_id: i64) -> AppResult<ScreenAnalysi
     }
 
     let record = memory_storage::get_record_by_id_sync(record_id)?;
-    let screenshot_path = record
-        .screenshot_path
-        .as_ref()
-        .ok_or_else(|| AppError::validation("Record has no screenshot"))?;
-    let image_data = std::fs::read(screenshot_path)?;
-    let image_base64 =
-        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &image_data);
+    let image_base64 = read_record_screenshot(&record)?;
     let settings = load_capture_settings();
-    require_api_key(&settings)?;
     tracing::info!("Reanalyzing record {}", record_id);
-    let analysis = analyze_screen(&settings, &image_base64).await?;
-    let content_json =
-        build_analyzed_content(&analysis, None, None, None, Some(record.content.as_str()))?;
-    memory_storage::update_record_content_sync(record_id, &content_json)?;
+    let analysis = analyze_screen_guarded(&SystemCaptureDeps, &settings, &image_base64).await?;
+    apply_reanalysis(&record, &analysis)?;
     tracing::info!(
         "Reanalysis complete for record {}: {}",
         record_id,
@@ -919,6 +2360,64 @@ pub async fn reanalyze_record_service(record_id: i64) -> AppResult<ScreenAnalysi
     Ok(analysis)
 }
 
+/// Load a record's screenshot as base64, failing clearly when the file is gone.
+fn read_record_screenshot(record: &memory_storage::Record) -> AppResult<String> {
+    let screenshot_path = record
+        .screenshot_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| AppError::validation(format!("Record {} has no screenshot", record.id)))?;
+    if !Path::new(screenshot_path).exists() {
+        return Err(AppError::file_io(format!(
+            "Screenshot file not found: {}",
+            screenshot_path
+        )));
+    }
+    let image_data = std::fs::read(screenshot_path)?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        &image_data,
+    ))
+}
+
+/// Store a fresh analysis result on the record and mark it analyzed.
+fn apply_reanalysis(record: &memory_storage::Record, analysis: &ScreenAnalysis) -> AppResult<()> {
+    let content_json =
+        build_analyzed_content(analysis, None, None, None, Some(record.content.as_str()))?;
+    memory_storage::update_record_analysis(record.id, &content_json)?;
+    record_analysis_usage(record.id, analysis);
+    Ok(())
+}
+
+/// Add the tokens `analysis` used to the record's `tokens_used`. Failures are
+/// only logged: the analysis itself is already stored.
+fn record_analysis_usage(record_id: i64, analysis: &ScreenAnalysis) {
+    let Some(usage) = analysis.usage else {
+        return;
+    };
+    if let Err(e) =
+        memory_storage::add_record_tokens_used_sync(record_id, i64::from(usage.total_tokens()))
+    {
+        tracing::warn!("Failed to store token usage of record {}: {}", record_id, e);
+    }
+}
+
+/// Re-run analysis on a stored record's screenshot and write the result back.
+/// Errors are returned as messages for `ReanalyzeResult::errors`.
+async fn reanalyze_stored_record(
+    settings: &CaptureSettings,
+    record: &memory_storage::Record,
+) -> Result<(), String> {
+    let record_id = record.id;
+    let image_base64 =
+        read_record_screenshot(record).map_err(|e| format!("Record {}: {}", record_id, e))?;
+    let analysis = analyze_screen_guarded(&SystemCaptureDeps, settings, &image_base64)
+        .await
+        .map_err(|e| format!("Record {}: Analysis failed: {}", record_id, e))?;
+    apply_reanalysis(record, &analysis)
+        .map_err(|e| format!("Record {}: Failed to update record: {}", record_id, e))
+}
+
 /// Reanalyze all records with screenshots from today
 pub async fn reanalyze_today_records_service() -> AppResult<ReanalyzeResult> {
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
@@ -942,55 +2441,11 @@ pub async fn reanalyze_today_records_service() -> AppResult<ReanalyzeResult> {
     let mut failed = 0;
     let mut errors = Vec::new();
     for record in records_with_screenshots {
-        let record_id = record.id;
-        // Filter above guarantees screenshot_path is Some
-        let screenshot_path = record.screenshot_path.expect("filtered to have screenshot");
-        let image_data = match std::fs::read(&screenshot_path) {
-            Ok(data) => data,
+        match reanalyze_stored_record(&settings, &record).await {
+            Ok(()) => success += 1,
             Err(e) => {
                 failed += 1;
-                errors.push(format!(
-                    "Record {}: Failed to read screenshot: {}",
-                    record_id, e
-                ));
-                continue;
-            }
-        };
-        let image_base64 =
-            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &image_data);
-        match analyze_screen(&settings, &image_base64).await {
-            Ok(analysis) => {
-                let content_json = match build_analyzed_content(
-                    &analysis,
-                    None,
-                    None,
-                    None,
-                    Some(record.content.as_str()),
-                ) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        failed += 1;
-                        errors.push(format!(
-                            "Record {}: Failed to serialize analysis: {}",
-                            record_id, e
-                        ));
-                        continue;
-                    }
-                };
-                if let Err(e) = memory_storage::update_record_content_sync(record_id, &content_json)
-                {
-                    failed += 1;
-                    errors.push(format!(
-                        "Record {}: Failed to update record: {}",
-                        record_id, e
-                    ));
-                    continue;
-                }
-                success += 1;
-            }
-            Err(e) => {
-                failed += 1;
-                errors.push(format!("Record {}: Analysis failed: {}", record_id, e));
+                errors.push(e);
             }
         }
     }
@@ -1030,55 +2485,11 @@ pub async fn reanalyze_records_by_date_service(date: String) -> AppResult<Reanal
     let mut failed = 0;
     let mut errors = Vec::new();
     for record in records_with_screenshots {
-        let record_id = record.id;
-        // Filter above guarantees screenshot_path is Some
-        let screenshot_path = record.screenshot_path.expect("filtered to have screenshot");
-        let image_data = match std::fs::read(&screenshot_path) {
-            Ok(data) => data,
-            Err(e) => {
-                failed += 1;
-                errors.push(format!(
-                    "Record {}: Failed to read screenshot: {}",
-                    record_id, e
-                ));
-                continue;
-            }
-        };
-        let image_base64 =
-            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &image_data);
-        match analyze_screen(&settings, &image_base64).await {
-            Ok(analysis) => {
-                let content_json = match build_analyzed_content(
-                    &analysis,
-                    None,
-                    None,
-                    None,
-                    Some(record.content.as_str()),
-                ) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        failed += 1;
-                        errors.push(format!(
-                            "Record {}: Failed to serialize analysis: {}",
-                            record_id, e
-                        ));
-                        continue;
-                    }
-                };
-                if let Err(e) = memory_storage::update_record_content_sync(record_id, &content_json)
-                {
-                    failed += 1;
-                    errors.push(format!(
-                        "Record {}: Failed to update record: {}",
-                        record_id, e
-                    ));
-                    continue;
-                }
-                success += 1;
-            }
+        match reanalyze_stored_record(&settings, &record).await {
+            Ok(()) => success += 1,
             Err(e) => {
                 failed += 1;
-                errors.push(format!("Record {}: Analysis failed: {}", record_id, e));
+                errors.push(e);
             }
         }
     }
@@ -1097,6 +2508,138 @@ pub async fn reanalyze_records_by_date_service(date: String) -> AppResult<Reanal
     })
 }
 
+/// Minimum time between two analysis requests of a full reanalysis (20 per minute).
+pub const REANALYZE_ALL_MIN_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Progress of a full reanalysis, reported after each record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReanalyzeProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub success: usize,
+    pub failed: usize,
+    /// Record just processed
+    pub record_id: i64,
+}
+
+/// Spaces out requests so that at most one starts every `min_interval`.
+#[derive(Debug)]
+pub struct RequestPacer {
+    min_interval: Duration,
+    last_start: Option<Instant>,
+}
+
+impl RequestPacer {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_start: None,
+        }
+    }
+
+    /// Time to wait before a request wanted at `now`; the request is booked
+    /// to start at `now` plus that wait.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let wait = self
+            .last_start
+            .map(|last| (last + self.min_interval).saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO);
+        self.last_start = Some(now + wait);
+        wait
+    }
+}
+
+static REANALYZE_ALL_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Held while a full reanalysis runs; a second one is refused until it is dropped.
+pub struct ReanalyzeAllGuard(());
+
+impl Drop for ReanalyzeAllGuard {
+    fn drop(&mut self) {
+        REANALYZE_ALL_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Claim the single full reanalysis slot.
+pub fn begin_reanalyze_all() -> AppResult<ReanalyzeAllGuard> {
+    REANALYZE_ALL_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .map(|_| ReanalyzeAllGuard(()))
+        .map_err(|_| AppError::validation("A full reanalysis is already running"))
+}
+
+/// Auto records with screenshots between `start_date` and `end_date` (YYYY-MM-DD, inclusive).
+pub fn reanalyze_all_targets(
+    start_date: String,
+    end_date: String,
+) -> AppResult<Vec<memory_storage::Record>> {
+    for date in [&start_date, &end_date] {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(AppError::validation(format!(
+                "Invalid date format: {}. Expected YYYY-MM-DD",
+                date
+            )));
+        }
+    }
+    require_api_key(&load_capture_settings())?;
+    Ok(
+        memory_storage::get_records_by_date_range_sync(start_date, end_date)?
+            .into_iter()
+            .filter(|r| r.source_type == "auto" && r.screenshot_path.is_some())
+            .collect(),
+    )
+}
+
+/// Reanalyze `records` one by one, starting at most one request per
+/// `min_interval`, and report progress after each record.
+pub async fn reanalyze_records_paced<F>(
+    records: Vec<memory_storage::Record>,
+    min_interval: Duration,
+    mut on_progress: F,
+) -> ReanalyzeResult
+where
+    F: FnMut(&ReanalyzeProgress),
+{
+    let settings = load_capture_settings();
+    let total = records.len();
+    let mut pacer = RequestPacer::new(min_interval);
+    let mut success = 0;
+    let mut failed = 0;
+    let mut errors = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let wait = pacer.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        match reanalyze_stored_record(&settings, record).await {
+            Ok(()) => success += 1,
+            Err(e) => {
+                failed += 1;
+                errors.push(e);
+            }
+        }
+        on_progress(&ReanalyzeProgress {
+            processed: index + 1,
+            total,
+            success,
+            failed,
+            record_id: record.id,
+        });
+    }
+    tracing::info!(
+        "Full reanalysis finished: {} records, {} success, {} failed",
+        total,
+        success,
+        failed
+    );
+    ReanalyzeResult {
+        total,
+        success,
+        failed,
+        errors,
+    }
+}
+
 /// Retry screenshot analysis (used by offline queue)
 pub async fn retry_screenshot_analysis_service(
     screenshot_path: &str,
@@ -1112,13 +2655,17 @@ pub async fn retry_screenshot_analysis_service(
         base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &image_data);
     let settings = load_capture_settings();
     if settings.api_base_url.is_empty() {
-        return Err(AppError::auth("API base URL not configured"));
+        return Err(AppError::localized(
+            ErrorCode::Auth,
+            MessageKey::ApiBaseUrlNotConfigured,
+            settings.language,
+        ));
     }
     let record = memory_storage::get_record_by_id_sync(record_id)?;
-    let analysis = analyze_screen(&settings, &image_base64).await?;
-    let content =
-        build_analyzed_content(&analysis, None, None, None, Some(record.content.as_str()))?;
-    memory_storage::update_record_content_sync(record_id, &content)?;
+    let analysis = analyze_screen_guarded(&SystemCaptureDeps, &settings, &image_base64).await?;
+    apply_reanalysis(&record, &analysis)?;
+    // Queued retries finish the analysis of a new capture
+    crate::record_hook::run_on_stored_record_hook(record_id);
     tracing::info!(
         "Successfully updated record {} with analysis result",
         record_id
@@ -1169,6 +2716,32 @@ fn build_pending_content(
     .to_string()
 }
 
+/// Content for a capture skipped because the window looked sensitive.
+/// The screenshot is neither saved nor sent for analysis.
+fn build_sensitive_skip_content(
+    active_window: &ActiveWindow,
+    monitor_info: &MonitorInfo,
+    capture_mode: CaptureMode,
+    matched_keyword: &str,
+) -> String {
+    serde_json::json!({
+        "current_focus": "因敏感内容跳过",
+        "active_software": active_window.process_name,
+        "context_keywords": [],
+        "active_window": {
+            "title": active_window.title,
+            "process_name": active_window.process_name
+        },
+        "monitor_info": {
+            "count": monitor_info.count,
+            "capture_mode": capture_mode.to_string()
+        },
+        "sensitive_skipped": true,
+        "matched_keyword": matched_keyword
+    })
+    .to_string()
+}
+
 fn extract_content_field(content: Option<&str>, field: &str) -> Option<serde_json::Value> {
     let content = content?;
     let parsed = serde_json::from_str::<serde_json::Value>(content).ok()?;
@@ -1183,6 +2756,10 @@ fn build_analyzed_content(
     existing_content: Option<&str>,
 ) -> AppResult<String> {
     let mut value = serde_json::to_value(analysis)?;
+    // Window and monitor details added below come from the system, not the model
+    if crate::redaction::redaction_enabled() {
+        crate::redaction::redact_model_fields(&mut value);
+    }
     let object = value
         .as_object_mut()
         .ok_or_else(|| AppError::validation("Invalid analysis result"))?;
@@ -1198,6 +2775,10 @@ fn build_analyzed_content(
     if let Some(window) = active_window_value {
         object.insert("active_window".to_string(), window);
     }
+    // Title the analysis prompt was given, only known for a live capture
+    if let Some(title) = active_window.and_then(active_window_title) {
+        object.insert("window_title".to_string(), serde_json::json!(title));
+    }
 
     let monitor_info_value = match (monitor_info, capture_mode) {
         (Some(info), Some(mode)) => Some(serde_json::json!({
@@ -1213,92 +2794,405 @@ fn build_analyzed_content(
     Ok(serde_json::to_string(&value)?)
 }
 
-async fn capture_and_store_inner(
+/// Run one capture, emitting `started` and then `succeeded`, `skipped` or
+/// `failed` capture events.
+async fn run_capture_with_events(
     settings: CaptureSettings,
     trigger_source: CaptureTriggerSource,
 ) -> AppResult<()> {
-    let should_analyze = should_analyze_immediately(&settings, trigger_source);
+    let started = Instant::now();
+    emit_capture_event(&CaptureEvent::new(
+        CaptureEventKind::Started,
+        trigger_source,
+    ));
+
+    let result = match CAPTURE_GATE.try_lock() {
+        Ok(_gate) if !CAPTURE_SUSPENDED.load(Ordering::SeqCst) => {
+            capture_and_store_inner(&SystemCaptureDeps, settings, trigger_source).await
+        }
+        _ => Ok(CaptureOutcome::Skipped("capture_paused")),
+    };
+    let event = match &result {
+        Ok(CaptureOutcome::Stored(record_id)) => CaptureEvent {
+            record_id: Some(*record_id),
+            ..CaptureEvent::new(CaptureEventKind::Succeeded, trigger_source)
+        },
+        Ok(CaptureOutcome::Skipped(reason)) => {
+            CaptureEvent::new(CaptureEventKind::Skipped, trigger_source).with_reason(*reason)
+        }
+        Err(e) => {
+            CaptureEvent::new(CaptureEventKind::Failed, trigger_source).with_reason(e.to_string())
+        }
+    };
+    emit_capture_event(&event.with_duration(started));
+    result.map(|_| ())
+}
+
+/// Parameters of one screenshot, taken from the capture settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenshotParams {
+    mode: CaptureMode,
+    monitor_index: usize,
+    crop_bottom_pixels: u32,
+    max_image_dimension: u32,
+    retry: CaptureRetry,
+}
 
-    let active_window = get_active_window();
+/// What to do before taking a screenshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PreCaptureDecision {
+    Skip(&'static str),
+    Capture(ScreenshotParams),
+}
+
+/// Capture mode to use: all monitors when `capture_all_monitors` is set,
+/// otherwise `capture_mode` (primary when unknown).
+fn effective_capture_mode(settings: &CaptureSettings) -> CaptureMode {
+    if settings.capture_all_monitors {
+        return CaptureMode::All;
+    }
+    settings
+        .capture_mode
+        .parse::<CaptureMode>()
+        .unwrap_or(CaptureMode::Primary)
+}
 
+/// Decide from the foreground window whether to take a screenshot, and how.
+fn decide_pre_capture(settings: &CaptureSettings, window: &ActiveWindow) -> PreCaptureDecision {
+    if excluded_app_match(window, &settings.excluded_apps).is_some() {
+        return PreCaptureDecision::Skip("app_excluded");
+    }
     if !should_capture_by_window(
-        &active_window,
+        window,
         &settings.window_whitelist,
         &settings.window_blacklist,
         settings.use_whitelist_only,
     ) {
-        tracing::info!(
-            "Skipping capture: window filtered (title='{}', process='{}')",
-            active_window.title,
-            active_window.process_name
-        );
-        return Ok(());
+        return PreCaptureDecision::Skip("window_filtered");
     }
+    PreCaptureDecision::Capture(ScreenshotParams {
+        mode: effective_capture_mode(settings),
+        monitor_index: settings.selected_monitor_index,
+        crop_bottom_pixels: settings.crop_bottom_pixels,
+        max_image_dimension: settings.max_image_dimension,
+        retry: settings.capture_retry,
+    })
+}
 
-    let capture_mode = settings
-        .capture_mode
-        .parse::<CaptureMode>()
-        .unwrap_or(CaptureMode::Primary);
+/// What is known about a screenshot once it has been taken.
+#[derive(Debug, Clone, PartialEq)]
+struct FrameFacts {
+    /// Result of the change detection against the last kept frame
+    capture_reason: Option<CaptureReason>,
+    /// Quality score, only computed when the quality filter is enabled
+    quality_score: Option<f64>,
+    /// Sensitive keyword found in the foreground window
+    sensitive_match: Option<String>,
+}
 
-    let (image_base64, monitor_info) =
-        capture_screen_with_mode(capture_mode, settings.selected_monitor_index).map_err(|e| {
-            let err_str = e.to_string();
-            tracing::error!("Screenshot capture failed: {}", err_str);
-            let kind = classify_screenshot_error(&err_str);
-            AppError::screenshot(get_screenshot_error_message(&kind, &err_str))
-        })?;
+/// What to do with a screenshot that has been taken.
+#[derive(Debug, Clone, PartialEq)]
+enum PostCaptureDecision {
+    Skip(&'static str),
+    /// Keep a record without screenshot and analysis
+    RecordSensitive {
+        keyword: String,
+    },
+    Store {
+        analyze: bool,
+    },
+}
+
+/// Decide what happens to a screenshot. Checks run in order: change
+/// detection, quality filter, sensitive window.
+fn decide_post_capture(
+    settings: &CaptureSettings,
+    trigger_source: CaptureTriggerSource,
+    facts: FrameFacts,
+) -> PostCaptureDecision {
+    if facts.capture_reason.is_none() {
+        return PostCaptureDecision::Skip("screen_unchanged");
+    }
+    if settings.quality_filter_enabled {
+        if let Some(score) = facts.quality_score {
+            if score < settings.quality_filter_threshold {
+                tracing::debug!(
+                    "Quality filter: score={:.2} < threshold={:.2}, skipping screenshot",
+                    score,
+                    settings.quality_filter_threshold
+                );
+                return PostCaptureDecision::Skip("low_quality");
+            }
+        }
+    }
+    if should_skip_for_sensitive(
+        settings.sensitive_filter_enabled,
+        facts.sensitive_match.as_deref(),
+    ) {
+        return PostCaptureDecision::RecordSensitive {
+            keyword: facts.sensitive_match.unwrap_or_default(),
+        };
+    }
+    PostCaptureDecision::Store {
+        analyze: should_analyze_immediately(settings, trigger_source),
+    }
+}
+
+/// Future of a screen analysis. It owns its inputs so it can run on its own task.
+type AnalysisFuture = std::pin::Pin<
+    Box<dyn std::future::Future<Output = AppResult<ScreenAnalysis>> + Send + 'static>,
+>;
+
+/// The parts of a capture that reach outside the process: the foreground
+/// window, the screen, the analysis API and the screenshot directory. Tests
+/// replace them to run the capture pipeline (change detection, analysis,
+/// record) without a display or network.
+trait CaptureDeps: Send + Sync {
+    fn active_window(&self) -> ActiveWindow;
+    fn capture_screen(&self, params: &ScreenshotParams) -> AppResult<(String, MonitorInfo)>;
+    fn analyze_screen(&self, settings: CaptureSettings, image_base64: String) -> AnalysisFuture;
+    fn screenshots_dir(&self) -> Option<PathBuf>;
+}
+
+/// The real window, screen and analysis API.
+struct SystemCaptureDeps;
+
+impl CaptureDeps for SystemCaptureDeps {
+    fn active_window(&self) -> ActiveWindow {
+        get_active_window()
+    }
+
+    fn capture_screen(&self, params: &ScreenshotParams) -> AppResult<(String, MonitorInfo)> {
+        capture_screen_with_mode(
+            params.mode,
+            params.monitor_index,
+            params.crop_bottom_pixels,
+            params.max_image_dimension,
+            params.retry,
+        )
+    }
+
+    fn analyze_screen(&self, settings: CaptureSettings, image_base64: String) -> AnalysisFuture {
+        Box::pin(async move { analyze_screen(&settings, &image_base64).await })
+    }
+
+    fn screenshots_dir(&self) -> Option<PathBuf> {
+        screenshots_dir()
+    }
+}
+
+async fn capture_and_store_inner(
+    deps: &dyn CaptureDeps,
+    settings: CaptureSettings,
+    trigger_source: CaptureTriggerSource,
+) -> AppResult<CaptureOutcome> {
+    let active_window = deps.active_window();
+
+    let params = match decide_pre_capture(&settings, &active_window) {
+        PreCaptureDecision::Capture(params) => params,
+        // Excluded apps are skipped quietly, without logging their window title
+        PreCaptureDecision::Skip(reason @ "app_excluded") => {
+            tracing::debug!(
+                "Skipping capture: excluded app in foreground (process='{}')",
+                active_window.process_name
+            );
+            return Ok(CaptureOutcome::Skipped(reason));
+        }
+        PreCaptureDecision::Skip(reason) => {
+            tracing::info!(
+                "Skipping capture: window filtered (title='{}', process='{}')",
+                active_window.title,
+                active_window.process_name
+            );
+            return Ok(CaptureOutcome::Skipped(reason));
+        }
+    };
+
+    let (image_base64, monitor_info) = deps.capture_screen(&params).map_err(|e| {
+        let err_str = e.to_string();
+        tracing::error!("Screenshot capture failed: {}", err_str);
+        let kind = classify_screenshot_error(&err_str);
+        AppError::screenshot(get_screenshot_error_message(
+            &kind,
+            &err_str,
+            settings.language,
+        ))
+    })?;
+    let image_base64 = mask_excluded_regions(image_base64, &settings.exclude_regions)?;
 
     let fingerprint = compute_fingerprint(&image_base64)?;
-    if should_capture(
+    let capture_reason = should_capture(
         &fingerprint,
+        &stitched_monitor_widths(params.mode, &monitor_info),
         settings.change_threshold,
         settings.max_silent_minutes,
-    )
-    .is_none()
-    {
-        return Ok(());
-    }
+    );
+    // Scoring decodes the whole image; skip it for frames that are dropped anyway
+    let quality_score = if settings.quality_filter_enabled && capture_reason.is_some() {
+        Some(compute_quality_score(&image_base64)?)
+    } else {
+        None
+    };
+    let facts = FrameFacts {
+        capture_reason,
+        quality_score,
+        sensitive_match: detect_sensitive_window(&active_window, &settings.sensitive_keywords),
+    };
 
-    if settings.quality_filter_enabled {
-        let threshold = settings.quality_filter_threshold;
-        let score = compute_quality_score(&image_base64)?;
-        if score < threshold {
-            increment_filtered_count();
-            tracing::debug!(
-                "Quality filter: score={:.2} < threshold={:.2}, skipping screenshot",
-                score,
-                threshold
+    match decide_post_capture(&settings, trigger_source, facts) {
+        PostCaptureDecision::Skip(reason) => {
+            if reason == "low_quality" {
+                increment_filtered_count();
+            }
+            Ok(CaptureOutcome::Skipped(reason))
+        }
+        PostCaptureDecision::RecordSensitive { keyword } => {
+            tracing::info!(
+                "Skipping analysis: sensitive content detected (keyword='{}')",
+                keyword
             );
-            return Ok(());
+            let content =
+                build_sensitive_skip_content(&active_window, &monitor_info, params.mode, &keyword);
+            let session_id = detect_or_create_session(&Utc::now().to_rfc3339())?;
+            memory_storage::add_record_with_session(
+                "auto",
+                &content,
+                None,
+                serde_json::to_string(&monitor_info).ok().as_deref(),
+                None,
+                Some(session_id),
+            )?;
+            Ok(CaptureOutcome::Skipped("sensitive_content"))
         }
-        tracing::trace!(
-            "Quality filter: score={:.2} >= threshold={:.2}, proceeding with capture",
-            score,
-            threshold
-        );
+        PostCaptureDecision::Store { analyze } => {
+            let screenshot_dir = deps
+                .screenshots_dir()
+                .ok_or_else(|| AppError::file_io("Cannot determine screenshot directory"))?;
+            let record_id = store_capture(
+                deps,
+                &settings,
+                &screenshot_dir,
+                &image_base64,
+                &active_window,
+                &monitor_info,
+                params.mode,
+                analyze,
+            )
+            .await?;
+            Ok(CaptureOutcome::Stored(record_id))
+        }
+    }
+}
+
+/// Default share of analyses that use prompt B of an A/B prompt experiment.
+pub const DEFAULT_PROMPT_B_PERCENT: u32 = 50;
+
+/// Variant for the next analysis, or `None` when no A/B prompt experiment is configured.
+fn pick_prompt_variant(settings: &CaptureSettings) -> Option<PromptVariant> {
+    settings.analysis_prompt_b.as_ref()?;
+    Some(choose_prompt_variant(
+        settings.prompt_b_percent,
+        rand::random::<u32>() % 100,
+    ))
+}
+
+/// Append the foreground window title to an analysis prompt so the model can
+/// check `active_software` against it. Without a title the prompt is unchanged.
+fn inject_window_title(prompt: &str, window_title: Option<&str>) -> String {
+    match window_title {
+        Some(title) => format!(
+            "{}\n\n当前前台窗口标题（由系统读取）：{}\n识别 active_software 时请以此为准。",
+            prompt, title
+        ),
+        None => prompt.to_string(),
+    }
+}
+
+/// Settings whose analysis prompt is chosen for the active window's software category,
+/// starting from prompt B when `variant` is B, and given the window title.
+fn settings_for_window(
+    settings: &CaptureSettings,
+    window: &ActiveWindow,
+    variant: Option<PromptVariant>,
+) -> CaptureSettings {
+    let category = classify_software(window);
+    let base_prompt = match variant {
+        Some(PromptVariant::B) => settings.analysis_prompt_b.as_deref(),
+        _ => settings.analysis_prompt.as_deref(),
+    }
+    .unwrap_or(DEFAULT_ANALYSIS_PROMPT);
+    tracing::debug!(
+        "Analysis prompt category {:?} for process '{}'",
+        category,
+        window.process_name
+    );
+    let prompt = select_analysis_prompt(category, &settings.category_prompts, base_prompt);
+    CaptureSettings {
+        analysis_prompt: Some(inject_window_title(
+            &prompt,
+            active_window_title(window).as_deref(),
+        )),
+        ..settings.clone()
     }
+}
 
-    let screenshot_path = save_screenshot(&image_base64);
+/// Persist a captured screenshot and its record, then optionally analyze it.
+///
+/// The screenshot file and the pending record are written before analysis starts,
+/// so an analysis failure (including a panic inside the analysis task) never loses
+/// the capture: the record is kept and marked `auto_error` for later re-analysis.
+///
+/// Each failure point is compensated so screenshots and records stay consistent:
+/// - record insert fails: the screenshot being saved is deleted
+/// - analysis fails: the screenshot moves to `failed/` and the record is marked
+/// - marking the record fails: the screenshot is moved back to its recorded path
+async fn store_capture(
+    deps: &dyn CaptureDeps,
+    settings: &CaptureSettings,
+    screenshots_dir: &Path,
+    image_base64: &str,
+    active_window: &ActiveWindow,
+    monitor_info: &MonitorInfo,
+    capture_mode: CaptureMode,
+    should_analyze: bool,
+) -> AppResult<i64> {
+    let pending_screenshot = spawn_screenshot_save(
+        screenshots_dir,
+        image_base64,
+        settings.screenshot_format,
+        settings.screenshot_format_auto,
+    );
+    let screenshot_path = pending_screenshot.as_ref().map(|p| p.path.clone());
 
     tracing::info!(
-        "Capture mode: screenshot saved, immediate_analysis={}",
+        "Capture mode: screenshot saving in background, immediate_analysis={}",
         should_analyze
     );
 
     let current_timestamp = Utc::now().to_rfc3339();
     let session_id = detect_or_create_session(&current_timestamp)?;
-    let content = build_pending_content(&active_window, &monitor_info, capture_mode);
+    let content = build_pending_content(active_window, monitor_info, capture_mode);
 
-    let monitor_info_json = serde_json::to_string(&monitor_info).ok();
+    let monitor_info_json = serde_json::to_string(monitor_info).ok();
 
-    let record_id = memory_storage::add_record_with_session(
+    let record_id = match memory_storage::add_record_with_session(
         "auto",
         &content,
         screenshot_path.as_deref(),
         monitor_info_json.as_deref(),
         None,
         Some(session_id),
-    )?;
+    ) {
+        Ok(record_id) => record_id,
+        Err(e) => {
+            tracing::error!(
+                "Failed to store capture record, discarding screenshot: {}",
+                e
+            );
+            discard_pending_screenshot(pending_screenshot).await;
+            return Err(e);
+        }
+    };
 
     tracing::debug!(
         "Screenshot saved with record_id={}, session_id={}",
@@ -1306,88 +3200,2357 @@ async fn capture_and_store_inner(
         session_id
     );
 
-    if should_analyze {
-        require_api_key(&settings)?;
-        let analysis = analyze_screen(&settings, &image_base64).await?;
-        let content_json = build_analyzed_content(
-            &analysis,
-            Some(&active_window),
-            Some(&monitor_info),
-            Some(capture_mode),
-            Some(content.as_str()),
-        )?;
-        memory_storage::update_record_content_sync(record_id, &content_json)?;
-        tracing::debug!(
-            "Screenshot analyzed immediately for record_id={}",
-            record_id
-        );
+    // Analysis runs while the screenshot is still being written
+    let analysis = if should_analyze {
+        let variant = pick_prompt_variant(settings);
+        let analysis_settings = settings_for_window(settings, active_window, variant);
+        // While the endpoint's circuit is open nothing is analyzed: the record
+        // stays pending with its screenshot in place and the cycle is not a failure
+        let analysis =
+            match analyze_screen_with_breaker(deps, &analysis_settings, image_base64).await {
+                Ok(Some(first)) if needs_recapture(&first) => Some(Ok(
+                    recapture_for_better_analysis(deps, settings, &analysis_settings, first).await,
+                )),
+                Ok(Some(analysis)) => Some(Ok(analysis)),
+                Ok(None) => None,
+                Err(e) => Some(Err(e)),
+            };
+        analysis.map(|analysis| {
+            analysis.map(|analysis| ScreenAnalysis {
+                prompt_variant: variant,
+                ..analysis
+            })
+        })
+    } else {
+        None
+    };
+    let screenshot_path = finish_screenshot_save(pending_screenshot, record_id).await;
+    LAST_ANALYSIS_FAILED.store(matches!(analysis, Some(Err(_))), Ordering::SeqCst);
+
+    if let Some(analysis) = analysis {
+        match analysis {
+            Ok(analysis) => {
+                let content_json = build_analyzed_content(
+                    &analysis,
+                    Some(active_window),
+                    Some(monitor_info),
+                    Some(capture_mode),
+                    Some(content.as_str()),
+                )?;
+                memory_storage::update_record_analysis(record_id, &content_json)?;
+                record_analysis_usage(record_id, &analysis);
+                crate::keyword_alert::check_analyzed_record(record_id, &content_json);
+                crate::record_hook::run_on_stored_record_hook(record_id);
+                tracing::debug!(
+                    "Screenshot analyzed immediately for record_id={}",
+                    record_id
+                );
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Screenshot analysis failed for record_id={}: {}",
+                    record_id,
+                    e
+                );
+                let failed_path = screenshot_path
+                    .as_deref()
+                    .and_then(|p| move_screenshot_to_failed(Path::new(p), screenshots_dir));
+                let error_content =
+                    build_analysis_error_content(&content, &e.to_string(), failed_path.as_deref());
+                if let Err(mark_err) = memory_storage::mark_record_analysis_failed_sync(
+                    record_id,
+                    &error_content,
+                    failed_path.as_deref(),
+                ) {
+                    if let (Some(failed), Some(original)) =
+                        (failed_path.as_deref(), screenshot_path.as_deref())
+                    {
+                        if !restore_failed_screenshot(Path::new(failed), Path::new(original)) {
+                            tracing::error!(
+                                "Failed to restore screenshot {} of record {}",
+                                failed,
+                                record_id
+                            );
+                        }
+                    }
+                    return Err(mark_err);
+                }
+                let mut queued = false;
+                if should_queue_analysis_retry(&e) {
+                    if let Some(path) = failed_path.as_deref().or(screenshot_path.as_deref()) {
+                        match crate::offline_queue::enqueue_screenshot_analysis(path, record_id) {
+                            Ok(_) => {
+                                queued = true;
+                                tracing::info!(
+                                    "Queued analysis of record {} for retry when online",
+                                    record_id
+                                );
+                            }
+                            Err(qe) => tracing::warn!(
+                                "Failed to queue analysis retry for record {}: {}",
+                                record_id,
+                                qe
+                            ),
+                        }
+                    }
+                }
+                // A queued retry runs the hook once it stores the analysis
+                if !queued {
+                    crate::record_hook::run_on_stored_record_hook(record_id);
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        // No analysis follows (capture-only mode or an open circuit), so the
+        // hook runs with the pending record
+        crate::record_hook::run_on_stored_record_hook(record_id);
     }
 
-    Ok(())
+    Ok(record_id)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Seconds to wait before re-capturing after a low-confidence analysis.
+pub const RECAPTURE_DELAY_SECONDS: u64 = 5;
 
-    #[test]
-    fn manual_capture_always_analyzes_even_in_capture_only_mode() {
-        let settings = CaptureSettings {
-            capture_only_mode: true,
-            ..CaptureSettings::default()
-        };
+/// Phrases in `current_focus` meaning the model could not tell what was on screen.
+const UNCERTAIN_FOCUS_MARKERS: &[&str] = &[
+    "无法确定",
+    "无法判断",
+    "无法识别",
+    "unable to determine",
+    "cannot determine",
+];
 
-        assert!(should_analyze_immediately(
-            &settings,
-            CaptureTriggerSource::Manual
-        ));
-    }
+fn is_uncertain_focus(focus: &str) -> bool {
+    let focus = focus.to_lowercase();
+    focus.trim().is_empty() || UNCERTAIN_FOCUS_MARKERS.iter().any(|m| focus.contains(m))
+}
+
+/// Whether an analysis is too uncertain to keep without a second look: the
+/// focus says the model could not tell. A low importance alone is a valid
+/// score for idle or browsing screens and does not trigger a re-capture.
+pub fn needs_recapture(analysis: &ScreenAnalysis) -> bool {
+    is_uncertain_focus(&analysis.current_focus)
+}
+
+/// The better of two analyses of the same moment: a certain focus beats an
+/// uncertain one, then the higher importance wins. Ties keep the first.
+pub fn better_analysis(first: ScreenAnalysis, second: ScreenAnalysis) -> ScreenAnalysis {
+    let rank = |a: &ScreenAnalysis| (!is_uncertain_focus(&a.current_focus), a.importance);
+    if rank(&second) > rank(&first) {
+        second
+    } else {
+        first
+    }
+}
+
+/// Screenshot parameters for a re-capture of `window`, or the reason it may not
+/// be captured. The window is checked like a fresh capture: excluded apps,
+/// window filters and, when the sensitive filter is on, sensitive windows.
+fn decide_recapture(
+    settings: &CaptureSettings,
+    window: &ActiveWindow,
+) -> Result<ScreenshotParams, &'static str> {
+    let params = match decide_pre_capture(settings, window) {
+        PreCaptureDecision::Capture(params) => params,
+        PreCaptureDecision::Skip(reason) => return Err(reason),
+    };
+    let sensitive = detect_sensitive_window(window, &settings.sensitive_keywords);
+    if should_skip_for_sensitive(settings.sensitive_filter_enabled, sensitive.as_deref()) {
+        return Err("sensitive_content");
+    }
+    Ok(params)
+}
+
+/// Capture again after a short delay and analyze once more, keeping the better
+/// result. Any failure of the second attempt keeps the first analysis; the
+/// record keeps the first screenshot either way.
+///
+/// The foreground window is read again after the delay: when the user has
+/// switched to an excluded, filtered or sensitive window, nothing is captured
+/// and the first analysis is kept.
+async fn recapture_for_better_analysis(
+    deps: &dyn CaptureDeps,
+    settings: &CaptureSettings,
+    analysis_settings: &CaptureSettings,
+    first: ScreenAnalysis,
+) -> ScreenAnalysis {
+    tracing::info!(
+        "Low-confidence analysis (focus='{}', importance={}), re-capturing in {}s",
+        first.current_focus,
+        first.importance,
+        RECAPTURE_DELAY_SECONDS
+    );
+    tokio::time::sleep(Duration::from_secs(RECAPTURE_DELAY_SECONDS)).await;
+
+    let params = match decide_recapture(settings, &deps.active_window()) {
+        Ok(params) => params,
+        Err(reason) => {
+            tracing::info!("Re-capture skipped ({}), keeping first analysis", reason);
+            return first;
+        }
+    };
+    let masked = deps.capture_screen(&params).and_then(|(image_base64, _)| {
+        mask_excluded_regions(image_base64, &settings.exclude_regions)
+    });
+    let image_base64 = match masked {
+        Ok(image_base64) => image_base64,
+        Err(e) => {
+            tracing::warn!("Re-capture failed, keeping first analysis: {}", e);
+            return first;
+        }
+    };
+    match analyze_screen_guarded(deps, analysis_settings, &image_base64).await {
+        // Both requests are paid for, whichever analysis is kept
+        Ok(second) => {
+            let usage = TokenUsage::combine(first.usage, second.usage);
+            ScreenAnalysis {
+                usage,
+                ..better_analysis(first, second)
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Re-analysis failed, keeping first analysis: {}", e);
+            first
+        }
+    }
+}
+
+/// `max_tokens` of an analysis request.
+pub const ANALYSIS_MAX_TOKENS: u32 = 1000;
+
+/// Screen size assumed when estimating the image part of a request.
+const ESTIMATE_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// Rough token count of an analysis request made with `settings`.
+///
+/// Uses the longest configured analysis prompt (default, prompt B or a
+/// software category prompt) and a 1080p screenshot without the cropped
+/// taskbar. Software corrections injected at request time are not counted.
+pub fn estimate_request_tokens(
+    settings: &memory_storage::Settings,
+) -> crate::services::model_service::TokenEstimate {
+    let capture = capture_settings_from_settings(settings);
+    let prompt = std::iter::once(
+        capture
+            .analysis_prompt
+            .as_deref()
+            .unwrap_or(DEFAULT_ANALYSIS_PROMPT),
+    )
+    .chain(capture.analysis_prompt_b.as_deref())
+    .chain(capture.category_prompts.values().map(String::as_str))
+    .max_by_key(|p| crate::services::model_service::estimate_text_tokens(p))
+    .unwrap_or(DEFAULT_ANALYSIS_PROMPT);
+    let (width, height) = ESTIMATE_SCREEN_SIZE;
+    let height = height.saturating_sub(capture.crop_bottom_pixels).max(1);
+    crate::services::model_service::estimate_tokens(
+        prompt,
+        Some((width, height)),
+        ANALYSIS_MAX_TOKENS,
+        &capture.model_name,
+    )
+}
+
+/// Log a warning when the estimated request does not fit the model's context window.
+pub fn warn_if_request_exceeds_context(settings: &memory_storage::Settings) {
+    let estimate = estimate_request_tokens(settings);
+    if estimate.exceeds_context {
+        tracing::warn!(
+            "Analysis request of about {} tokens exceeds the {} token context window of model '{}'",
+            estimate.total_tokens,
+            estimate.context_window.unwrap_or_default(),
+            settings.model_name.as_deref().unwrap_or("gpt-4o")
+        );
+    }
+}
+
+/// Error for a non-2xx analysis response. Server errors count as network
+/// failures and rate limits as quota errors, both worth retrying; any other
+/// client error (bad request, auth, unknown model) would fail again as sent.
+fn analysis_status_error(status: reqwest::StatusCode, body: &str) -> AppError {
+    let message = format!("API request failed with status {}: {}", status, body);
+    match status.as_u16() {
+        401 | 403 => AppError::auth(message),
+        408 => AppError::timeout(message),
+        429 => AppError::quota(message),
+        500..=599 => AppError::network(message),
+        _ => AppError::validation(message),
+    }
+}
+
+/// Transport errors, timeouts, rate limits and server errors (or any failure
+/// while offline) are retried from the persistent offline queue; other
+/// errors would only fail again.
+fn should_queue_analysis_retry(error: &AppError) -> bool {
+    matches!(
+        error.code,
+        ErrorCode::Network | ErrorCode::Timeout | ErrorCode::Quota
+    ) || !crate::network_status::is_online()
+}
+
+/// Run the analysis of `deps` on its own task so a panic surfaces as an error
+/// instead of unwinding through the capture pipeline.
+async fn analyze_screen_guarded(
+    deps: &dyn CaptureDeps,
+    settings: &CaptureSettings,
+    image_base64: &str,
+) -> AppResult<ScreenAnalysis> {
+    require_api_key(settings)?;
+    tokio::spawn(deps.analyze_screen(settings.clone(), image_base64.to_string()))
+        .await
+        .map_err(|e| AppError::internal(format!("Screen analysis task failed: {}", e)))?
+}
+
+/// Consecutive endpoint failures after which auto capture stops calling it.
+const ANALYSIS_BREAKER_THRESHOLD: u32 = 5;
+/// How long auto capture skips analysis once the endpoint's circuit opened.
+const ANALYSIS_BREAKER_OPEN_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// Circuit breakers of the analysis endpoints, by `api_base_url`.
+static ANALYSIS_BREAKERS: Lazy<Mutex<HashMap<String, CircuitBreaker>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Network errors and timeouts mean the endpoint is unhealthy; any other
+/// outcome means it answered.
+fn is_endpoint_failure(error: &AppError) -> bool {
+    matches!(error.code, ErrorCode::Network | ErrorCode::Timeout)
+}
+
+/// `analyze_screen_guarded` behind a circuit breaker for the analysis
+/// endpoint, so auto capture does not hit a dead endpoint every cycle.
+/// While the circuit is open the call is skipped and `None` returned.
+async fn analyze_screen_with_breaker(
+    deps: &dyn CaptureDeps,
+    settings: &CaptureSettings,
+    image_base64: &str,
+) -> AppResult<Option<ScreenAnalysis>> {
+    let endpoint = settings.api_base_url.clone();
+    let permission = ANALYSIS_BREAKERS
+        .lock()?
+        .entry(endpoint.clone())
+        .or_insert_with(|| {
+            CircuitBreaker::new(ANALYSIS_BREAKER_THRESHOLD, ANALYSIS_BREAKER_OPEN_DURATION)
+        })
+        .allow(Instant::now());
+    if let CallPermission::Rejected { retry_in } = permission {
+        tracing::info!(
+            "Analysis endpoint {} is failing, skipping analysis (next try in {}s)",
+            endpoint,
+            retry_in.as_secs()
+        );
+        return Ok(None);
+    }
+
+    let result = analyze_screen_guarded(deps, settings, image_base64).await;
+
+    let mut breakers = ANALYSIS_BREAKERS.lock()?;
+    let breaker = breakers.entry(endpoint.clone()).or_insert_with(|| {
+        CircuitBreaker::new(ANALYSIS_BREAKER_THRESHOLD, ANALYSIS_BREAKER_OPEN_DURATION)
+    });
+    match &result {
+        Err(e) if is_endpoint_failure(e) => {
+            if breaker.record_failure(Instant::now()) {
+                tracing::warn!(
+                    "Analysis endpoint {} keeps failing, pausing analysis for {}s: {}",
+                    endpoint,
+                    ANALYSIS_BREAKER_OPEN_DURATION.as_secs(),
+                    e
+                );
+            }
+        }
+        _ => {
+            if breaker.state() != CircuitState::Closed {
+                tracing::info!("Analysis endpoint {} recovered", endpoint);
+            }
+            breaker.record_success();
+        }
+    }
+    result.map(Some)
+}
+
+fn build_analysis_error_content(
+    pending_content: &str,
+    error: &str,
+    failed_screenshot_path: Option<&str>,
+) -> String {
+    let mut value = serde_json::from_str::<serde_json::Value>(pending_content)
+        .unwrap_or_else(|_| serde_json::json!({}));
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            "current_focus".to_string(),
+            serde_json::Value::String("分析失败".to_string()),
+        );
+        object.insert(
+            "analysis_error".to_string(),
+            serde_json::Value::String(error.to_string()),
+        );
+        if let Some(path) = failed_screenshot_path {
+            object.insert(
+                "failed_screenshot_path".to_string(),
+                serde_json::Value::String(path.to_string()),
+            );
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_window() -> ActiveWindow {
+        ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        }
+    }
+
+    fn changed_frame() -> FrameFacts {
+        FrameFacts {
+            capture_reason: Some(CaptureReason::ScreenChanged),
+            quality_score: None,
+            sensitive_match: None,
+        }
+    }
+
+    #[test]
+    fn pre_capture_skips_filtered_windows() {
+        let settings = CaptureSettings {
+            window_blacklist: vec!["VS Code".to_string()],
+            ..CaptureSettings::default()
+        };
+
+        assert_eq!(
+            decide_pre_capture(&settings, &code_window()),
+            PreCaptureDecision::Skip("window_filtered")
+        );
+    }
+
+    #[test]
+    fn pre_capture_skips_excluded_apps_even_in_whitelist_mode() {
+        let settings = CaptureSettings {
+            excluded_apps: vec!["code".to_string()],
+            window_whitelist: vec!["VS Code".to_string()],
+            use_whitelist_only: true,
+            ..CaptureSettings::default()
+        };
+
+        assert_eq!(
+            decide_pre_capture(&settings, &code_window()),
+            PreCaptureDecision::Skip("app_excluded")
+        );
+    }
+
+    #[test]
+    fn pre_capture_takes_screenshot_parameters_from_settings() {
+        let settings = CaptureSettings {
+            capture_mode: "all".to_string(),
+            selected_monitor_index: 2,
+            crop_bottom_pixels: 48,
+            max_image_dimension: 4096,
+            ..CaptureSettings::default()
+        };
+
+        assert_eq!(
+            decide_pre_capture(&settings, &code_window()),
+            PreCaptureDecision::Capture(ScreenshotParams {
+                mode: CaptureMode::All,
+                monitor_index: 2,
+                crop_bottom_pixels: 48,
+                max_image_dimension: 4096,
+                retry: settings.capture_retry,
+            })
+        );
+    }
+
+    #[test]
+    fn pre_capture_falls_back_to_primary_for_unknown_mode() {
+        let settings = CaptureSettings {
+            capture_mode: "bogus".to_string(),
+            ..CaptureSettings::default()
+        };
+
+        let PreCaptureDecision::Capture(params) = decide_pre_capture(&settings, &code_window())
+        else {
+            panic!("expected a capture");
+        };
+        assert_eq!(params.mode, CaptureMode::Primary);
+    }
+
+    #[test]
+    fn capture_all_monitors_overrides_capture_mode() {
+        let settings = CaptureSettings {
+            capture_mode: "secondary".to_string(),
+            capture_all_monitors: true,
+            ..CaptureSettings::default()
+        };
+        assert_eq!(effective_capture_mode(&settings), CaptureMode::All);
+        assert_eq!(
+            effective_capture_mode(&CaptureSettings::default()),
+            CaptureMode::Primary
+        );
+    }
+
+    #[test]
+    fn fingerprint_bands_follow_monitor_widths() {
+        assert_eq!(fingerprint_column_bands(&[]), vec![0..64]);
+        assert_eq!(fingerprint_column_bands(&[1920]), vec![0..64]);
+        assert_eq!(fingerprint_column_bands(&[1920, 1920]), vec![0..32, 32..64]);
+        assert_eq!(fingerprint_column_bands(&[2560, 1280]), vec![0..43, 43..64]);
+    }
+
+    #[test]
+    fn change_on_one_monitor_of_a_stitched_frame_is_detected() {
+        let side = THUMB_SIZE as usize;
+        let before = vec![100u8; side * side];
+        // Two columns of the left monitor change: 3% of the stitched frame,
+        // but 6% of that monitor
+        let mut after = before.clone();
+        for row in after.chunks_mut(side) {
+            row[..2].fill(250);
+        }
+        let recently = Duration::from_secs(60);
+
+        assert_eq!(
+            decide_capture_reason(Some(&before), &after, &[], recently, 5.0, 30),
+            None
+        );
+        assert_eq!(
+            decide_capture_reason(Some(&before), &after, &[1920, 1920], recently, 5.0, 30),
+            Some(CaptureReason::ScreenChanged)
+        );
+        assert_eq!(stitched_change_rate(&before, &before, &[1920, 1920]), 0.0);
+    }
+
+    #[test]
+    fn only_all_monitor_captures_are_stitched() {
+        let monitor_info = MonitorInfo {
+            count: 2,
+            monitors: vec![monitor(1920, 1080), monitor(1280, 1024)],
+        };
+        assert_eq!(
+            stitched_monitor_widths(CaptureMode::All, &monitor_info),
+            vec![1920, 1280]
+        );
+        assert!(stitched_monitor_widths(CaptureMode::Primary, &monitor_info).is_empty());
+    }
+
+    #[test]
+    fn capture_reason_for_first_changed_and_silent_frames() {
+        let frame = [10u8, 20, 30, 40];
+        let same = frame;
+        let different = [200u8, 20, 30, 40];
+        let recently = Duration::from_secs(60);
+        let long_ago = Duration::from_secs(31 * 60);
+
+        assert_eq!(
+            decide_capture_reason(None, &frame, &[], recently, 5.0, 30),
+            Some(CaptureReason::ScreenChanged)
+        );
+        assert_eq!(
+            decide_capture_reason(Some(&same), &different, &[], recently, 5.0, 30),
+            Some(CaptureReason::ScreenChanged)
+        );
+        assert_eq!(
+            decide_capture_reason(Some(&same), &frame, &[], recently, 5.0, 30),
+            None
+        );
+        assert_eq!(
+            decide_capture_reason(Some(&same), &frame, &[], long_ago, 5.0, 30),
+            Some(CaptureReason::SilentTimeout)
+        );
+        // A change below the threshold counts as unchanged
+        assert_eq!(
+            decide_capture_reason(Some(&same), &different, &[], recently, 30.0, 30),
+            None
+        );
+    }
+
+    #[test]
+    fn post_capture_skips_unchanged_frames_first() {
+        let settings = CaptureSettings {
+            quality_filter_enabled: true,
+            sensitive_filter_enabled: true,
+            ..CaptureSettings::default()
+        };
+        let facts = FrameFacts {
+            capture_reason: None,
+            quality_score: Some(0.0),
+            sensitive_match: Some("bank".to_string()),
+        };
+
+        assert_eq!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, facts),
+            PostCaptureDecision::Skip("screen_unchanged")
+        );
+    }
+
+    #[test]
+    fn post_capture_applies_quality_filter_only_when_enabled() {
+        let mut settings = CaptureSettings {
+            quality_filter_enabled: true,
+            quality_filter_threshold: 0.5,
+            ..CaptureSettings::default()
+        };
+        let low = FrameFacts {
+            quality_score: Some(0.2),
+            ..changed_frame()
+        };
+
+        assert_eq!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, low.clone()),
+            PostCaptureDecision::Skip("low_quality")
+        );
+        let good = FrameFacts {
+            quality_score: Some(0.8),
+            ..changed_frame()
+        };
+        assert!(matches!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, good),
+            PostCaptureDecision::Store { .. }
+        ));
+
+        settings.quality_filter_enabled = false;
+        assert!(matches!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, low),
+            PostCaptureDecision::Store { .. }
+        ));
+    }
+
+    #[test]
+    fn post_capture_records_sensitive_windows_without_screenshot() {
+        let mut settings = CaptureSettings {
+            sensitive_filter_enabled: true,
+            ..CaptureSettings::default()
+        };
+        let facts = FrameFacts {
+            sensitive_match: Some("网银".to_string()),
+            ..changed_frame()
+        };
+
+        assert_eq!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, facts.clone()),
+            PostCaptureDecision::RecordSensitive {
+                keyword: "网银".to_string()
+            }
+        );
+        settings.sensitive_filter_enabled = false;
+        assert!(matches!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, facts),
+            PostCaptureDecision::Store { .. }
+        ));
+    }
+
+    #[test]
+    fn post_capture_analyzes_unless_auto_capture_only_mode() {
+        let settings = CaptureSettings {
+            capture_only_mode: true,
+            ..CaptureSettings::default()
+        };
+
+        assert_eq!(
+            decide_post_capture(&settings, CaptureTriggerSource::Auto, changed_frame()),
+            PostCaptureDecision::Store { analyze: false }
+        );
+        assert_eq!(
+            decide_post_capture(&settings, CaptureTriggerSource::Manual, changed_frame()),
+            PostCaptureDecision::Store { analyze: true }
+        );
+        assert_eq!(
+            decide_post_capture(
+                &CaptureSettings::default(),
+                CaptureTriggerSource::Auto,
+                changed_frame()
+            ),
+            PostCaptureDecision::Store { analyze: true }
+        );
+    }
+
+    #[test]
+    fn manual_capture_always_analyzes_even_in_capture_only_mode() {
+        let settings = CaptureSettings {
+            capture_only_mode: true,
+            ..CaptureSettings::default()
+        };
+
+        assert!(should_analyze_immediately(
+            &settings,
+            CaptureTriggerSource::Manual
+        ));
+    }
+
+    #[test]
+    fn capture_interval_prefers_seconds_and_falls_back_to_minutes() {
+        assert_eq!(capture_interval_seconds(Some(20), Some(5)), 20);
+        assert_eq!(capture_interval_seconds(None, Some(2)), 120);
+        assert_eq!(capture_interval_seconds(Some(0), Some(2)), 120);
+        assert_eq!(capture_interval_seconds(None, None), 300);
+        assert_eq!(capture_interval_seconds(Some(1), None), 10);
+    }
+
+    #[test]
+    fn capture_backoff_doubles_per_failure_up_to_cap() {
+        assert_eq!(capture_backoff_seconds(300, 0, 3600), 300);
+        assert_eq!(capture_backoff_seconds(300, 1, 3600), 600);
+        assert_eq!(capture_backoff_seconds(300, 3, 3600), 2400);
+        assert_eq!(capture_backoff_seconds(300, 4, 3600), 3600);
+        assert_eq!(capture_backoff_seconds(300, 200, 3600), 3600);
+        // An interval longer than the cap is never shortened
+        assert_eq!(capture_backoff_seconds(7200, 2, 3600), 7200);
+    }
+
+    #[test]
+    fn missing_api_key_error_is_localized() {
+        let en = require_api_key(&CaptureSettings::default()).unwrap_err();
+        let zh = require_api_key(&CaptureSettings {
+            language: Lang::ZhCn,
+            ..CaptureSettings::default()
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            en.message_key.as_deref(),
+            Some("error.api_key_not_configured")
+        );
+        assert_eq!(zh.message, "API 密钥未配置，请在设置中配置");
+        assert_ne!(en.message, zh.message);
+    }
+
+    #[test]
+    fn extra_api_keys_satisfy_api_key_requirement() {
+        let settings = CaptureSettings {
+            api_keys: vec!["sk-extra".to_string()],
+            ..CaptureSettings::default()
+        };
+        assert!(require_api_key(&settings).is_ok());
+    }
+
+    #[tokio::test]
+    async fn startup_delay_sleeps_for_configured_seconds() {
+        let slept = std::sync::Arc::new(Mutex::new(Vec::new()));
+
+        let recorder = slept.clone();
+        wait_startup_delay(15, |d| async move {
+            recorder.lock().unwrap().push(d);
+        })
+        .await;
+        let recorder = slept.clone();
+        wait_startup_delay(0, |d| async move {
+            recorder.lock().unwrap().push(d);
+        })
+        .await;
+
+        assert_eq!(*slept.lock().unwrap(), vec![Duration::from_secs(15)]);
+    }
+
+    fn monitor(width: u32, height: u32) -> MonitorDetail {
+        MonitorDetail {
+            index: 0,
+            name: "Monitor 1".to_string(),
+            width,
+            height,
+            x: 0,
+            y: 0,
+            is_primary: true,
+        }
+    }
+
+    #[test]
+    fn missing_display_yields_no_display_error() {
+        let no_display = [
+            require_display(Ok(vec![])).unwrap_err(),
+            require_display(Ok(vec![monitor(0, 0)])).unwrap_err(),
+            require_display(Err(AppError::screenshot("No monitors found"))).unwrap_err(),
+        ];
+        for err in &no_display {
+            assert!(err.message.starts_with(NO_DISPLAY_ERROR), "{}", err.message);
+            let kind = classify_screenshot_error(&err.message);
+            assert_eq!(kind, ScreenshotErrorKind::NoDisplay);
+            assert!(
+                get_screenshot_error_message(&kind, &err.message, Lang::ZhCn)
+                    .contains("当前无可用显示器")
+            );
+        }
+
+        assert_eq!(
+            require_display(Ok(vec![monitor(1920, 1080)]))
+                .unwrap()
+                .len(),
+            1
+        );
+        // Unrelated errors are passed through
+        let denied = require_display(Err(AppError::screenshot("access denied"))).unwrap_err();
+        assert_eq!(
+            classify_screenshot_error(&denied.message),
+            ScreenshotErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn extra_headers_are_added_to_analysis_headers() {
+        let settings = crate::memory_storage::Settings {
+            extra_headers: Some(r#"{"X-Gateway-Route":"vision"}"#.to_string()),
+            ..Default::default()
+        };
+        let headers = capture_settings_from_settings(&settings).custom_headers;
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].key, "X-Gateway-Route");
+        assert_eq!(headers[0].value, "vision");
+    }
+
+    #[test]
+    fn startup_delay_is_read_from_settings() {
+        let settings = crate::memory_storage::Settings {
+            startup_delay_seconds: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&settings).startup_delay_seconds,
+            30
+        );
+
+        let negative = crate::memory_storage::Settings {
+            startup_delay_seconds: Some(-5),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&negative).startup_delay_seconds,
+            0
+        );
+    }
+
+    #[test]
+    fn auto_capture_respects_capture_only_mode() {
+        let settings = CaptureSettings {
+            capture_only_mode: true,
+            ..CaptureSettings::default()
+        };
+
+        assert!(!should_analyze_immediately(
+            &settings,
+            CaptureTriggerSource::Auto
+        ));
+    }
+
+    #[test]
+    fn screen_analysis_importance_defaults_when_missing_or_invalid() {
+        let parse = |json: &str| serde_json::from_str::<ScreenAnalysis>(json).unwrap();
+        let base = r#""current_focus":"x","active_software":"y","context_keywords":[]"#;
+
+        assert_eq!(parse(&format!("{{{}}}", base)).importance, 50);
+        assert_eq!(
+            parse(&format!("{{{},\"importance\":85}}", base)).importance,
+            85
+        );
+        assert_eq!(
+            parse(&format!("{{{},\"importance\":\"92\"}}", base)).importance,
+            92
+        );
+        assert_eq!(
+            parse(&format!("{{{},\"importance\":null}}", base)).importance,
+            50
+        );
+    }
+
+    #[test]
+    fn analyzed_content_keeps_existing_window_metadata() {
+        let analysis = ScreenAnalysis {
+            current_focus: "Reviewing code".to_string(),
+            active_software: "VS Code".to_string(),
+            context_keywords: vec!["rust".to_string()],
+            active_window: None,
+            tags: None,
+            prompt_variant: None,
+            importance: 50,
+            usage: None,
+        };
+        let existing_content = serde_json::json!({
+            "current_focus": "待分析",
+            "active_window": {
+                "title": "main.rs - VS Code",
+                "process_name": "Code"
+            },
+            "monitor_info": {
+                "count": 1,
+                "capture_mode": "primary"
+            }
+        })
+        .to_string();
+
+        let enriched =
+            build_analyzed_content(&analysis, None, None, None, Some(existing_content.as_str()))
+                .expect("build analyzed content");
+        let parsed: serde_json::Value =
+            serde_json::from_str(&enriched).expect("parse analyzed content");
+
+        assert_eq!(
+            parsed["active_window"]["title"].as_str(),
+            Some("main.rs - VS Code")
+        );
+        assert_eq!(parsed["monitor_info"]["count"].as_u64(), Some(1));
+    }
+
+    #[test]
+    fn analysis_prompt_and_content_carry_the_window_title() {
+        let window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        let settings = CaptureSettings {
+            analysis_prompt: Some("PROMPT".to_string()),
+            ..CaptureSettings::default()
+        };
+
+        let prompt = settings_for_window(&settings, &window, None)
+            .analysis_prompt
+            .unwrap();
+        assert!(prompt.starts_with("PROMPT"));
+        assert!(prompt.contains("main.rs - VS Code"));
+        // No focused window: the prompt gets no empty title line
+        let desktop = ActiveWindow::default();
+        assert_eq!(
+            settings_for_window(&settings, &desktop, None).analysis_prompt,
+            Some("PROMPT".to_string())
+        );
+
+        let analysis = analysis_with("Reviewing code", 50);
+        let content = build_analyzed_content(&analysis, Some(&window), None, None, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["window_title"], "main.rs - VS Code");
+        let content = build_analyzed_content(&analysis, Some(&desktop), None, None, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert!(parsed.get("window_title").is_none());
+    }
+
+    #[test]
+    fn sensitive_skip_content_marks_record_as_skipped() {
+        let active_window = ActiveWindow {
+            title: "网上银行".to_string(),
+            process_name: "chrome".to_string(),
+        };
+        let monitor_info = MonitorInfo {
+            count: 1,
+            monitors: Vec::new(),
+        };
+
+        let content = build_sensitive_skip_content(
+            &active_window,
+            &monitor_info,
+            CaptureMode::Primary,
+            "银行",
+        );
+        let parsed: serde_json::Value = serde_json::from_str(&content).expect("parse content");
+
+        assert_eq!(parsed["current_focus"].as_str(), Some("因敏感内容跳过"));
+        assert_eq!(parsed["sensitive_skipped"].as_bool(), Some(true));
+        assert_eq!(parsed["matched_keyword"].as_str(), Some("银行"));
+        assert_eq!(parsed["active_software"].as_str(), Some("chrome"));
+    }
+
+    fn test_png_base64() -> String {
+        solid_png_base64([120, 60, 30])
+    }
+
+    fn solid_png_base64(rgb: [u8; 3]) -> String {
+        let img = image::RgbImage::from_pixel(8, 8, image::Rgb(rgb));
+        let mut buffer = Vec::new();
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .expect("encode test png");
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &buffer)
+    }
+
+    /// Chat completion response as returned by an OpenAI-compatible API.
+    fn mock_completion_response(usage: Option<serde_json::Value>) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "choices": [{"message": {"content": "```json\n{\"current_focus\":\"Writing tests\",\"active_software\":\"VS Code\",\"context_keywords\":[\"rust\"]}\n```"}}]
+        });
+        if let Some(usage) = usage {
+            body["usage"] = usage;
+        }
+        body
+    }
+
+    #[test]
+    fn analysis_response_usage_is_returned() {
+        let body = mock_completion_response(Some(serde_json::json!({
+            "prompt_tokens": 1105,
+            "completion_tokens": 42,
+            "total_tokens": 1147
+        })));
+
+        let analysis = parse_analysis_response(&body, true).unwrap();
+
+        assert_eq!(analysis.current_focus, "Writing tests");
+        assert_eq!(
+            analysis.usage,
+            Some(TokenUsage {
+                prompt_tokens: 1105,
+                completion_tokens: 42
+            })
+        );
+        assert_eq!(analysis.usage.unwrap().total_tokens(), 1147);
+        let without = parse_analysis_response(&mock_completion_response(None), true).unwrap();
+        assert_eq!(without.usage, None);
+    }
+
+    #[test]
+    fn analysis_parse_errors_quote_model_output_only_when_payload_logging_is_on() {
+        let body = serde_json::json!({
+            "choices": [{"message": {"content": "屏幕上是银行卡号 6222 0000 1111 2222"}}]
+        });
+
+        let logged = parse_analysis_response(&body, true).unwrap_err();
+        let hidden = parse_analysis_response(&body, false).unwrap_err();
+        let no_choices =
+            parse_analysis_response(&serde_json::json!({"secret": "x"}), false).unwrap_err();
+
+        assert!(logged.message.contains("6222 0000 1111 2222"));
+        assert!(!hidden.message.contains("6222"));
+        assert!(hidden.message.contains("\"chars\":28"));
+        assert!(!no_choices.message.contains("secret"));
+    }
+
+    #[test]
+    fn token_usage_of_two_requests_adds_up() {
+        let a = TokenUsage {
+            prompt_tokens: 100,
+            completion_tokens: 10,
+        };
+        assert_eq!(
+            TokenUsage::combine(Some(a), Some(a)),
+            Some(TokenUsage {
+                prompt_tokens: 200,
+                completion_tokens: 20
+            })
+        );
+        assert_eq!(TokenUsage::combine(None, Some(a)), Some(a));
+        assert_eq!(TokenUsage::combine(None, None), None);
+    }
+
+    /// Serve `body` as the JSON response to one HTTP request on a local port.
+    fn serve_one_response(body: String) -> String {
+        use std::io::{BufRead, Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("accept request");
+            let mut reader = std::io::BufReader::new(stream.try_clone().expect("clone stream"));
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("read header");
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().expect("content length");
+                }
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).expect("read body");
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .expect("write response");
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn analyze_screen_returns_usage_of_mock_response() {
+        memory_storage::setup_test_db_with_schema();
+        let body = mock_completion_response(Some(serde_json::json!({
+            "prompt_tokens": 900,
+            "completion_tokens": 60
+        })));
+        let settings = CaptureSettings {
+            api_base_url: serve_one_response(body.to_string()),
+            api_key: "test-key".to_string(),
+            ..Default::default()
+        };
+
+        let analysis = analyze_screen(&settings, &test_png_base64()).await.unwrap();
+
+        assert_eq!(analysis.active_software, "VS Code");
+        assert_eq!(analysis.usage.map(|u| u.total_tokens()), Some(960));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn only_transient_analysis_failures_are_queued_for_retry() {
+        let queued = |status: u16| {
+            let status = reqwest::StatusCode::from_u16(status).unwrap();
+            should_queue_analysis_retry(&analysis_status_error(status, "{}"))
+        };
+
+        for status in [408, 429, 500, 502, 503] {
+            assert!(queued(status), "{} should be retried", status);
+        }
+        for status in [400, 401, 403, 404, 422] {
+            assert!(!queued(status), "{} should not be retried", status);
+        }
+        assert!(should_queue_analysis_retry(&AppError::network(
+            "connection refused"
+        )));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn unreachable_analysis_endpoint_opens_the_circuit() {
+        memory_storage::setup_test_db_with_schema();
+        let settings = CaptureSettings {
+            // Nothing listens on port 1: every request fails to connect
+            api_base_url: "http://127.0.0.1:1".to_string(),
+            api_key: "test-key".to_string(),
+            ..Default::default()
+        };
+        ANALYSIS_BREAKERS
+            .lock()
+            .unwrap()
+            .remove(&settings.api_base_url);
+
+        for _ in 0..ANALYSIS_BREAKER_THRESHOLD {
+            let err =
+                analyze_screen_with_breaker(&SystemCaptureDeps, &settings, &test_png_base64())
+                    .await
+                    .unwrap_err();
+            assert_eq!(err.code, ErrorCode::Network);
+        }
+        let skipped =
+            analyze_screen_with_breaker(&SystemCaptureDeps, &settings, &test_png_base64())
+                .await
+                .unwrap();
+
+        assert!(skipped.is_none());
+        ANALYSIS_BREAKERS
+            .lock()
+            .unwrap()
+            .remove(&settings.api_base_url);
+    }
+
+    #[tokio::test]
+    async fn background_screenshot_save_lands_on_disk() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let pending =
+            spawn_screenshot_save(dir.path(), &test_png_base64(), ScreenshotFormat::Png, false)
+                .expect("spawn save");
+        let planned = pending.path.clone();
+
+        let saved = pending.wait().await.expect("screenshot saved");
+
+        assert_eq!(saved, planned);
+        assert!(Path::new(&saved).exists());
+        assert!(!crate::manual_entry::pending_screenshot_path(Path::new(&saved)).exists());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn capture_without_analysis_records_saved_screenshot() {
+        memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let active_window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        let monitor_info = MonitorInfo {
+            count: 1,
+            monitors: Vec::new(),
+        };
+
+        let record_id = store_capture(
+            &SystemCaptureDeps,
+            &CaptureSettings::default(),
+            dir.path(),
+            &test_png_base64(),
+            &active_window,
+            &monitor_info,
+            CaptureMode::Primary,
+            false,
+        )
+        .await
+        .expect("store capture");
+
+        let record = memory_storage::get_record_by_id_sync(record_id).expect("load record");
+        let screenshot_path = record.screenshot_path.expect("screenshot path recorded");
+        assert!(Path::new(&screenshot_path).exists());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn analysis_failure_keeps_screenshot_and_record() {
+        memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().expect("create temp dir");
+        // No API key configured, so analysis fails
+        let settings = CaptureSettings::default();
+        let active_window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        let monitor_info = MonitorInfo {
+            count: 1,
+            monitors: Vec::new(),
+        };
+
+        let result = store_capture(
+            &SystemCaptureDeps,
+            &settings,
+            dir.path(),
+            &test_png_base64(),
+            &active_window,
+            &monitor_info,
+            CaptureMode::Primary,
+            true,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let records = memory_storage::get_today_records_sync().expect("load records");
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.analysis_status.as_deref(), Some("auto_error"));
+        let screenshot_path = record
+            .screenshot_path
+            .as_ref()
+            .expect("screenshot path recorded");
+        assert!(std::path::Path::new(screenshot_path).exists());
+        assert_eq!(
+            std::path::Path::new(screenshot_path).parent(),
+            Some(dir.path().join(FAILED_SCREENSHOTS_DIR).as_path())
+        );
+
+        let content: serde_json::Value =
+            serde_json::from_str(&record.content).expect("parse record content");
+        assert!(content["analysis_error"].as_str().is_some());
+        assert_eq!(
+            content["failed_screenshot_path"].as_str(),
+            Some(screenshot_path.as_str())
+        );
+        assert_eq!(
+            content["active_window"]["process_name"].as_str(),
+            Some("Code")
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn record_insert_failure_discards_saved_screenshot() {
+        memory_storage::setup_test_db_with_schema();
+        {
+            let db = memory_storage::DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute_batch("DROP TABLE records")
+                .unwrap();
+        }
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let active_window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        let monitor_info = MonitorInfo {
+            count: 1,
+            monitors: Vec::new(),
+        };
+
+        let result = store_capture(
+            &SystemCaptureDeps,
+            &CaptureSettings::default(),
+            dir.path(),
+            &test_png_base64(),
+            &active_window,
+            &monitor_info,
+            CaptureMode::Primary,
+            false,
+        )
+        .await;
+
+        assert!(result.is_err());
+        let leftover_files: Vec<_> = walkdir::WalkDir::new(dir.path())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect();
+        assert!(leftover_files.is_empty(), "{:?}", leftover_files);
+        memory_storage::setup_test_db_with_schema();
+    }
+
+    /// Fixed window and screen, and an analysis API answering with
+    /// `mock_completion_response`; counts the analyses requested.
+    struct MockCaptureDeps {
+        screenshots_dir: PathBuf,
+        screen: Mutex<String>,
+        analyses: AtomicU32,
+    }
+
+    impl MockCaptureDeps {
+        fn new(screenshots_dir: &Path, screen: String) -> Self {
+            Self {
+                screenshots_dir: screenshots_dir.to_path_buf(),
+                screen: Mutex::new(screen),
+                analyses: AtomicU32::new(0),
+            }
+        }
+
+        fn show(&self, screen: String) {
+            *self.screen.lock().unwrap() = screen;
+        }
+
+        fn analyses(&self) -> u32 {
+            self.analyses.load(Ordering::SeqCst)
+        }
+    }
+
+    impl CaptureDeps for MockCaptureDeps {
+        fn active_window(&self) -> ActiveWindow {
+            ActiveWindow {
+                title: "main.rs - VS Code".to_string(),
+                process_name: "Code".to_string(),
+            }
+        }
+
+        fn capture_screen(&self, _params: &ScreenshotParams) -> AppResult<(String, MonitorInfo)> {
+            let monitor_info = MonitorInfo {
+                count: 1,
+                monitors: Vec::new(),
+            };
+            Ok((self.screen.lock().unwrap().clone(), monitor_info))
+        }
+
+        fn analyze_screen(
+            &self,
+            _settings: CaptureSettings,
+            _image_base64: String,
+        ) -> AnalysisFuture {
+            self.analyses.fetch_add(1, Ordering::SeqCst);
+            let body = mock_completion_response(Some(serde_json::json!({
+                "prompt_tokens": 700,
+                "completion_tokens": 50
+            })));
+            Box::pin(async move { parse_analysis_response(&body, true) })
+        }
+
+        fn screenshots_dir(&self) -> Option<PathBuf> {
+            Some(self.screenshots_dir.clone())
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn changed_screens_are_analyzed_and_stored_with_mock_deps() {
+        memory_storage::setup_test_db_with_schema();
+        SCREEN_STATE.lock().unwrap().last_fingerprint = None;
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let deps = MockCaptureDeps::new(dir.path(), solid_png_base64([120, 60, 30]));
+        let settings = CaptureSettings {
+            api_base_url: "http://mock-analysis.invalid".to_string(),
+            api_key: "test-key".to_string(),
+            quality_filter_enabled: false,
+            ..Default::default()
+        };
+        let capture =
+            || capture_and_store_inner(&deps, settings.clone(), CaptureTriggerSource::Auto);
+
+        // First frame: analyzed and stored with its screenshot and token usage
+        let CaptureOutcome::Stored(first_id) = capture().await.unwrap() else {
+            panic!("first frame should be stored");
+        };
+        assert_eq!(deps.analyses(), 1);
+        let record = memory_storage::get_record_by_id_sync(first_id).unwrap();
+        let content: serde_json::Value = serde_json::from_str(&record.content).unwrap();
+        assert_eq!(content["current_focus"], "Writing tests");
+        assert_eq!(content["active_window"]["process_name"], "Code");
+        assert!(record
+            .screenshot_path
+            .as_deref()
+            .is_some_and(|p| Path::new(p).starts_with(dir.path())));
+        assert_eq!(
+            memory_storage::get_tokens_used_for_records(vec![first_id]).unwrap()[&first_id],
+            750
+        );
+
+        // Same frame again: skipped before analysis
+        assert_eq!(
+            capture().await.unwrap(),
+            CaptureOutcome::Skipped("screen_unchanged")
+        );
+        assert_eq!(deps.analyses(), 1);
+
+        // Changed frame: analyzed and stored as a new record
+        deps.show(solid_png_base64([20, 200, 240]));
+        let CaptureOutcome::Stored(second_id) = capture().await.unwrap() else {
+            panic!("changed frame should be stored");
+        };
+        assert_ne!(second_id, first_id);
+        assert_eq!(deps.analyses(), 2);
+        assert_eq!(memory_storage::get_today_records_sync().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn open_circuit_keeps_the_record_pending_without_failure() {
+        memory_storage::setup_test_db_with_schema();
+        SCREEN_STATE.lock().unwrap().last_fingerprint = None;
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let deps = MockCaptureDeps::new(dir.path(), solid_png_base64([120, 60, 30]));
+        let settings = CaptureSettings {
+            api_base_url: "http://mock-analysis.invalid/open-circuit".to_string(),
+            api_key: "test-key".to_string(),
+            quality_filter_enabled: false,
+            ..Default::default()
+        };
+        let mut breaker =
+            CircuitBreaker::new(ANALYSIS_BREAKER_THRESHOLD, ANALYSIS_BREAKER_OPEN_DURATION);
+        for _ in 0..ANALYSIS_BREAKER_THRESHOLD {
+            breaker.record_failure(Instant::now());
+        }
+        ANALYSIS_BREAKERS
+            .lock()
+            .unwrap()
+            .insert(settings.api_base_url.clone(), breaker);
+        LAST_ANALYSIS_FAILED.store(false, Ordering::SeqCst);
+
+        let outcome =
+            capture_and_store_inner(&deps, settings.clone(), CaptureTriggerSource::Auto).await;
+        ANALYSIS_BREAKERS
+            .lock()
+            .unwrap()
+            .remove(&settings.api_base_url);
+
+        let CaptureOutcome::Stored(record_id) = outcome.unwrap() else {
+            panic!("frame should be stored");
+        };
+        assert_eq!(deps.analyses(), 0);
+        assert!(!take_analysis_failed());
+        let record = memory_storage::get_record_by_id_sync(record_id).unwrap();
+        assert_eq!(record.analysis_status.as_deref(), Some("pending"));
+        let screenshot = record.screenshot_path.expect("screenshot kept");
+        assert!(Path::new(&screenshot).starts_with(dir.path()));
+        assert!(!Path::new(&screenshot).starts_with(dir.path().join("failed")));
+        assert!(Path::new(&screenshot).is_file());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn excluded_apps_are_neither_captured_nor_stored() {
+        memory_storage::setup_test_db_with_schema();
+        SCREEN_STATE.lock().unwrap().last_fingerprint = None;
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let deps = MockCaptureDeps::new(dir.path(), solid_png_base64([120, 60, 30]));
+        let settings = CaptureSettings {
+            api_base_url: "http://mock-analysis.invalid".to_string(),
+            api_key: "test-key".to_string(),
+            excluded_apps: vec!["CODE".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            capture_and_store_inner(&deps, settings, CaptureTriggerSource::Auto)
+                .await
+                .unwrap(),
+            CaptureOutcome::Skipped("app_excluded")
+        );
+        assert_eq!(deps.analyses(), 0);
+        assert!(memory_storage::get_today_records_sync().unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn changes_inside_excluded_regions_are_not_seen() {
+        memory_storage::setup_test_db_with_schema();
+        SCREEN_STATE.lock().unwrap().last_fingerprint = None;
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let deps = MockCaptureDeps::new(dir.path(), solid_png_base64([120, 60, 30]));
+        let settings = CaptureSettings {
+            api_base_url: "http://mock-analysis.invalid".to_string(),
+            api_key: "test-key".to_string(),
+            quality_filter_enabled: false,
+            // Larger than the 8x8 test screen: clamped to cover all of it
+            exclude_regions: vec![ExcludeRegion {
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 100,
+            }],
+            ..Default::default()
+        };
+        let capture =
+            || capture_and_store_inner(&deps, settings.clone(), CaptureTriggerSource::Auto);
+
+        assert!(matches!(
+            capture().await.unwrap(),
+            CaptureOutcome::Stored(_)
+        ));
+        deps.show(solid_png_base64([20, 200, 240]));
+        assert_eq!(
+            capture().await.unwrap(),
+            CaptureOutcome::Skipped("screen_unchanged")
+        );
+        assert_eq!(deps.analyses(), 1);
+    }
+
+    #[tokio::test]
+    async fn discarding_pending_screenshot_removes_file_and_thumbnail() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let pending =
+            spawn_screenshot_save(dir.path(), &test_png_base64(), ScreenshotFormat::Png, false)
+                .expect("spawn save");
+        let planned = PathBuf::from(&pending.path);
+
+        discard_pending_screenshot(Some(pending)).await;
+
+        assert!(!planned.exists());
+        assert!(!crate::manual_entry::thumbnail_path(&planned).exists());
+        // Nothing pending is a no-op
+        discard_pending_screenshot(None).await;
+    }
+
+    #[test]
+    fn failed_screenshot_can_be_restored_to_recorded_path() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let original = dir.path().join("shot.png");
+        std::fs::write(&original, b"png").unwrap();
+
+        let failed = move_screenshot_to_failed(&original, dir.path()).expect("moved to failed");
+        assert!(!original.exists());
+
+        assert!(restore_failed_screenshot(Path::new(&failed), &original));
+        assert!(original.exists());
+        assert!(!Path::new(&failed).exists());
+        assert!(!restore_failed_screenshot(Path::new(&failed), &original));
+    }
+
+    #[test]
+    fn screenshot_format_from_setting_defaults_to_png() {
+        assert_eq!(ScreenshotFormat::from_setting(None), ScreenshotFormat::Png);
+        assert_eq!(
+            ScreenshotFormat::from_setting(Some("JPEG")),
+            ScreenshotFormat::Jpeg
+        );
+        assert_eq!(
+            ScreenshotFormat::from_setting(Some("webp")),
+            ScreenshotFormat::WebP
+        );
+        assert_eq!(
+            ScreenshotFormat::from_setting(Some("bmp")),
+            ScreenshotFormat::Png
+        );
+    }
+
+    #[test]
+    fn encode_screenshot_uses_configured_format() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8));
+        let (bytes, format) = encode_screenshot(&img, ScreenshotFormat::Jpeg).unwrap();
+        assert_eq!(format, ScreenshotFormat::Jpeg);
+        assert_eq!(
+            image::guess_format(&bytes).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+    }
+
+    #[test]
+    fn request_pacer_spaces_out_requests() {
+        let interval = Duration::from_secs(3);
+        let mut pacer = RequestPacer::new(interval);
+        let t0 = Instant::now();
+
+        // First request goes out immediately
+        assert_eq!(pacer.reserve(t0), Duration::ZERO);
+        // Back-to-back requests wait for the interval, queueing behind each other
+        assert_eq!(pacer.reserve(t0), interval);
+        assert_eq!(
+            pacer.reserve(t0 + Duration::from_secs(1)),
+            Duration::from_secs(5)
+        );
+        // After a long pause no wait is needed
+        assert_eq!(pacer.reserve(t0 + Duration::from_secs(60)), Duration::ZERO);
+    }
+
+    #[test]
+    fn only_one_full_reanalysis_at_a_time() {
+        let guard = begin_reanalyze_all().expect("first run starts");
+        assert!(begin_reanalyze_all().is_err());
+        drop(guard);
+        assert!(begin_reanalyze_all().is_ok());
+    }
+
+    #[test]
+    fn prompt_b_variant_replaces_base_prompt() {
+        let settings = CaptureSettings {
+            analysis_prompt: Some("PROMPT A".to_string()),
+            analysis_prompt_b: Some("PROMPT B".to_string()),
+            ..CaptureSettings::default()
+        };
+        let window = ActiveWindow {
+            title: String::new(),
+            process_name: "unknown".to_string(),
+        };
+
+        let b = settings_for_window(&settings, &window, Some(PromptVariant::B));
+        assert_eq!(b.analysis_prompt.as_deref(), Some("PROMPT B"));
+        let a = settings_for_window(&settings, &window, Some(PromptVariant::A));
+        assert_eq!(a.analysis_prompt.as_deref(), Some("PROMPT A"));
+        let none = settings_for_window(&settings, &window, None);
+        assert_eq!(none.analysis_prompt.as_deref(), Some("PROMPT A"));
+
+        assert_eq!(pick_prompt_variant(&CaptureSettings::default()), None);
+        assert!(pick_prompt_variant(&settings).is_some());
+    }
+
+    #[test]
+    fn endpoint_image_format_follows_mapping() {
+        let formats = parse_endpoint_image_formats(Some(
+            r#"{"example.com": "jpeg", "png.example.com": "png", "webp.example": "webp"}"#,
+        ));
+        assert_eq!(formats.len(), 2);
+
+        assert_eq!(
+            endpoint_image_format("https://API.Example.com/v1", &formats),
+            ScreenshotFormat::Jpeg
+        );
+        // The longest matching pattern wins
+        assert_eq!(
+            endpoint_image_format("https://png.example.com/v1", &formats),
+            ScreenshotFormat::Png
+        );
+        assert_eq!(
+            endpoint_image_format("https://api.openai.com/v1", &formats),
+            ScreenshotFormat::Png
+        );
+        assert!(parse_endpoint_image_formats(Some("not json")).is_empty());
+    }
+
+    #[test]
+    fn memory_screenshots_are_not_written_to_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = test_png_base64();
+        let settings = CaptureSettings::default();
+
+        let url = deliver_screenshot(
+            &png,
+            ScreenshotDestination::Memory,
+            Some(dir.path()),
+            &settings,
+        )
+        .unwrap();
+
+        assert_eq!(url, format!("data:image/png;base64,{}", png));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
 
     #[test]
-    fn auto_capture_respects_capture_only_mode() {
-        let settings = CaptureSettings {
-            capture_only_mode: true,
-            ..CaptureSettings::default()
-        };
+    fn disk_screenshots_are_saved_in_the_screenshot_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = CaptureSettings::default();
 
-        assert!(!should_analyze_immediately(
+        let path = deliver_screenshot(
+            &test_png_base64(),
+            ScreenshotDestination::Disk,
+            Some(dir.path()),
             &settings,
-            CaptureTriggerSource::Auto
+        )
+        .unwrap();
+
+        assert!(Path::new(&path).starts_with(dir.path()));
+        assert!(Path::new(&path).is_file());
+    }
+
+    #[test]
+    fn analysis_data_url_matches_endpoint_format() {
+        let png = test_png_base64();
+        assert_eq!(
+            analysis_image_data_url(&png, ScreenshotFormat::Png).unwrap(),
+            format!("data:image/png;base64,{}", png)
+        );
+
+        let url = analysis_image_data_url(&png, ScreenshotFormat::Jpeg).unwrap();
+        let jpeg = url.strip_prefix("data:image/jpeg;base64,").unwrap();
+        let bytes =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, jpeg).unwrap();
+        assert_eq!(
+            image::guess_format(&bytes).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+    }
+
+    #[test]
+    fn encode_falls_back_to_png_when_configured_format_fails() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8));
+        let (bytes, format) = encode_with_fallback(&img, ScreenshotFormat::WebP, |img, format| {
+            if format == ScreenshotFormat::WebP {
+                Err(AppError::screenshot("unsupported dimensions"))
+            } else {
+                encode_image(img, format)
+            }
+        })
+        .unwrap();
+
+        assert_eq!(format, ScreenshotFormat::Png);
+        assert_eq!(
+            image::guess_format(&bytes).unwrap(),
+            image::ImageFormat::Png
+        );
+    }
+
+    #[test]
+    fn encode_errors_only_when_png_fallback_also_fails() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(8, 8));
+        let err = encode_with_fallback(&img, ScreenshotFormat::Jpeg, |_, _| {
+            Err(AppError::screenshot("encoder unavailable"))
+        })
+        .unwrap_err();
+
+        assert!(err.message.contains("jpg"));
+        assert!(err.message.contains("PNG"));
+    }
+
+    #[test]
+    fn parses_exclude_regions_and_ignores_invalid_json() {
+        assert_eq!(
+            parse_exclude_regions(Some(r#"[{"x": 10, "y": 20, "width": 300, "height": 200}]"#)),
+            vec![ExcludeRegion {
+                x: 10,
+                y: 20,
+                width: 300,
+                height: 200,
+            }]
+        );
+        assert!(parse_exclude_regions(None).is_empty());
+        assert!(parse_exclude_regions(Some("[{\"x\": -1}]")).is_empty());
+    }
+
+    #[test]
+    fn excluded_regions_are_blacked_out_and_clamped() {
+        let white = image::Rgba([255, 255, 255, 255]);
+        let black = image::Rgba([0, 0, 0, 255]);
+        let mut img = image::RgbaImage::from_pixel(10, 10, white);
+        black_out_regions(
+            &mut img,
+            &[
+                ExcludeRegion {
+                    x: 2,
+                    y: 3,
+                    width: 2,
+                    height: 2,
+                },
+                // Runs past the right and bottom edges
+                ExcludeRegion {
+                    x: 8,
+                    y: 8,
+                    width: 50,
+                    height: u32::MAX,
+                },
+                // Entirely outside the image
+                ExcludeRegion {
+                    x: 40,
+                    y: 40,
+                    width: 5,
+                    height: 5,
+                },
+            ],
+        );
+
+        assert_eq!(*img.get_pixel(2, 3), black);
+        assert_eq!(*img.get_pixel(3, 4), black);
+        assert_eq!(*img.get_pixel(4, 4), white);
+        assert_eq!(*img.get_pixel(9, 9), black);
+        assert_eq!(*img.get_pixel(7, 9), white);
+        let blacked = img.pixels().filter(|p| **p == black).count();
+        assert_eq!(blacked, 4 + 4);
+    }
+
+    #[test]
+    fn masking_without_regions_keeps_the_image() {
+        let image_base64 = test_png_base64();
+        assert_eq!(
+            mask_excluded_regions(image_base64.clone(), &[]).unwrap(),
+            image_base64
+        );
+    }
+
+    #[test]
+    fn screenshot_is_saved_with_extension_of_format_used() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = save_screenshot_to(
+            dir.path(),
+            &test_png_base64(),
+            ScreenshotFormat::Jpeg,
+            false,
+        )
+        .expect("save screenshot");
+        assert!(path.ends_with(".jpg"));
+    }
+
+    /// A smooth gradient: every pixel a different color, no sharp edges.
+    fn photo_like_image() -> image::DynamicImage {
+        image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(256, 256, |x, y| {
+            image::Rgb([x as u8, y as u8, ((x + y) / 2) as u8])
+        }))
+    }
+
+    /// Black one-pixel strokes on white, like lines of text.
+    fn text_like_image() -> image::DynamicImage {
+        image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(256, 256, |x, y| {
+            if x % 4 == 0 && y % 8 < 6 {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn content_format_is_jpeg_for_photos_and_png_for_text() {
+        let photo = image_complexity(&photo_like_image());
+        assert!(photo.color_variety > 0.9);
+        assert_eq!(photo.edge_density, 0.0);
+        assert_eq!(format_for_content(photo), ScreenshotFormat::Jpeg);
+
+        let text = image_complexity(&text_like_image());
+        assert!(text.color_variety < 0.01);
+        assert!(text.edge_density > 0.2);
+        assert_eq!(format_for_content(text), ScreenshotFormat::Png);
+
+        // A flat screen has few colors and stays lossless
+        let flat = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            64,
+            64,
+            image::Rgb([30, 30, 30]),
         ));
+        assert_eq!(
+            format_for_content(image_complexity(&flat)),
+            ScreenshotFormat::Png
+        );
     }
 
     #[test]
-    fn analyzed_content_keeps_existing_window_metadata() {
+    fn auto_format_saves_photos_as_jpeg() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let mut buffer = Vec::new();
+        photo_like_image()
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .expect("encode test png");
+        let image_base64 =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &buffer);
+
+        let path = save_screenshot_to(dir.path(), &image_base64, ScreenshotFormat::Png, true)
+            .expect("save screenshot");
+        assert!(path.ends_with(".jpg"));
+
+        let path = save_screenshot_to(dir.path(), &test_png_base64(), ScreenshotFormat::Jpeg, true)
+            .expect("save screenshot");
+        assert!(path.ends_with(".png"));
+    }
+
+    #[test]
+    fn auto_screenshot_format_setting_is_recognized() {
+        assert!(ScreenshotFormat::is_auto_setting(Some(" Auto ")));
+        assert!(!ScreenshotFormat::is_auto_setting(Some("png")));
+        assert!(!ScreenshotFormat::is_auto_setting(None));
+        assert_eq!(
+            ScreenshotFormat::from_setting(Some("auto")),
+            ScreenshotFormat::Png
+        );
+    }
+
+    #[test]
+    fn saved_screenshot_gets_scaled_down_thumbnail() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let img = image::RgbImage::from_pixel(1280, 720, image::Rgb([10, 20, 30]));
+        let mut buffer = Vec::new();
+        image::DynamicImage::ImageRgb8(img)
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .expect("encode test png");
+        let image_base64 =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &buffer);
+
+        let path = save_screenshot_to(dir.path(), &image_base64, ScreenshotFormat::Png, false)
+            .expect("save screenshot");
+
+        let thumbnail = crate::manual_entry::thumbnail_path(Path::new(&path));
+        assert_eq!(
+            thumbnail.parent(),
+            Some(
+                dir.path()
+                    .join(crate::manual_entry::THUMBNAILS_DIR)
+                    .as_path()
+            )
+        );
+        let thumbnail = image::open(&thumbnail).expect("open thumbnail");
+        assert_eq!((thumbnail.width(), thumbnail.height()), (320, 180));
+        assert_eq!(
+            crate::manual_entry::existing_thumbnail_path(&path),
+            Some(
+                crate::manual_entry::thumbnail_path(Path::new(&path))
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn failed_screenshot_is_moved_into_failed_dir() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let original =
+            save_screenshot_to(dir.path(), &test_png_base64(), ScreenshotFormat::Png, false)
+                .expect("save screenshot");
+
+        let moved =
+            move_screenshot_to_failed(Path::new(&original), dir.path()).expect("move screenshot");
+
+        let failed_dir = dir.path().join(FAILED_SCREENSHOTS_DIR);
+        assert_eq!(Path::new(&moved).parent(), Some(failed_dir.as_path()));
+        assert!(Path::new(&moved).exists());
+        assert!(!Path::new(&original).exists());
+        assert!(!crate::manual_entry::thumbnail_path(Path::new(&original)).exists());
+        // Only the failed/ subdirectory is left next to the thumbnails directory
+        let entries = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().file_name() != crate::manual_entry::THUMBNAILS_DIR)
+            .count();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn reanalyze_screenshot_reports_missing_file() {
+        memory_storage::setup_test_db_with_schema();
+        let record_id = memory_storage::add_record(
+            "auto",
+            "{}",
+            Some("/nonexistent/dailylogger/screenshot.png"),
+            None,
+            None,
+        )
+        .expect("add record");
+        let record = memory_storage::get_record_by_id_sync(record_id).expect("load record");
+
+        let err = read_record_screenshot(&record).expect_err("missing file should fail");
+        assert!(err.to_string().contains("Screenshot file not found"));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn reanalyze_screenshot_updates_record_content() {
+        memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let screenshot_path =
+            save_screenshot_to(dir.path(), &test_png_base64(), ScreenshotFormat::Png, false)
+                .expect("save screenshot");
+        let failed_content = serde_json::json!({
+            "current_focus": "分析失败",
+            "analysis_error": "timeout",
+            "active_window": {"title": "main.rs", "process_name": "Code"}
+        })
+        .to_string();
+        let record_id = memory_storage::add_record(
+            "auto",
+            &failed_content,
+            Some(screenshot_path.as_str()),
+            None,
+            None,
+        )
+        .expect("add record");
+        memory_storage::mark_record_analysis_failed_sync(record_id, &failed_content, None)
+            .expect("mark failed");
+
+        let record = memory_storage::get_record_by_id_sync(record_id).expect("load record");
+        assert!(read_record_screenshot(&record).is_ok());
+
         let analysis = ScreenAnalysis {
-            current_focus: "Reviewing code".to_string(),
+            current_focus: "Writing tests".to_string(),
             active_software: "VS Code".to_string(),
             context_keywords: vec!["rust".to_string()],
             active_window: None,
             tags: None,
+            prompt_variant: None,
+            importance: 50,
+            usage: Some(TokenUsage {
+                prompt_tokens: 1200,
+                completion_tokens: 80,
+            }),
         };
-        let existing_content = serde_json::json!({
-            "current_focus": "待分析",
-            "active_window": {
-                "title": "main.rs - VS Code",
-                "process_name": "Code"
+        apply_reanalysis(&record, &analysis).expect("apply reanalysis");
+
+        let updated = memory_storage::get_record_by_id_sync(record_id).expect("reload record");
+        let content: serde_json::Value =
+            serde_json::from_str(&updated.content).expect("parse content");
+        assert_eq!(content["current_focus"].as_str(), Some("Writing tests"));
+        assert!(content.get("analysis_error").is_none());
+        assert_eq!(
+            content["active_window"]["process_name"].as_str(),
+            Some("Code")
+        );
+        assert_eq!(updated.analysis_status.as_deref(), Some("analyzed"));
+        assert!(content.get("usage").is_none());
+        assert_eq!(
+            memory_storage::get_tokens_used_for_records(vec![record_id]).unwrap()[&record_id],
+            1280
+        );
+    }
+
+    #[test]
+    fn recommend_change_threshold_defaults_without_samples() {
+        assert_eq!(
+            recommend_change_threshold(&[]),
+            DEFAULT_CHANGE_THRESHOLD as i32
+        );
+        assert_eq!(
+            recommend_change_threshold(&[f64::NAN]),
+            DEFAULT_CHANGE_THRESHOLD as i32
+        );
+    }
+
+    #[test]
+    fn recommend_change_threshold_adds_margin_above_noise() {
+        // 90th percentile of these samples is 4.0 -> 4.0 * 1.5 = 6
+        let samples = [0.5, 1.0, 1.2, 2.0, 2.5, 3.0, 3.1, 3.5, 4.0, 4.0, 40.0];
+        assert_eq!(recommend_change_threshold(&samples), 6);
+    }
+
+    #[test]
+    fn recommend_change_threshold_is_clamped() {
+        assert_eq!(
+            recommend_change_threshold(&[0.0, 0.0, 0.0]),
+            MIN_CHANGE_THRESHOLD
+        );
+        assert_eq!(
+            recommend_change_threshold(&[80.0, 90.0, 100.0]),
+            MAX_CHANGE_THRESHOLD
+        );
+    }
+
+    #[test]
+    fn resolution_plausibility() {
+        // Empty or tiny captures are never plausible
+        assert!(!is_resolution_plausible(0, 0, None));
+        assert!(!is_resolution_plausible(1920, 10, Some((1920, 1080))));
+
+        // Matching or HiDPI-scaled captures are fine
+        assert!(is_resolution_plausible(1920, 1080, Some((1920, 1080))));
+        assert!(is_resolution_plausible(3840, 2160, Some((1920, 1080))));
+        assert!(is_resolution_plausible(960, 540, Some((1920, 1080))));
+
+        // Far smaller than the monitor
+        assert!(!is_resolution_plausible(320, 200, Some((2560, 1440))));
+
+        // Stitched captures only need the minimum size
+        assert!(is_resolution_plausible(320, 200, None));
+    }
+
+    #[test]
+    fn check_screenshot_resolution_rejects_tiny_image() {
+        let encode = |w: u32, h: u32| {
+            let img = image::DynamicImage::ImageRgba8(image::RgbaImage::new(w, h));
+            let mut buffer = Vec::new();
+            img.write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &buffer)
+        };
+
+        assert!(check_screenshot_resolution(&encode(200, 100), Some((200, 100))).is_ok());
+        let err = check_screenshot_resolution(&encode(8, 8), Some((1920, 1080))).unwrap_err();
+        assert!(err.to_string().contains("8x8"));
+    }
+
+    #[test]
+    fn crop_bottom_removes_taskbar_rows() {
+        let img = image::DynamicImage::ImageRgba8(image::RgbaImage::new(1920, 1080));
+
+        let cropped = crop_bottom(&img, DEFAULT_TASKBAR_HEIGHT);
+        assert_eq!(cropped.width(), 1920);
+        assert_eq!(cropped.height(), 1080 - DEFAULT_TASKBAR_HEIGHT);
+
+        // Disabled or oversized crops leave the image untouched
+        assert_eq!(crop_bottom(&img, 0).height(), 1080);
+        assert_eq!(crop_bottom(&img, 1080).height(), 1080);
+    }
+
+    #[test]
+    fn crop_bottom_pixels_follow_settings() {
+        let settings = crate::memory_storage::Settings {
+            crop_taskbar: Some(true),
+            taskbar_height: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&settings).crop_bottom_pixels,
+            60
+        );
+
+        let disabled = crate::memory_storage::Settings {
+            taskbar_height: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&disabled).crop_bottom_pixels,
+            0
+        );
+    }
+
+    #[test]
+    fn fit_within_keeps_aspect_ratio_and_small_images() {
+        assert_eq!(fit_within(11520, 2160, 7680), (7680, 1440));
+        assert_eq!(fit_within(1080, 9000, 4500), (540, 4500));
+        assert_eq!(fit_within(1920, 1080, 7680), (1920, 1080));
+        assert_eq!(fit_within(20000, 1080, 0), (20000, 1080));
+        assert_eq!(fit_within(100000, 1, 1000), (1000, 1));
+    }
+
+    #[test]
+    fn stitched_image_is_scaled_to_max_dimension() {
+        let monitor = |w, h| (w, image::RgbaImage::new(w, h));
+        let images = vec![
+            monitor(1920, 1080),
+            monitor(2560, 1440),
+            monitor(1920, 1080),
+        ];
+
+        let unlimited = stitch_and_fit(images.clone(), 0);
+        assert_eq!(unlimited.dimensions(), (6400, 1440));
+
+        let capped = stitch_and_fit(images, 3200);
+        assert_eq!(capped.dimensions(), (3200, 720));
+        assert!(capped.width().max(capped.height()) <= 3200);
+
+        let single = stitch_and_fit(vec![monitor(1920, 1080)], 3200);
+        assert_eq!(single.dimensions(), (1920, 1080));
+    }
+
+    #[test]
+    fn max_image_dimension_follows_settings() {
+        let default = capture_settings_from_settings(&Default::default());
+        assert_eq!(default.max_image_dimension, DEFAULT_MAX_IMAGE_DIMENSION);
+
+        let disabled = crate::memory_storage::Settings {
+            max_image_dimension: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&disabled).max_image_dimension,
+            0
+        );
+    }
+
+    #[test]
+    fn retry_capture_returns_last_error_when_every_attempt_fails() {
+        let mut attempts = Vec::new();
+        let result: AppResult<()> = retry_capture(
+            2,
+            |n| {
+                attempts.push(n);
+                Err(AppError::screenshot(format!("failure {}", n)))
             },
-            "monitor_info": {
-                "count": 1,
-                "capture_mode": "primary"
+            |_| true,
+        );
+
+        assert_eq!(attempts, vec![0, 1, 2]);
+        assert_eq!(result.unwrap_err().message, "failure 2");
+    }
+
+    #[test]
+    fn retry_capture_stops_at_first_success_or_fatal_error() {
+        let mut calls = 0;
+        let value = retry_capture(
+            3,
+            |n| {
+                calls += 1;
+                if n < 1 {
+                    Err(AppError::screenshot("flaky"))
+                } else {
+                    Ok(n)
+                }
+            },
+            |_| true,
+        )
+        .unwrap();
+        assert_eq!((value, calls), (1, 2));
+
+        let mut calls = 0;
+        let result: AppResult<()> = retry_capture(
+            3,
+            |_| {
+                calls += 1;
+                Err(no_display_error("monitor list is empty"))
+            },
+            |e| !e.message.starts_with(NO_DISPLAY_ERROR),
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        let result: AppResult<()> =
+            retry_capture(0, |_| Err(AppError::screenshot("once")), |_| true);
+        assert_eq!(result.unwrap_err().message, "once");
+    }
+
+    #[test]
+    fn run_with_timeout_gives_up_on_slow_captures() {
+        let slow = run_with_timeout(Duration::from_millis(20), || {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        });
+        assert!(slow.unwrap_err().message.contains("timed out"));
+
+        let fast = run_with_timeout(Duration::from_secs(5), || Ok(42));
+        assert_eq!(fast.unwrap(), 42);
+    }
+
+    #[test]
+    fn capture_retry_follows_settings() {
+        let settings = crate::memory_storage::Settings {
+            capture_timeout_secs: Some(0),
+            capture_retries: Some(99),
+            ..Default::default()
+        };
+        assert_eq!(
+            capture_settings_from_settings(&settings).capture_retry,
+            CaptureRetry {
+                timeout: Duration::from_secs(1),
+                retries: MAX_CAPTURE_RETRIES,
             }
-        })
-        .to_string();
+        );
+        assert_eq!(
+            capture_settings_from_settings(&Default::default()).capture_retry,
+            CaptureRetry::default()
+        );
+    }
 
-        let enriched =
-            build_analyzed_content(&analysis, None, None, None, Some(existing_content.as_str()))
-                .expect("build analyzed content");
-        let parsed: serde_json::Value =
-            serde_json::from_str(&enriched).expect("parse analyzed content");
+    fn analysis_with(focus: &str, importance: u8) -> ScreenAnalysis {
+        ScreenAnalysis {
+            current_focus: focus.to_string(),
+            active_software: "VS Code".to_string(),
+            context_keywords: vec![],
+            active_window: None,
+            tags: None,
+            prompt_variant: None,
+            importance,
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn recapture_needed_only_for_uncertain_focus() {
+        assert!(!needs_recapture(&analysis_with("编写单元测试", 60)));
+        assert!(needs_recapture(&analysis_with("无法确定当前工作内容", 60)));
+        assert!(needs_recapture(&analysis_with(
+            "Unable to determine the task",
+            60
+        )));
+        assert!(needs_recapture(&analysis_with("  ", 60)));
+        // A low score is a valid result for idle or browsing screens
+        assert!(!needs_recapture(&analysis_with("浏览网页", 0)));
+    }
 
+    #[test]
+    fn recapture_rechecks_the_current_window() {
+        let settings = CaptureSettings {
+            excluded_apps: vec!["1password".to_string()],
+            sensitive_filter_enabled: true,
+            ..CaptureSettings::default()
+        };
+        assert!(decide_recapture(&settings, &code_window()).is_ok());
+        let password_manager = ActiveWindow {
+            title: "Vault".to_string(),
+            process_name: "1Password".to_string(),
+        };
         assert_eq!(
-            parsed["active_window"]["title"].as_str(),
-            Some("main.rs - VS Code")
+            decide_recapture(&settings, &password_manager),
+            Err("app_excluded")
         );
-        assert_eq!(parsed["monitor_info"]["count"].as_u64(), Some(1));
+        let bank = ActiveWindow {
+            title: "网上银行 - Chrome".to_string(),
+            process_name: "chrome".to_string(),
+        };
+        assert_eq!(decide_recapture(&settings, &bank), Err("sensitive_content"));
+    }
+
+    #[test]
+    fn better_analysis_prefers_certain_focus_then_importance() {
+        let uncertain = analysis_with("无法确定", 90);
+        let certain = analysis_with("调试接口", 10);
+        assert_eq!(
+            better_analysis(uncertain.clone(), certain.clone()).current_focus,
+            "调试接口"
+        );
+        assert_eq!(
+            better_analysis(certain.clone(), uncertain).current_focus,
+            "调试接口"
+        );
+
+        let higher = analysis_with("评审代码", 40);
+        assert_eq!(
+            better_analysis(certain.clone(), higher).current_focus,
+            "评审代码"
+        );
+        // Ties keep the first analysis
+        let same = analysis_with("另一个结果", 10);
+        assert_eq!(better_analysis(certain, same).current_focus, "调试接口");
+    }
+
+    #[test]
+    fn request_estimate_uses_longest_prompt_and_model_window() {
+        let default = estimate_request_tokens(&crate::memory_storage::Settings {
+            model_name: Some("gpt-4o".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(default.completion_tokens, ANALYSIS_MAX_TOKENS);
+        assert_eq!(default.image_tokens, 85 + 170 * 6);
+        assert!(!default.exceeds_context);
+
+        let long = estimate_request_tokens(&crate::memory_storage::Settings {
+            model_name: Some("llava:7b".to_string()),
+            analysis_prompt_b: Some("很长的提示词".repeat(1000)),
+            ..Default::default()
+        });
+        assert!(long.prompt_tokens > default.prompt_tokens);
+        assert!(long.exceeds_context);
+    }
+
+    #[test]
+    fn capture_event_serializes_kind_trigger_and_optional_fields() {
+        let started = CaptureEvent::new(CaptureEventKind::Started, CaptureTriggerSource::Auto);
+        let json = serde_json::to_value(&started).unwrap();
+        assert_eq!(json["kind"], "started");
+        assert_eq!(json["trigger"], "auto");
+        assert!(chrono::DateTime::parse_from_rfc3339(json["timestamp"].as_str().unwrap()).is_ok());
+        assert!(json.get("reason").is_none());
+        assert!(json.get("duration_ms").is_none());
+        assert!(json.get("record_id").is_none());
+
+        let skipped = CaptureEvent::new(CaptureEventKind::Skipped, CaptureTriggerSource::Manual)
+            .with_reason("screen_unchanged")
+            .with_duration(Instant::now());
+        let json = serde_json::to_value(&skipped).unwrap();
+        assert_eq!(json["kind"], "skipped");
+        assert_eq!(json["trigger"], "manual");
+        assert_eq!(json["reason"], "screen_unchanged");
+        assert!(json["duration_ms"].is_u64());
+
+        let succeeded = CaptureEvent {
+            record_id: Some(42),
+            ..CaptureEvent::new(CaptureEventKind::Succeeded, CaptureTriggerSource::Auto)
+        };
+        let json = serde_json::to_value(&succeeded).unwrap();
+        assert_eq!(json["kind"], "succeeded");
+        assert_eq!(json["record_id"], 42);
     }
 }
@@ -8,12 +8,16 @@
 //! - Services use idiomatic Rust error handling
 //! - Commands (in `commands/`) are responsible for error mapping to Tauri responses
 
+pub mod analysis_prompts;
 #[cfg(feature = "screenshot")]
 pub mod capture_service;
 pub mod model_service;
+pub mod prompt_migration;
 pub mod report_service;
 pub mod session_service;
+pub mod settings_schema;
 pub mod settings_service;
+pub mod setup_service;
 pub mod vision_api;
 
 // Re-export business logic from existing modules
@@ -40,12 +44,13 @@ pub use settings_service::{get_settings_service, save_settings_service};
 // Re-export capture_service functions for use by auto_perception and commands
 #[cfg(feature = "screenshot")]
 pub use capture_service::{
-    evaluate_and_adjust_threshold, get_auto_capture_status_service,
-    get_default_analysis_prompt_service, get_filtered_today, get_quality_filter_stats_service,
-    get_work_time_status_service, is_auto_capture_running, reanalyze_record_service,
-    reanalyze_records_by_date_service, reanalyze_today_records_service, reset_filtered_count,
-    reset_quality_filter_counter_service, retry_screenshot_analysis_service,
+    calibrate_change_threshold_service, evaluate_and_adjust_threshold,
+    get_auto_capture_status_service, get_default_analysis_prompt_service, get_filtered_today,
+    get_quality_filter_stats_service, get_work_time_status_service, is_auto_capture_running,
+    reanalyze_record_service, reanalyze_records_by_date_service, reanalyze_today_records_service,
+    reset_filtered_count, reset_quality_filter_counter_service, retry_screenshot_analysis_service,
     should_capture_by_work_time, start_auto_capture_service, stop_auto_capture_service,
     take_screenshot_service, trigger_auto_capture_service, trigger_capture_service,
-    CaptureSettings, QualityFilterStats, ReanalyzeResult, ScreenAnalysis, ThresholdAdjustment,
+    CaptureSettings, QualityFilterStats, ReanalyzeResult, ScreenAnalysis, ScreenshotDestination,
+    ThresholdAdjustment,
 };
@@ -3,9 +3,114 @@
 //! This module contains the business logic for querying AI model information.
 //! Commands should delegate to these service functions rather than implementing logic directly.
 
+use serde::Serialize;
+
 use crate::errors::AppResult;
 use crate::memory_storage::ModelInfo;
 
+/// Rough token count of one analysis request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenEstimate {
+    pub prompt_tokens: u32,
+    pub image_tokens: u32,
+    /// `max_tokens` reserved for the response
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    /// Context window of the model, when known
+    pub context_window: Option<u32>,
+    /// `total_tokens` is larger than the known context window
+    pub exceeds_context: bool,
+}
+
+/// Approximate token count of a text: one token per CJK character and
+/// one token per four other characters.
+pub fn estimate_text_tokens(text: &str) -> u32 {
+    let (cjk, other) = text.chars().fold((0u32, 0u32), |(cjk, other), c| {
+        if is_cjk(c) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3000..=0x303F // CJK punctuation
+        | 0x3040..=0x30FF // Hiragana, Katakana
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xAC00..=0xD7AF // Hangul
+        | 0xF900..=0xFAFF
+        | 0xFF00..=0xFFEF) // Full-width forms
+}
+
+/// Approximate token count of an image sent in high detail, following the
+/// usual tiling rule: fit into 2048x2048, scale the short side down to 768,
+/// then 170 tokens per 512px tile plus 85 base tokens.
+pub fn estimate_image_tokens(width: u32, height: u32) -> u32 {
+    if width == 0 || height == 0 {
+        return 0;
+    }
+    let (mut w, mut h) = (width as f64, height as f64);
+    let fit = (2048.0 / w.max(h)).min(1.0);
+    w *= fit;
+    h *= fit;
+    let shrink = (768.0 / w.min(h)).min(1.0);
+    w *= shrink;
+    h *= shrink;
+    let tiles = (w / 512.0).ceil() as u32 * (h / 512.0).ceil() as u32;
+    85 + 170 * tiles
+}
+
+/// Context window of well-known models, matched by name prefix.
+pub fn model_context_window(model_name: &str) -> Option<u32> {
+    const WINDOWS: &[(&str, u32)] = &[
+        ("gpt-4o", 128_000),
+        ("gpt-4.1", 1_000_000),
+        ("gpt-4-turbo", 128_000),
+        ("gpt-4-vision", 128_000),
+        ("gpt-4", 8_192),
+        ("claude", 200_000),
+        ("gemini", 1_000_000),
+        ("qwen-vl", 32_000),
+        ("qwen2.5-vl", 32_000),
+        ("glm-4v", 8_192),
+        ("llava", 4_096),
+        ("moondream", 2_048),
+    ];
+    let name = model_name.trim().to_lowercase();
+    // Ollama style tags ("llava:13b") and vendor prefixes ("openai/gpt-4o")
+    let name = name.rsplit('/').next().unwrap_or(&name);
+    WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+/// Estimate the tokens of a request made of `prompt`, one image of
+/// `image_size` and `max_output_tokens` reserved for the answer.
+pub fn estimate_tokens(
+    prompt: &str,
+    image_size: Option<(u32, u32)>,
+    max_output_tokens: u32,
+    model_name: &str,
+) -> TokenEstimate {
+    let prompt_tokens = estimate_text_tokens(prompt);
+    let image_tokens = image_size.map_or(0, |(w, h)| estimate_image_tokens(w, h));
+    let total_tokens = prompt_tokens + image_tokens + max_output_tokens;
+    let context_window = model_context_window(model_name);
+    TokenEstimate {
+        prompt_tokens,
+        image_tokens,
+        completion_tokens: max_output_tokens,
+        total_tokens,
+        context_window,
+        exceeds_context: context_window.is_some_and(|w| total_tokens > w),
+    }
+}
+
 /// Get model information including context window from an OpenAI-compatible API.
 ///
 /// This function handles the HTTP communication with the model API to retrieve
@@ -68,3 +173,52 @@ pub async fn get_model_info_service(
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_tokens_count_cjk_per_char_and_latin_per_four_chars() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("abcd"), 1);
+        assert_eq!(estimate_text_tokens("abcde"), 2);
+        assert_eq!(estimate_text_tokens("工作日志"), 4);
+        assert_eq!(estimate_text_tokens("分析 code"), 2 + 2);
+    }
+
+    #[test]
+    fn image_tokens_follow_tiling_rule() {
+        // 1920x1080 -> 1365x768 -> 3x2 tiles
+        assert_eq!(estimate_image_tokens(1920, 1080), 85 + 170 * 6);
+        // Small images are not scaled up
+        assert_eq!(estimate_image_tokens(512, 512), 85 + 170);
+        // 4096x2048 -> 2048x1024 -> 1536x768 -> 3x2 tiles
+        assert_eq!(estimate_image_tokens(4096, 2048), 85 + 170 * 6);
+        assert_eq!(estimate_image_tokens(0, 100), 0);
+    }
+
+    #[test]
+    fn context_window_matches_known_model_prefixes() {
+        assert_eq!(model_context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(model_context_window("GPT-4"), Some(8_192));
+        assert_eq!(model_context_window("llava:13b"), Some(4_096));
+        assert_eq!(model_context_window("openai/gpt-4o"), Some(128_000));
+        assert_eq!(model_context_window("my-private-model"), None);
+    }
+
+    #[test]
+    fn estimate_flags_requests_over_the_context_window() {
+        let small = estimate_tokens("分析截图", Some((1920, 1080)), 1000, "llava");
+        assert_eq!(small.total_tokens, 4 + 1105 + 1000);
+        assert!(!small.exceeds_context);
+
+        let long_prompt = "字".repeat(3000);
+        let large = estimate_tokens(&long_prompt, Some((1920, 1080)), 1000, "llava");
+        assert!(large.exceeds_context);
+
+        let unknown = estimate_tokens(&long_prompt, None, 1000, "my-private-model");
+        assert_eq!(unknown.image_tokens, 0);
+        assert!(!unknown.exceeds_context);
+    }
+}
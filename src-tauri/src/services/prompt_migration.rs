@@ -0,0 +1,273 @@
+//! Version migration of user-defined prompts.
+//!
+//! Custom prompts are stored verbatim, so a change of placeholder semantics
+//! silently breaks them. `settings.prompt_schema_version` records which
+//! placeholder version the stored prompts were written for; on startup they
+//! are rewritten up to [`CURRENT_PROMPT_SCHEMA_VERSION`]. Prompts that still
+//! lack a placeholder they need afterwards are reported so the user can fix
+//! them by hand.
+//!
+//! Versions:
+//! - 1: prompts saved before the version was tracked
+//! - 2: report placeholders are single-brace (`{records}`); the double-brace
+//!   form of the export templates (`{{records}}`) was sent to the model as-is
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppResult;
+use crate::memory_storage::Settings;
+
+/// Placeholder version of the prompts written by this build.
+pub const CURRENT_PROMPT_SCHEMA_VERSION: i32 = 2;
+
+/// Version assumed when none is stored.
+const LEGACY_PROMPT_SCHEMA_VERSION: i32 = 1;
+
+/// A custom prompt setting and the placeholders it must contain.
+struct PromptField {
+    name: &'static str,
+    get: fn(&Settings) -> Option<&str>,
+    get_mut: fn(&mut Settings) -> &mut Option<String>,
+    required: &'static [&'static str],
+}
+
+const PROMPT_FIELDS: &[PromptField] = &[
+    PromptField {
+        name: "summary_prompt",
+        get: |s| s.summary_prompt.as_deref(),
+        get_mut: |s| &mut s.summary_prompt,
+        required: &["{records}"],
+    },
+    PromptField {
+        name: "weekly_report_prompt",
+        get: |s| s.weekly_report_prompt.as_deref(),
+        get_mut: |s| &mut s.weekly_report_prompt,
+        required: &["{records}"],
+    },
+    PromptField {
+        name: "monthly_report_prompt",
+        get: |s| s.monthly_report_prompt.as_deref(),
+        get_mut: |s| &mut s.monthly_report_prompt,
+        required: &["{records}"],
+    },
+    PromptField {
+        name: "custom_report_prompt",
+        get: |s| s.custom_report_prompt.as_deref(),
+        get_mut: |s| &mut s.custom_report_prompt,
+        required: &["{records}"],
+    },
+    PromptField {
+        name: "comparison_report_prompt",
+        get: |s| s.comparison_report_prompt.as_deref(),
+        get_mut: |s| &mut s.comparison_report_prompt,
+        required: &["{records_a}", "{records_b}"],
+    },
+];
+
+/// Report placeholders rewritten from `{{name}}` to `{name}` by version 2.
+const V2_PLACEHOLDERS: &[&str] = &[
+    "records",
+    "records_a",
+    "records_b",
+    "start_date",
+    "end_date",
+    "start_date_a",
+    "end_date_a",
+    "start_date_b",
+    "end_date_b",
+];
+
+/// A custom prompt that needs the user's attention after migration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptNotice {
+    /// Settings field of the prompt
+    pub field: String,
+    /// Placeholders the prompt must contain but does not
+    pub missing_placeholders: Vec<String>,
+}
+
+fn migrate_to_v2(prompt: &str) -> String {
+    V2_PLACEHOLDERS
+        .iter()
+        .fold(prompt.to_string(), |prompt, name| {
+            prompt.replace(&format!("{{{{{}}}}}", name), &format!("{{{}}}", name))
+        })
+}
+
+/// Whether prompts saved for `stored_version` must be migrated.
+pub fn needs_prompt_migration(stored_version: Option<i32>) -> bool {
+    stored_version.unwrap_or(LEGACY_PROMPT_SCHEMA_VERSION) < CURRENT_PROMPT_SCHEMA_VERSION
+}
+
+/// Custom prompts (non-blank) that lack a required placeholder.
+pub fn check_custom_prompts(settings: &Settings) -> Vec<PromptNotice> {
+    PROMPT_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let prompt = (field.get)(settings).filter(|p| !p.trim().is_empty())?;
+            let missing: Vec<String> = field
+                .required
+                .iter()
+                .filter(|placeholder| !prompt.contains(*placeholder))
+                .map(|placeholder| placeholder.to_string())
+                .collect();
+            (!missing.is_empty()).then(|| PromptNotice {
+                field: field.name.to_string(),
+                missing_placeholders: missing,
+            })
+        })
+        .collect()
+}
+
+/// Rewrite the custom prompts of `settings` up to the current version and
+/// record that version. Returns whether anything changed (prompt or version).
+pub fn migrate_prompts(settings: &mut Settings) -> bool {
+    let stored = settings
+        .prompt_schema_version
+        .unwrap_or(LEGACY_PROMPT_SCHEMA_VERSION);
+    if stored >= CURRENT_PROMPT_SCHEMA_VERSION {
+        return false;
+    }
+    for field in PROMPT_FIELDS {
+        if let Some(prompt) = (field.get_mut)(settings).as_mut() {
+            if stored < 2 {
+                *prompt = migrate_to_v2(prompt);
+            }
+        }
+    }
+    settings.prompt_schema_version = Some(CURRENT_PROMPT_SCHEMA_VERSION);
+    true
+}
+
+/// Migrate the stored prompts if needed and return the prompts the user
+/// should update. Run once on startup after the database is initialized.
+pub fn run_prompt_migration() -> AppResult<Vec<PromptNotice>> {
+    let settings = crate::memory_storage::get_settings_sync()?;
+    let settings = if needs_prompt_migration(settings.prompt_schema_version) {
+        let from = settings.prompt_schema_version;
+        let migrated = crate::memory_storage::update_settings_sync(|s| {
+            migrate_prompts(s);
+        })?;
+        tracing::info!(
+            "Custom prompts migrated from version {:?} to {}",
+            from,
+            CURRENT_PROMPT_SCHEMA_VERSION
+        );
+        migrated
+    } else {
+        settings
+    };
+
+    let notices = check_custom_prompts(&settings);
+    for notice in &notices {
+        tracing::warn!(
+            "Custom prompt {} is missing placeholders {:?}, please update it",
+            notice.field,
+            notice.missing_placeholders
+        );
+    }
+    Ok(notices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn only_older_versions_need_migration() {
+        assert!(needs_prompt_migration(None));
+        assert!(needs_prompt_migration(Some(1)));
+        assert!(!needs_prompt_migration(Some(CURRENT_PROMPT_SCHEMA_VERSION)));
+        assert!(!needs_prompt_migration(Some(
+            CURRENT_PROMPT_SCHEMA_VERSION + 1
+        )));
+    }
+
+    #[test]
+    fn legacy_prompts_get_single_brace_placeholders() {
+        let mut settings = Settings {
+            summary_prompt: Some("总结：{{records}}\n{{section:完成事项}}".to_string()),
+            comparison_report_prompt: Some(
+                "{{start_date_a}}~{{end_date_a}}: {{records_a}}\n{records_b}".to_string(),
+            ),
+            ..Default::default()
+        };
+
+        assert!(migrate_prompts(&mut settings));
+
+        assert_eq!(
+            settings.summary_prompt.as_deref(),
+            Some("总结：{records}\n{{section:完成事项}}")
+        );
+        assert_eq!(
+            settings.comparison_report_prompt.as_deref(),
+            Some("{start_date_a}~{end_date_a}: {records_a}\n{records_b}")
+        );
+        assert_eq!(settings.weekly_report_prompt, None);
+        assert_eq!(
+            settings.prompt_schema_version,
+            Some(CURRENT_PROMPT_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn current_prompts_are_left_alone() {
+        let mut settings = Settings {
+            summary_prompt: Some("{{records}}".to_string()),
+            prompt_schema_version: Some(CURRENT_PROMPT_SCHEMA_VERSION),
+            ..Default::default()
+        };
+
+        assert!(!migrate_prompts(&mut settings));
+        assert_eq!(settings.summary_prompt.as_deref(), Some("{{records}}"));
+    }
+
+    #[test]
+    fn check_reports_missing_placeholders_of_custom_prompts_only() {
+        let settings = Settings {
+            summary_prompt: Some("请总结今天的工作".to_string()),
+            weekly_report_prompt: Some("{records}".to_string()),
+            monthly_report_prompt: Some("  ".to_string()),
+            comparison_report_prompt: Some("{records_a}".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_custom_prompts(&settings),
+            vec![
+                PromptNotice {
+                    field: "summary_prompt".to_string(),
+                    missing_placeholders: vec!["{records}".to_string()],
+                },
+                PromptNotice {
+                    field: "comparison_report_prompt".to_string(),
+                    missing_placeholders: vec!["{records_b}".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn startup_migration_is_saved_once() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let mut settings = (*crate::memory_storage::get_settings_sync().unwrap()).clone();
+        settings.summary_prompt = Some("{{records}}".to_string());
+        settings.weekly_report_prompt = Some("本周".to_string());
+        crate::memory_storage::save_settings_sync(&settings).unwrap();
+
+        let notices = run_prompt_migration().unwrap();
+
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].field, "weekly_report_prompt");
+        crate::memory_storage::invalidate_settings_cache();
+        let stored = crate::memory_storage::get_settings_sync().unwrap();
+        assert_eq!(stored.summary_prompt.as_deref(), Some("{records}"));
+        assert_eq!(
+            stored.prompt_schema_version,
+            Some(CURRENT_PROMPT_SCHEMA_VERSION)
+        );
+        assert_eq!(run_prompt_migration().unwrap(), notices);
+    }
+}
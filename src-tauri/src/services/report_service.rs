@@ -7,6 +7,7 @@
 //! REPORT-002: Monthly report generation
 //! REPORT-003: Custom period report generation
 //! REPORT-004: Comparison report between two time periods
+//! Range summary: one summary file for several days
 //! DATA-007: Multi-language daily report support
 
 // Re-export helper functions and types from synthesis for use by service functions
@@ -14,14 +15,15 @@ pub use crate::synthesis::{
     build_session_based_report, filter_records_by_settings, format_records_by_week,
     format_records_for_summary, generate_base_daily_summary, generate_comparison_report_filename,
     generate_custom_report_filename, generate_monthly_report_filename, generate_summary_filename,
-    generate_summary_filename_with_lang, generate_weekly_report_filename,
+    generate_summary_filename_with_lang, generate_weekly_report_filename, get_daily_report_records,
     get_default_comparison_report_prompt, get_default_custom_report_prompt,
     get_default_monthly_report_prompt, get_default_summary_prompt,
     get_default_weekly_report_prompt, get_supported_languages, translate_report,
     write_report_to_obsidian, ApiConfig,
 };
 
-use crate::errors::{AppError, AppResult};
+use crate::errors::{AppError, AppResult, ErrorCode};
+use crate::i18n::{current_lang, Lang, MessageKey};
 use crate::synthesis::{
     non_empty_or, DEFAULT_COMPARISON_REPORT_PROMPT, DEFAULT_CUSTOM_REPORT_PROMPT,
     DEFAULT_MONTHLY_REPORT_PROMPT, DEFAULT_SUMMARY_PROMPT, DEFAULT_WEEKLY_REPORT_PROMPT,
@@ -31,9 +33,10 @@ use crate::synthesis::{
 /// optionally persist the path in settings, and return the Obsidian file path.
 ///
 /// `obsidian_path` - The Obsidian output path to write to.
-/// `update_settings` is called with a mutable reference to a cloned settings
-/// object and the file path string, allowing the caller to set the appropriate
-/// `last_*_path` field. Pass `None` to skip settings persistence (e.g. for
+/// `update_settings` is called with a mutable reference to the currently
+/// stored settings and the file path string, allowing the caller to set the
+/// appropriate `last_*_path` field without overwriting settings changed while
+/// the report was generated. Pass `None` to skip settings persistence (e.g. for
 /// comparison reports).
 #[allow(clippy::type_complexity)]
 fn write_report_to_all_destinations(
@@ -48,9 +51,7 @@ fn write_report_to_all_destinations(
 
     // Persist last-report-path in settings
     if let Some(updater) = update_settings {
-        let mut updated_settings = settings.clone();
-        updater(&mut updated_settings, &path_str);
-        crate::memory_storage::save_settings_sync(&updated_settings)?;
+        crate::memory_storage::update_settings_sync(|s| updater(s, &path_str))?;
     }
 
     tracing::info!("{} generated: {}", report_label, path_str);
@@ -61,15 +62,31 @@ fn write_report_to_all_destinations(
 ///
 /// # Arguments
 /// * `vault_name` - Optional vault name to use. If None, uses default vault or auto-detection.
-pub async fn generate_daily_summary_service(vault_name: Option<String>) -> AppResult<String> {
+/// * `dry_run` - When true, only returns the generated text: nothing is written to
+///   Obsidian and `last_summary_path` is left untouched.
+///
+/// Returns the Obsidian file path, or the summary text itself when `dry_run` is true.
+pub async fn generate_daily_summary_service(
+    vault_name: Option<String>,
+    dry_run: bool,
+) -> AppResult<String> {
     if !crate::network_status::is_online() {
+        if dry_run {
+            return Err(AppError::localized(
+                ErrorCode::Network,
+                MessageKey::OfflineSummaryPreviewUnavailable,
+                current_lang(),
+            ));
+        }
         let _ = crate::offline_queue::enqueue_task(
             &crate::offline_queue::OfflineTaskType::DailySummary,
             "{}",
             None,
         );
-        return Err(AppError::network(
-            "当前处于离线状态，日报生成已加入队列，网络恢复后将自动处理",
+        return Err(AppError::localized(
+            ErrorCode::Network,
+            MessageKey::OfflineDailySummaryQueued,
+            current_lang(),
         ));
     }
 
@@ -103,56 +120,57 @@ pub async fn generate_daily_summary_service(vault_name: Option<String>) -> AppRe
         if let Some(content) = build_session_based_report(&sessions) {
             let prompt_template =
                 non_empty_or(settings.summary_prompt.as_deref(), DEFAULT_SUMMARY_PROMPT);
-            let prompt = prompt_template
-                .replace("{records}", &content)
-                .replace("{github_activity}", "");
-
-            let summary = crate::synthesis::call_llm_api_with_retry(
+            let summary = crate::synthesis::generate_structured_summary(
                 &api_config,
-                &prompt,
-                2000,
-                "generate_daily_summary",
+                prompt_template,
+                &content,
             )
             .await?;
 
-            let filename = generate_summary_filename(&settings);
-            return write_report_to_all_destinations(
-                &settings,
-                &obsidian_path,
-                &filename,
-                &summary,
-                "Daily summary",
-                Some(&|s, p| s.last_summary_path = Some(p.to_string())),
-            );
+            let records = get_daily_report_records(&settings)?;
+            return finalize_daily_summary(&settings, &obsidian_path, summary, &records, dry_run);
         }
     }
 
     // Legacy record-based approach (when no sessions)
-    let all_records = crate::memory_storage::get_today_records_sync()?;
-    let records = filter_records_by_settings(all_records, &settings);
+    let records = get_daily_report_records(&settings)?;
 
     if records.is_empty() {
-        return Err(AppError::validation("今日无记录"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsToday,
+            Lang::from_settings(&settings),
+        ));
     }
 
     let records_text = format_records_for_summary(&records);
     let prompt_template = non_empty_or(settings.summary_prompt.as_deref(), DEFAULT_SUMMARY_PROMPT);
-    let prompt = prompt_template
-        .replace("{records}", &records_text)
-        .replace("{github_activity}", "");
+    let summary =
+        crate::synthesis::generate_structured_summary(&api_config, prompt_template, &records_text)
+            .await?;
 
-    let summary = crate::synthesis::call_llm_api_with_retry(
-        &api_config,
-        &prompt,
-        2000,
-        "generate_daily_summary",
-    )
-    .await?;
+    finalize_daily_summary(&settings, &obsidian_path, summary, &records, dry_run)
+}
+
+/// Return the summary text for a dry run, otherwise write it and return the file path.
+/// `records` are the daily report records the header is built from.
+fn finalize_daily_summary(
+    settings: &crate::memory_storage::Settings,
+    obsidian_path: &str,
+    summary: String,
+    records: &[crate::memory_storage::Record],
+    dry_run: bool,
+) -> AppResult<String> {
+    if dry_run {
+        tracing::info!("Daily summary generated in dry-run mode, not written");
+        return Ok(summary);
+    }
 
-    let filename = generate_summary_filename(&settings);
+    let summary = with_daily_header(summary, records)?;
+    let filename = generate_summary_filename(settings);
     write_report_to_all_destinations(
-        &settings,
-        &obsidian_path,
+        settings,
+        obsidian_path,
         &filename,
         &summary,
         "Daily summary",
@@ -160,6 +178,134 @@ pub async fn generate_daily_summary_service(vault_name: Option<String>) -> AppRe
     )
 }
 
+/// Whether a generated daily summary should be opened in the default editor.
+fn should_open_summary(settings: &crate::memory_storage::Settings, dry_run: bool) -> bool {
+    !dry_run && settings.open_summary_after_generate.unwrap_or(false)
+}
+
+/// Open a freshly written daily summary with the system default app when
+/// `open_summary_after_generate` is enabled. Failures are only logged since
+/// the summary itself has already been saved.
+pub fn open_summary_after_generate(app: &tauri::AppHandle, path: &str, dry_run: bool) {
+    let enabled = crate::memory_storage::get_settings_sync()
+        .map(|s| should_open_summary(&s, dry_run))
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    if !std::path::Path::new(path).is_file() {
+        tracing::warn!("Generated summary not found, skip opening: {}", path);
+        return;
+    }
+
+    use tauri_plugin_shell::ShellExt;
+    #[allow(deprecated)]
+    match app.shell().open(path, None) {
+        Ok(()) => tracing::info!("Opened daily summary: {}", path),
+        Err(e) => tracing::warn!("Failed to open daily summary {}: {}", path, e),
+    }
+}
+
+/// Prepend Obsidian frontmatter and the statistics section built from the
+/// daily report records (`get_daily_report_records`, so drafts and settings
+/// apply as in the body), unless the summary already has a frontmatter.
+fn with_daily_header(
+    summary: String,
+    records: &[crate::memory_storage::Record],
+) -> AppResult<String> {
+    if summary.starts_with("---\n") {
+        return Ok(summary);
+    }
+
+    let today = crate::memory_storage::logical_today()
+        .format("%Y-%m-%d")
+        .to_string();
+    let software = crate::memory_storage::get_software_normalized_for_records(
+        records.iter().map(|r| r.id).collect(),
+    )?;
+    let frontmatter = crate::export::frontmatter_for_records(&today, &today, records);
+    let stats = daily_stats_section(records, &software);
+    Ok(format!("{}\n{}\n{}", frontmatter, stats, summary))
+}
+
+/// Statistics written above the LLM text of a daily summary: record counts by
+/// source, the local time span and the most used software. Generated from the
+/// records only, never by the LLM. `software_by_record` maps record ids to
+/// their stored `software_normalized`.
+pub fn daily_stats_section(
+    records: &[crate::memory_storage::Record],
+    software_by_record: &std::collections::HashMap<i64, String>,
+) -> String {
+    let auto_count = records.iter().filter(|r| r.source_type == "auto").count();
+    let manual_count = records.iter().filter(|r| r.source_type == "manual").count();
+
+    let local_times: Vec<chrono::DateTime<chrono::Local>> = records
+        .iter()
+        .filter_map(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).ok())
+        .map(|dt| dt.with_timezone(&chrono::Local))
+        .collect();
+    let span = match (local_times.iter().min(), local_times.iter().max()) {
+        (Some(first), Some(last)) => {
+            format!("{} - {}", first.format("%H:%M"), last.format("%H:%M"))
+        }
+        _ => "-".to_string(),
+    };
+
+    // Ties go to the software listed first in name order, so the output is stable
+    let mut software: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    for record in records.iter().filter(|r| r.source_type == "auto") {
+        if let Some(name) = software_by_record.get(&record.id) {
+            *software.entry(name.clone()).or_insert(0) += 1;
+        }
+    }
+    let top_software = software
+        .iter()
+        .fold(
+            None::<(&String, usize)>,
+            |best, (name, &count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((name, count)),
+            },
+        )
+        .map_or_else(
+            || "-".to_string(),
+            |(name, count)| format!("{}（{} 条）", name, count),
+        );
+
+    format!(
+        "> [!info] 今日统计\n\
+         > - 总记录数：{}（自动 {} / 手动 {}）\n\
+         > - 时间跨度：{}\n\
+         > - 最常用软件：{}\n",
+        records.len(),
+        auto_count,
+        manual_count,
+        span,
+        top_software
+    )
+}
+
+/// Save a previously previewed (dry-run) daily summary to Obsidian and return the file path.
+pub fn save_daily_summary_service(
+    content: String,
+    vault_name: Option<String>,
+) -> AppResult<String> {
+    if content.trim().is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::DailySummaryEmpty,
+            current_lang(),
+        ));
+    }
+
+    let settings = crate::memory_storage::get_settings_sync()?;
+    let auto_detect = settings.auto_detect_vault_by_window.unwrap_or(false);
+    let obsidian_path = settings.get_effective_vault(vault_name.as_deref(), auto_detect)?;
+    let records = get_daily_report_records(&settings)?;
+    finalize_daily_summary(&settings, &obsidian_path, content, &records, false)
+}
+
 /// Generate multilingual daily summary - report generation service
 pub async fn generate_multilingual_daily_summary_service(target_lang: String) -> AppResult<String> {
     if !crate::network_status::is_online() {
@@ -216,7 +362,11 @@ pub async fn generate_weekly_report_service() -> AppResult<String> {
     let all_records = crate::memory_storage::get_week_records_sync(week_start_day)?;
     let records = filter_records_by_settings(all_records, &settings);
     if records.is_empty() {
-        return Err(AppError::validation("本周无记录"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsThisWeek,
+            Lang::from_settings(&settings),
+        ));
     }
 
     let records_text = format_records_for_summary(&records);
@@ -265,7 +415,11 @@ pub async fn generate_monthly_report_service() -> AppResult<String> {
     let all_records = crate::memory_storage::get_month_records_sync()?;
     let records = filter_records_by_settings(all_records, &settings);
     if records.is_empty() {
-        return Err(AppError::validation("本月无记录"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsThisMonth,
+            Lang::from_settings(&settings),
+        ));
     }
 
     let records_text = format_records_by_week(&records);
@@ -313,7 +467,11 @@ pub async fn generate_custom_report_service(
         AppError::validation(format!("无效的结束日期格式 (需要 YYYY-MM-DD): {}", e))
     })?;
     if parsed_end < parsed_start {
-        return Err(AppError::validation("结束日期不能早于起始日期"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::EndDateBeforeStartDate,
+            current_lang(),
+        ));
     }
 
     let settings = crate::memory_storage::get_settings_sync()?;
@@ -326,7 +484,11 @@ pub async fn generate_custom_report_service(
     )?;
     let records = filter_records_by_settings(all_records, &settings);
     if records.is_empty() {
-        return Err(AppError::validation("所选时间范围内无记录"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsInRange,
+            Lang::from_settings(&settings),
+        ));
     }
 
     let day_count = (parsed_end - parsed_start).num_days() + 1;
@@ -362,6 +524,183 @@ pub async fn generate_custom_report_service(
     )
 }
 
+/// Character budget of the records sent in one range summary request.
+/// Longer ranges are summarized chunk by chunk and the partial summaries merged,
+/// so a long period never overflows the model context.
+pub(crate) const RANGE_SUMMARY_CHUNK_CHARS: usize = 24_000;
+
+const RANGE_SUMMARY_CHUNK_PROMPT: &str = r"
+你是一个工作日志助手。以下是 {start_date} 至 {end_date} 期间工作记录的第 {part}/{total} 部分。
+请按日期提炼这部分记录中的主要工作内容、关键成果和遇到的问题，输出简洁的 Markdown 要点，不要有其他说明文字。
+
+记录：
+{records}
+
+请输出要点：";
+
+/// Format range records as one line per record, grouped under a `## YYYY-MM-DD`
+/// heading per local date so the model can tell days apart.
+pub fn format_range_record_lines(records: &[crate::memory_storage::Record]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current_date: Option<String> = None;
+    for record in records {
+        let date = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+            .map(|dt| {
+                dt.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| "unknown".to_string());
+        if current_date.as_deref() != Some(date.as_str()) {
+            lines.push(format!("## {}", date));
+            current_date = Some(date);
+        }
+        lines.push(format_records_for_summary(std::slice::from_ref(record)));
+    }
+    lines
+}
+
+/// Split lines into chunks of at most `max_chars` characters, keeping lines whole.
+/// A single line longer than the budget becomes a chunk of its own.
+pub fn chunk_lines(lines: &[String], max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        let needed = line.chars().count() + usize::from(!current.is_empty());
+        if !current.is_empty() && current.chars().count() + needed > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Write the range summary to `dest_path`, creating missing parent directories.
+fn write_range_summary(dest_path: &str, summary: &str) -> AppResult<String> {
+    let path = std::path::PathBuf::from(dest_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, summary)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Generate one summary file for the records of several days.
+///
+/// Records are fetched with the same range query as custom reports, drafts are
+/// left out and the `include_manual_records` setting applies. When the records
+/// exceed [`RANGE_SUMMARY_CHUNK_CHARS`], each chunk is summarized first and the
+/// partial summaries are merged by a final request.
+///
+/// Returns the path of the written file.
+pub async fn generate_range_summary_service(
+    start_date: String,
+    end_date: String,
+    dest_path: String,
+) -> AppResult<String> {
+    if !crate::network_status::is_online() {
+        return Err(AppError::network(
+            "当前处于离线状态，报告生成需要网络连接。请检查网络连接后重试。",
+        ));
+    }
+    if dest_path.trim().is_empty() {
+        return Err(AppError::validation("输出路径不能为空"));
+    }
+
+    let parsed_start = chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").map_err(|e| {
+        AppError::validation(format!("无效的起始日期格式 (需要 YYYY-MM-DD): {}", e))
+    })?;
+    let parsed_end = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|e| {
+        AppError::validation(format!("无效的结束日期格式 (需要 YYYY-MM-DD): {}", e))
+    })?;
+    if parsed_end < parsed_start {
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::EndDateBeforeStartDate,
+            current_lang(),
+        ));
+    }
+
+    let settings = crate::memory_storage::get_settings_sync()?;
+    let api_config = crate::synthesis::load_api_config(&settings)?;
+
+    let confirmed = crate::memory_storage::get_confirmed_records_by_date_range_sync(
+        start_date.clone(),
+        end_date.clone(),
+    )?;
+    let mut records = filter_records_by_settings(confirmed, &settings);
+    if records.is_empty() {
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsInRange,
+            Lang::from_settings(&settings),
+        ));
+    }
+    // The range query returns newest first; the summary reads oldest first
+    records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let chunks = chunk_lines(
+        &format_range_record_lines(&records),
+        RANGE_SUMMARY_CHUNK_CHARS,
+    );
+    let records_text = if chunks.len() == 1 {
+        chunks.into_iter().next().unwrap_or_default()
+    } else {
+        tracing::info!(
+            "Range summary {}..{} split into {} chunks",
+            start_date,
+            end_date,
+            chunks.len()
+        );
+        let total = chunks.len();
+        let mut partials = Vec::with_capacity(total);
+        for (i, chunk) in chunks.iter().enumerate() {
+            let prompt = RANGE_SUMMARY_CHUNK_PROMPT
+                .replace("{start_date}", &start_date)
+                .replace("{end_date}", &end_date)
+                .replace("{part}", &(i + 1).to_string())
+                .replace("{total}", &total.to_string())
+                .replace("{records}", chunk);
+            let partial = crate::synthesis::call_llm_api_with_retry(
+                &api_config,
+                &prompt,
+                2000,
+                "generate_range_summary_chunk",
+            )
+            .await?;
+            partials.push(partial);
+        }
+        partials.join("\n\n")
+    };
+
+    let prompt_template = non_empty_or(
+        settings.custom_report_prompt.as_deref(),
+        DEFAULT_CUSTOM_REPORT_PROMPT,
+    );
+    let prompt = prompt_template
+        .replace("{start_date}", &start_date)
+        .replace("{end_date}", &end_date)
+        .replace("{records}", &records_text);
+
+    let summary = crate::synthesis::call_llm_api_with_retry(
+        &api_config,
+        &prompt,
+        4000,
+        "generate_range_summary",
+    )
+    .await?;
+
+    let path = write_range_summary(&dest_path, &summary)?;
+    tracing::info!("Range summary generated: {}", path);
+    Ok(path)
+}
+
 /// Generate comparison report between two time periods - REPORT-004 service
 pub async fn compare_reports_service(
     start_date_a: String,
@@ -414,7 +753,11 @@ pub async fn compare_reports_service(
     let records_b = filter_records_by_settings(all_records_b, &settings);
 
     if records_a.is_empty() && records_b.is_empty() {
-        return Err(AppError::validation("两个时间段内均无记录"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsInBothPeriods,
+            Lang::from_settings(&settings),
+        ));
     }
 
     let day_count_a = (parsed_end_a - parsed_start_a).num_days() + 1;
@@ -458,3 +801,250 @@ pub async fn compare_reports_service(
         None,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn summary_is_opened_only_when_enabled_and_written() {
+        let disabled = crate::memory_storage::Settings::default();
+        assert!(!should_open_summary(&disabled, false));
+
+        let enabled = crate::memory_storage::Settings {
+            open_summary_after_generate: Some(true),
+            ..Default::default()
+        };
+        assert!(should_open_summary(&enabled, false));
+        assert!(!should_open_summary(&enabled, true));
+    }
+
+    #[test]
+    #[serial]
+    fn dry_run_returns_summary_without_writing_file() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().unwrap();
+        let obsidian_path = dir.path().to_string_lossy().to_string();
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+
+        let result =
+            finalize_daily_summary(&settings, &obsidian_path, "# 日报".to_string(), &[], true)
+                .unwrap();
+
+        assert_eq!(result, "# 日报");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+        assert!(settings.last_summary_path.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn non_dry_run_writes_file_and_updates_last_summary_path() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().unwrap();
+        let obsidian_path = dir.path().to_string_lossy().to_string();
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+
+        let path =
+            finalize_daily_summary(&settings, &obsidian_path, "# 日报".to_string(), &[], false)
+                .unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("---\ndate: "));
+        assert!(written.contains("record_count: 0\n"));
+        assert!(written.contains("总记录数：0（自动 0 / 手动 0）"));
+        assert!(written.ends_with("\n\n# 日报"));
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+        assert_eq!(settings.last_summary_path.as_deref(), Some(path.as_str()));
+    }
+
+    #[test]
+    #[serial]
+    fn writing_summary_keeps_settings_changed_during_generation() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let dir = tempfile::tempdir().unwrap();
+        let obsidian_path = dir.path().to_string_lossy().to_string();
+        // Snapshot taken when generation starts
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+
+        // The user changes a setting while the summary is being generated
+        let mut changed = (*settings).clone();
+        changed.summary_time = Some("09:30".to_string());
+        crate::memory_storage::save_settings_sync(&changed).unwrap();
+
+        let path =
+            finalize_daily_summary(&settings, &obsidian_path, "# 日报".to_string(), &[], false)
+                .unwrap();
+
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+        assert_eq!(settings.summary_time.as_deref(), Some("09:30"));
+        assert_eq!(settings.last_summary_path.as_deref(), Some(path.as_str()));
+    }
+
+    #[test]
+    fn chunk_lines_respects_budget_and_keeps_lines_whole() {
+        let lines: Vec<String> = (0..10).map(|i| format!("- line {}", i)).collect();
+
+        let single = chunk_lines(&lines, 10_000);
+        assert_eq!(single, vec![lines.join("\n")]);
+
+        let chunks = chunk_lines(&lines, 20);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 20));
+        assert_eq!(chunks.join("\n"), lines.join("\n"));
+
+        let long = vec!["x".repeat(50), "y".to_string()];
+        assert_eq!(
+            chunk_lines(&long, 20),
+            vec!["x".repeat(50), "y".to_string()]
+        );
+        assert!(chunk_lines(&[], 20).is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn range_record_lines_group_records_by_date() {
+        crate::memory_storage::setup_test_db_with_schema();
+        crate::memory_storage::add_record("manual", "写周报", None, None, None).unwrap();
+        crate::memory_storage::add_record("manual", "评审代码", None, None, None).unwrap();
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let records =
+            crate::memory_storage::get_records_by_date_range_sync(today.clone(), today.clone())
+                .unwrap();
+        let lines = format_range_record_lines(&records);
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], format!("## {}", today));
+        assert!(lines[1..].iter().any(|l| l.ends_with("写周报")));
+        assert!(lines[1..].iter().any(|l| l.ends_with("评审代码")));
+    }
+
+    #[test]
+    fn range_summary_is_written_to_dest_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("nested").join("range.md");
+
+        let path = write_range_summary(&dest.to_string_lossy(), "# 总结").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# 总结");
+    }
+
+    #[test]
+    #[serial]
+    fn daily_summary_header_uses_the_report_records() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let confirmed =
+            crate::memory_storage::add_record("auto", r#"{"tags":["开发"]}"#, None, None, None)
+                .unwrap();
+        crate::memory_storage::confirm_record_sync(confirmed).unwrap();
+        // Drafts are not part of the report, so neither of its header
+        crate::memory_storage::add_record("auto", r#"{"tags":["摸鱼"]}"#, None, None, None)
+            .unwrap();
+        crate::memory_storage::add_record("manual", "记录想法", None, None, None).unwrap();
+
+        let settings = crate::memory_storage::get_settings_sync().unwrap();
+        let records = get_daily_report_records(&settings).unwrap();
+        let with_fm = with_daily_header("# 日报".to_string(), &records).unwrap();
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        assert!(with_fm.starts_with(&format!("---\ndate: \"{}\"\n", today)));
+        assert!(with_fm.contains("  - \"开发\"\n"));
+        assert!(with_fm.contains("record_count: 2\n"));
+        assert!(with_fm.contains("  \"auto\": 1\n  \"manual\": 1\n"));
+        assert!(!with_fm.contains("摸鱼"));
+        assert!(with_fm.contains("总记录数：2（自动 1 / 手动 1）"));
+        assert!(with_fm.ends_with("# 日报"));
+
+        // Existing frontmatter is left alone
+        let already = "---\ndate: \"x\"\n---\n# 日报".to_string();
+        assert_eq!(
+            with_daily_header(already.clone(), &records).unwrap(),
+            already
+        );
+    }
+
+    fn stats_record(
+        id: i64,
+        timestamp: &str,
+        source_type: &str,
+        content: &str,
+    ) -> crate::memory_storage::Record {
+        crate::memory_storage::Record {
+            id,
+            timestamp: timestamp.to_string(),
+            source_type: source_type.to_string(),
+            content: content.to_string(),
+            screenshot_path: None,
+            monitor_info: None,
+            tags: None,
+            user_notes: None,
+            session_id: None,
+            analysis_status: None,
+            metadata: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn daily_stats_section_counts_sources_span_and_software() {
+        let records = vec![
+            stats_record(
+                1,
+                "2026-03-16T09:00:00+00:00",
+                "auto",
+                r#"{"active_software":"code.exe"}"#,
+            ),
+            stats_record(
+                2,
+                "2026-03-16T11:30:00+00:00",
+                "auto",
+                r#"{"active_software":"Chrome"}"#,
+            ),
+            stats_record(
+                3,
+                "2026-03-16T10:00:00+00:00",
+                "auto",
+                r#"{"active_software":"VSCode"}"#,
+            ),
+            stats_record(4, "2026-03-16T10:15:00+00:00", "manual", "想法"),
+        ];
+        let local = |ts: &str| {
+            chrono::DateTime::parse_from_rfc3339(ts)
+                .unwrap()
+                .with_timezone(&chrono::Local)
+                .format("%H:%M")
+                .to_string()
+        };
+
+        let software = std::collections::HashMap::from([
+            (1, "VS Code".to_string()),
+            (2, "Chrome".to_string()),
+            (3, "VS Code".to_string()),
+        ]);
+
+        let stats = daily_stats_section(&records, &software);
+
+        assert!(
+            stats.contains("总记录数：4（自动 3 / 手动 1）"),
+            "{}",
+            stats
+        );
+        assert!(stats.contains(&format!(
+            "时间跨度：{} - {}",
+            local("2026-03-16T09:00:00+00:00"),
+            local("2026-03-16T11:30:00+00:00")
+        )));
+        assert!(stats.contains("最常用软件：VS Code（2 条）"), "{}", stats);
+        assert!(stats.lines().all(|line| line.starts_with('>')));
+    }
+
+    #[test]
+    fn daily_stats_section_without_records() {
+        let stats = daily_stats_section(&[], &std::collections::HashMap::new());
+        assert!(stats.contains("总记录数：0（自动 0 / 手动 0）"));
+        assert!(stats.contains("时间跨度：-"));
+        assert!(stats.contains("最常用软件：-"));
+    }
+}
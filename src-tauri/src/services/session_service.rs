@@ -101,7 +101,7 @@ impl Default for Session {
 // ── Session CRUD Operations ───────────────────────────────────────────────────
 
 /// 获取时段间隔阈值（分钟）
-fn get_session_gap_minutes() -> i64 {
+pub(crate) fn get_session_gap_minutes() -> i64 {
     get_settings_sync()
         .ok()
         .and_then(|s| s.session_gap_minutes)
@@ -413,19 +413,25 @@ pub async fn analyze_session_service(session_id: i64) -> AppResult<()> {
     }
 
     // 7. Store results
+    let redact = crate::redaction::redaction_enabled();
     for (screenshot, analysis) in screenshots
         .iter()
         .zip(response.per_screenshot_analysis.iter())
     {
-        let content = serde_json::json!({
+        let mut content = serde_json::json!({
             "current_focus": analysis.current_focus,
             "active_software": analysis.active_software,
             "context_keywords": analysis.context_keywords,
             "tags": analysis.tags,
-        })
-        .to_string();
+        });
+        if redact {
+            crate::redaction::redact_model_fields(&mut content);
+        }
+        let content = content.to_string();
 
         crate::memory_storage::update_record_analysis(screenshot.record_id, &content)?;
+        crate::keyword_alert::check_analyzed_record(screenshot.record_id, &content);
+        crate::record_hook::run_on_stored_record_hook(screenshot.record_id);
     }
 
     // 8. Update session with summary
@@ -0,0 +1,369 @@
+//! Settings schema - Type, default and range of every settings field
+//!
+//! The table in `settings_schema` is the single description of the settings
+//! fields for the frontend form validation. Defaults match the database
+//! column defaults; fields without a default are unset until configured.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Value type of a settings field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingType {
+    Boolean,
+    Integer,
+    Number,
+    String,
+    /// A string holding serialized JSON
+    Json,
+}
+
+/// Description of one settings field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingFieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: SettingType,
+    pub description: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Inclusive lower bound of numeric fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Value>,
+    /// Inclusive upper bound of numeric fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Value>,
+    /// Allowed values of enumerated string fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<&'static [&'static str]>,
+}
+
+impl SettingFieldSchema {
+    fn new(name: &'static str, kind: SettingType, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            description,
+            default: None,
+            minimum: None,
+            maximum: None,
+            options: None,
+        }
+    }
+
+    fn default_value(mut self, value: Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    fn min(mut self, value: Value) -> Self {
+        self.minimum = Some(value);
+        self
+    }
+
+    fn range(mut self, minimum: Value, maximum: Value) -> Self {
+        self.minimum = Some(minimum);
+        self.maximum = Some(maximum);
+        self
+    }
+
+    fn options(mut self, options: &'static [&'static str]) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
+fn boolean(name: &'static str, description: &'static str) -> SettingFieldSchema {
+    SettingFieldSchema::new(name, SettingType::Boolean, description)
+}
+
+fn integer(name: &'static str, description: &'static str) -> SettingFieldSchema {
+    SettingFieldSchema::new(name, SettingType::Integer, description)
+}
+
+fn number(name: &'static str, description: &'static str) -> SettingFieldSchema {
+    SettingFieldSchema::new(name, SettingType::Number, description)
+}
+
+fn string(name: &'static str, description: &'static str) -> SettingFieldSchema {
+    SettingFieldSchema::new(name, SettingType::String, description)
+}
+
+fn json_text(name: &'static str, description: &'static str) -> SettingFieldSchema {
+    SettingFieldSchema::new(name, SettingType::Json, description)
+}
+
+/// Schema of all `Settings` fields, in declaration order.
+pub fn settings_schema() -> Vec<SettingFieldSchema> {
+    vec![
+        string("api_base_url", "AI API 地址"),
+        string("api_key", "AI API 密钥"),
+        string("model_name", "分析模型名称"),
+        integer("screenshot_interval", "感知间隔（分钟，仅供界面展示）")
+            .default_value(json!(5))
+            .min(json!(1)),
+        string("summary_time", "日报生成时间（HH:MM）").default_value(json!("18:00")),
+        string("obsidian_path", "Obsidian 输出目录"),
+        boolean("auto_capture_enabled", "启用自动感知").default_value(json!(false)),
+        string("last_summary_path", "最近一次日报路径"),
+        string("summary_model_name", "日报模型名称"),
+        string("analysis_prompt", "截图分析 prompt"),
+        string("summary_prompt", "日报 prompt"),
+        integer("change_threshold", "屏幕变化阈值（百分比）")
+            .default_value(json!(3))
+            .range(json!(0), json!(100)),
+        integer("max_silent_minutes", "最长静默时间（分钟）")
+            .default_value(json!(30))
+            .min(json!(1)),
+        string("summary_title_format", "日报标题格式，{date} 为日期")
+            .default_value(json!("工作日报 - {date}")),
+        boolean("include_manual_records", "日报包含手动记录").default_value(json!(true)),
+        json_text("window_whitelist", "窗口白名单（字符串数组）").default_value(json!("[]")),
+        json_text("window_blacklist", "窗口黑名单（字符串数组）").default_value(json!("[]")),
+        boolean("use_whitelist_only", "仅感知白名单窗口").default_value(json!(false)),
+        boolean("auto_adjust_silent", "自动调整静默阈值").default_value(json!(true)),
+        string(
+            "silent_adjustment_paused_until",
+            "静默阈值调整暂停至（RFC3339）",
+        ),
+        boolean("auto_detect_work_time", "自动识别工作时间").default_value(json!(true)),
+        boolean("use_custom_work_time", "使用自定义工作时间").default_value(json!(false)),
+        string("custom_work_time_start", "自定义工作开始时间（HH:MM）")
+            .default_value(json!("09:00")),
+        string("custom_work_time_end", "自定义工作结束时间（HH:MM）").default_value(json!("18:00")),
+        json_text("learned_work_time", "学习到的工作时段"),
+        string("capture_mode", "多显示器感知模式")
+            .default_value(json!("primary"))
+            .options(&["primary", "secondary", "all"]),
+        integer("selected_monitor_index", "secondary 模式使用的显示器序号")
+            .default_value(json!(0))
+            .min(json!(0)),
+        json_text("tag_categories", "自定义标签分类（字符串数组）").default_value(json!("[]")),
+        boolean("is_ollama", "使用 Ollama 本地模型").default_value(json!(false)),
+        string("weekly_report_prompt", "周报 prompt"),
+        integer("weekly_report_day", "周报生成日（0=周一，6=周日）")
+            .default_value(json!(0))
+            .range(json!(0), json!(6)),
+        string("last_weekly_report_path", "最近一次周报路径"),
+        string("monthly_report_prompt", "月报 prompt"),
+        string("last_monthly_report_path", "最近一次月报路径"),
+        string("custom_report_prompt", "自定义周期报告 prompt"),
+        string("last_custom_report_path", "最近一次自定义报告路径"),
+        json_text("obsidian_vaults", "Obsidian Vault 列表").default_value(json!("[]")),
+        boolean("auto_detect_vault_by_window", "按窗口标题选择 Vault").default_value(json!(false)),
+        string("comparison_report_prompt", "对比报告 prompt"),
+        boolean("capture_only_mode", "仅截图不分析").default_value(json!(false)),
+        json_text("custom_headers", "自定义 API 请求头").default_value(json!("[]")),
+        boolean("quality_filter_enabled", "启用截图质量过滤").default_value(json!(true)),
+        number("quality_filter_threshold", "截图质量过滤阈值")
+            .default_value(json!(0.3))
+            .range(json!(0.0), json!(1.0)),
+        integer("session_gap_minutes", "工作时段间隔阈值（分钟）")
+            .default_value(json!(30))
+            .min(json!(1)),
+        boolean("proxy_enabled", "启用代理").default_value(json!(false)),
+        string("proxy_host", "代理主机"),
+        integer("proxy_port", "代理端口")
+            .default_value(json!(8080))
+            .range(json!(1), json!(65535)),
+        string("proxy_username", "代理用户名"),
+        string("proxy_password", "代理密码"),
+        string("test_model_name", "连接测试使用的模型名称"),
+        boolean("onboarding_completed", "已完成新用户引导").default_value(json!(false)),
+        string("language", "界面语言").default_value(json!("en")),
+        string("preferred_language", "日报首选语言").default_value(json!("zh-CN")),
+        json_text("supported_languages", "支持的日报语言（字符串数组）")
+            .default_value(json!(r#"["zh-CN","en","ja"]"#)),
+        boolean("auto_backup_enabled", "启用自动备份").default_value(json!(false)),
+        string("auto_backup_interval", "自动备份周期")
+            .default_value(json!("daily"))
+            .options(&["daily", "weekly", "monthly"]),
+        integer("auto_backup_retention", "自动备份保留数量")
+            .default_value(json!(5))
+            .range(json!(3), json!(20)),
+        string("last_auto_backup_at", "最近一次自动备份时间（RFC3339）"),
+        string("custom_export_template", "自定义导出模板"),
+        boolean("sensitive_filter_enabled", "启用敏感内容检测").default_value(json!(false)),
+        json_text("sensitive_keywords", "额外的敏感关键词（字符串数组）")
+            .default_value(json!("[]")),
+        boolean("log_llm_payload", "日志记录 LLM 请求/响应原文").default_value(json!(true)),
+        string(
+            "screenshot_format",
+            "截图保存格式，auto 按内容选择（文字多用 PNG，照片类用 JPEG）",
+        )
+        .default_value(json!("png"))
+        .options(&["png", "jpeg", "webp", "auto"]),
+        json_text("api_keys", "额外的 API 密钥（字符串数组）").default_value(json!("[]")),
+        boolean("open_summary_after_generate", "日报生成后自动打开").default_value(json!(false)),
+        integer("startup_delay_seconds", "首次自动感知前等待的秒数")
+            .default_value(json!(0))
+            .min(json!(0)),
+        integer("day_start_hour", "一天的起点小时")
+            .default_value(json!(0))
+            .range(json!(0), json!(23)),
+        integer("failure_alert_window_minutes", "失败告警聚合窗口（分钟）")
+            .default_value(json!(10))
+            .min(json!(1)),
+        string("failure_alert_webhook_url", "失败汇总告警 webhook 地址"),
+        json_text("projects", "项目与关键词列表"),
+        integer("screenshot_interval_seconds", "自动感知间隔（秒）")
+            .default_value(json!(300))
+            .min(json!(10)),
+        boolean("debug_dump_failed_requests", "保存失败的分析请求").default_value(json!(false)),
+        boolean("crop_taskbar", "截图裁掉底部任务栏").default_value(json!(false)),
+        integer("taskbar_height", "裁剪的任务栏高度（像素）")
+            .default_value(json!(48))
+            .min(json!(0)),
+        json_text("category_prompts", "按软件类别的分析 prompt"),
+        json_text("endpoint_image_formats", "按 API 地址选择分析图片格式"),
+        string("analysis_prompt_b", "A/B 实验的 B 组分析 prompt"),
+        integer("prompt_b_percent", "使用 B 组 prompt 的比例（百分比）")
+            .default_value(json!(50))
+            .range(json!(0), json!(100)),
+        json_text("extra_headers", "AI 请求附加请求头（名称到值的映射）"),
+        boolean(
+            "pause_capture_without_display",
+            "无可用显示器时暂停自动感知",
+        )
+        .default_value(json!(true)),
+        string(
+            "on_record_command",
+            "记录产生后执行的命令，可用 {id} {timestamp} {source_type} {content} {screenshot_path}",
+        ),
+        json_text(
+            "software_aliases",
+            "软件名别名映射（JSON 对象：别名 → 规范名），补充或覆盖内置映射",
+        ),
+        integer(
+            "max_image_dimension",
+            "多显示器拼接截图的最长边上限（像素），超过时按比例缩放，0 表示不限制",
+        )
+        .default_value(json!(7680))
+        .min(json!(0)),
+        integer("capture_timeout_secs", "单次截图的超时时间（秒）")
+            .default_value(json!(5))
+            .min(json!(1)),
+        integer("capture_retries", "截图失败或超时后的重试次数")
+            .default_value(json!(2))
+            .range(json!(0), json!(10)),
+        boolean(
+            "capture_on_idle_wake",
+            "从空闲（5 分钟无输入）恢复操作时立即截图一次",
+        )
+        .default_value(json!(true)),
+        boolean(
+            "redact_sensitive_content",
+            "分析结果入库前将邮箱、手机号和疑似密钥替换为占位符",
+        )
+        .default_value(json!(true)),
+        integer(
+            "prompt_schema_version",
+            "自定义 prompt 的占位符版本，启动时自动迁移到当前版本",
+        )
+        .min(json!(1)),
+        boolean("auto_summary_enabled", "每天在 summary_time 自动生成日报")
+            .default_value(json!(false)),
+        boolean(
+            "auto_summary_catch_up",
+            "错过定时（应用未运行）时，下次启动补生成当天日报",
+        )
+        .default_value(json!(true)),
+        string(
+            "last_auto_summary_date",
+            "最近一次定时日报的日期（YYYY-MM-DD）",
+        ),
+        json_text(
+            "keyword_alert_rules",
+            "关键词提醒规则（JSON 数组：keywords 命中任一即用 message 提醒）",
+        )
+        .default_value(json!("[]")),
+        boolean(
+            "capture_all_monitors",
+            "感知所有显示器并横向拼接（优先于 capture_mode）",
+        )
+        .default_value(json!(false)),
+        json_text(
+            "exclude_regions",
+            "截图涂黑区域（JSON 数组：x、y、width、height 像素，原点为左上角）",
+        )
+        .default_value(json!("[]")),
+        json_text(
+            "excluded_apps",
+            "前台时不感知的应用（字符串数组，匹配进程名或窗口标题）",
+        )
+        .default_value(json!("[]")),
+        integer(
+            "draft_retention_days",
+            "未确认草稿的保留天数，过期的草稿在启动时删除；0 表示永久保留",
+        )
+        .default_value(json!(7))
+        .min(json!(0)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_storage::Settings;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn schema_lists_every_settings_field_once() {
+        let fields: BTreeSet<String> = serde_json::to_value(Settings::default())
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        let schema = settings_schema();
+        let names: BTreeSet<String> = schema.iter().map(|f| f.name.to_string()).collect();
+
+        assert_eq!(names.len(), schema.len(), "duplicate schema entries");
+        assert_eq!(names, fields);
+    }
+
+    #[test]
+    fn defaults_match_type_and_range() {
+        for field in settings_schema() {
+            let Some(default) = &field.default else {
+                continue;
+            };
+            let type_ok = match field.kind {
+                SettingType::Boolean => default.is_boolean(),
+                SettingType::Integer => default.is_i64(),
+                SettingType::Number => default.is_number(),
+                SettingType::String => default.is_string(),
+                SettingType::Json => default
+                    .as_str()
+                    .is_some_and(|s| serde_json::from_str::<Value>(s).is_ok()),
+            };
+            assert!(type_ok, "{} default has the wrong type", field.name);
+
+            let value = default.as_f64();
+            if let (Some(value), Some(min)) = (value, field.minimum.as_ref()) {
+                assert!(value >= min.as_f64().unwrap(), "{}", field.name);
+            }
+            if let (Some(value), Some(max)) = (value, field.maximum.as_ref()) {
+                assert!(value <= max.as_f64().unwrap(), "{}", field.name);
+            }
+            if let Some(options) = field.options {
+                assert!(
+                    options.contains(&default.as_str().unwrap()),
+                    "{}",
+                    field.name
+                );
+            }
+        }
+
+        let threshold = settings_schema()
+            .into_iter()
+            .find(|f| f.name == "change_threshold")
+            .unwrap();
+        let json = serde_json::to_value(&threshold).unwrap();
+        assert_eq!(json["type"], "integer");
+        assert_eq!(json["minimum"], 0);
+        assert_eq!(json["maximum"], 100);
+    }
+}
@@ -16,18 +16,126 @@ pub fn get_settings_service() -> AppResult<Arc<Settings>> {
     crate::memory_storage::get_settings_sync()
 }
 
+/// Drop the in-memory settings cache and reload settings from the database.
+///
+/// Use this when the database may have been changed outside this process
+/// (another app instance, manual edits), since reads are normally served from cache.
+pub fn reload_settings_service() -> AppResult<Arc<Settings>> {
+    crate::memory_storage::invalidate_settings_cache();
+    crate::memory_storage::get_settings_sync()
+}
+
+/// Copy of the settings with secrets masked, safe to show in the UI or logs.
+///
+/// `api_key`, every entry of `api_keys` and `proxy_password` are masked.
+/// Saving a masked value back keeps the stored secret (see `unmask_secret`).
+pub fn mask_settings(settings: &Settings) -> Settings {
+    Settings {
+        api_key: settings.api_key.as_deref().map(crate::mask_api_key),
+        api_keys: settings.api_keys.as_deref().map(mask_api_keys_json),
+        proxy_password: settings.proxy_password.as_deref().map(mask_password),
+        ..settings.clone()
+    }
+}
+
+/// Get settings with secrets masked.
+pub fn get_settings_masked_service() -> AppResult<Settings> {
+    Ok(mask_settings(&crate::memory_storage::get_settings_sync()?))
+}
+
+fn mask_password(_password: &str) -> String {
+    "****".to_string()
+}
+
+fn mask_api_keys_json(json: &str) -> String {
+    let keys: Vec<String> = serde_json::from_str(json).unwrap_or_default();
+    let masked: Vec<String> = keys.iter().map(|k| crate::mask_api_key(k)).collect();
+    serde_json::to_string(&masked).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Keep the stored secret when the update is just its masked form,
+/// so a settings page built from `get_settings_masked` can be saved as is.
+fn unmask_secret(
+    update: &Option<String>,
+    current: &Option<String>,
+    mask: fn(&str) -> String,
+) -> Option<String> {
+    match (update, current) {
+        (Some(u), Some(c)) if *u == mask(c) => Some(c.clone()),
+        (Some(u), _) => Some(u.clone()),
+        (None, c) => c.clone(),
+    }
+}
+
+/// `unmask_secret` for the `api_keys` JSON list, entry by entry: a masked
+/// entry is replaced by the stored key it masks, so keys can be added to or
+/// removed from a masked list without losing the others.
+fn unmask_api_keys(update: &Option<String>, current: &Option<String>) -> Option<String> {
+    let (Some(update), Some(current)) = (update, current) else {
+        return unmask_secret(update, current, mask_api_keys_json);
+    };
+    let (Ok(keys), Ok(mut stored)) = (
+        serde_json::from_str::<Vec<String>>(update),
+        serde_json::from_str::<Vec<String>>(current),
+    ) else {
+        return Some(update.clone());
+    };
+
+    let keys: Vec<String> = keys
+        .into_iter()
+        .map(|key| {
+            // Each stored key is used once, in case two keys share a mask
+            match stored.iter().position(|s| crate::mask_api_key(s) == key) {
+                Some(i) => stored.remove(i),
+                None => key,
+            }
+        })
+        .collect();
+    Some(serde_json::to_string(&keys).unwrap_or_else(|_| update.clone()))
+}
+
+/// Merge the capture interval, keeping minutes (UI display) and seconds (used
+/// by the capture loop) consistent. Returns `(minutes, seconds)`.
+///
+/// Whichever of the two differs from the stored value wins; when the UI only
+/// edits minutes, seconds follow, and an edited seconds value is shown as
+/// whole minutes (at least 1).
+fn merge_capture_interval(current: &Settings, updates: &Settings) -> (Option<i32>, Option<i32>) {
+    let seconds_changed = updates
+        .screenshot_interval_seconds
+        .is_some_and(|s| Some(s) != current.screenshot_interval_seconds);
+    let minutes_changed = updates
+        .screenshot_interval
+        .is_some_and(|m| Some(m) != current.screenshot_interval);
+
+    if seconds_changed {
+        let seconds = updates.screenshot_interval_seconds;
+        (seconds.map(|s| ((s + 30) / 60).max(1)), seconds)
+    } else if minutes_changed {
+        let minutes = updates.screenshot_interval;
+        (minutes, minutes.map(|m| m * 60))
+    } else {
+        (
+            current.screenshot_interval,
+            current.screenshot_interval_seconds,
+        )
+    }
+}
+
 fn merge_settings(current: &Settings, updates: &Settings) -> Settings {
+    let (screenshot_interval, screenshot_interval_seconds) =
+        merge_capture_interval(current, updates);
     Settings {
         api_base_url: updates
             .api_base_url
             .clone()
             .or_else(|| current.api_base_url.clone()),
-        api_key: updates.api_key.clone().or_else(|| current.api_key.clone()),
+        api_key: unmask_secret(&updates.api_key, &current.api_key, crate::mask_api_key),
         model_name: updates
             .model_name
             .clone()
             .or_else(|| current.model_name.clone()),
-        screenshot_interval: updates.screenshot_interval.or(current.screenshot_interval),
+        screenshot_interval,
         summary_time: updates
             .summary_time
             .clone()
@@ -166,10 +274,11 @@ fn merge_settings(current: &Settings, updates: &Settings) -> Settings {
             .proxy_username
             .clone()
             .or_else(|| current.proxy_username.clone()),
-        proxy_password: updates
-            .proxy_password
-            .clone()
-            .or_else(|| current.proxy_password.clone()),
+        proxy_password: unmask_secret(
+            &updates.proxy_password,
+            &current.proxy_password,
+            mask_password,
+        ),
         test_model_name: updates
             .test_model_name
             .clone()
@@ -205,6 +314,143 @@ fn merge_settings(current: &Settings, updates: &Settings) -> Settings {
             .custom_export_template
             .clone()
             .or_else(|| current.custom_export_template.clone()),
+        // Sensitive content skip
+        sensitive_filter_enabled: updates
+            .sensitive_filter_enabled
+            .or(current.sensitive_filter_enabled),
+        sensitive_keywords: updates
+            .sensitive_keywords
+            .clone()
+            .or_else(|| current.sensitive_keywords.clone()),
+        // LLM payload logging
+        log_llm_payload: updates.log_llm_payload.or(current.log_llm_payload),
+        // Screenshot encoding format
+        screenshot_format: updates
+            .screenshot_format
+            .clone()
+            .or_else(|| current.screenshot_format.clone()),
+        // API key rotation
+        api_keys: unmask_api_keys(&updates.api_keys, &current.api_keys),
+        // Open summary after generate
+        open_summary_after_generate: updates
+            .open_summary_after_generate
+            .or(current.open_summary_after_generate),
+        // Startup delay before first capture
+        startup_delay_seconds: updates
+            .startup_delay_seconds
+            .or(current.startup_delay_seconds),
+        // Day start hour
+        day_start_hour: updates.day_start_hour.or(current.day_start_hour),
+        // Failure alert aggregation
+        failure_alert_window_minutes: updates
+            .failure_alert_window_minutes
+            .or(current.failure_alert_window_minutes),
+        failure_alert_webhook_url: updates
+            .failure_alert_webhook_url
+            .clone()
+            .or_else(|| current.failure_alert_webhook_url.clone()),
+        // Keyword based project classification
+        projects: updates
+            .projects
+            .clone()
+            .or_else(|| current.projects.clone()),
+        // Capture interval in seconds
+        screenshot_interval_seconds,
+        // 调试：保存失败的分析请求
+        debug_dump_failed_requests: updates
+            .debug_dump_failed_requests
+            .or(current.debug_dump_failed_requests),
+        // 截图裁剪任务栏
+        crop_taskbar: updates.crop_taskbar.or(current.crop_taskbar),
+        taskbar_height: updates.taskbar_height.or(current.taskbar_height),
+        // 按软件类别的分析 prompt
+        category_prompts: updates
+            .category_prompts
+            .clone()
+            .or_else(|| current.category_prompts.clone()),
+        // 按端点选择分析图片格式
+        endpoint_image_formats: updates
+            .endpoint_image_formats
+            .clone()
+            .or_else(|| current.endpoint_image_formats.clone()),
+        // A/B 分析 prompt 实验
+        analysis_prompt_b: updates
+            .analysis_prompt_b
+            .clone()
+            .or_else(|| current.analysis_prompt_b.clone()),
+        prompt_b_percent: updates.prompt_b_percent.or(current.prompt_b_percent),
+        // AI 请求附加请求头
+        extra_headers: updates
+            .extra_headers
+            .clone()
+            .or_else(|| current.extra_headers.clone()),
+        // 无显示器时暂停感知
+        pause_capture_without_display: updates
+            .pause_capture_without_display
+            .or(current.pause_capture_without_display),
+        // 记录产生后执行的自定义命令
+        on_record_command: updates
+            .on_record_command
+            .clone()
+            .or_else(|| current.on_record_command.clone()),
+        // 软件名规范化
+        software_aliases: updates
+            .software_aliases
+            .clone()
+            .or_else(|| current.software_aliases.clone()),
+        // Stitched screenshot size cap
+        max_image_dimension: updates.max_image_dimension.or(current.max_image_dimension),
+        // Screenshot timeout and retries
+        capture_timeout_secs: updates
+            .capture_timeout_secs
+            .or(current.capture_timeout_secs),
+        capture_retries: updates.capture_retries.or(current.capture_retries),
+        // Capture when the user returns from idle
+        capture_on_idle_wake: updates
+            .capture_on_idle_wake
+            .or(current.capture_on_idle_wake),
+        // Sensitive content redaction
+        redact_sensitive_content: updates
+            .redact_sensitive_content
+            .or(current.redact_sensitive_content),
+        // Placeholder semantics version of the custom prompts
+        prompt_schema_version: updates
+            .prompt_schema_version
+            .or(current.prompt_schema_version),
+        // Scheduled daily summary
+        auto_summary_enabled: updates
+            .auto_summary_enabled
+            .or(current.auto_summary_enabled),
+        auto_summary_catch_up: updates
+            .auto_summary_catch_up
+            .or(current.auto_summary_catch_up),
+        last_auto_summary_date: updates
+            .last_auto_summary_date
+            .clone()
+            .or_else(|| current.last_auto_summary_date.clone()),
+        // Keyword alerts
+        keyword_alert_rules: updates
+            .keyword_alert_rules
+            .clone()
+            .or_else(|| current.keyword_alert_rules.clone()),
+        // Multi-monitor capture
+        capture_all_monitors: updates
+            .capture_all_monitors
+            .or(current.capture_all_monitors),
+        // Excluded screen regions
+        exclude_regions: updates
+            .exclude_regions
+            .clone()
+            .or_else(|| current.exclude_regions.clone()),
+        // Excluded apps
+        excluded_apps: updates
+            .excluded_apps
+            .clone()
+            .or_else(|| current.excluded_apps.clone()),
+        // Draft retention
+        draft_retention_days: updates
+            .draft_retention_days
+            .or(current.draft_retention_days),
     }
 }
 
@@ -213,9 +459,22 @@ fn merge_settings(current: &Settings, updates: &Settings) -> Settings {
 /// This function saves the application settings.
 /// It wraps the sync function from memory_storage for consistency with the service layer pattern.
 pub fn save_settings_service(settings: &Settings) -> AppResult<()> {
-    let current = crate::memory_storage::get_settings_sync()?;
-    let merged = merge_settings(&current, settings);
-    crate::memory_storage::save_settings_sync(&merged)
+    let mut aliases_changed = false;
+    #[cfg_attr(not(feature = "screenshot"), allow(unused_variables))]
+    let merged = crate::memory_storage::update_settings_sync(|current| {
+        let updated = merge_settings(current, settings);
+        aliases_changed = updated.software_aliases != current.software_aliases;
+        *current = updated;
+    })?;
+    #[cfg(feature = "screenshot")]
+    crate::services::capture_service::warn_if_request_exceeds_context(&merged);
+
+    // Stored software names follow the aliases they were normalized with
+    if aliases_changed {
+        let changed = crate::memory_storage::renormalize_software_sync()?;
+        tracing::info!("software_aliases changed, renormalized {} records", changed);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -223,6 +482,108 @@ mod tests {
     use super::*;
     use serial_test::serial;
 
+    #[test]
+    #[serial]
+    fn changing_software_aliases_renormalizes_records() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let id = crate::memory_storage::add_record(
+            "auto",
+            r#"{"current_focus":"写代码","active_software":"code.exe"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let stored =
+            || crate::memory_storage::get_software_normalized_for_records(vec![id]).unwrap();
+        assert_eq!(stored().get(&id).map(String::as_str), Some("VS Code"));
+
+        save_settings_service(&Settings {
+            software_aliases: Some(r#"{"code": "Visual Studio Code"}"#.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            stored().get(&id).map(String::as_str),
+            Some("Visual Studio Code")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn masked_settings_do_not_contain_full_keys() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let secret = "sk-abcdefghijklmnopqrstuvwxyz";
+        let extra = "sk-zyxwvutsrqponmlkjihgfedcba";
+        save_settings_service(&Settings {
+            api_key: Some(secret.to_string()),
+            api_keys: Some(serde_json::to_string(&[extra]).unwrap()),
+            proxy_password: Some("hunter2".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let masked = get_settings_masked_service().unwrap();
+        let json = serde_json::to_string(&masked).unwrap();
+        assert!(!json.contains(secret));
+        assert!(!json.contains(extra));
+        assert!(!json.contains("hunter2"));
+        assert_eq!(masked.api_key.as_deref(), Some("sk-ab...****"));
+
+        // The unmasked settings still hold the real key
+        assert_eq!(
+            get_settings_service().unwrap().api_key.as_deref(),
+            Some(secret)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn saving_masked_settings_keeps_stored_secrets() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let secret = "sk-abcdefghijklmnopqrstuvwxyz";
+        save_settings_service(&Settings {
+            api_key: Some(secret.to_string()),
+            proxy_password: Some("hunter2".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut masked = get_settings_masked_service().unwrap();
+        masked.model_name = Some("gpt-4o".to_string());
+        save_settings_service(&masked).unwrap();
+
+        let saved = get_settings_service().unwrap();
+        assert_eq!(saved.api_key.as_deref(), Some(secret));
+        assert_eq!(saved.proxy_password.as_deref(), Some("hunter2"));
+        assert_eq!(saved.model_name.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    #[serial]
+    fn adding_a_key_to_the_masked_list_keeps_stored_keys() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let stored = "sk-abcdefghijklmnopqrstuvwxyz";
+        save_settings_service(&Settings {
+            api_keys: Some(serde_json::to_string(&[stored]).unwrap()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut masked = get_settings_masked_service().unwrap();
+        let mut keys: Vec<String> =
+            serde_json::from_str(masked.api_keys.as_deref().unwrap()).unwrap();
+        keys.push("sk-newkey0123456789".to_string());
+        masked.api_keys = Some(serde_json::to_string(&keys).unwrap());
+        save_settings_service(&masked).unwrap();
+
+        let saved: Vec<String> =
+            serde_json::from_str(get_settings_service().unwrap().api_keys.as_deref().unwrap())
+                .unwrap();
+        assert_eq!(saved, vec![stored, "sk-newkey0123456789"]);
+    }
+
     #[test]
     #[serial]
     fn save_settings_service_merges_partial_updates() {
@@ -250,4 +611,118 @@ mod tests {
         assert_eq!(updated.screenshot_interval, Some(5));
         assert_eq!(updated.summary_time.as_deref(), Some("18:00"));
     }
+
+    #[test]
+    #[serial]
+    fn capture_interval_minutes_and_seconds_stay_in_sync() {
+        crate::memory_storage::setup_test_db_with_schema();
+
+        let initial = get_settings_service().unwrap();
+        assert_eq!(initial.screenshot_interval_seconds, Some(300));
+
+        // UI edits minutes only: seconds follow
+        save_settings_service(&Settings {
+            screenshot_interval: Some(2),
+            ..(*initial).clone()
+        })
+        .unwrap();
+        let updated = get_settings_service().unwrap();
+        assert_eq!(updated.screenshot_interval_seconds, Some(120));
+
+        // Sub-minute interval: minutes display is rounded, seconds are kept
+        save_settings_service(&Settings {
+            screenshot_interval_seconds: Some(20),
+            ..(*updated).clone()
+        })
+        .unwrap();
+        let updated = get_settings_service().unwrap();
+        assert_eq!(updated.screenshot_interval_seconds, Some(20));
+        assert_eq!(updated.screenshot_interval, Some(1));
+    }
+
+    #[test]
+    #[serial]
+    fn save_settings_updates_cache_immediately() {
+        crate::memory_storage::setup_test_db_with_schema();
+
+        // Warm the cache
+        let before = get_settings_service().unwrap();
+        assert_eq!(before.summary_time.as_deref(), Some("18:00"));
+
+        let partial = Settings {
+            summary_time: Some("20:30".to_string()),
+            ..Default::default()
+        };
+        save_settings_service(&partial).unwrap();
+
+        let after = get_settings_service().unwrap();
+        assert_eq!(after.summary_time.as_deref(), Some("20:30"));
+        assert!(!Arc::ptr_eq(&before, &after));
+
+        // Subsequent reads share the cached allocation instead of hitting the DB
+        let again = get_settings_service().unwrap();
+        assert!(Arc::ptr_eq(&after, &again));
+    }
+
+    #[test]
+    #[serial]
+    fn reload_settings_picks_up_external_changes() {
+        crate::memory_storage::setup_test_db_with_schema();
+
+        let cached = get_settings_service().unwrap();
+        assert_eq!(cached.summary_time.as_deref(), Some("18:00"));
+
+        // Simulate another instance writing to the database directly
+        {
+            let db = crate::memory_storage::DB_CONNECTION.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute(
+                    "UPDATE settings SET summary_time = '09:15' WHERE id = 1",
+                    [],
+                )
+                .unwrap();
+        }
+
+        let stale = get_settings_service().unwrap();
+        assert_eq!(stale.summary_time.as_deref(), Some("18:00"));
+
+        let reloaded = reload_settings_service().unwrap();
+        assert_eq!(reloaded.summary_time.as_deref(), Some("09:15"));
+        assert_eq!(
+            get_settings_service().unwrap().summary_time.as_deref(),
+            Some("09:15")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn concurrent_saves_only_overwrite_their_own_fields() {
+        crate::memory_storage::setup_test_db_with_schema();
+
+        let user = std::thread::spawn(|| {
+            for i in 0..20 {
+                save_settings_service(&Settings {
+                    model_name: Some(format!("model-{}", i)),
+                    ..Default::default()
+                })
+                .unwrap();
+            }
+        });
+        let report = std::thread::spawn(|| {
+            for i in 0..20 {
+                crate::memory_storage::update_settings_sync(|s| {
+                    s.last_summary_path = Some(format!("/notes/{}.md", i));
+                })
+                .unwrap();
+            }
+        });
+        user.join().unwrap();
+        report.join().unwrap();
+
+        crate::memory_storage::invalidate_settings_cache();
+        let settings = get_settings_service().unwrap();
+        assert_eq!(settings.model_name.as_deref(), Some("model-19"));
+        assert_eq!(settings.last_summary_path.as_deref(), Some("/notes/19.md"));
+    }
 }
@@ -0,0 +1,185 @@
+//! Setup service - First-run checks for the setup wizard
+//!
+//! Each check is a small function over the settings or the database so the
+//! frontend can show which setup steps are still open.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AppError, AppResult};
+use crate::memory_storage::{Settings, DB_CONNECTION};
+
+/// Name fragments of models that accept image input.
+/// Matched case-insensitively against the configured model name.
+const VISION_MODEL_MARKERS: &[&str] = &[
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-4-turbo",
+    "gpt-4-vision",
+    "gpt-5",
+    "o1",
+    "o3",
+    "o4",
+    "claude-3",
+    "claude-sonnet",
+    "claude-opus",
+    "claude-haiku",
+    "gemini",
+    "vision",
+    "-vl",
+    "vl-",
+    "4v",
+    "llava",
+    "minicpm-v",
+    "pixtral",
+    "moondream",
+    "bakllava",
+];
+
+/// Which setup steps are done.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SetupStatus {
+    pub api_key_configured: bool,
+    pub obsidian_path_configured: bool,
+    /// The configured model is known to accept images
+    pub model_supports_vision: bool,
+    /// At least one automatic capture was analyzed successfully
+    pub has_successful_capture: bool,
+    /// All steps above are done
+    pub complete: bool,
+}
+
+/// An API key (or an extra key of the key pool) is set.
+pub fn is_api_key_configured(settings: &Settings) -> bool {
+    let primary = settings
+        .api_key
+        .as_deref()
+        .is_some_and(|key| !key.trim().is_empty());
+    let extra = settings
+        .api_keys
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .is_some_and(|keys| keys.iter().any(|key| !key.trim().is_empty()));
+    primary || extra
+}
+
+/// An Obsidian vault or the legacy output path is set.
+pub fn is_obsidian_path_configured(settings: &Settings) -> bool {
+    settings.get_obsidian_output_path().is_ok()
+}
+
+/// Whether the model name looks like a vision-capable model.
+pub fn model_supports_vision(model_name: Option<&str>) -> bool {
+    let Some(model_name) = model_name else {
+        return false;
+    };
+    let model_name = model_name.to_lowercase();
+    VISION_MODEL_MARKERS
+        .iter()
+        .any(|marker| model_name.contains(marker))
+}
+
+/// Whether any automatic capture was analyzed successfully. Captures are
+/// stored as `pending` and only marked `analyzed` once a result is written,
+/// so raw captures (e.g. in capture-only mode) do not count.
+pub fn has_successful_capture_sync() -> AppResult<bool> {
+    let db = DB_CONNECTION.lock()?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM records
+         WHERE source_type = 'auto' AND analysis_status = 'analyzed')",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Setup status for the first-run wizard.
+pub fn get_setup_status_service() -> AppResult<SetupStatus> {
+    // Settings are read before taking the DB lock (a cache miss locks it too)
+    let settings = crate::memory_storage::get_settings_sync()?;
+    let api_key_configured = is_api_key_configured(&settings);
+    let obsidian_path_configured = is_obsidian_path_configured(&settings);
+    let model_supports_vision = model_supports_vision(settings.model_name.as_deref());
+    let has_successful_capture = has_successful_capture_sync()?;
+
+    Ok(SetupStatus {
+        api_key_configured,
+        obsidian_path_configured,
+        model_supports_vision,
+        has_successful_capture,
+        complete: api_key_configured
+            && obsidian_path_configured
+            && model_supports_vision
+            && has_successful_capture,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn api_key_and_obsidian_checks() {
+        let mut settings = Settings::default();
+        assert!(!is_api_key_configured(&settings));
+        assert!(!is_obsidian_path_configured(&settings));
+
+        settings.api_key = Some("  ".to_string());
+        assert!(!is_api_key_configured(&settings));
+        settings.api_keys = Some("[]".to_string());
+        assert!(!is_api_key_configured(&settings));
+        settings.api_keys = Some(r#"["sk-extra"]"#.to_string());
+        assert!(is_api_key_configured(&settings));
+
+        settings.obsidian_path = Some("/vault".to_string());
+        assert!(is_obsidian_path_configured(&settings));
+    }
+
+    #[test]
+    fn vision_model_detection() {
+        assert!(model_supports_vision(Some("gpt-4o-mini")));
+        assert!(model_supports_vision(Some("Qwen2.5-VL-7B-Instruct")));
+        assert!(model_supports_vision(Some("llava:13b")));
+        assert!(model_supports_vision(Some("glm-4v-plus")));
+        assert!(!model_supports_vision(Some("gpt-3.5-turbo")));
+        assert!(!model_supports_vision(Some("deepseek-chat")));
+        assert!(!model_supports_vision(None));
+    }
+
+    #[test]
+    #[serial]
+    fn successful_capture_requires_analyzed_auto_record() {
+        crate::memory_storage::setup_test_db_with_schema();
+        crate::memory_storage::add_record("manual", r#"{"current_focus":"x"}"#, None, None, None)
+            .unwrap();
+        let id = crate::memory_storage::add_record("auto", "{}", None, None, None).unwrap();
+        assert!(!has_successful_capture_sync().unwrap());
+
+        crate::memory_storage::update_record_analysis(
+            id,
+            r#"{"current_focus":"写代码","active_software":"VS Code","context_keywords":[]}"#,
+        )
+        .unwrap();
+        assert!(has_successful_capture_sync().unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn pending_capture_alone_is_not_a_successful_capture() {
+        crate::memory_storage::setup_test_db_with_schema();
+        // Pending placeholder content already carries a current_focus
+        crate::memory_storage::add_record(
+            "auto",
+            r#"{"current_focus":"待分析","active_software":"Code"}"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(!has_successful_capture_sync().unwrap());
+    }
+}
@@ -117,10 +117,7 @@ pub async fn call_vision_api_batch(
         .json(request);
 
     // Check if custom headers contain auth
-    let has_custom_auth = config
-        .custom_headers()
-        .iter()
-        .any(|h| h.key.to_lowercase() == "authorization" || h.key.to_lowercase() == "api-key");
+    let has_custom_auth = crate::memory_storage::has_custom_auth_header(config.custom_headers());
 
     if !config.api_key().is_empty() && !has_custom_auth {
         request_builder =
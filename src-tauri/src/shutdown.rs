@@ -0,0 +1,177 @@
+//! Graceful shutdown of background loops.
+//!
+//! Background loops (auto capture, threshold evaluation) check the signal at
+//! their sleep points only, so a cycle that is already running — e.g. waiting
+//! on `analyze_screen` or writing a screenshot — is allowed to finish. On app
+//! exit the signal is raised and the exit path waits, bounded by a timeout,
+//! until every registered loop has returned.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use tokio::sync::Notify;
+
+/// How long app exit waits for running loops to finish their current cycle.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Shutdown signal shared by background loops.
+#[derive(Debug, Default)]
+pub struct ShutdownSignal {
+    requested: AtomicBool,
+    requested_notify: Notify,
+    running_loops: AtomicUsize,
+    idle_notify: Notify,
+}
+
+/// Registration of a running loop; dropping it marks the loop as finished.
+#[derive(Debug)]
+pub struct LoopGuard<'a> {
+    signal: &'a ShutdownSignal,
+}
+
+impl Drop for LoopGuard<'_> {
+    fn drop(&mut self) {
+        if self.signal.running_loops.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.signal.idle_notify.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask all loops to exit after their current cycle.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.requested_notify.notify_waiters();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Register a running loop for `wait_idle`.
+    pub fn enter_loop(&self) -> LoopGuard<'_> {
+        self.running_loops.fetch_add(1, Ordering::SeqCst);
+        LoopGuard { signal: self }
+    }
+
+    pub fn running_loops(&self) -> usize {
+        self.running_loops.load(Ordering::SeqCst)
+    }
+
+    /// Sleep for `duration`, waking early when shutdown is requested.
+    /// Returns `true` when the loop should exit.
+    pub async fn sleep(&self, duration: Duration) -> bool {
+        let notified = self.requested_notify.notified();
+        tokio::pin!(notified);
+        // Register before checking the flag so a concurrent `request` is not missed
+        notified.as_mut().enable();
+        if self.is_requested() {
+            return true;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => self.is_requested(),
+            _ = notified => true,
+        }
+    }
+
+    /// Wait until no loop is registered. Returns `false` on timeout.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.idle_notify.notified();
+                tokio::pin!(idle);
+                idle.as_mut().enable();
+                if self.running_loops() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+}
+
+/// Signal for the auto capture loops.
+pub static CAPTURE_SHUTDOWN: Lazy<ShutdownSignal> = Lazy::new(ShutdownSignal::new);
+
+/// Request shutdown of the capture loops and block until they finish
+/// (or `SHUTDOWN_TIMEOUT` elapses). Called from the app exit handler.
+pub fn shutdown_background_loops() {
+    if CAPTURE_SHUTDOWN.running_loops() == 0 {
+        return;
+    }
+    tracing::info!(
+        "Waiting for {} background loop(s) to finish",
+        CAPTURE_SHUTDOWN.running_loops()
+    );
+    CAPTURE_SHUTDOWN.request();
+    let finished = tauri::async_runtime::block_on(CAPTURE_SHUTDOWN.wait_idle(SHUTDOWN_TIMEOUT));
+    if finished {
+        tracing::info!("Background loops stopped cleanly");
+    } else {
+        tracing::warn!(
+            "Background loops still running after {:?}, exiting anyway",
+            SHUTDOWN_TIMEOUT
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn loop_exits_after_shutdown_request() {
+        let signal = Arc::new(ShutdownSignal::new());
+        let cycles = Arc::new(AtomicUsize::new(0));
+
+        let (loop_signal, loop_cycles) = (signal.clone(), cycles.clone());
+        let handle = tokio::spawn(async move {
+            let _guard = loop_signal.enter_loop();
+            loop {
+                loop_cycles.fetch_add(1, Ordering::SeqCst);
+                if loop_signal.sleep(Duration::from_secs(3600)).await {
+                    break;
+                }
+            }
+        });
+
+        // Let the loop start its first cycle and go to sleep
+        while cycles.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(signal.running_loops(), 1);
+
+        signal.request();
+        assert!(signal.wait_idle(Duration::from_secs(5)).await);
+        handle.await.unwrap();
+        assert_eq!(cycles.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sleep_returns_immediately_once_requested() {
+        let signal = ShutdownSignal::new();
+        signal.request();
+        assert!(signal.sleep(Duration::from_secs(3600)).await);
+    }
+
+    #[tokio::test]
+    async fn sleep_completes_without_request() {
+        let signal = ShutdownSignal::new();
+        assert!(!signal.sleep(Duration::from_millis(1)).await);
+    }
+
+    #[tokio::test]
+    async fn wait_idle_times_out_while_loop_is_busy() {
+        let signal = ShutdownSignal::new();
+        let _guard = signal.enter_loop();
+        signal.request();
+        assert!(!signal.wait_idle(Duration::from_millis(10)).await);
+    }
+}
@@ -2,11 +2,15 @@ use chrono::Datelike;
 use rusqlite::params;
 use std::path::PathBuf;
 
-use crate::errors::{AppError, AppResult};
+use crate::errors::{AppError, AppResult, ErrorCode};
+use crate::i18n::{Lang, MessageKey};
 use crate::infrastructure::retry;
 use crate::memory_storage::{self, Record, Settings};
 use crate::services::session_service::{Session, SessionStatus};
 
+mod sections;
+pub use sections::*;
+
 // STAB-001: Retry configuration for AI API calls
 const MAX_RETRIES: u32 = 3;
 const INITIAL_RETRY_DELAY_MS: u64 = 1000; // 1 second
@@ -19,10 +23,12 @@ pub struct ApiConfig {
     api_key: String,
     model_name: String,
     is_ollama: bool,
-    // AI-006: Custom API headers
+    // AI-006: Custom API headers (including the extra_headers map)
     custom_headers: Vec<crate::memory_storage::CustomHeader>,
     // PERF-001: Proxy configuration
     proxy_config: crate::ProxyConfig,
+    // Log full prompt/response text (false: only lengths and digests)
+    log_llm_payload: bool,
 }
 
 impl ApiConfig {
@@ -49,15 +55,22 @@ impl ApiConfig {
     pub fn proxy_config(&self) -> &crate::ProxyConfig {
         &self.proxy_config
     }
+
+    pub fn log_llm_payload(&self) -> bool {
+        self.log_llm_payload
+    }
 }
 
 /// Extract API configuration from settings (shared by all report generators).
 /// Uses `summary_model_name` with fallback to `model_name`.
 pub fn load_api_config(settings: &Settings) -> AppResult<ApiConfig> {
-    let api_base_url = settings
-        .api_base_url
-        .clone()
-        .ok_or_else(|| AppError::validation("API Base URL not configured"))?;
+    let api_base_url = settings.api_base_url.clone().ok_or_else(|| {
+        AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::ApiBaseUrlNotConfigured,
+            Lang::from_settings(settings),
+        )
+    })?;
     let api_key = settings.api_key.clone().unwrap_or_default();
 
     let model_name = settings
@@ -75,10 +88,13 @@ pub fn load_api_config(settings: &Settings) -> AppResult<ApiConfig> {
 pub fn load_vision_api_config() -> AppResult<ApiConfig> {
     let settings = crate::memory_storage::get_settings_sync()?;
 
-    let api_base_url = settings
-        .api_base_url
-        .clone()
-        .ok_or_else(|| AppError::validation("API Base URL not configured"))?;
+    let api_base_url = settings.api_base_url.clone().ok_or_else(|| {
+        AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::ApiBaseUrlNotConfigured,
+            Lang::from_settings(&settings),
+        )
+    })?;
     let api_key = settings.api_key.clone().unwrap_or_default();
 
     let model_name = settings
@@ -99,22 +115,15 @@ fn build_api_config(
     let is_ollama = crate::ollama::is_ollama_endpoint(&api_base_url);
 
     if !is_ollama && api_key.is_empty() {
-        return Err(AppError::validation(
-            "API Key is required for non-Ollama endpoints",
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::ApiKeyRequiredNonOllama,
+            Lang::from_settings(settings),
         ));
     }
 
-    // AI-006: Parse custom headers from settings
-    let custom_headers = if let Some(ref headers_json) = settings.custom_headers {
-        if !headers_json.is_empty() {
-            serde_json::from_str::<Vec<crate::memory_storage::CustomHeader>>(headers_json)
-                .unwrap_or_default()
-        } else {
-            Vec::new()
-        }
-    } else {
-        Vec::new()
-    };
+    // AI-006: Custom headers plus the extra_headers map
+    let custom_headers = settings.request_headers();
 
     // PERF-001: Parse proxy configuration from settings
     let proxy_config = crate::ProxyConfig::from_settings(settings);
@@ -126,6 +135,82 @@ fn build_api_config(
         is_ollama,
         custom_headers,
         proxy_config,
+        log_llm_payload: settings.log_llm_payload.unwrap_or(true),
+    })
+}
+
+/// Represent LLM prompt/response text for logging.
+///
+/// When `log_payload` is false the original text is replaced by its length
+/// and a short digest, so screen text never reaches the log files.
+pub(crate) fn llm_payload_for_log(text: &str, log_payload: bool) -> serde_json::Value {
+    if log_payload {
+        return serde_json::Value::String(text.to_string());
+    }
+
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+
+    serde_json::json!({
+        "redacted": true,
+        "chars": text.chars().count(),
+        "lines": text.lines().count(),
+        "digest": format!("{:016x}", hasher.finish()),
+    })
+}
+
+/// Build the structured `llm_request` log event.
+fn build_llm_request_log(
+    config: &ApiConfig,
+    endpoint: &str,
+    caller: &str,
+    prompt: &str,
+    max_tokens: u32,
+) -> serde_json::Value {
+    // AI-006: Log custom headers (mask sensitive values)
+    let custom_headers_debug: Vec<_> = config
+        .custom_headers
+        .iter()
+        .map(|h| {
+            if h.sensitive {
+                format!("{}: {}", h.key, "***MASKED***")
+            } else {
+                format!("{}: {}", h.key, h.value)
+            }
+        })
+        .collect();
+
+    serde_json::json!({
+        "event": "llm_request",
+        "caller": caller,
+        "endpoint": endpoint,
+        "model": config.model_name,
+        "max_tokens": max_tokens,
+        "api_key_masked": crate::mask_api_key(&config.api_key),
+        "has_image": false,
+        "prompt": llm_payload_for_log(prompt, config.log_llm_payload),
+        "custom_headers": custom_headers_debug,
+    })
+}
+
+/// Build the structured `llm_response` log event.
+fn build_llm_response_log(
+    config: &ApiConfig,
+    caller: &str,
+    elapsed_ms: u128,
+    response_json: &serde_json::Value,
+    content: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "event": "llm_response",
+        "caller": caller,
+        "status": 200,
+        "elapsed_ms": elapsed_ms,
+        "usage": response_json.get("usage"),
+        "model": response_json.get("model"),
+        "response_id": response_json.get("id"),
+        "content": llm_payload_for_log(content, config.log_llm_payload),
     })
 }
 
@@ -148,33 +233,9 @@ async fn call_llm_api(
         "max_tokens": max_tokens
     });
 
-    let masked_key = crate::mask_api_key(&config.api_key);
-    // AI-006: Log custom headers (mask sensitive values)
-    let custom_headers_debug: Vec<_> = config
-        .custom_headers
-        .iter()
-        .map(|h| {
-            if h.sensitive {
-                format!("{}: {}", h.key, "***MASKED***")
-            } else {
-                format!("{}: {}", h.key, h.value)
-            }
-        })
-        .collect();
-
     tracing::info!(
         "{}",
-        serde_json::json!({
-            "event": "llm_request",
-            "caller": caller,
-            "endpoint": endpoint,
-            "model": config.model_name,
-            "max_tokens": max_tokens,
-            "api_key_masked": masked_key,
-            "has_image": false,
-            "prompt": prompt,
-            "custom_headers": custom_headers_debug,
-        })
+        build_llm_request_log(config, &endpoint, caller, prompt, max_tokens)
     );
 
     let start = std::time::Instant::now();
@@ -184,10 +245,7 @@ async fn call_llm_api(
         .json(&request_body);
 
     // AI-006: Check if custom headers contain Authorization or api-key header
-    let has_custom_auth = config
-        .custom_headers
-        .iter()
-        .any(|h| h.key.to_lowercase() == "authorization" || h.key.to_lowercase() == "api-key");
+    let has_custom_auth = crate::memory_storage::has_custom_auth_header(&config.custom_headers);
 
     // Set Authorization header only if api_key is provided and no custom auth header
     if !config.api_key.is_empty() && !has_custom_auth {
@@ -246,16 +304,7 @@ async fn call_llm_api(
 
     tracing::info!(
         "{}",
-        serde_json::json!({
-            "event": "llm_response",
-            "caller": caller,
-            "status": 200,
-            "elapsed_ms": elapsed_ms,
-            "usage": response_json.get("usage"),
-            "model": response_json.get("model"),
-            "response_id": response_json.get("id"),
-            "content": content,
-        })
+        build_llm_response_log(config, caller, elapsed_ms, &response_json, &content)
     );
 
     Ok(content)
@@ -331,7 +380,8 @@ pub(crate) const DEFAULT_SUMMARY_PROMPT: &str = r"
 1. 按时间顺序组织
 2. 提取关键工作内容和技术关键词
 3. 总结今日工作成果和遇到的问题
-4. 输出纯 Markdown 格式，不要有其他说明文字
+4. 标记为「⭐ 重要」的记录优先、重点总结
+5. 输出纯 Markdown 格式，不要有其他说明文字
 
 今日记录：
 {records}
@@ -489,9 +539,11 @@ pub fn generate_monthly_report_filename() -> String {
 }
 
 /// Format the summary title by replacing placeholders.
-/// Supports: {date} - replaced with YYYY-MM-DD format
+/// Supports: {date} - replaced with YYYY-MM-DD format (the logical day, see `day_start_hour`)
 pub fn format_summary_title(format: &str) -> String {
-    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let today = crate::memory_storage::logical_today()
+        .format("%Y-%m-%d")
+        .to_string();
     format.replace("{date}", &today)
 }
 
@@ -509,6 +561,13 @@ pub fn filter_records_by_settings(records: Vec<Record>, settings: &Settings) ->
     }
 }
 
+/// Today's records for the daily report: drafts are left out until confirmed,
+/// then the `include_manual_records` setting applies.
+pub fn get_daily_report_records(settings: &Settings) -> AppResult<Vec<Record>> {
+    let confirmed = memory_storage::get_all_today_records_for_summary()?;
+    Ok(filter_records_by_settings(confirmed, settings))
+}
+
 /// SESSION-005: Get the display summary for a session.
 /// Priority: user_summary > ai_summary > "暂无摘要"
 fn get_session_display_summary(session: &Session) -> String {
@@ -523,7 +582,7 @@ fn get_session_display_summary(session: &Session) -> String {
 }
 
 /// SESSION-005: Get records for a specific session, with user_notes preferred over content.
-/// Skips records with analysis_status = 'pending'.
+/// Skips records with analysis_status = 'pending' and unconfirmed drafts.
 fn get_session_records_for_summary(session_id: i64) -> AppResult<Vec<(String, String, String)>> {
     // Use get_records_by_session_id which returns SessionScreenshot (id, timestamp, screenshot_path)
     // We need full Record to get user_notes and content
@@ -533,9 +592,9 @@ fn get_session_records_for_summary(session_id: i64) -> AppResult<Vec<(String, St
         .ok_or_else(|| AppError::database("Database not initialized"))?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, timestamp, content, user_notes, analysis_status, source_type
+        "SELECT id, timestamp, content, user_notes, analysis_status, source_type, status
              FROM records
-             WHERE session_id = ?1
+             WHERE session_id = ?1 AND (status IS NULL OR status != 'draft')
              ORDER BY timestamp ASC",
     )?;
 
@@ -551,6 +610,8 @@ fn get_session_records_for_summary(session_id: i64) -> AppResult<Vec<(String, St
             user_notes: row.get(3)?,
             session_id: Some(session_id),
             analysis_status: row.get(4)?,
+            metadata: None,
+            status: row.get(6)?,
         })
     })?;
 
@@ -672,6 +733,7 @@ pub fn build_session_based_report(sessions: &[Session]) -> Option<String> {
 /// Format records into a string for the summary prompt.
 /// Each record is formatted as: "- [HH:MM] 🖥️/⚡ source: content"
 /// SESSION-003: Prefers user_notes over content when available.
+/// Records with a high importance score are marked "⭐ 重要" so the report puts them first.
 pub fn format_records_for_summary(records: &[Record]) -> String {
     records
         .iter()
@@ -690,7 +752,13 @@ pub fn format_records_for_summary(records: &[Record]) -> String {
                 .map(|n| n.as_str())
                 .unwrap_or(&r.content);
 
-            format!("- [{}] {}: {}", time, source, display_content)
+            if memory_storage::record_importance(&r.content)
+                >= memory_storage::HIGH_IMPORTANCE_THRESHOLD
+            {
+                format!("- [{}] {} ⭐ 重要: {}", time, source, display_content)
+            } else {
+                format!("- [{}] {}: {}", time, source, display_content)
+            }
         })
         .collect::<Vec<_>>()
         .join("\n")
@@ -851,29 +919,23 @@ pub async fn generate_base_daily_summary(
         if let Some(content) = build_session_based_report(&sessions) {
             let prompt_template =
                 non_empty_or(settings.summary_prompt.as_deref(), DEFAULT_SUMMARY_PROMPT);
-            let prompt = prompt_template
-                .replace("{records}", &content)
-                .replace("{github_activity}", "");
-
-            return call_llm_api_with_retry(api_config, &prompt, 2000, "generate_daily_summary")
-                .await;
+            return generate_structured_summary(api_config, prompt_template, &content).await;
         }
     }
 
     // Fallback to legacy flat record format
-    let all_records = memory_storage::get_all_today_records_for_summary()?;
-    let records = filter_records_by_settings(all_records, settings);
+    let records = get_daily_report_records(settings)?;
     if records.is_empty() {
-        return Err(AppError::validation("No records for today after filtering"));
+        return Err(AppError::localized(
+            ErrorCode::Validation,
+            MessageKey::NoRecordsToday,
+            Lang::from_settings(settings),
+        ));
     }
 
     let records_text = format_records_for_summary(&records);
     let prompt_template = non_empty_or(settings.summary_prompt.as_deref(), DEFAULT_SUMMARY_PROMPT);
-    let prompt = prompt_template
-        .replace("{records}", &records_text)
-        .replace("{github_activity}", "");
-
-    call_llm_api_with_retry(api_config, &prompt, 2000, "generate_daily_summary").await
+    generate_structured_summary(api_config, prompt_template, &records_text).await
 }
 
 #[cfg(test)]
@@ -893,6 +955,8 @@ mod tests {
             user_notes: None,
             session_id: None,
             analysis_status: None,
+            metadata: None,
+            status: None,
         }
     }
 
@@ -961,6 +1025,73 @@ mod tests {
             last_auto_backup_at: None,
             // FEAT-008: Custom export template
             custom_export_template: None,
+            // Sensitive content skip
+            sensitive_filter_enabled: None,
+            sensitive_keywords: None,
+            // LLM payload logging
+            log_llm_payload: None,
+            // Screenshot encoding format
+            screenshot_format: None,
+            // API key rotation
+            api_keys: None,
+            // Open summary after generate
+            open_summary_after_generate: None,
+            // Startup delay before first capture
+            startup_delay_seconds: None,
+            // Day start hour
+            day_start_hour: None,
+            // Failure alert aggregation
+            failure_alert_window_minutes: None,
+            failure_alert_webhook_url: None,
+            // Keyword based project classification
+            projects: None,
+            // Capture interval in seconds
+            screenshot_interval_seconds: None,
+            // 调试：保存失败的分析请求
+            debug_dump_failed_requests: None,
+            // 截图裁剪任务栏
+            crop_taskbar: None,
+            taskbar_height: None,
+            // 按软件类别的分析 prompt
+            category_prompts: None,
+            // 按端点选择分析图片格式
+            endpoint_image_formats: None,
+            // A/B 分析 prompt 实验
+            analysis_prompt_b: None,
+            prompt_b_percent: None,
+            // AI 请求附加请求头
+            extra_headers: None,
+            // 无显示器时暂停感知
+            pause_capture_without_display: None,
+            // 记录产生后执行的自定义命令
+            on_record_command: None,
+            // 软件名规范化
+            software_aliases: None,
+            // Stitched screenshot size cap
+            max_image_dimension: None,
+            // Screenshot timeout and retries
+            capture_timeout_secs: None,
+            capture_retries: None,
+            // Capture when the user returns from idle
+            capture_on_idle_wake: None,
+            // Sensitive content redaction
+            redact_sensitive_content: None,
+            // Placeholder semantics version of the custom prompts
+            prompt_schema_version: None,
+            // Scheduled daily summary
+            auto_summary_enabled: None,
+            auto_summary_catch_up: None,
+            last_auto_summary_date: None,
+            // Keyword alerts
+            keyword_alert_rules: None,
+            // Multi-monitor capture
+            capture_all_monitors: None,
+            // Excluded screen regions
+            exclude_regions: None,
+            // Excluded apps
+            excluded_apps: None,
+            // Draft retention
+            draft_retention_days: None,
         }
     }
 
@@ -1057,6 +1188,36 @@ mod tests {
         assert!(filtered.is_empty());
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn daily_report_uses_draft_only_after_confirm() {
+        crate::memory_storage::setup_test_db_with_schema();
+        let settings = create_settings_with_include_manual(true);
+
+        let draft_id =
+            memory_storage::add_record("auto", r#"{"current_focus":"写代码"}"#, None, None, None)
+                .unwrap();
+        let manual_id = memory_storage::add_record("manual", "开会", None, None, None).unwrap();
+
+        let ids: Vec<i64> = get_daily_report_records(&settings)
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![manual_id]);
+
+        memory_storage::confirm_record_sync(draft_id).unwrap();
+        let ids: Vec<i64> = get_daily_report_records(&settings)
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert!(ids.contains(&draft_id));
+        assert!(ids.contains(&manual_id));
+
+        assert!(memory_storage::confirm_record_sync(9999).is_err());
+    }
+
     // ── Tests for generate_summary_filename ──
 
     #[test]
@@ -1119,6 +1280,20 @@ mod tests {
         assert_eq!(lines.len(), 2);
     }
 
+    #[test]
+    fn format_records_marks_high_importance() {
+        let records = vec![
+            create_test_record("auto", r#"{"current_focus":"修复故障","importance":90}"#),
+            create_test_record("auto", r#"{"current_focus":"浏览新闻","importance":20}"#),
+            create_test_record("auto", r#"{"current_focus":"老记录"}"#),
+        ];
+        let formatted = format_records_for_summary(&records);
+        let lines: Vec<&str> = formatted.lines().collect();
+        assert!(lines[0].contains("⭐ 重要"));
+        assert!(!lines[1].contains("⭐ 重要"));
+        assert!(!lines[2].contains("⭐ 重要"));
+    }
+
     #[test]
     fn format_records_empty_returns_empty_string() {
         let records: Vec<Record> = vec![];
@@ -1420,6 +1595,26 @@ mod tests {
         assert!(result.unwrap_err().message.contains("API Key is required"));
     }
 
+    #[test]
+    fn load_api_config_error_follows_ui_language() {
+        let mut settings = create_settings_with_include_manual(true);
+        settings.api_base_url = Some("https://api.openai.com/v1".to_string());
+        settings.api_key = None;
+
+        settings.language = Some("en".to_string());
+        let en = load_api_config(&settings).unwrap_err();
+        settings.language = Some("zh-CN".to_string());
+        let zh = load_api_config(&settings).unwrap_err();
+
+        assert_eq!(en.message_key, zh.message_key);
+        assert_eq!(
+            en.message_key.as_deref(),
+            Some("error.api_key_required_non_ollama")
+        );
+        assert_ne!(en.message, zh.message);
+        assert!(zh.message.contains("API 密钥"));
+    }
+
     #[test]
     fn load_api_config_uses_summary_model_name_over_model_name() {
         let mut settings = create_settings_with_include_manual(true);
@@ -1516,6 +1711,74 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    // ── Tests for LLM payload log redaction ──
+
+    fn api_config_with_payload_logging(log_llm_payload: Option<bool>) -> ApiConfig {
+        let settings = Settings {
+            api_base_url: Some("https://api.openai.com/v1".to_string()),
+            api_key: Some("sk-test-key".to_string()),
+            log_llm_payload,
+            ..Default::default()
+        };
+        load_api_config(&settings).unwrap()
+    }
+
+    #[test]
+    fn llm_request_log_masks_gateway_tokens_in_extra_headers() {
+        let settings = Settings {
+            api_base_url: Some("https://gateway.example.com/v1".to_string()),
+            api_key: Some("sk-test-key".to_string()),
+            extra_headers: Some(
+                r#"{"Authorization":"Bearer gw-secret","X-Route":"vision"}"#.to_string(),
+            ),
+            ..Default::default()
+        };
+        let config = load_api_config(&settings).unwrap();
+
+        let request = build_llm_request_log(&config, "endpoint", "test", "prompt", 100);
+        let request_text = request.to_string();
+        assert!(!request_text.contains("gw-secret"));
+        assert!(request_text.contains("X-Route: vision"));
+    }
+
+    #[test]
+    fn llm_logs_include_payload_by_default() {
+        let config = api_config_with_payload_logging(None);
+        assert!(config.log_llm_payload());
+
+        let request = build_llm_request_log(&config, "endpoint", "test", "屏幕上的隐私文字", 100);
+        assert_eq!(request["prompt"].as_str(), Some("屏幕上的隐私文字"));
+
+        let response =
+            build_llm_response_log(&config, "test", 10, &serde_json::json!({}), "回复原文");
+        assert_eq!(response["content"].as_str(), Some("回复原文"));
+    }
+
+    #[test]
+    fn llm_logs_omit_payload_when_disabled() {
+        let config = api_config_with_payload_logging(Some(false));
+        let prompt = "屏幕上的隐私文字: account 6222-0000";
+        let content = "用户正在查看 account 6222-0000";
+
+        let request = build_llm_request_log(&config, "endpoint", "test", prompt, 100);
+        let request_text = request.to_string();
+        assert!(!request_text.contains("6222-0000"));
+        assert!(!request_text.contains("隐私文字"));
+        assert_eq!(request["prompt"]["redacted"].as_bool(), Some(true));
+        assert_eq!(
+            request["prompt"]["chars"].as_u64(),
+            Some(prompt.chars().count() as u64)
+        );
+
+        let response = build_llm_response_log(&config, "test", 10, &serde_json::json!({}), content);
+        let response_text = response.to_string();
+        assert!(!response_text.contains("6222-0000"));
+        assert_eq!(
+            response["content"]["chars"].as_u64(),
+            Some(content.chars().count() as u64)
+        );
+    }
+
     // NOTE: Performance benchmark tests moved to dedicated `mod benchmarks` below (CORE-008)
 }
 
@@ -1797,6 +2060,73 @@ mod benchmarks {
             last_auto_backup_at: None,
             // FEAT-008: Custom export template
             custom_export_template: None,
+            // Sensitive content skip
+            sensitive_filter_enabled: None,
+            sensitive_keywords: None,
+            // LLM payload logging
+            log_llm_payload: None,
+            // Screenshot encoding format
+            screenshot_format: None,
+            // API key rotation
+            api_keys: None,
+            // Open summary after generate
+            open_summary_after_generate: None,
+            // Startup delay before first capture
+            startup_delay_seconds: None,
+            // Day start hour
+            day_start_hour: None,
+            // Failure alert aggregation
+            failure_alert_window_minutes: None,
+            failure_alert_webhook_url: None,
+            // Keyword based project classification
+            projects: None,
+            // Capture interval in seconds
+            screenshot_interval_seconds: None,
+            // 调试：保存失败的分析请求
+            debug_dump_failed_requests: None,
+            // 截图裁剪任务栏
+            crop_taskbar: None,
+            taskbar_height: None,
+            // 按软件类别的分析 prompt
+            category_prompts: None,
+            // 按端点选择分析图片格式
+            endpoint_image_formats: None,
+            // A/B 分析 prompt 实验
+            analysis_prompt_b: None,
+            prompt_b_percent: None,
+            // AI 请求附加请求头
+            extra_headers: None,
+            // 无显示器时暂停感知
+            pause_capture_without_display: None,
+            // 记录产生后执行的自定义命令
+            on_record_command: None,
+            // 软件名规范化
+            software_aliases: None,
+            // Stitched screenshot size cap
+            max_image_dimension: None,
+            // Screenshot timeout and retries
+            capture_timeout_secs: None,
+            capture_retries: None,
+            // Capture when the user returns from idle
+            capture_on_idle_wake: None,
+            // Sensitive content redaction
+            redact_sensitive_content: None,
+            // Placeholder semantics version of the custom prompts
+            prompt_schema_version: None,
+            // Scheduled daily summary
+            auto_summary_enabled: None,
+            auto_summary_catch_up: None,
+            last_auto_summary_date: None,
+            // Keyword alerts
+            keyword_alert_rules: None,
+            // Multi-monitor capture
+            capture_all_monitors: None,
+            // Excluded screen regions
+            exclude_regions: None,
+            // Excluded apps
+            excluded_apps: None,
+            // Draft retention
+            draft_retention_days: None,
         }
     }
 
@@ -1817,6 +2147,8 @@ mod benchmarks {
                 user_notes: None,
                 session_id: None,
                 analysis_status: None,
+                metadata: None,
+                status: None,
             });
         }
 
@@ -1854,6 +2186,8 @@ mod benchmarks {
                 user_notes: None,
                 session_id: None,
                 analysis_status: None,
+                metadata: None,
+                status: None,
             });
         }
 
@@ -1931,6 +2265,8 @@ mod benchmarks {
                 user_notes: None,
                 session_id: None,
                 analysis_status: None,
+                metadata: None,
+                status: None,
             });
         }
 
@@ -0,0 +1,206 @@
+//! Fixed daily report structure declared in the summary prompt.
+//!
+//! A template may contain `{{section:完成事项}}` placeholders. Each one is
+//! rendered as a `## 完成事项` heading and the model is instructed to fill
+//! exactly these sections. The returned summary is checked for every section
+//! heading; missing ones are requested again and, as a last resort, appended
+//! empty so the report keeps its structure.
+
+use crate::errors::AppResult;
+
+use super::ApiConfig;
+
+const SECTION_OPEN: &str = "{{section:";
+const SECTION_CLOSE: &str = "}}";
+
+/// Placeholder text used for sections the model left out.
+pub const EMPTY_SECTION_TEXT: &str = "（无）";
+
+/// Section names declared in a template, in order and without duplicates.
+pub fn parse_template_sections(template: &str) -> Vec<String> {
+    let mut sections: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(SECTION_OPEN) {
+        let after = &rest[start + SECTION_OPEN.len()..];
+        let Some(end) = after.find(SECTION_CLOSE) else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !name.is_empty() && !sections.iter().any(|s| s == name) {
+            sections.push(name.to_string());
+        }
+        rest = &after[end + SECTION_CLOSE.len()..];
+    }
+    sections
+}
+
+/// Replace section placeholders with `##` headings and, when the template
+/// declares sections, append the instruction to follow that structure.
+pub fn render_section_template(template: &str) -> String {
+    let sections = parse_template_sections(template);
+    if sections.is_empty() {
+        return template.to_string();
+    }
+
+    let mut rendered = template.to_string();
+    for name in &sections {
+        // Placeholders may contain spaces around the name
+        while let Some(start) = find_placeholder(&rendered, name) {
+            let end = start
+                + rendered[start..]
+                    .find(SECTION_CLOSE)
+                    .map_or(0, |i| i + SECTION_CLOSE.len());
+            rendered.replace_range(start..end, &format!("## {}", name));
+        }
+    }
+    format!("{}\n\n{}", rendered, section_instruction(&sections))
+}
+
+fn find_placeholder(text: &str, name: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(pos) = text[offset..].find(SECTION_OPEN) {
+        let start = offset + pos;
+        let after = &text[start + SECTION_OPEN.len()..];
+        let end = after.find(SECTION_CLOSE)?;
+        if after[..end].trim() == name {
+            return Some(start);
+        }
+        offset = start + SECTION_OPEN.len();
+    }
+    None
+}
+
+fn section_instruction(sections: &[String]) -> String {
+    let headings: Vec<String> = sections.iter().map(|s| format!("## {}", s)).collect();
+    format!(
+        "请严格按以下段落结构输出日报，每个段落以对应的二级标题开头，不要省略或改名，没有内容的段落写\"{}\"：\n{}",
+        EMPTY_SECTION_TEXT,
+        headings.join("\n")
+    )
+}
+
+/// Sections whose heading is not present in the summary.
+/// Any Markdown heading level is accepted, e.g. `## 完成事项` or `### 完成事项：`.
+pub fn missing_sections(summary: &str, sections: &[String]) -> Vec<String> {
+    let headings: Vec<&str> = summary
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('#'))
+        .map(|line| {
+            line.trim_start_matches('#')
+                .trim()
+                .trim_end_matches([':', '：'])
+                .trim()
+        })
+        .collect();
+
+    sections
+        .iter()
+        .filter(|name| !headings.contains(&name.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Append empty sections for the ones still missing from the summary.
+pub fn append_missing_sections(summary: &str, missing: &[String]) -> String {
+    let mut result = summary.trim_end().to_string();
+    for name in missing {
+        result.push_str(&format!("\n\n## {}\n\n{}", name, EMPTY_SECTION_TEXT));
+    }
+    result
+}
+
+/// Generate a daily summary from a prompt template, enforcing any declared sections.
+pub async fn generate_structured_summary(
+    api_config: &ApiConfig,
+    template: &str,
+    records_text: &str,
+) -> AppResult<String> {
+    let sections = parse_template_sections(template);
+    let prompt = render_section_template(template)
+        .replace("{records}", records_text)
+        .replace("{github_activity}", "");
+
+    let summary =
+        super::call_llm_api_with_retry(api_config, &prompt, 2000, "generate_daily_summary").await?;
+
+    let missing = missing_sections(&summary, &sections);
+    if missing.is_empty() {
+        return Ok(summary);
+    }
+
+    tracing::warn!(
+        "Daily summary is missing sections {:?}, asking the model again",
+        missing
+    );
+    let retry_prompt = format!(
+        "{}\n\n注意：上一次输出缺少以下段落，请务必包含：{}",
+        prompt,
+        missing.join("、")
+    );
+    let summary =
+        super::call_llm_api_with_retry(api_config, &retry_prompt, 2000, "generate_daily_summary")
+            .await?;
+
+    let missing = missing_sections(&summary, &sections);
+    if !missing.is_empty() {
+        tracing::warn!("Appending empty sections {:?} to daily summary", missing);
+    }
+    Ok(append_missing_sections(&summary, &missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "今日记录：\n{records}\n\n{{section:完成事项}}\n{{section: 进行中 }}\n{{section:阻塞}}\n{{section:明日计划}}\n{{section:完成事项}}";
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_sections_in_order_without_duplicates() {
+        assert_eq!(
+            parse_template_sections(TEMPLATE),
+            names(&["完成事项", "进行中", "阻塞", "明日计划"])
+        );
+        assert!(parse_template_sections("{records}").is_empty());
+        assert!(parse_template_sections("{{section:未闭合").is_empty());
+        assert!(parse_template_sections("{{section: }}").is_empty());
+    }
+
+    #[test]
+    fn renders_placeholders_as_headings_with_instruction() {
+        let rendered = render_section_template(TEMPLATE);
+        assert!(!rendered.contains("{{section"));
+        assert!(rendered.contains("## 完成事项\n## 进行中\n## 阻塞\n## 明日计划\n## 完成事项"));
+        assert!(rendered.contains("请严格按以下段落结构输出日报"));
+        assert!(rendered.contains("{records}"));
+
+        // Templates without sections are left untouched
+        assert_eq!(
+            render_section_template("plain {records}"),
+            "plain {records}"
+        );
+    }
+
+    #[test]
+    fn detects_missing_sections_by_heading() {
+        let sections = names(&["完成事项", "进行中", "阻塞", "明日计划"]);
+        let summary = "# 日报\n\n## 完成事项\n- 修复 bug\n\n### 进行中：\n- 重构\n\n阻塞\n";
+
+        assert_eq!(
+            missing_sections(summary, &sections),
+            names(&["阻塞", "明日计划"])
+        );
+        assert!(missing_sections(summary, &[]).is_empty());
+    }
+
+    #[test]
+    fn appends_empty_missing_sections() {
+        let summary = append_missing_sections("## 完成事项\n- A\n", &names(&["阻塞"]));
+        assert_eq!(summary, "## 完成事项\n- A\n\n## 阻塞\n\n（无）");
+        assert!(missing_sections(&summary, &names(&["完成事项", "阻塞"])).is_empty());
+    }
+}
@@ -24,6 +24,9 @@ pub struct TimelineEvent {
     pub event_type: String,
     /// Short preview of content (truncated)
     pub preview: String,
+    /// Recorded after midnight but shown on the day the activity started
+    #[serde(default)]
+    pub next_day: bool,
 }
 
 /// A group of events within the same hour.
@@ -37,6 +40,9 @@ pub struct TimelineHourGroup {
     pub events: Vec<TimelineEvent>,
     /// Count of events
     pub count: usize,
+    /// Hour of the following day (cross-midnight activity shown on its start day)
+    #[serde(default)]
+    pub next_day: bool,
 }
 
 /// Complete timeline data for a day.
@@ -52,8 +58,42 @@ pub struct TimelineData {
     pub active_hours: usize,
     /// Work time estimate (hours)
     pub work_time_estimate: f64,
+    /// The first records continue an activity that started before midnight
+    #[serde(default)]
+    pub continues_from_previous_day: bool,
+    /// The last records continue into the following day
+    #[serde(default)]
+    pub continues_into_next_day: bool,
 }
 
+/// How an activity running across midnight is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossMidnightMode {
+    /// Each record stays on its calendar day; both days are flagged as continued
+    #[default]
+    Split,
+    /// The whole activity is shown on the day it started
+    StartDay,
+}
+
+/// A stretch of time with no records between two consecutive records.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageGap {
+    /// Timestamp of the record before the gap (RFC3339, local time)
+    pub start: String,
+    /// Timestamp of the record after the gap (RFC3339, local time)
+    pub end: String,
+    /// Formatted range (e.g., "12:05 - 13:40")
+    pub label: String,
+    /// Gap length in minutes
+    pub duration_minutes: i64,
+}
+
+/// A gap is reported when consecutive records are further apart than
+/// this multiple of the expected capture interval.
+const COVERAGE_GAP_FACTOR: f64 = 1.5;
+
 /// Generate preview text from content.
 fn generate_preview(content: &str, max_len: usize) -> String {
     fn truncate_preview(text: &str, max_len: usize) -> String {
@@ -90,7 +130,7 @@ fn parse_timestamp(timestamp: &str) -> AppResult<DateTime<Local>> {
 }
 
 /// Convert records to timeline events.
-fn records_to_events(records: Vec<Record>) -> Vec<TimelineEvent> {
+fn records_to_events(records: Vec<Record>, next_day: bool) -> Vec<TimelineEvent> {
     records
         .into_iter()
         .filter_map(|record| {
@@ -105,6 +145,7 @@ fn records_to_events(records: Vec<Record>) -> Vec<TimelineEvent> {
                 time_str,
                 event_type: "auto".to_string(), // Will be set based on source_type
                 preview,
+                next_day,
             })
         })
         .collect()
@@ -112,26 +153,34 @@ fn records_to_events(records: Vec<Record>) -> Vec<TimelineEvent> {
 
 /// Group events by hour.
 fn group_by_hour(events: Vec<TimelineEvent>) -> Vec<TimelineHourGroup> {
-    let mut hour_map: std::collections::BTreeMap<u32, Vec<TimelineEvent>> =
+    // Hours of the following day sort after the hours of the day itself
+    let mut hour_map: std::collections::BTreeMap<(bool, u32), Vec<TimelineEvent>> =
         std::collections::BTreeMap::new();
 
     for event in events {
         let event_type = event.record.source_type.clone();
         let mut event = event;
         event.event_type = event_type;
-        hour_map.entry(event.hour).or_default().push(event);
+        hour_map
+            .entry((event.next_day, event.hour))
+            .or_default()
+            .push(event);
     }
 
     hour_map
         .into_iter()
-        .map(|(hour, events)| {
-            let label = format!("{:02}:00 - {:02}:00", hour, (hour + 1) % 24);
+        .map(|((next_day, hour), events)| {
+            let mut label = format!("{:02}:00 - {:02}:00", hour, (hour + 1) % 24);
+            if next_day {
+                label.push_str(" (次日)");
+            }
             let count = events.len();
             TimelineHourGroup {
                 hour,
                 label,
                 events,
                 count,
+                next_day,
             }
         })
         .collect()
@@ -153,14 +202,108 @@ fn calculate_work_time_estimate(hour_groups: &[TimelineHourGroup]) -> f64 {
     active_hours as f64 * 0.75
 }
 
-/// Get timeline data for a specific date.
-pub fn get_timeline_data_for_date(date: &str) -> AppResult<TimelineData> {
-    let db = DB_CONNECTION.lock().map_err(AppError::from)?;
-    let conn = db
-        .as_ref()
-        .ok_or_else(|| AppError::database("Database not initialized"))?;
+/// Find gaps between consecutive records that exceed 1.5x the expected interval.
+///
+/// Timestamps need not be sorted. Returns an empty list when fewer than two
+/// timestamps are given or the expected interval is zero.
+pub fn find_coverage_gaps(
+    timestamps: &[DateTime<Local>],
+    expected_interval_minutes: u32,
+) -> Vec<CoverageGap> {
+    if expected_interval_minutes == 0 {
+        return Vec::new();
+    }
+
+    let mut sorted = timestamps.to_vec();
+    sorted.sort();
+
+    let threshold_seconds = expected_interval_minutes as f64 * 60.0 * COVERAGE_GAP_FACTOR;
+
+    sorted
+        .windows(2)
+        .filter_map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            let duration = end - start;
+            if (duration.num_seconds() as f64) <= threshold_seconds {
+                return None;
+            }
+            Some(CoverageGap {
+                start: start.to_rfc3339(),
+                end: end.to_rfc3339(),
+                label: format!("{} - {}", start.format("%H:%M"), end.format("%H:%M")),
+                duration_minutes: duration.num_minutes(),
+            })
+        })
+        .collect()
+}
+
+/// Number of leading `day` timestamps (sorted) that continue the activity
+/// running at `previous`, the last record before midnight.
+///
+/// Records belong to the same activity while consecutive records are at most
+/// `max_gap_minutes` apart, the same rule that splits sessions.
+pub fn continued_across_midnight(
+    previous: Option<DateTime<Local>>,
+    day: &[DateTime<Local>],
+    max_gap_minutes: i64,
+) -> usize {
+    let Some(mut last) = previous else {
+        return 0;
+    };
+    let mut count = 0;
+    for &timestamp in day {
+        if (timestamp - last).num_minutes() > max_gap_minutes {
+            break;
+        }
+        last = timestamp;
+        count += 1;
+    }
+    count
+}
+
+/// Records shown on a day, given the records of the previous, same and next
+/// calendar day (each sorted by time).
+///
+/// Returns the day's records, the records moved in from the next day, and the
+/// continuation flags. With [`CrossMidnightMode::StartDay`] an activity that
+/// started before midnight is left to the previous day, and one that runs past
+/// midnight is completed with the next day's records. Only a single midnight
+/// is bridged.
+pub fn assign_cross_midnight<T>(
+    previous: Option<DateTime<Local>>,
+    day: Vec<(DateTime<Local>, T)>,
+    next: Vec<(DateTime<Local>, T)>,
+    max_gap_minutes: i64,
+    mode: CrossMidnightMode,
+) -> (Vec<T>, Vec<T>, bool, bool) {
+    let day_times: Vec<DateTime<Local>> = day.iter().map(|(t, _)| *t).collect();
+    let next_times: Vec<DateTime<Local>> = next.iter().map(|(t, _)| *t).collect();
+    let leading = continued_across_midnight(previous, &day_times, max_gap_minutes);
+    let trailing =
+        continued_across_midnight(day_times.last().copied(), &next_times, max_gap_minutes);
+
+    match mode {
+        CrossMidnightMode::Split => (
+            day.into_iter().map(|(_, r)| r).collect(),
+            Vec::new(),
+            leading > 0,
+            trailing > 0,
+        ),
+        CrossMidnightMode::StartDay => {
+            // A day made only of a continued activity has no activity of its own to complete
+            let moved_in = if leading < day.len() { trailing } else { 0 };
+            (
+                day.into_iter().skip(leading).map(|(_, r)| r).collect(),
+                next.into_iter().take(moved_in).map(|(_, r)| r).collect(),
+                false,
+                false,
+            )
+        }
+    }
+}
 
-    // Parse date and create time range
+/// Convert a local date (YYYY-MM-DD) into its UTC RFC3339 start/end bounds.
+fn date_bounds_utc(date: &str) -> AppResult<(String, String)> {
     let target_date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
         .map_err(|e| AppError::validation(format!("Invalid date format: {}", e)))?;
 
@@ -187,10 +330,44 @@ pub fn get_timeline_data_for_date(date: &str) -> AppResult<TimelineData> {
         naive_to_utc_rfc3339(dt)?
     };
 
+    Ok((start_time, end_time))
+}
+
+/// Get timeline data for a specific date.
+pub fn get_timeline_data_for_date(date: &str) -> AppResult<TimelineData> {
+    get_timeline_data_for_date_with_mode(date, CrossMidnightMode::default())
+}
+
+/// Get timeline data for a specific date, handling activities that run across
+/// midnight according to `mode`.
+pub fn get_timeline_data_for_date_with_mode(
+    date: &str,
+    mode: CrossMidnightMode,
+) -> AppResult<TimelineData> {
+    let target_date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| AppError::validation(format!("Invalid date format: {}", e)))?;
+    let previous_date = target_date
+        .pred_opt()
+        .ok_or_else(|| AppError::validation("Date overflow"))?;
+    let next_date = target_date
+        .succ_opt()
+        .ok_or_else(|| AppError::validation("Date overflow"))?;
+    // The neighbouring days are queried too, to see activities crossing midnight
+    let (start_time, _) = date_bounds_utc(&previous_date.format("%Y-%m-%d").to_string())?;
+    let (_, end_time) = date_bounds_utc(&next_date.format("%Y-%m-%d").to_string())?;
+
+    // Settings are read before taking the DB lock (a cache miss locks it too)
+    let max_gap_minutes = crate::services::session_service::get_session_gap_minutes();
+
+    let db = DB_CONNECTION.lock().map_err(AppError::from)?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
     // Query records for the date
     let mut stmt = conn
         .prepare(
-            "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status
+            "SELECT id, timestamp, source_type, content, screenshot_path, monitor_info, tags, user_notes, session_id, analysis_status, metadata, status
              FROM records
              WHERE timestamp >= ?1 AND timestamp <= ?2
              ORDER BY timestamp ASC",
@@ -210,14 +387,36 @@ pub fn get_timeline_data_for_date(date: &str) -> AppResult<TimelineData> {
                 user_notes: row.get(7)?,
                 session_id: row.get(8)?,
                 analysis_status: row.get(9)?,
+                metadata: row.get(10)?,
+                status: row.get(11)?,
             })
         })
         .map_err(|e| AppError::database(format!("Failed to query records: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| AppError::database(format!("Failed to collect records: {}", e)))?;
+    drop(stmt);
+    drop(db);
+
+    let mut previous = None;
+    let mut day = Vec::new();
+    let mut next = Vec::new();
+    for record in records {
+        let Ok(timestamp) = parse_timestamp(&record.timestamp) else {
+            continue;
+        };
+        match timestamp.date_naive() {
+            d if d == previous_date => previous = Some(timestamp),
+            d if d == target_date => day.push((timestamp, record)),
+            d if d == next_date => next.push((timestamp, record)),
+            _ => {}
+        }
+    }
+    let (day_records, next_day_records, continues_from_previous_day, continues_into_next_day) =
+        assign_cross_midnight(previous, day, next, max_gap_minutes, mode);
 
     // Convert to timeline
-    let events = records_to_events(records);
+    let mut events = records_to_events(day_records, false);
+    events.extend(records_to_events(next_day_records, true));
     let hour_groups = group_by_hour(events);
     let total_events: usize = hour_groups.iter().map(|g| g.count).sum();
     let active_hours = hour_groups.len();
@@ -229,9 +428,47 @@ pub fn get_timeline_data_for_date(date: &str) -> AppResult<TimelineData> {
         total_events,
         active_hours,
         work_time_estimate,
+        continues_from_previous_day,
+        continues_into_next_day,
     })
 }
 
+/// Get gaps in record coverage for a specific date.
+pub fn get_coverage_gaps_for_date(
+    date: &str,
+    expected_interval_minutes: u32,
+) -> AppResult<Vec<CoverageGap>> {
+    if expected_interval_minutes == 0 {
+        return Err(AppError::validation(
+            "Expected interval must be greater than 0",
+        ));
+    }
+
+    let (start_time, end_time) = date_bounds_utc(date)?;
+
+    let db = DB_CONNECTION.lock().map_err(AppError::from)?;
+    let conn = db
+        .as_ref()
+        .ok_or_else(|| AppError::database("Database not initialized"))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp FROM records
+             WHERE timestamp >= ?1 AND timestamp <= ?2
+             ORDER BY timestamp ASC",
+        )
+        .map_err(|e| AppError::database(format!("Failed to prepare query: {}", e)))?;
+
+    let timestamps: Vec<DateTime<Local>> = stmt
+        .query_map(params![start_time, end_time], |row| row.get::<_, String>(0))
+        .map_err(|e| AppError::database(format!("Failed to query records: {}", e)))?
+        .filter_map(|ts| ts.ok())
+        .filter_map(|ts| parse_timestamp(&ts).ok())
+        .collect();
+
+    Ok(find_coverage_gaps(&timestamps, expected_interval_minutes))
+}
+
 /// Get timeline data for today.
 pub fn get_today_timeline_data() -> AppResult<TimelineData> {
     let today = Local::now().format("%Y-%m-%d").to_string();
@@ -242,6 +479,7 @@ pub fn get_today_timeline_data() -> AppResult<TimelineData> {
 pub fn get_timeline_data_for_range(
     start_date: &str,
     end_date: &str,
+    mode: CrossMidnightMode,
 ) -> AppResult<Vec<TimelineData>> {
     let start = chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
         .map_err(|e| AppError::validation(format!("Invalid start date format: {}", e)))?;
@@ -259,7 +497,7 @@ pub fn get_timeline_data_for_range(
 
     while current <= end {
         let date_str = current.format("%Y-%m-%d").to_string();
-        match get_timeline_data_for_date(&date_str) {
+        match get_timeline_data_for_date_with_mode(&date_str, mode) {
             Ok(data) => result.push(data),
             Err(e) => tracing::warn!("Failed to get timeline for {}: {}", date_str, e),
         }
@@ -282,18 +520,35 @@ pub fn get_timeline_today() -> Result<TimelineData, String> {
 }
 
 /// Tauri command to get timeline data for a specific date.
+/// `cross_midnight` defaults to `split`.
 #[command]
-pub fn get_timeline_for_date(date: String) -> Result<TimelineData, String> {
-    get_timeline_data_for_date(&date).map_err(|e| e.to_string())
+pub fn get_timeline_for_date(
+    date: String,
+    cross_midnight: Option<CrossMidnightMode>,
+) -> Result<TimelineData, String> {
+    get_timeline_data_for_date_with_mode(&date, cross_midnight.unwrap_or_default())
+        .map_err(|e| e.to_string())
 }
 
 /// Tauri command to get timeline data for a date range.
+/// `cross_midnight` defaults to `split`.
 #[command]
 pub fn get_timeline_for_range(
     start_date: String,
     end_date: String,
+    cross_midnight: Option<CrossMidnightMode>,
 ) -> Result<Vec<TimelineData>, String> {
-    get_timeline_data_for_range(&start_date, &end_date).map_err(|e| e.to_string())
+    get_timeline_data_for_range(&start_date, &end_date, cross_midnight.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Tauri command to find periods without any records on a specific date.
+#[command]
+pub fn get_coverage_gaps(
+    date: String,
+    expected_interval_minutes: u32,
+) -> Result<Vec<CoverageGap>, String> {
+    get_coverage_gaps_for_date(&date, expected_interval_minutes).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -366,6 +621,7 @@ mod tests {
             label: "10:00 - 11:00".to_string(),
             events: vec![],
             count: 1, // Less than threshold
+            next_day: false,
         }];
         let estimate = calculate_work_time_estimate(&groups);
         assert_eq!(estimate, 0.0);
@@ -379,18 +635,21 @@ mod tests {
                 label: "09:00 - 10:00".to_string(),
                 events: vec![],
                 count: 3, // Active
+                next_day: false,
             },
             TimelineHourGroup {
                 hour: 10,
                 label: "10:00 - 11:00".to_string(),
                 events: vec![],
                 count: 2, // Active
+                next_day: false,
             },
             TimelineHourGroup {
                 hour: 14,
                 label: "14:00 - 15:00".to_string(),
                 events: vec![],
                 count: 5, // Active
+                next_day: false,
             },
         ];
         let estimate = calculate_work_time_estimate(&groups);
@@ -408,6 +667,166 @@ mod tests {
         assert_eq!(dt.minute(), 30);
     }
 
+    fn local_time(hour: u32, minute: u32) -> DateTime<Local> {
+        chrono::NaiveDate::from_ymd_opt(2024, 3, 15)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find_coverage_gaps_reports_gaps_over_threshold() {
+        let timestamps = vec![
+            local_time(9, 0),
+            local_time(9, 5),
+            local_time(9, 12), // 7 min: within 1.5 x 5
+            local_time(10, 0), // 48 min: gap
+            local_time(10, 5),
+        ];
+        let gaps = find_coverage_gaps(&timestamps, 5);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].label, "09:12 - 10:00");
+        assert_eq!(gaps[0].duration_minutes, 48);
+    }
+
+    #[test]
+    fn test_find_coverage_gaps_threshold_is_exclusive() {
+        // Exactly 1.5 x 10 minutes is not a gap
+        let timestamps = vec![local_time(9, 0), local_time(9, 15), local_time(9, 31)];
+        let gaps = find_coverage_gaps(&timestamps, 10);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].label, "09:15 - 09:31");
+    }
+
+    #[test]
+    fn test_find_coverage_gaps_sorts_unordered_input() {
+        let timestamps = vec![local_time(14, 0), local_time(9, 0), local_time(9, 5)];
+        let gaps = find_coverage_gaps(&timestamps, 5);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].label, "09:05 - 14:00");
+        assert_eq!(gaps[0].duration_minutes, 295);
+    }
+
+    #[test]
+    fn test_find_coverage_gaps_handles_empty_and_zero_interval() {
+        assert!(find_coverage_gaps(&[], 5).is_empty());
+        assert!(find_coverage_gaps(&[local_time(9, 0)], 5).is_empty());
+        assert!(find_coverage_gaps(&[local_time(9, 0), local_time(12, 0)], 0).is_empty());
+    }
+
+    fn local_datetime(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        chrono::NaiveDate::from_ymd_opt(2024, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_continued_across_midnight_follows_small_gaps() {
+        let previous = Some(local_datetime(15, 23, 50));
+        let day = vec![
+            local_datetime(16, 0, 5),
+            local_datetime(16, 0, 20),
+            local_datetime(16, 9, 0), // New activity in the morning
+        ];
+        assert_eq!(continued_across_midnight(previous, &day, 30), 2);
+        assert_eq!(continued_across_midnight(previous, &day, 10), 0);
+        assert_eq!(continued_across_midnight(None, &day, 30), 0);
+        assert_eq!(continued_across_midnight(previous, &[], 30), 0);
+    }
+
+    #[test]
+    fn test_assign_cross_midnight_split_flags_both_days() {
+        let previous = Some(local_datetime(15, 23, 50));
+        let day = vec![
+            (local_datetime(16, 0, 10), "a"),
+            (local_datetime(16, 9, 0), "b"),
+            (local_datetime(16, 23, 45), "c"),
+        ];
+        let next = vec![(local_datetime(17, 0, 5), "d")];
+
+        let (records, moved, from_previous, into_next) =
+            assign_cross_midnight(previous, day, next, 30, CrossMidnightMode::Split);
+
+        assert_eq!(records, vec!["a", "b", "c"]);
+        assert!(moved.is_empty());
+        assert!(from_previous);
+        assert!(into_next);
+    }
+
+    #[test]
+    fn test_assign_cross_midnight_start_day_moves_activity_to_start() {
+        let previous = Some(local_datetime(15, 23, 50));
+        let day = vec![
+            (local_datetime(16, 0, 10), "a"),
+            (local_datetime(16, 9, 0), "b"),
+            (local_datetime(16, 23, 45), "c"),
+        ];
+        let next = vec![
+            (local_datetime(17, 0, 5), "d"),
+            (local_datetime(17, 0, 20), "e"),
+            (local_datetime(17, 10, 0), "f"),
+        ];
+
+        let (records, moved, from_previous, into_next) =
+            assign_cross_midnight(previous, day, next, 30, CrossMidnightMode::StartDay);
+
+        // "a" belongs to the activity started the day before
+        assert_eq!(records, vec!["b", "c"]);
+        assert_eq!(moved, vec!["d", "e"]);
+        assert!(!from_previous);
+        assert!(!into_next);
+    }
+
+    #[test]
+    fn test_assign_cross_midnight_start_day_with_only_continued_records() {
+        let previous = Some(local_datetime(15, 23, 50));
+        let day = vec![(local_datetime(16, 0, 10), "a")];
+        let next = vec![(local_datetime(17, 0, 5), "b")];
+
+        let (records, moved, _, _) =
+            assign_cross_midnight(previous, day, next, 30, CrossMidnightMode::StartDay);
+
+        assert!(records.is_empty());
+        assert!(moved.is_empty());
+    }
+
+    #[test]
+    fn test_group_by_hour_puts_next_day_hours_last() {
+        let event = |hour: u32, next_day: bool| TimelineEvent {
+            record: Record {
+                id: 1,
+                timestamp: String::new(),
+                source_type: "auto".to_string(),
+                content: String::new(),
+                screenshot_path: None,
+                monitor_info: None,
+                tags: None,
+                user_notes: None,
+                session_id: None,
+                analysis_status: None,
+                metadata: None,
+                status: None,
+            },
+            hour,
+            time_str: String::new(),
+            event_type: String::new(),
+            preview: String::new(),
+            next_day,
+        };
+
+        let groups = group_by_hour(vec![event(0, true), event(23, false)]);
+
+        assert_eq!(groups[0].hour, 23);
+        assert_eq!(groups[1].hour, 0);
+        assert!(groups[1].next_day);
+        assert_eq!(groups[1].label, "00:00 - 01:00 (次日)");
+    }
+
     #[test]
     fn test_parse_timestamp_invalid() {
         let timestamp = "invalid-timestamp";
@@ -184,6 +184,18 @@ pub fn get_active_window() -> ActiveWindow {
     ActiveWindow::default()
 }
 
+/// Title of `window`, or `None` when it has no title (e.g. no window is
+/// focused and the desktop is shown).
+pub fn active_window_title(window: &ActiveWindow) -> Option<String> {
+    let title = window.title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Title of the foreground window, or `None` when no titled window has focus.
+pub fn get_active_window_title() -> Option<String> {
+    active_window_title(&get_active_window())
+}
+
 // ── Window filtering logic (SMART-001 Task 3) ──
 
 /// Check if any pattern matches the given text (case-insensitive partial match).
@@ -247,6 +259,77 @@ pub fn should_capture_by_window(
     true
 }
 
+/// The first entry of `excluded_apps` found in the window's process name or
+/// title (case-insensitive partial match), or `None` to capture the window.
+///
+/// Unlike the blacklist this also applies in whitelist-only mode.
+pub fn excluded_app_match<'a>(
+    window: &ActiveWindow,
+    excluded_apps: &'a [String],
+) -> Option<&'a str> {
+    let title = window.title.to_lowercase();
+    let process = window.process_name.to_lowercase();
+    excluded_apps
+        .iter()
+        .map(|app| app.trim())
+        .filter(|app| !app.is_empty())
+        .find(|app| {
+            let app = app.to_lowercase();
+            process.contains(&app) || title.contains(&app)
+        })
+}
+
+// ── Sensitive content detection ──
+
+/// Built-in keywords for pages that should never be sent to a cloud model
+/// (online banking, identity documents, payment and password managers).
+pub const DEFAULT_SENSITIVE_KEYWORDS: &[&str] = &[
+    "银行",
+    "网银",
+    "bank",
+    "身份证",
+    "护照",
+    "passport",
+    "密码",
+    "password",
+    "信用卡",
+    "credit card",
+    "支付宝",
+    "alipay",
+    "paypal",
+    "证券",
+    "1password",
+    "keepass",
+    "bitwarden",
+];
+
+/// Detect whether the active window looks like a sensitive page.
+///
+/// Checks the window title and process name (case-insensitive partial match)
+/// against [`DEFAULT_SENSITIVE_KEYWORDS`] plus any user-supplied keywords.
+/// Returns the first keyword that matched, or `None` if the window looks safe.
+pub fn detect_sensitive_window(window: &ActiveWindow, extra_keywords: &[String]) -> Option<String> {
+    let title = window.title.to_lowercase();
+    let process = window.process_name.to_lowercase();
+
+    DEFAULT_SENSITIVE_KEYWORDS
+        .iter()
+        .map(|k| k.to_string())
+        .chain(extra_keywords.iter().map(|k| k.trim().to_string()))
+        .filter(|k| !k.is_empty())
+        .find(|k| {
+            let k = k.to_lowercase();
+            title.contains(&k) || process.contains(&k)
+        })
+}
+
+/// Decide whether AI analysis should be skipped for a detection result.
+///
+/// Skipping only happens when the filter is enabled and a keyword matched.
+pub fn should_skip_for_sensitive(enabled: bool, detected: Option<&str>) -> bool {
+    enabled && detected.is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,6 +344,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn active_window_title_is_none_without_a_titled_window() {
+        assert_eq!(active_window_title(&ActiveWindow::default()), None);
+        let desktop = ActiveWindow {
+            title: "  ".to_string(),
+            process_name: "explorer.exe".to_string(),
+        };
+        assert_eq!(active_window_title(&desktop), None);
+        let editor = ActiveWindow {
+            title: " main.rs - VS Code ".to_string(),
+            process_name: "Code".to_string(),
+        };
+        assert_eq!(
+            active_window_title(&editor).as_deref(),
+            Some("main.rs - VS Code")
+        );
+    }
+
     #[test]
     fn active_window_can_be_created_with_values() {
         let window = ActiveWindow {
@@ -659,4 +760,90 @@ mod tests {
             true
         ));
     }
+
+    #[test]
+    fn detect_sensitive_window_matches_builtin_keyword_in_title() {
+        let window = ActiveWindow {
+            title: "招商银行 - 个人网银".to_string(),
+            process_name: "chrome".to_string(),
+        };
+        assert_eq!(
+            detect_sensitive_window(&window, &[]),
+            Some("银行".to_string())
+        );
+    }
+
+    #[test]
+    fn detect_sensitive_window_matches_process_name_case_insensitive() {
+        let window = ActiveWindow {
+            title: "Vault".to_string(),
+            process_name: "KeePassXC".to_string(),
+        };
+        assert_eq!(
+            detect_sensitive_window(&window, &[]),
+            Some("keepass".to_string())
+        );
+    }
+
+    #[test]
+    fn detect_sensitive_window_uses_extra_keywords() {
+        let window = ActiveWindow {
+            title: "Payroll - Internal HR".to_string(),
+            process_name: "firefox".to_string(),
+        };
+        assert_eq!(detect_sensitive_window(&window, &[]), None);
+        assert_eq!(
+            detect_sensitive_window(&window, &["payroll".to_string(), "  ".to_string()]),
+            Some("payroll".to_string())
+        );
+    }
+
+    #[test]
+    fn detect_sensitive_window_returns_none_for_ordinary_window() {
+        let window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        assert_eq!(detect_sensitive_window(&window, &[]), None);
+    }
+
+    #[test]
+    fn excluded_app_matches_process_or_title_case_insensitive() {
+        let window = ActiveWindow {
+            title: "Vault - 1Password".to_string(),
+            process_name: "1Password.exe".to_string(),
+        };
+        let apps = vec!["  ".to_string(), "1password".to_string()];
+        assert_eq!(excluded_app_match(&window, &apps), Some("1password"));
+
+        let banking = ActiveWindow {
+            title: "招商银行 - 个人网银".to_string(),
+            process_name: "chrome".to_string(),
+        };
+        assert_eq!(
+            excluded_app_match(&banking, &["个人网银".to_string()]),
+            Some("个人网银")
+        );
+    }
+
+    #[test]
+    fn excluded_app_returns_none_for_other_windows() {
+        let window = ActiveWindow {
+            title: "main.rs - VS Code".to_string(),
+            process_name: "Code".to_string(),
+        };
+        assert_eq!(excluded_app_match(&window, &[]), None);
+        assert_eq!(
+            excluded_app_match(&window, &["KeePassXC".to_string(), "".to_string()]),
+            None
+        );
+    }
+
+    #[test]
+    fn should_skip_for_sensitive_requires_enabled_and_match() {
+        assert!(should_skip_for_sensitive(true, Some("bank")));
+        assert!(!should_skip_for_sensitive(false, Some("bank")));
+        assert!(!should_skip_for_sensitive(true, None));
+        assert!(!should_skip_for_sensitive(false, None));
+    }
 }